@@ -24,7 +24,7 @@
 
 mod playwright;
 
-pub use playwright::{BrowserCrawlerPlaywright, CrawlHtmlResult};
+pub use playwright::{is_not_block_page, BrowserCrawlerPlaywright, Cookie, CrawlHtmlResult};
 
 use anyhow::{Result};
 use headless_chrome::{Browser, Tab};
@@ -3,9 +3,15 @@ use std::slice::Iter;
 use chrono::{Datelike, NaiveDate};
 use itertools::Itertools;
 
+use crate::data_type::TimePeriod;
+
 mod volatility;
+mod rolling;
+mod period;
 
 pub use volatility::*;
+pub use rolling::{rolling_apply, rolling_volatility};
+pub use period::{group_by_period, AggFn, PeriodKey};
 
 #[derive(Debug, Clone)]
 pub struct Vol {
@@ -23,14 +29,33 @@ pub fn calc(date_period: &str, vols: &Vec<Vol>) {
     // group_by_week(vols, m_groups);
 }
 
-fn group_by_week(vols: &Vec<Vol>) -> Vec<(u32, f64)> {
-    let m_groups = vols.iter().group_by(|vol| vol.date.month());
-    let w_groups = vols.iter().group_by(|vol| vol.date.iso_week().week());
-    let mut datas = vec![];
-    for (week, data) in &w_groups {
-        let sum = data.collect_vec().iter().map(|v| v.vol).sum::<f64>();
-        datas.push((week, sum));
+/// 按 (ISO 周所属年份, ISO 周) 汇总成交量, 不同年份的同一周不会合并, 结果按周正序
+pub fn group_by_week(vols: &[Vol]) -> anyhow::Result<Vec<(PeriodKey, f64)>> {
+    let dates: Vec<NaiveDate> = vols.iter().map(|v| v.date).collect();
+    let values: Vec<f64> = vols.iter().map(|v| v.vol).collect();
+    group_by_period(&dates, &values, TimePeriod::Weekly, AggFn::Sum)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vol(date: &str, vol: f64) -> Vol {
+        Vol { vol, date: NaiveDate::parse_from_str(date, "%Y%m%d").unwrap() }
+    }
+
+    #[test]
+    fn test_group_by_week_across_years() {
+        // 日期逆序; 20241230 属于 2025 年第 1 周
+        let vols = vec![vol("20250102", 4.0), vol("20241230", 3.0), vol("20240102", 2.0), vol("20230103", 1.0)];
+        let weeks = group_by_week(&vols).unwrap();
+        assert_eq!(
+            weeks,
+            vec![
+                (PeriodKey::Week { year: 2023, week: 1 }, 1.0),
+                (PeriodKey::Week { year: 2024, week: 1 }, 2.0),
+                (PeriodKey::Week { year: 2025, week: 1 }, 7.0),
+            ]
+        );
     }
-    datas.sort_by(|v1, v2| v2.0.cmp(&v1.0));
-    datas
 }
\ No newline at end of file
@@ -0,0 +1,120 @@
+use anyhow::bail;
+use chrono::{Datelike, NaiveDate};
+use itertools::Itertools;
+use serde::Serialize;
+
+use crate::data_type::TimePeriod;
+
+/// 日期所属的周期, 按时间先后排序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub enum PeriodKey {
+    Day(NaiveDate),
+    Week { year: i32, week: u32 }, // ISO 周, `year` 为 ISO 周所属的年份, 跨年的周不会被拆开
+    Month { year: i32, month: u32 },
+    Year(i32),
+}
+
+impl PeriodKey {
+    pub fn of(date: &NaiveDate, period: TimePeriod) -> Self {
+        match period {
+            TimePeriod::Daily => PeriodKey::Day(*date),
+            TimePeriod::Weekly => {
+                let week = date.iso_week();
+                PeriodKey::Week { year: week.year(), week: week.week() }
+            }
+            TimePeriod::Monthly => PeriodKey::Month { year: date.year(), month: date.month() },
+            TimePeriod::Yearly => PeriodKey::Year(date.year()),
+        }
+    }
+}
+
+/// 同一周期内多个值的聚合方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AggFn {
+    Sum,
+    Avg,
+    First, // 周期内日期最早的值
+    Last,  // 周期内日期最晚的值, 如月末净值
+}
+
+/// 按周期分组并聚合, 如按周汇总成交量(`Weekly` + `Sum`)、取每月末净值(`Monthly` + `Last`)
+///
+/// `dates` 与 `values` 一一对应, 顺序不限; 结果按周期正序, 没有数据的周期不输出.
+/// 两者长度不一致时返回错误
+///
+/// # Example
+/// ```rust,ignore
+/// let weekly_vol = group_by_period(&dates, &vols, TimePeriod::Weekly, AggFn::Sum)?;
+/// ```
+pub fn group_by_period(dates: &[NaiveDate], values: &[f64], period: TimePeriod, agg: AggFn) -> anyhow::Result<Vec<(PeriodKey, f64)>> {
+    if dates.len() != values.len() {
+        bail!("dates and values must have the same length, dates: {}, values: {}", dates.len(), values.len());
+    }
+    let mut points: Vec<(&NaiveDate, f64)> = dates.iter().zip(values.iter().copied()).collect();
+    // 稳定排序, 同一天的多个值保持输入顺序
+    points.sort_by_key(|(date, _)| **date);
+
+    let groups = points.into_iter().group_by(|(date, _)| PeriodKey::of(date, period));
+    let mut result = Vec::new();
+    for (key, group) in &groups {
+        let values: Vec<f64> = group.map(|(_, value)| value).collect();
+        let value = match agg {
+            AggFn::Sum => values.iter().sum(),
+            AggFn::Avg => values.iter().sum::<f64>() / values.len() as f64,
+            AggFn::First => values[0],
+            AggFn::Last => values[values.len() - 1],
+        };
+        result.push((key, value));
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y%m%d").unwrap()
+    }
+
+    #[test]
+    fn test_weekly_sum() {
+        // 20241230 ~ 20250103 同属 2025 年第 1 周
+        let dates: Vec<NaiveDate> = ["20250106", "20241227", "20241230", "20241231", "20250102", "20250103", "20250107"].map(date).to_vec();
+        let vols = [10.0, 1.0, 2.0, 3.0, 4.0, 5.0, 20.0];
+        let weekly = group_by_period(&dates, &vols, TimePeriod::Weekly, AggFn::Sum).unwrap();
+        assert_eq!(
+            weekly,
+            vec![
+                (PeriodKey::Week { year: 2024, week: 52 }, 1.0),
+                (PeriodKey::Week { year: 2025, week: 1 }, 14.0),
+                (PeriodKey::Week { year: 2025, week: 2 }, 30.0),
+            ]
+        );
+
+        let avg = group_by_period(&dates, &vols, TimePeriod::Weekly, AggFn::Avg).unwrap();
+        assert_eq!(avg[1].1, 3.5);
+
+        assert!(group_by_period(&dates, &vols[1..], TimePeriod::Weekly, AggFn::Sum).is_err());
+        assert!(group_by_period(&[], &[], TimePeriod::Weekly, AggFn::Sum).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_monthly_last() {
+        // 日期倒序输入, 与数据库查询的默认顺序一致
+        let dates: Vec<NaiveDate> = ["20240301", "20240229", "20240215", "20240131", "20240102"].map(date).to_vec();
+        let navs = [1.30, 1.25, 1.20, 1.10, 1.00];
+        let monthly = group_by_period(&dates, &navs, TimePeriod::Monthly, AggFn::Last).unwrap();
+        assert_eq!(
+            monthly,
+            vec![
+                (PeriodKey::Month { year: 2024, month: 1 }, 1.10),
+                (PeriodKey::Month { year: 2024, month: 2 }, 1.25),
+                (PeriodKey::Month { year: 2024, month: 3 }, 1.30),
+            ]
+        );
+
+        let first = group_by_period(&dates, &navs, TimePeriod::Monthly, AggFn::First).unwrap();
+        assert_eq!(first[1], (PeriodKey::Month { year: 2024, month: 2 }, 1.20));
+    }
+}
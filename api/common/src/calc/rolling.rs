@@ -0,0 +1,92 @@
+use anyhow::bail;
+
+use crate::stastics::TRADING_DAYS_PER_YEAR;
+
+/// 在每个长度为 `window` 的滑动窗口上计算 `f`, 每个完整窗口输出一个值, 共 `data.len() - window + 1` 个
+///
+/// 第 i 个输出对应 `data[i..i + window]`, 即以 `data[i + window - 1]` 结尾的窗口;
+/// `window` 为 0 或数据不足一个窗口时返回错误
+///
+/// # Example
+/// ```rust,ignore
+/// let rolling_max = rolling_apply(&closes, 20, |w| w.iter().copied().fold(f64::NEG_INFINITY, f64::max))?;
+/// ```
+pub fn rolling_apply(data: &[f64], window: usize, f: impl Fn(&[f64]) -> f64) -> anyhow::Result<Vec<f64>> {
+    if window == 0 {
+        bail!("window must be greater than 0");
+    }
+    if data.len() < window {
+        bail!("not enough data: {}, window: {}", data.len(), window);
+    }
+    Ok(data.windows(window).map(f).collect())
+}
+
+/// 滚动历史波动率: 每个窗口内 `window` 个对数收益率的样本标准差, `annualize` 为 true 时乘以 sqrt(252)
+///
+/// 第 i 个输出对应 `closes[i..=i + window]`, 即以 `closes[i + window]` 结尾的窗口, 共 `closes.len() - window` 个;
+/// `window` 小于 2、数据不足一个窗口或收盘价不大于 0 时返回错误
+///
+/// # Arguments
+/// - `closes` 收盘价, 按日期正序
+/// - `window` 每个窗口的收益率个数, 如 20
+pub fn rolling_volatility(closes: &[f64], window: usize, annualize: bool) -> anyhow::Result<Vec<f64>> {
+    if window < 2 {
+        bail!("window must be at least 2");
+    }
+    if closes.iter().any(|c| *c <= 0f64) {
+        bail!("closes must be positive");
+    }
+    let returns: Vec<f64> = closes.windows(2).map(|w| (w[1] / w[0]).ln()).collect();
+    let scale = if annualize { TRADING_DAYS_PER_YEAR.sqrt() } else { 1f64 };
+    rolling_apply(&returns, window, |w| {
+        let mean = w.iter().sum::<f64>() / w.len() as f64;
+        let variance = w.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (w.len() - 1) as f64;
+        variance.sqrt() * scale
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::indicators::sma;
+
+    #[test]
+    fn test_rolling_mean_matches_sma() {
+        let closes = [10.0, 10.5, 10.2, 10.8, 11.0, 10.6, 11.2, 11.5];
+        let mean = rolling_apply(&closes, 3, |w| w.iter().sum::<f64>() / w.len() as f64).unwrap();
+        let expected = sma(&closes, 3).unwrap();
+        assert_eq!(mean.len(), closes.len() - 2);
+        assert_eq!(mean.len(), expected.len());
+        for (a, b) in mean.iter().zip(&expected) {
+            assert!((a - b).abs() < 1e-9);
+        }
+
+        assert_eq!(rolling_apply(&closes, closes.len(), |w| w[0]).unwrap(), vec![10.0]);
+        assert!(rolling_apply(&closes, 0, |w| w[0]).is_err());
+        assert!(rolling_apply(&closes[..2], 3, |w| w[0]).is_err());
+    }
+
+    #[test]
+    fn test_rolling_volatility() {
+        // 每天固定上涨 1%, 对数收益率恒定, 波动率为 0
+        let closes: Vec<f64> = (0..10).map(|i| 100.0 * 1.01f64.powi(i)).collect();
+        let vol = rolling_volatility(&closes, 5, true).unwrap();
+        assert_eq!(vol.len(), closes.len() - 5);
+        assert!(vol.iter().all(|v| v.abs() < 1e-9));
+
+        // 对数收益率为 +a, -a 交替, 两个收益率的样本标准差为 sqrt(2) * a
+        let a = 0.02f64;
+        let closes = [100.0, 100.0 * a.exp(), 100.0, 100.0 * a.exp()];
+        let daily = rolling_volatility(&closes, 2, false).unwrap();
+        assert_eq!(daily.len(), 2);
+        for v in &daily {
+            assert!((v - 2f64.sqrt() * a).abs() < 1e-12);
+        }
+        let annual = rolling_volatility(&closes, 2, true).unwrap();
+        assert!((annual[0] - daily[0] * 252f64.sqrt()).abs() < 1e-12);
+
+        assert!(rolling_volatility(&closes, 1, true).is_err());
+        assert!(rolling_volatility(&closes, 4, true).is_err());
+        assert!(rolling_volatility(&[100.0, 0.0, 100.0], 2, true).is_err());
+    }
+}
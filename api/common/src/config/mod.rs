@@ -1,5 +1,9 @@
+use std::collections::{BTreeMap, HashMap};
 use std::env;
+use std::time::Duration;
+use chrono::{Duration as DateDuration, NaiveDate, NaiveTime};
 use config::{Config, ConfigError, Environment, File};
+use entity::sea_orm::ConnectOptions;
 use serde::Deserialize;
 use tracing::info;
 
@@ -7,12 +11,241 @@ use tracing::info;
 #[allow(unused)]
 struct Database {
     url: String,
+    /// 连接池最大连接数, 默认 20: 全市场扫描默认 8 并发, 另需留给定时任务和 web 请求
+    #[serde(default = "default_max_connections")]
+    max_connections: u32,
+    /// 连接池保持的最小连接数, 默认 2
+    #[serde(default = "default_min_connections")]
+    min_connections: u32,
+    /// 建立连接的超时秒数, 默认 8
+    #[serde(default = "default_connect_timeout")]
+    connect_timeout: u64,
+    /// 空闲连接回收秒数, 默认 600, 应小于 MySQL 的 wait_timeout
+    #[serde(default = "default_idle_timeout")]
+    idle_timeout: u64,
+    /// 单次查询最多返回的行数, 默认 50000, 见 `crate::db::BoundedSelect`
+    #[serde(default = "default_max_rows")]
+    max_rows: usize,
+}
+
+fn default_max_connections() -> u32 {
+    20
+}
+
+fn default_min_connections() -> u32 {
+    2
+}
+
+fn default_connect_timeout() -> u64 {
+    8
+}
+
+fn default_idle_timeout() -> u64 {
+    600
+}
+
+fn default_max_rows() -> usize {
+    crate::db::DEFAULT_MAX_ROWS
+}
+
+/// 历史数据接口的回看窗口, 单位由接口决定(自然日或年)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+pub struct LookbackWindow {
+    /// 请求未指定区间时的默认长度
+    pub default: u32,
+    /// 允许请求的最大长度, 超过时截断为该值
+    pub max: u32,
+}
+
+impl LookbackWindow {
+    /// 请求的长度, 未指定时取默认值, 超过最大值时截断
+    pub fn clamp(&self, requested: Option<u32>) -> u32 {
+        requested.unwrap_or(self.default).min(self.max)
+    }
+
+    /// 按自然日解析查询区间: `end` 默认为 `today`, `start` 默认为 `end` 往前 `default` 天;
+    /// 区间超过 `max` 天时保留靠近 `end` 的部分
+    pub fn date_range(&self, start: Option<NaiveDate>, end: Option<NaiveDate>, today: NaiveDate) -> (NaiveDate, NaiveDate) {
+        let end = end.unwrap_or(today);
+        let earliest = end - DateDuration::days(self.max as i64);
+        let start = start.unwrap_or(end - DateDuration::days(self.default as i64));
+        (start.max(earliest), end)
+    }
+}
+
+/// 各类历史数据接口的默认及最大回看窗口, 运维可在 `[lookback]` 中调整而无需重新编译
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct Lookback {
+    /// 个股历史行情(K线), 单位: 自然日
+    pub stock_history: LookbackWindow,
+    /// 个股日线价格, 单位: 自然日
+    pub stock_price: LookbackWindow,
+    /// 证券历年走势对比, 单位: 年; 按年份对比时指定的年份超过 `max` 个返回 400
+    pub security_compare: LookbackWindow,
+}
+
+impl Default for Lookback {
+    fn default() -> Self {
+        Self {
+            stock_history: LookbackWindow { default: 365, max: 3650 },
+            stock_price: LookbackWindow { default: 365, max: 3650 },
+            security_compare: LookbackWindow { default: 3, max: 10 },
+        }
+    }
+}
+
+/// 单个市场的交易时段, 时间为该市场当地时间
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "RawMarketSession")]
+pub struct MarketSession {
+    /// 连续交易时段 (开始, 结束), 按时间正序, 左闭右开
+    pub sessions: Vec<(NaiveTime, NaiveTime)>,
+    /// 半日市: 交易日(YYYYMMDD) -> 提前收盘时间
+    pub early_close: BTreeMap<String, NaiveTime>,
+}
+
+impl MarketSession {
+    /// `date` 当天的交易时段, 半日市时截断到提前收盘时间
+    pub fn sessions_on(&self, date: NaiveDate) -> Vec<(NaiveTime, NaiveTime)> {
+        let Some(close) = self.early_close.get(&date.format(crate::date::FORMAT).to_string()) else {
+            return self.sessions.clone();
+        };
+        self.sessions
+            .iter()
+            .filter(|(start, _)| start < close)
+            .map(|(start, end)| (*start, (*end).min(*close)))
+            .collect()
+    }
+}
+
+/// 配置文件中的时段, 时间格式为 HH:MM
+#[derive(Deserialize)]
+struct RawMarketSession {
+    sessions: Vec<(String, String)>,
+    #[serde(default)]
+    early_close: BTreeMap<String, String>,
+}
+
+impl TryFrom<RawMarketSession> for MarketSession {
+    type Error = String;
+
+    fn try_from(raw: RawMarketSession) -> Result<Self, Self::Error> {
+        let parse = |v: &str| NaiveTime::parse_from_str(v, "%H:%M").map_err(|e| format!("invalid session time {}: {}", v, e));
+        let sessions = raw
+            .sessions
+            .iter()
+            .map(|(start, end)| Ok((parse(start)?, parse(end)?)))
+            .collect::<Result<Vec<_>, String>>()?;
+        if sessions.is_empty() || sessions.iter().any(|(start, end)| start >= end) {
+            return Err("sessions must be non-empty and each start before end".to_string());
+        }
+        let early_close = raw
+            .early_close
+            .iter()
+            .map(|(date, close)| Ok((date.clone(), parse(close)?)))
+            .collect::<Result<_, String>>()?;
+        Ok(Self { sessions, early_close })
+    }
+}
+
+/// 各市场的交易时段, 按市场代码(与交易日历的交易所代码一致, 如 SSE)索引
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MarketSessions(HashMap<String, MarketSession>);
+
+impl MarketSessions {
+    pub fn new(sessions: HashMap<String, MarketSession>) -> Self {
+        Self(sessions)
+    }
+
+    pub fn get(&self, market: &str) -> Option<&MarketSession> {
+        self.0.get(market)
+    }
+}
+
+impl Default for MarketSessions {
+    /// 沪深北交易所 9:30-11:30, 13:00-15:00
+    fn default() -> Self {
+        let time = |h, m| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+        let a_share = MarketSession {
+            sessions: vec![(time(9, 30), time(11, 30)), (time(13, 0), time(15, 0))],
+            early_close: BTreeMap::new(),
+        };
+        Self(["SSE", "SZSE", "BSE"].iter().map(|m| (m.to_string(), a_share.clone())).collect())
+    }
 }
 
 #[derive(Debug, Deserialize)]
 #[allow(unused)]
 struct Tushare {
     token: String,
+    /// 响应缓存的有效期(秒), 按接口名索引, 如 `stock_basic = 3600`, 未配置的接口使用代码中的默认值
+    #[serde(default)]
+    cache_ttl: HashMap<String, u64>,
+    #[serde(default)]
+    rate_limit: TushareRateLimit,
+}
+
+/// 所有 tushare 接口共享的全局限流: 每 `window_secs` 秒最多 `max_requests` 次请求
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct TushareRateLimit {
+    pub max_requests: usize,
+    pub window_secs: u64,
+}
+
+impl Default for TushareRateLimit {
+    /// tushare 积分账户默认的每分钟 500 次
+    fn default() -> Self {
+        Self { max_requests: 500, window_secs: 60 }
+    }
+}
+
+/// 管理接口配置
+#[derive(Debug, Default, Deserialize)]
+#[allow(unused)]
+struct Admin {
+    /// 管理接口(如手动触发任务)校验的 token, 未配置时这些接口一律拒绝
+    token: Option<String>,
+}
+
+/// 定时任务配置
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Schedule {
+    /// 各抓取任务的回溯天数, 如 `stock_daily = 5`, 未配置的任务使用代码中的默认值
+    windows: HashMap<String, u64>,
+    us_daily: UsDailyFetch,
+}
+
+/// 美股日线抓取任务的并发配置, 未配置的项使用任务中的默认值
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct UsDailyFetch {
+    /// 最大并发数
+    pub concurrency: Option<usize>,
+    /// 单个 symbol 的超时秒数
+    pub timeout_secs: Option<u64>,
+    /// 每完成多少个 symbol 打印一次进度
+    pub progress_interval: Option<usize>,
+}
+
+/// 日线历史导出任务配置
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct Export {
+    /// 导出文件所在目录, 每只股票一个 `<ts_code>.<format>`
+    pub dir: String,
+    /// 要导出的股票代码, 为空时任务不导出
+    pub ts_codes: Vec<String>,
+    /// 导出格式: csv 或 parquet
+    pub format: String,
+}
+
+impl Default for Export {
+    fn default() -> Self {
+        Self { dir: "export".to_string(), ts_codes: vec![], format: "csv".to_string() }
+    }
 }
 
 #[derive(Debug, Deserialize, Clone)]
@@ -29,6 +262,16 @@ pub struct AppConfig {
     database: Database,
     tushare: Tushare,
     ms: Ms,
+    #[serde(default)]
+    lookback: Lookback,
+    #[serde(default)]
+    market_sessions: HashMap<String, MarketSession>,
+    #[serde(default)]
+    admin: Admin,
+    #[serde(default)]
+    schedule: Schedule,
+    #[serde(default)]
+    export: Export,
 }
 
 impl AppConfig {
@@ -49,11 +292,180 @@ impl AppConfig {
         self.database.url.clone()
     }
 
+    /// 按 `[database]` 中的连接池大小和超时配置创建连接参数
+    pub fn db_connect_options(&self) -> ConnectOptions {
+        let db = &self.database;
+        let mut opt = ConnectOptions::new(db.url.clone());
+        opt.max_connections(db.max_connections)
+            .min_connections(db.min_connections)
+            .connect_timeout(Duration::from_secs(db.connect_timeout))
+            .idle_timeout(Duration::from_secs(db.idle_timeout))
+            .sqlx_logging(false);
+        opt
+    }
+
+    /// `[database] max_rows`, 启动时传给 `crate::db::set_max_rows`
+    pub fn db_max_rows(&self) -> usize {
+        self.database.max_rows
+    }
+
     pub fn tushare_token(&self) -> String {
         self.tushare.token.clone()
     }
 
+    /// `[tushare.cache_ttl]` 中配置的各接口缓存有效期, 单位: 秒
+    pub fn tushare_cache_ttl(&self) -> HashMap<String, u64> {
+        self.tushare.cache_ttl.clone()
+    }
+
+    /// `[tushare.rate_limit]` 中配置的全局限流
+    pub fn tushare_rate_limit(&self) -> TushareRateLimit {
+        self.tushare.rate_limit
+    }
+
     pub fn mstar(&self) -> &Ms {
         &self.ms
     }
-}
\ No newline at end of file
+
+    /// 管理接口 token, 未配置或为空时为 None
+    pub fn admin_token(&self) -> Option<String> {
+        self.admin.token.clone().filter(|token| !token.is_empty())
+    }
+
+    /// `[schedule.windows]` 中配置的各抓取任务回溯天数
+    pub fn fetch_windows(&self) -> HashMap<String, u64> {
+        self.schedule.windows.clone()
+    }
+
+    /// `[schedule.us_daily]` 中配置的美股日线抓取并发参数
+    pub fn us_daily_fetch(&self) -> UsDailyFetch {
+        self.schedule.us_daily
+    }
+
+    /// `[export]` 中配置的导出目录和股票
+    pub fn export(&self) -> Export {
+        self.export.clone()
+    }
+
+    pub fn lookback(&self) -> Lookback {
+        self.lookback
+    }
+
+    /// 内置的 A 股时段, 被 `[market_sessions.<市场>]` 中的同名配置覆盖
+    pub fn market_sessions(&self) -> MarketSessions {
+        let mut sessions = MarketSessions::default();
+        sessions.0.extend(self.market_sessions.clone());
+        sessions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use config::FileFormat;
+
+    fn parse(database: &str) -> AppConfig {
+        let toml = format!("{database}\n[tushare]\ntoken = \"x\"\n[ms]\nemail = \"\"\npassword = \"\"\nlogin_url = \"\"\n");
+        Config::builder()
+            .add_source(File::from_str(&toml, FileFormat::Toml))
+            .build()
+            .unwrap()
+            .try_deserialize()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_db_connect_options() {
+        let config = parse("[database]\nurl = \"mysql://localhost/test\"\nmax_connections = 32\nmin_connections = 4\nconnect_timeout = 3\nidle_timeout = 120");
+        let opt = config.db_connect_options();
+        assert_eq!(opt.get_url(), "mysql://localhost/test");
+        assert_eq!(opt.get_max_connections(), Some(32));
+        assert_eq!(opt.get_min_connections(), Some(4));
+        assert_eq!(opt.get_connect_timeout(), Some(Duration::from_secs(3)));
+        assert_eq!(opt.get_idle_timeout(), Some(Duration::from_secs(120)));
+        assert_eq!(parse("[database]\nurl = \"mysql://localhost/test\"\nmax_rows = 1000").db_max_rows(), 1000);
+
+        // 未配置时使用默认值
+        let opt = parse("[database]\nurl = \"mysql://localhost/test\"").db_connect_options();
+        assert_eq!(opt.get_max_connections(), Some(20));
+        assert_eq!(opt.get_min_connections(), Some(2));
+        assert_eq!(opt.get_connect_timeout(), Some(Duration::from_secs(8)));
+        assert_eq!(opt.get_idle_timeout(), Some(Duration::from_secs(600)));
+        assert_eq!(parse("[database]\nurl = \"mysql://localhost/test\"").db_max_rows(), crate::db::DEFAULT_MAX_ROWS);
+    }
+
+    #[test]
+    fn test_lookback_clamped_to_max() {
+        let config = parse("[database]\nurl = \"mysql://localhost/test\"\n[lookback.stock_history]\ndefault = 90\nmax = 730");
+        let lookback = config.lookback();
+        assert_eq!(lookback.stock_history, LookbackWindow { default: 90, max: 730 });
+        // 未配置的接口使用默认值
+        assert_eq!(lookback.security_compare, Lookback::default().security_compare);
+
+        let today = NaiveDate::from_ymd_opt(2024, 6, 30).unwrap();
+        let history = lookback.stock_history;
+        assert_eq!(history.date_range(None, None, today), (NaiveDate::from_ymd_opt(2024, 4, 1).unwrap(), today));
+        // 请求 10 年, 截断为最近 730 天
+        let start = NaiveDate::from_ymd_opt(2014, 1, 1).unwrap();
+        assert_eq!(history.date_range(Some(start), None, today), (NaiveDate::from_ymd_opt(2022, 7, 1).unwrap(), today));
+        assert_eq!(lookback.security_compare.clamp(Some(30)), 10);
+        assert_eq!(lookback.security_compare.clamp(None), 3);
+    }
+
+    #[test]
+    fn test_fetch_windows() {
+        let config = parse("[database]\nurl = \"mysql://localhost/test\"\n[schedule.windows]\nstock_daily = 5\nhk_hold = 60");
+        let windows = config.fetch_windows();
+        assert_eq!(windows.get("stock_daily"), Some(&5));
+        assert_eq!(windows.get("hk_hold"), Some(&60));
+        assert!(parse("[database]\nurl = \"mysql://localhost/test\"").fetch_windows().is_empty());
+    }
+
+    #[test]
+    fn test_us_daily_fetch() {
+        let config = parse("[database]\nurl = \"mysql://localhost/test\"\n[schedule.us_daily]\nconcurrency = 4\ntimeout_secs = 30");
+        let expected = UsDailyFetch { concurrency: Some(4), timeout_secs: Some(30), progress_interval: None };
+        assert_eq!(config.us_daily_fetch(), expected);
+        assert_eq!(parse("[database]\nurl = \"mysql://localhost/test\"").us_daily_fetch(), UsDailyFetch::default());
+    }
+
+    #[test]
+    fn test_tushare_cache_ttl() {
+        let config = parse("[database]\nurl = \"mysql://localhost/test\"\n[tushare.cache_ttl]\nstock_basic = 3600");
+        assert_eq!(config.tushare_cache_ttl().get("stock_basic"), Some(&3600));
+        assert_eq!(config.tushare_token(), "x");
+        assert!(parse("[database]\nurl = \"mysql://localhost/test\"").tushare_cache_ttl().is_empty());
+    }
+
+    #[test]
+    fn test_tushare_rate_limit() {
+        let config = parse("[database]\nurl = \"mysql://localhost/test\"\n[tushare.rate_limit]\nmax_requests = 200");
+        assert_eq!(config.tushare_rate_limit(), TushareRateLimit { max_requests: 200, window_secs: 60 });
+        assert_eq!(parse("[database]\nurl = \"mysql://localhost/test\"").tushare_rate_limit(), TushareRateLimit::default());
+    }
+
+    #[test]
+    fn test_export() {
+        let config = parse("[database]\nurl = \"mysql://localhost/test\"\n[export]\nts_codes = [\"600000.SH\"]");
+        let expected = Export { dir: "export".to_string(), ts_codes: vec!["600000.SH".to_string()], format: "csv".to_string() };
+        assert_eq!(config.export(), expected);
+        let config = parse("[database]\nurl = \"mysql://localhost/test\"\n[export]\nformat = \"parquet\"");
+        assert_eq!(config.export().format, "parquet");
+        assert!(parse("[database]\nurl = \"mysql://localhost/test\"").export().ts_codes.is_empty());
+    }
+
+    #[test]
+    fn test_market_sessions_with_early_close() {
+        let config = parse("[database]\nurl = \"mysql://localhost/test\"\n[market_sessions.HK]\nsessions = [[\"09:30\", \"12:00\"], [\"13:00\", \"16:00\"]]\nearly_close = { \"20241224\" = \"12:00\" }");
+        let sessions = config.market_sessions();
+        let time = |h, m| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+        // 内置 A 股时段仍可用
+        assert_eq!(sessions.get("SSE").unwrap().sessions[1], (time(13, 0), time(15, 0)));
+
+        let hk = sessions.get("HK").unwrap();
+        let normal = hk.sessions_on(NaiveDate::from_ymd_opt(2024, 12, 23).unwrap());
+        assert_eq!(normal, vec![(time(9, 30), time(12, 0)), (time(13, 0), time(16, 0))]);
+        let half_day = hk.sessions_on(NaiveDate::from_ymd_opt(2024, 12, 24).unwrap());
+        assert_eq!(half_day, vec![(time(9, 30), time(12, 0))]);
+    }
+}
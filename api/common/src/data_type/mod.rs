@@ -4,6 +4,7 @@ mod num_or_string;
 mod range;
 pub mod period;
 mod all_single;
+mod ohlcv;
 
 pub use self::date_range::{DateRange, StartEnd};
 pub use self::num_or_string::NumOrString;
@@ -12,6 +13,7 @@ use anyhow::anyhow;
 pub use date_format::DateFormat;
 use serde::{Deserialize, Serialize};
 pub use all_single::AllSingle;
+pub use ohlcv::Ohlcv;
 
 /// such as 600051.SH
 pub type TsCode = String;
@@ -0,0 +1,74 @@
+use entity::{fund_daily, index_daily, stock_daily, stock_monthly, stock_weekly};
+use num_traits::ToPrimitive;
+use entity::sea_orm::prelude::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// 单根K线的开高低收和成交量, 代替 `(high, low, close)` 之类容易写错顺序的元组
+///
+/// 从实体转换时缺失或无法转换的值为 0
+#[derive(Debug, Copy, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct Ohlcv {
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    pub volume: f64,
+}
+
+impl Ohlcv {
+    /// 供 ATR、KDJ 等指标使用的 `(high, low, close)`
+    pub fn hlc(&self) -> (f64, f64, f64) {
+        (self.high, self.low, self.close)
+    }
+}
+
+fn to_f64(value: Decimal) -> f64 {
+    value.to_f64().unwrap_or_default()
+}
+
+macro_rules! impl_from_daily {
+    ($($model:path),*) => {
+        $(
+            impl From<&$model> for Ohlcv {
+                fn from(m: &$model) -> Self {
+                    Ohlcv { open: to_f64(m.open), high: to_f64(m.high), low: to_f64(m.low), close: to_f64(m.close), volume: to_f64(m.vol) }
+                }
+            }
+        )*
+    };
+}
+
+impl_from_daily!(stock_daily::Model, fund_daily::Model, stock_weekly::Model, stock_monthly::Model);
+
+impl From<&index_daily::Model> for Ohlcv {
+    fn from(m: &index_daily::Model) -> Self {
+        let value = |v: Option<Decimal>| v.map(to_f64).unwrap_or_default();
+        Ohlcv { open: value(m.open), high: value(m.high), low: value(m.low), close: value(m.close), volume: value(m.vol) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_from_stock_daily() {
+        let daily = stock_daily::Model {
+            ts_code: "000001.SZ".to_string(),
+            trade_date: "20240102".to_string(),
+            open: Decimal::from_str("10.10").unwrap(),
+            high: Decimal::from_str("10.50").unwrap(),
+            low: Decimal::from_str("9.90").unwrap(),
+            close: Decimal::from_str("10.30").unwrap(),
+            pre_close: None,
+            change: None,
+            pct_chg: None,
+            vol: Decimal::from(123456),
+            amount: Decimal::ZERO,
+        };
+        let bar = Ohlcv::from(&daily);
+        assert_eq!(bar, Ohlcv { open: 10.1, high: 10.5, low: 9.9, close: 10.3, volume: 123456.0 });
+        assert_eq!(bar.hlc(), (10.5, 9.9, 10.3));
+    }
+}
@@ -0,0 +1,119 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use anyhow::bail;
+use entity::sea_orm::{DatabaseConnection, EntityTrait, Select};
+use futures::TryStreamExt;
+
+/// 未配置 `[database] max_rows` 时单次查询最多返回的行数
+pub const DEFAULT_MAX_ROWS: usize = 50_000;
+
+static MAX_ROWS: AtomicUsize = AtomicUsize::new(DEFAULT_MAX_ROWS);
+
+/// 当前的行数上限, 启动时由 `set_max_rows` 设置为 `[database] max_rows`
+pub fn max_rows() -> usize {
+    MAX_ROWS.load(Ordering::Relaxed)
+}
+
+/// 修改全局的行数上限, 对之后的查询生效
+pub fn set_max_rows(max_rows: usize) {
+    MAX_ROWS.store(max_rows, Ordering::Relaxed);
+}
+
+/// 带行数上限的查询, 结果超过上限时返回错误而不是把整张表加载到内存
+///
+/// 确实需要全表数据(如全部股票列表)时用 `unbounded()` 显式放开
+///
+/// # Example
+/// ```rust,ignore
+/// let stocks = stock::Entity::find().bounded().all(conn).await?;
+/// let dailies = stock_daily::Entity::find().bounded().unbounded().all(conn).await?;
+/// ```
+pub struct BoundedSelect<E: EntityTrait> {
+    select: Select<E>,
+    max_rows: Option<usize>,
+}
+
+impl<E: EntityTrait> BoundedSelect<E> {
+    /// 不限制行数
+    pub fn unbounded(mut self) -> Self {
+        self.max_rows = None;
+        self
+    }
+
+    /// 本次查询使用的行数上限, 覆盖全局配置
+    pub fn max_rows(mut self, max_rows: usize) -> Self {
+        self.max_rows = Some(max_rows);
+        self
+    }
+
+    /// 逐行读取, 超过上限立即停止并返回错误, 不会先把全部结果读入内存
+    pub async fn all(self, conn: &DatabaseConnection) -> anyhow::Result<Vec<E::Model>> {
+        let Some(max_rows) = self.max_rows else {
+            return Ok(self.select.all(conn).await?);
+        };
+        let mut stream = self.select.stream(conn).await?;
+        let mut models = Vec::new();
+        while let Some(model) = stream.try_next().await? {
+            if models.len() == max_rows {
+                bail!(
+                    "query on {} returned more than {} rows, add a filter/limit or call .unbounded() to load all rows",
+                    E::default().table_name(),
+                    max_rows
+                );
+            }
+            models.push(model);
+        }
+        Ok(models)
+    }
+}
+
+pub trait BoundedSelectExt<E: EntityTrait> {
+    /// 使用全局行数上限
+    fn bounded(self) -> BoundedSelect<E>;
+}
+
+impl<E: EntityTrait> BoundedSelectExt<E> for Select<E> {
+    fn bounded(self) -> BoundedSelect<E> {
+        BoundedSelect { select: self, max_rows: Some(max_rows()) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use entity::fund_daily;
+    use entity::sea_orm::prelude::Decimal;
+    use crate::db::test_util;
+    use entity::sea_orm::{IntoActiveModel, QuerySelect};
+
+    fn fund(trade_date: &str) -> fund_daily::Model {
+        fund_daily::Model {
+            ts_code: "510300.SH".to_string(),
+            trade_date: trade_date.to_string(),
+            open: Decimal::ONE,
+            high: Decimal::ONE,
+            low: Decimal::ONE,
+            close: Decimal::ONE,
+            pre_close: None,
+            change: None,
+            pct_chg: None,
+            vol: Decimal::ZERO,
+            amount: Decimal::ZERO,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_bounded_query_rejects_too_many_rows() {
+        let conn = test_util::memory_db().await;
+        test_util::create_table(&conn, fund_daily::Entity).await;
+        let funds = vec![fund("20240102"), fund("20240103"), fund("20240104"), fund("20240105")];
+        fund_daily::Entity::insert_many(funds.into_iter().map(IntoActiveModel::into_active_model)).exec(&conn).await.unwrap();
+
+        let err = fund_daily::Entity::find().bounded().max_rows(3).all(&conn).await.unwrap_err();
+        assert!(err.to_string().contains("more than 3 rows"));
+
+        assert_eq!(fund_daily::Entity::find().bounded().max_rows(4).all(&conn).await.unwrap().len(), 4);
+        assert_eq!(fund_daily::Entity::find().limit(3).bounded().max_rows(3).all(&conn).await.unwrap().len(), 3);
+        assert_eq!(fund_daily::Entity::find().bounded().max_rows(3).unbounded().all(&conn).await.unwrap().len(), 4);
+    }
+}
@@ -0,0 +1,141 @@
+use std::fmt::Debug;
+
+use chrono::Local;
+use entity::sea_orm::sea_query::OnConflict;
+use entity::sea_orm::{ActiveModelTrait, ActiveValue, ColumnTrait, ConnectionTrait, EntityTrait, IntoActiveModel};
+use entity::task_dead_letter;
+use serde::Serialize;
+use tracing::error;
+
+use super::get_entity_update_columns;
+
+/// 死信状态: 待处理
+pub const DEAD_LETTER_PENDING: &str = "pending";
+/// 死信状态: 重试成功
+pub const DEAD_LETTER_RETRIED: &str = "retried";
+
+/// 错误信息的最大字符数, 与 `task_dead_letter.error` 的长度一致
+const MAX_ERROR_CHARS: usize = 1000;
+
+/// 一批数据的写入结果
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct UpsertSummary {
+    pub written: usize,
+    pub dead_lettered: usize,
+}
+
+/// 按主键 `pks` 逐行 upsert, 单行写入失败时把该行和错误记入 `task_dead_letter`, 继续写入其余的行
+///
+/// 死信本身写入失败时只记录日志, 不中断整批数据
+///
+/// # Example
+/// ```rust,ignore
+/// let pks = [stock_daily::Column::TsCode, stock_daily::Column::TradeDate];
+/// let summary = upsert_or_dead_letter::<stock_daily::Entity, _>(&tx, "FetchStockDailyTask", dailies, &pks).await;
+/// ```
+pub async fn upsert_or_dead_letter<E, C>(conn: &C, task_name: &str, models: Vec<E::Model>, pks: &[E::Column]) -> UpsertSummary
+where
+    E: EntityTrait,
+    E::Model: Serialize + IntoActiveModel<E::ActiveModel>,
+    E::ActiveModel: ActiveModelTrait<Entity = E> + Send,
+    E::Column: ColumnTrait + Clone + PartialEq + Debug,
+    C: ConnectionTrait,
+{
+    let mut summary = UpsertSummary::default();
+    for model in models {
+        let payload = serde_json::to_string(&model);
+        match upsert_one::<E, C>(conn, model, pks).await {
+            Ok(()) => summary.written += 1,
+            Err(e) => {
+                summary.dead_lettered += 1;
+                let payload = payload.unwrap_or_else(|e| format!("failed to serialize payload: {}", e));
+                if let Err(dead_letter_err) = insert_dead_letter(conn, task_name, E::default().table_name(), payload, &e).await {
+                    error!("insert dead letter failed, task: {}, error: {:?}, dead letter error: {:?}", task_name, e, dead_letter_err);
+                }
+            }
+        }
+    }
+    summary
+}
+
+/// 按主键 `pks` upsert 单行
+pub async fn upsert_one<E, C>(conn: &C, model: E::Model, pks: &[E::Column]) -> anyhow::Result<()>
+where
+    E: EntityTrait,
+    E::Model: IntoActiveModel<E::ActiveModel>,
+    E::ActiveModel: ActiveModelTrait<Entity = E> + Send,
+    E::Column: ColumnTrait + Clone + PartialEq + Debug,
+    C: ConnectionTrait,
+{
+    let on_conflict = OnConflict::columns(pks.iter().cloned()).update_columns(get_entity_update_columns::<E>(pks)).to_owned();
+    E::insert(model.into_active_model()).on_conflict(on_conflict).exec_without_returning(conn).await?;
+    Ok(())
+}
+
+async fn insert_dead_letter<C: ConnectionTrait>(conn: &C, task_name: &str, target_table: &str, payload: String, err: &anyhow::Error) -> anyhow::Result<()> {
+    let letter = task_dead_letter::ActiveModel {
+        id: ActiveValue::NotSet,
+        task_name: ActiveValue::Set(task_name.to_string()),
+        target_table: ActiveValue::Set(target_table.to_string()),
+        payload: ActiveValue::Set(payload),
+        error: ActiveValue::Set(truncate_error(err)),
+        status: ActiveValue::Set(DEAD_LETTER_PENDING.to_string()),
+        retry_count: ActiveValue::Set(0),
+        created_at: ActiveValue::Set(Local::now().format("%Y-%m-%d %H:%M:%S").to_string()),
+        retried_at: ActiveValue::Set(None),
+    };
+    task_dead_letter::Entity::insert(letter).exec_without_returning(conn).await?;
+    Ok(())
+}
+
+/// 截断到 `task_dead_letter.error` 的长度
+pub fn truncate_error(err: &anyhow::Error) -> String {
+    format!("{:#}", err).chars().take(MAX_ERROR_CHARS).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_util;
+    use entity::sea_orm::DatabaseConnection;
+    use entity::stock;
+
+    fn stock(ts_code: &str, name: Option<&str>) -> stock::Model {
+        stock::Model { name: name.map(str::to_string), ..test_util::stock(ts_code) }
+    }
+
+    async fn memory_db() -> DatabaseConnection {
+        let conn = test_util::memory_db().await;
+        test_util::create_table(&conn, task_dead_letter::Entity).await;
+        // name 设为 NOT NULL, 模拟数据库拒绝名称缺失的行
+        conn.execute_unprepared(
+            "CREATE TABLE stock (ts_code TEXT PRIMARY KEY, symbol TEXT NOT NULL, name TEXT NOT NULL, area TEXT, industry TEXT, \
+             fullname TEXT, enname TEXT, cnspell TEXT, market TEXT, exchange TEXT, curr_type TEXT, list_status TEXT, list_date TEXT, \
+             delist_date TEXT, is_hs TEXT, act_name TEXT, act_ent_type TEXT, name_py TEXT)",
+        )
+        .await
+        .unwrap();
+        conn
+    }
+
+    #[tokio::test]
+    async fn test_bad_row_dead_lettered() {
+        let conn = memory_db().await;
+        let stocks = vec![stock("000001.SZ", Some("平安银行")), stock("000002.SZ", None), stock("600000.SH", Some("浦发银行"))];
+        let summary = upsert_or_dead_letter::<stock::Entity, _>(&conn, "FetchStockListTask", stocks, &[stock::Column::TsCode]).await;
+        assert_eq!(summary, UpsertSummary { written: 2, dead_lettered: 1 });
+
+        let written: Vec<String> = stock::Entity::find().all(&conn).await.unwrap().into_iter().map(|s| s.ts_code).collect();
+        assert_eq!(written, vec!["000001.SZ", "600000.SH"]);
+
+        let letters = task_dead_letter::Entity::find().all(&conn).await.unwrap();
+        assert_eq!(letters.len(), 1);
+        let letter = &letters[0];
+        assert_eq!(letter.task_name, "FetchStockListTask");
+        assert_eq!(letter.target_table, "stock");
+        assert_eq!(letter.status, DEAD_LETTER_PENDING);
+        assert!(letter.error.contains("NOT NULL"));
+        let payload: stock::Model = serde_json::from_str(&letter.payload).unwrap();
+        assert_eq!(payload, stock("000002.SZ", None));
+    }
+}
@@ -1,3 +1,13 @@
 pub mod conflict_helper;
+mod bounded;
+mod reconnect;
+mod query;
+mod dead_letter;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
 
 pub use conflict_helper::*;
+pub use bounded::{max_rows, set_max_rows, BoundedSelect, BoundedSelectExt, DEFAULT_MAX_ROWS};
+pub use reconnect::{is_connection_error, with_reconnect, ReconnectableConnection};
+pub use query::{find_between, find_latest_n};
+pub use dead_letter::{truncate_error, upsert_one, upsert_or_dead_letter, UpsertSummary, DEAD_LETTER_PENDING, DEAD_LETTER_RETRIED};
@@ -0,0 +1,137 @@
+use entity::sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, Order, QueryFilter, QueryOrder, QuerySelect};
+
+use super::bounded::BoundedSelectExt;
+
+/// 查询单只证券在 [start, end] 内的数据并按交易日排序
+///
+/// `start`/`end` 为 YYYYMMDD 格式, 与 `date_col` 中的格式一致; 结果超过全局行数上限时返回错误
+///
+/// # Example
+/// ```rust,ignore
+/// let prices = find_between(
+///     conn,
+///     stock_daily::Column::TsCode,
+///     stock_daily::Column::TradeDate,
+///     "000001.SZ",
+///     "20240101",
+///     "20241231",
+///     Order::Desc,
+/// ).await?;
+/// ```
+pub async fn find_between<E>(
+    conn: &DatabaseConnection,
+    ts_code_col: E::Column,
+    date_col: E::Column,
+    ts_code: &str,
+    start: &str,
+    end: &str,
+    order: Order,
+) -> anyhow::Result<Vec<E::Model>>
+where
+    E: EntityTrait,
+{
+    let models = E::find()
+        .filter(ColumnTrait::eq(&ts_code_col, ts_code))
+        .filter(date_col.gte(start))
+        .filter(date_col.lte(end))
+        .order_by(date_col, order)
+        .bounded()
+        .all(conn)
+        .await?;
+    Ok(models)
+}
+
+/// 查询单只证券最近 `n` 条数据, 按交易日正序返回
+///
+/// 只需要最近若干根K线的指标(如 RSI(14))用它代替加载全部历史, 数据不足 `n` 条时返回全部;
+/// `n` 同样受全局行数上限约束
+///
+/// # Example
+/// ```rust,ignore
+/// let prices = find_latest_n::<stock_daily::Entity>(conn, stock_daily::Column::TsCode, stock_daily::Column::TradeDate, "000001.SZ", 30).await?;
+/// ```
+pub async fn find_latest_n<E>(
+    conn: &DatabaseConnection,
+    ts_code_col: E::Column,
+    date_col: E::Column,
+    ts_code: &str,
+    n: usize,
+) -> anyhow::Result<Vec<E::Model>>
+where
+    E: EntityTrait,
+{
+    let mut models = E::find()
+        .filter(ColumnTrait::eq(&ts_code_col, ts_code))
+        .order_by_desc(date_col)
+        .limit(n as u64)
+        .bounded()
+        .all(conn)
+        .await?;
+    models.reverse();
+    Ok(models)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use entity::fund_daily;
+    use crate::db::test_util;
+    use entity::sea_orm::IntoActiveModel;
+    use entity::sea_orm::prelude::Decimal;
+
+    fn fund(ts_code: &str, trade_date: &str) -> fund_daily::Model {
+        fund_daily::Model {
+            ts_code: ts_code.to_string(),
+            trade_date: trade_date.to_string(),
+            open: Decimal::ONE,
+            high: Decimal::ONE,
+            low: Decimal::ONE,
+            close: Decimal::ONE,
+            pre_close: None,
+            change: None,
+            pct_chg: None,
+            vol: Decimal::ZERO,
+            amount: Decimal::ZERO,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_find_between() {
+        let conn = test_util::memory_db().await;
+        test_util::create_table(&conn, fund_daily::Entity).await;
+        let funds = vec![
+            fund("510300.SH", "20240102"),
+            fund("510300.SH", "20240103"),
+            fund("510300.SH", "20240104"),
+            fund("510300.SH", "20240105"),
+            fund("510500.SH", "20240103"),
+        ];
+        fund_daily::Entity::insert_many(funds.into_iter().map(IntoActiveModel::into_active_model)).exec(&conn).await.unwrap();
+
+        let dates = |models: Vec<fund_daily::Model>| models.into_iter().map(|m| m.trade_date).collect::<Vec<_>>();
+        let desc = find_between::<fund_daily::Entity>(&conn, fund_daily::Column::TsCode, fund_daily::Column::TradeDate, "510300.SH", "20240103", "20240104", Order::Desc).await.unwrap();
+        assert_eq!(dates(desc), vec!["20240104", "20240103"]);
+        let asc = find_between::<fund_daily::Entity>(&conn, fund_daily::Column::TsCode, fund_daily::Column::TradeDate, "510300.SH", "20240101", "20241231", Order::Asc).await.unwrap();
+        assert_eq!(dates(asc), vec!["20240102", "20240103", "20240104", "20240105"]);
+    }
+
+    #[tokio::test]
+    async fn test_find_latest_n() {
+        let conn = test_util::memory_db().await;
+        test_util::create_table(&conn, fund_daily::Entity).await;
+        let funds = vec![
+            fund("510300.SH", "20240104"),
+            fund("510300.SH", "20240102"),
+            fund("510300.SH", "20240105"),
+            fund("510300.SH", "20240103"),
+            fund("510500.SH", "20240108"),
+        ];
+        fund_daily::Entity::insert_many(funds.into_iter().map(IntoActiveModel::into_active_model)).exec(&conn).await.unwrap();
+
+        let dates = |models: Vec<fund_daily::Model>| models.into_iter().map(|m| m.trade_date).collect::<Vec<_>>();
+        let latest = find_latest_n::<fund_daily::Entity>(&conn, fund_daily::Column::TsCode, fund_daily::Column::TradeDate, "510300.SH", 3).await.unwrap();
+        assert_eq!(dates(latest), vec!["20240103", "20240104", "20240105"]);
+        let all = find_latest_n::<fund_daily::Entity>(&conn, fund_daily::Column::TsCode, fund_daily::Column::TradeDate, "510300.SH", 10).await.unwrap();
+        assert_eq!(all.len(), 4);
+    }
+}
@@ -0,0 +1,152 @@
+use std::future::Future;
+use std::pin::Pin;
+
+use entity::sea_orm::sqlx;
+use entity::sea_orm::{ConnectOptions, Database, DatabaseConnection, DbErr, RuntimeErr};
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+type ConnectFuture = Pin<Box<dyn Future<Output = Result<DatabaseConnection, DbErr>> + Send>>;
+type Connector = fn(ConnectOptions) -> ConnectFuture;
+
+/// 可自动重连的数据库连接，保存建立连接时使用的 `ConnectOptions`，
+/// 连接断开（如 MySQL wait_timeout）后可用同样的参数重新建立连接
+pub struct ReconnectableConnection {
+    options: ConnectOptions,
+    conn: RwLock<DatabaseConnection>,
+    connector: Connector,
+}
+
+impl ReconnectableConnection {
+    /// 使用给定的参数建立连接
+    pub async fn connect(options: ConnectOptions) -> anyhow::Result<Self> {
+        let conn = Database::connect(options.clone()).await?;
+        Ok(Self::from_parts(options, conn, default_connector))
+    }
+
+    /// 包装一个已建立的连接，`options` 用于之后的重连
+    pub fn new(options: ConnectOptions, conn: DatabaseConnection) -> Self {
+        Self::from_parts(options, conn, default_connector)
+    }
+
+    fn from_parts(options: ConnectOptions, conn: DatabaseConnection, connector: Connector) -> Self {
+        Self {
+            options,
+            conn: RwLock::new(conn),
+            connector,
+        }
+    }
+
+    /// 当前连接（`DatabaseConnection` 内部是连接池，clone 开销很小）
+    pub async fn get(&self) -> DatabaseConnection {
+        self.conn.read().await.clone()
+    }
+
+    /// 使用保存的 `ConnectOptions` 重新建立连接并替换当前连接
+    pub async fn reconnect(&self) -> anyhow::Result<()> {
+        let mut guard = self.conn.write().await;
+        let conn = (self.connector)(self.options.clone()).await?;
+        *guard = conn;
+        info!("database reconnected");
+        Ok(())
+    }
+}
+
+fn default_connector(options: ConnectOptions) -> ConnectFuture {
+    Box::pin(Database::connect(options))
+}
+
+/// 执行查询，若因连接断开失败则重连后重试一次，其它错误直接返回
+///
+/// # Example
+/// ```ignore
+/// let stocks = db::with_reconnect(&conn, |db| async move {
+///     stock::Entity::find().all(&db).await
+/// }).await?;
+/// ```
+pub async fn with_reconnect<T, F, Fut>(conn: &ReconnectableConnection, query: F) -> anyhow::Result<T>
+where
+    F: Fn(DatabaseConnection) -> Fut,
+    Fut: Future<Output = Result<T, DbErr>>,
+{
+    match query(conn.get().await).await {
+        Ok(data) => Ok(data),
+        Err(err) if is_connection_error(&err) => {
+            warn!("database connection lost, reconnecting, error: {:?}", err);
+            conn.reconnect().await?;
+            Ok(query(conn.get().await).await?)
+        }
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// 判断是否为连接类错误（连接断开、连接池关闭/超时等）
+pub fn is_connection_error(err: &DbErr) -> bool {
+    match err {
+        DbErr::Conn(_) | DbErr::ConnectionAcquire(_) => true,
+        DbErr::Exec(e) | DbErr::Query(e) => is_runtime_connection_error(e),
+        _ => false,
+    }
+}
+
+fn is_runtime_connection_error(err: &RuntimeErr) -> bool {
+    match err {
+        RuntimeErr::SqlxError(e) => matches!(
+            e,
+            sqlx::Error::Io(_) | sqlx::Error::PoolClosed | sqlx::Error::PoolTimedOut | sqlx::Error::WorkerCrashed
+        ) || is_connection_message(&e.to_string()),
+        RuntimeErr::Internal(msg) => is_connection_message(msg),
+    }
+}
+
+/// MySQL 2006 / 2013 等错误只能通过错误信息识别
+fn is_connection_message(msg: &str) -> bool {
+    let msg = msg.to_lowercase();
+    ["server has gone away", "lost connection", "broken pipe", "connection reset", "disconnected"]
+        .iter()
+        .any(|pattern| msg.contains(pattern))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    static RECONNECTS: AtomicUsize = AtomicUsize::new(0);
+
+    fn counting_connector(_options: ConnectOptions) -> ConnectFuture {
+        RECONNECTS.fetch_add(1, Ordering::SeqCst);
+        Box::pin(async { Ok(DatabaseConnection::Disconnected) })
+    }
+
+    #[tokio::test]
+    async fn test_with_reconnect_after_dropped_connection() {
+        let conn = ReconnectableConnection::from_parts(
+            ConnectOptions::new("mysql://localhost/test"),
+            DatabaseConnection::Disconnected,
+            counting_connector,
+        );
+        let attempts = AtomicUsize::new(0);
+        let result = with_reconnect(&conn, |_db| {
+            let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+            async move {
+                if attempt == 0 {
+                    Err(DbErr::Query(RuntimeErr::Internal("MySQL server has gone away".into())))
+                } else {
+                    Ok(42)
+                }
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(result, 42);
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+        assert_eq!(RECONNECTS.load(Ordering::SeqCst), 1);
+
+        // 非连接错误不重连也不重试
+        let err = with_reconnect(&conn, |_db| async { Err::<i32, _>(DbErr::RecordNotFound("stock".into())) }).await;
+        assert!(err.is_err());
+        assert_eq!(RECONNECTS.load(Ordering::SeqCst), 1);
+    }
+}
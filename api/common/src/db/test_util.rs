@@ -0,0 +1,73 @@
+//! 单元测试用的内存数据库(sqlite), 其他 crate 通过 `test-util` feature 在测试中使用
+
+use entity::sea_orm::sea_query::{ColumnType, Table, TableCreateStatement};
+use entity::sea_orm::{ConnectOptions, ConnectionTrait, Database, DatabaseConnection, EntityTrait, Schema};
+use entity::stock;
+
+/// sea-query 的 sqlite 后端允许的 DECIMAL 最大精度
+const SQLITE_MAX_DECIMAL_PRECISION: u32 = 16;
+
+/// 创建一个空的内存数据库, 连接池只保留一个连接(每个 sqlite 内存连接都是独立的库)
+///
+/// 关闭外键检查, 测试数据可以按任意顺序写入
+pub async fn memory_db() -> DatabaseConnection {
+    let mut opt = ConnectOptions::new("sqlite::memory:");
+    opt.max_connections(1).min_connections(1).sqlx_logging(false);
+    let conn = Database::connect(opt).await.expect("failed to open sqlite memory db");
+    conn.execute_unprepared("PRAGMA foreign_keys = OFF").await.expect("failed to disable foreign keys");
+    conn
+}
+
+/// 按实体定义建表
+///
+/// 精度超过 16 的 DECIMAL 列(如 `DECIMAL(20, 6)`)在 sqlite 中按 16 位精度建表, 小数位不变
+pub async fn create_table<E: EntityTrait>(conn: &DatabaseConnection, entity: E) {
+    let backend = conn.get_database_backend();
+    let stmt = clamp_decimal_precision(Schema::new(backend).create_table_from_entity(entity));
+    conn.execute(backend.build(&stmt)).await.expect("failed to create table");
+}
+
+fn clamp_decimal_precision(stmt: TableCreateStatement) -> TableCreateStatement {
+    let mut table = Table::create();
+    if let Some(name) = stmt.get_table_name() {
+        table.table(name.clone());
+    }
+    for column in stmt.get_columns() {
+        let mut column = column.clone();
+        if let Some(ColumnType::Decimal(Some((precision, scale)))) = column.get_column_type().cloned() {
+            column.decimal_len(precision.min(SQLITE_MAX_DECIMAL_PRECISION), scale);
+        }
+        table.col(column);
+    }
+    for index in stmt.get_indexes() {
+        table.index(&mut index.clone());
+    }
+    for foreign_key in stmt.get_foreign_key_create_stmts() {
+        table.foreign_key(&mut foreign_key.clone());
+    }
+    table
+}
+
+/// 只有代码的股票, 其余字段为空, 需要的字段用结构体更新语法补充
+pub fn stock(ts_code: &str) -> stock::Model {
+    stock::Model {
+        ts_code: ts_code.to_string(),
+        symbol: ts_code.split('.').next().unwrap_or(ts_code).to_string(),
+        name: None,
+        area: None,
+        industry: None,
+        fullname: None,
+        enname: None,
+        cnspell: None,
+        market: None,
+        exchange: None,
+        curr_type: None,
+        list_status: None,
+        list_date: None,
+        delist_date: None,
+        is_hs: None,
+        act_name: None,
+        act_ent_type: None,
+        name_py: None,
+    }
+}
@@ -23,8 +23,54 @@ pub fn pct_chg(begin: f64, end: f64) -> f64 {
     100f64*(end - begin) / begin
 }
 
+/// 最大回撤区间, 下标对应传入的价格序列
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Drawdown {
+    pub peak: usize,             // 回撤开始的高点
+    pub trough: usize,           // 回撤最低点
+    pub drawdown_pct: f64,       // 回撤幅度 x%100, 为正数
+    pub recovery: Option<usize>, // 最低点之后第一次回到高点价格, 未修复为 None
+}
+
+/// 计算最大回撤
+/// # Arguments
+/// - `prices` 价格, 日期按正序排序
+///
+/// 价格持续上涨(没有回撤)或为空时返回 None
+pub fn max_drawdown(prices: &[f64]) -> Option<Drawdown> {
+    let mut peak = 0;
+    let mut max: Option<(usize, usize, f64)> = None;
+    for (i, price) in prices.iter().enumerate() {
+        if *price > prices[peak] {
+            peak = i;
+            continue;
+        }
+        let drawdown = -pct_chg(prices[peak], *price);
+        if drawdown > 0f64 && max.is_none_or(|(_, _, max)| drawdown > max) {
+            max = Some((peak, i, drawdown));
+        }
+    }
+    let (peak, trough, drawdown_pct) = max?;
+    let recovery = (trough + 1..prices.len()).find(|i| prices[*i] >= prices[peak]);
+    Some(Drawdown { peak, trough, drawdown_pct, recovery })
+}
+
 #[cfg(test)]
 mod tests {
+    use super::*;
+
+    #[test]
+    fn test_max_drawdown() {
+        assert_eq!(max_drawdown(&[]), None);
+        assert_eq!(max_drawdown(&[1f64, 2f64, 3f64]), None);
+
+        let drawdown = max_drawdown(&[10f64, 12f64, 9f64, 11f64, 6f64, 8f64, 12f64, 13f64]).unwrap();
+        assert_eq!((drawdown.peak, drawdown.trough, drawdown.recovery), (1, 4, Some(6)));
+        assert!((drawdown.drawdown_pct - 50f64).abs() < 1e-9);
+
+        let drawdown = max_drawdown(&[10f64, 8f64, 9f64]).unwrap();
+        assert_eq!(drawdown.recovery, None);
+    }
 
     #[test]
     fn test_ma() {
@@ -1,3 +1,8 @@
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
+
 pub struct InvestmentPrice {
     pub ts_code: String,
     pub pct_chg: f64,
@@ -6,25 +11,110 @@ pub struct InvestmentPrice {
 }
 
 pub fn is_price_limitup(stock: &InvestmentPrice) -> bool {
-    let tscode = &stock.ts_code;
-    let pct_chg = stock.pct_chg;
-    let limitup: f64 = if tscode.ends_with("BJ") {
-        30f64
-    } else if tscode.starts_with("688") {
-        20f64
-    } else if tscode.starts_with("300") {
-        20f64
-    } else {
-        10f64
-    };
-    let delta = pct_chg - limitup;
+    let limitup = Board::from_ts_code(&stock.ts_code).limit_pct();
+    let delta = stock.pct_chg - limitup;
     delta.abs() < 0.01 && stock.close == stock.high
 }
 
+/// 上市板块
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Board {
+    Main,    // 主板
+    ChiNext, // 创业板 300/301
+    Star,    // 科创板 688/689
+    Bse,     // 北交所
+}
+
+impl Board {
+    pub fn from_ts_code(ts_code: &str) -> Self {
+        if ts_code.ends_with(".BJ") {
+            Self::Bse
+        } else if ts_code.starts_with("300") || ts_code.starts_with("301") {
+            Self::ChiNext
+        } else if ts_code.starts_with("688") || ts_code.starts_with("689") {
+            Self::Star
+        } else {
+            Self::Main
+        }
+    }
+
+    /// 涨跌幅限制 x%100, 不考虑 ST 股(5%)及新股上市初期不设涨跌幅的情况
+    pub fn limit_pct(&self) -> f64 {
+        match self {
+            Self::Main => 10f64,
+            Self::ChiNext | Self::Star => 20f64,
+            Self::Bse => 30f64,
+        }
+    }
+}
+
+impl FromStr for Board {
+    type Err = anyhow::Error;
+
+    /// 不区分大小写, 如 `main` / `ChiNext` / `star` / `bse`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "main" => Ok(Self::Main),
+            "chinext" => Ok(Self::ChiNext),
+            "star" => Ok(Self::Star),
+            "bse" => Ok(Self::Bse),
+            _ => Err(anyhow!("Unknown board: {}", s)),
+        }
+    }
+}
+
+/// 按昨收和所属板块的涨跌幅限制计算 (涨停价, 跌停价), 四舍五入到分
+pub fn limit_prices(ts_code: &str, pre_close: f64) -> (f64, f64) {
+    let limit = Board::from_ts_code(ts_code).limit_pct() / 100f64;
+    let round = |v: f64| (v * 100f64).round() / 100f64;
+    (round(pre_close * (1f64 + limit)), round(pre_close * (1f64 - limit)))
+}
+
+/// 收盘价是否为涨停价
+pub fn is_limit_up_close(ts_code: &str, pre_close: f64, close: f64) -> bool {
+    (close - limit_prices(ts_code, pre_close).0).abs() < 0.001
+}
+
+/// 收盘价是否为跌停价
+pub fn is_limit_down_close(ts_code: &str, pre_close: f64, close: f64) -> bool {
+    (close - limit_prices(ts_code, pre_close).1).abs() < 0.001
+}
+
+/// 是否为 ST 股票(名称以 ST / *ST / SST / S*ST 开头)
+pub fn is_st_name(name: &str) -> bool {
+    ["ST", "*ST", "SST", "S*ST"].iter().any(|prefix| name.starts_with(prefix))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_board_and_st() {
+        assert_eq!(Board::from_ts_code("600000.SH"), Board::Main);
+        assert_eq!(Board::from_ts_code("301236.SZ"), Board::ChiNext);
+        assert_eq!(Board::from_ts_code("688981.SH"), Board::Star);
+        assert_eq!(Board::from_ts_code("430047.BJ"), Board::Bse);
+        assert_eq!("STAR".parse::<Board>().unwrap(), Board::Star);
+        assert!("sh".parse::<Board>().is_err());
+
+        assert!(is_st_name("ST华仪"));
+        assert!(is_st_name("*ST左江"));
+        assert!(is_st_name("S*ST前锋"));
+        assert!(!is_st_name("平安银行"));
+        assert!(!is_st_name("TCL科技"));
+    }
+
+    #[test]
+    fn test_limit_prices_by_board() {
+        assert_eq!(limit_prices("600000.SH", 10.05), (11.06, 9.05));
+        assert_eq!(limit_prices("301236.SZ", 10.0), (12.0, 8.0));
+        assert_eq!(limit_prices("430047.BJ", 10.0), (13.0, 7.0));
+        assert!(is_limit_up_close("688981.SH", 50.0, 60.0));
+        assert!(!is_limit_up_close("600000.SH", 50.0, 54.99));
+        assert!(is_limit_down_close("000001.SZ", 10.05, 9.05));
+    }
+
     #[test]
     fn test_is_price_limitup() {
         // Arrange
@@ -0,0 +1,103 @@
+//! Divergence detection module
+//!
+//! Finds disagreements between price and an oscillator at swing points.
+
+use super::{rsi, IndicatorError, IndicatorResult};
+
+/// A bullish divergence: price makes a lower low while RSI makes a higher low
+#[derive(Debug, Clone, PartialEq)]
+pub struct BullishDivergence {
+    /// Index of the earlier swing low in the input series
+    pub prev_index: usize,
+    /// Index of the later swing low in the input series
+    pub index: usize,
+    pub prev_price: f64,
+    pub price: f64,
+    pub prev_rsi: f64,
+    pub rsi: f64,
+}
+
+impl BullishDivergence {
+    /// RSI points gained between the two lows; larger means a stronger divergence
+    pub fn strength(&self) -> f64 {
+        self.rsi - self.prev_rsi
+    }
+
+    /// Index at which the later swing low is confirmed (`pivot_window` bars after it)
+    pub fn confirmed_index(&self, pivot_window: usize) -> usize {
+        self.index + pivot_window
+    }
+}
+
+/// Detects bullish RSI divergences between consecutive swing lows
+///
+/// A swing low is a close that is the lowest within `pivot_window` bars on each side,
+/// so a low is only known `pivot_window` bars after it happens.
+///
+/// # Arguments
+/// * `closes` - Close prices in chronological order
+/// * `rsi_period` - RSI period (typically 14)
+/// * `pivot_window` - Bars required on each side of a swing low
+pub fn bullish_rsi_divergences(closes: &[f64], rsi_period: usize, pivot_window: usize) -> IndicatorResult<Vec<BullishDivergence>> {
+    if pivot_window == 0 {
+        return Err(IndicatorError::InvalidParameter("Pivot window must be at least 1".to_string()));
+    }
+    let rsi_values = rsi(closes, rsi_period)?;
+    // rsi_values[0] corresponds to closes[rsi_period]
+    let rsi_at = |i: usize| i.checked_sub(rsi_period).and_then(|j| rsi_values.get(j).copied());
+
+    let lows: Vec<usize> = (pivot_window..closes.len().saturating_sub(pivot_window))
+        .filter(|&i| rsi_at(i).is_some())
+        .filter(|&i| {
+            let window = &closes[i - pivot_window..=i + pivot_window];
+            window.iter().all(|v| closes[i] <= *v)
+                && closes[i - pivot_window..i].iter().all(|v| closes[i] < *v)
+        })
+        .collect();
+
+    let divergences = lows
+        .windows(2)
+        .filter_map(|pair| {
+            let (prev_index, index) = (pair[0], pair[1]);
+            let (prev_rsi, rsi) = (rsi_at(prev_index)?, rsi_at(index)?);
+            (closes[index] < closes[prev_index] && rsi > prev_rsi).then(|| BullishDivergence {
+                prev_index,
+                index,
+                prev_price: closes[prev_index],
+                price: closes[index],
+                prev_rsi,
+                rsi,
+            })
+        })
+        .collect();
+    Ok(divergences)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bullish_rsi_divergence() {
+        // Sharp drop to a low, bounce, then a slow grind to a slightly lower low
+        let mut closes: Vec<f64> = vec![100.0; 5];
+        closes.extend([96.0, 92.0, 88.0, 84.0, 80.0]);
+        closes.extend([83.0, 86.0, 89.0, 90.0]);
+        closes.extend([89.5, 89.0, 88.0, 87.0, 86.0, 85.0, 84.0, 83.0, 82.0, 81.0, 80.5, 79.5]);
+        closes.extend([81.0, 82.5, 84.0]);
+
+        let divergences = bullish_rsi_divergences(&closes, 4, 3).unwrap();
+        assert_eq!(divergences.len(), 1);
+        let divergence = &divergences[0];
+        assert_eq!((divergence.prev_index, divergence.index), (9, 25));
+        assert!(divergence.price < divergence.prev_price);
+        assert!(divergence.strength() > 0.0);
+        assert_eq!(divergence.confirmed_index(3), closes.len() - 1);
+    }
+
+    #[test]
+    fn test_no_divergence_when_rsi_confirms_low() {
+        let closes: Vec<f64> = (0..30).map(|i| 100.0 - i as f64).collect();
+        assert!(bullish_rsi_divergences(&closes, 4, 3).unwrap().is_empty());
+    }
+}
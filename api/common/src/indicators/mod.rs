@@ -6,11 +6,13 @@
 //! - Momentum indicators (RSI, MACD, KDJ, WR, CCI, STOCH)
 //! - Volatility indicators (ATR, BOLL)
 //! - Volume indicators (OBV)
+//! - Divergence detection (RSI bullish divergence)
 
 pub mod trend;
 pub mod momentum;
 pub mod volatility;
 pub mod volume;
+pub mod divergence;
 pub mod examples;
 
 /// Common error type for technical indicators
@@ -45,10 +47,15 @@ pub trait Indicator {
 }
 
 // Re-export commonly used types for convenience
-pub use trend::{SMA, EMA, SAR};
-pub use momentum::{RSI, MACD, KDJ};
+pub use trend::{SMA, EMA, EmaSeed, SAR};
+pub use momentum::{RSI, RsiMethod, MACD, KDJ};
 pub use volatility::{ATR, BollingerBands};
 pub use volume::OBV;
+pub use divergence::{bullish_rsi_divergences, BullishDivergence};
+
+use serde::Serialize;
+
+use crate::data_type::Ohlcv;
 
 /// Convenience functions for quick indicator calculations
 /// These functions provide a simple API for common use cases
@@ -94,6 +101,8 @@ pub fn ma(prices: &[f64], period: usize) -> IndicatorResult<Vec<f64>> {
 
 /// Calculate Exponential Moving Average for a price series
 /// 
+/// The EMA is SMA seeded (TA-Lib convention), so the result has `prices.len() - period + 1` values.
+/// 
 /// # Arguments
 /// * `prices` - Price data slice
 /// * `period` - EMA period
@@ -391,6 +400,85 @@ pub fn obv(closes: &[f64], volumes: &[f64]) -> IndicatorResult<Vec<f64>> {
     Ok(results)
 }
 
+/// Calculate ATR from OHLCV bars, same as [`atr`] without splitting the bars into separate slices
+///
+/// # Example
+/// ```ignore
+/// use common::data_type::Ohlcv;
+/// use common::indicators::atr_ohlcv;
+/// let bars: Vec<Ohlcv> = dailies.iter().map(Ohlcv::from).collect();
+/// let atr_values = atr_ohlcv(&bars, 14).unwrap();
+/// ```
+pub fn atr_ohlcv(bars: &[Ohlcv], period: usize) -> IndicatorResult<Vec<f64>> {
+    let (highs, lows, closes) = split_hlc(bars);
+    atr(&highs, &lows, &closes, period)
+}
+
+/// Calculate KDJ from OHLCV bars, same as [`kdj`]
+pub fn kdj_ohlcv(bars: &[Ohlcv], k_period: usize, d_period: usize, j_period: usize)
+    -> IndicatorResult<Vec<(f64, f64, f64)>> {
+    let (highs, lows, closes) = split_hlc(bars);
+    kdj(&highs, &lows, &closes, k_period, d_period, j_period)
+}
+
+/// Calculate SAR from OHLCV bars, same as [`sar`]
+pub fn sar_ohlcv(bars: &[Ohlcv], acceleration: f64, max_acceleration: f64) -> IndicatorResult<Vec<f64>> {
+    let highs: Vec<f64> = bars.iter().map(|b| b.high).collect();
+    let lows: Vec<f64> = bars.iter().map(|b| b.low).collect();
+    sar(&highs, &lows, acceleration, max_acceleration)
+}
+
+/// Calculate OBV from OHLCV bars, same as [`obv`]
+pub fn obv_ohlcv(bars: &[Ohlcv]) -> IndicatorResult<Vec<f64>> {
+    let closes: Vec<f64> = bars.iter().map(|b| b.close).collect();
+    let volumes: Vec<f64> = bars.iter().map(|b| b.volume).collect();
+    obv(&closes, &volumes)
+}
+
+/// Direction of a fast line crossing a slow line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Cross {
+    /// Fast line crosses above the slow line
+    Golden,
+    /// Fast line crosses below the slow line
+    Death,
+}
+
+/// Detect whether `fast` crossed `slow` on the last bar
+///
+/// The two series may have different lengths (e.g. MA5 and MA20); they are aligned at their
+/// last element. Touching the slow line on the previous bar counts as being on the other side.
+///
+/// # Example
+/// ```
+/// use common::indicators::{last_cross, Cross};
+/// assert_eq!(last_cross(&[1.0, 3.0], &[2.0, 2.0]), Some(Cross::Golden));
+/// assert_eq!(last_cross(&[3.0, 1.0], &[2.0, 2.0]), Some(Cross::Death));
+/// assert_eq!(last_cross(&[3.0, 4.0], &[2.0, 2.0]), None);
+/// ```
+pub fn last_cross(fast: &[f64], slow: &[f64]) -> Option<Cross> {
+    if fast.len() < 2 || slow.len() < 2 {
+        return None;
+    }
+    let (fast_prev, fast_cur) = (fast[fast.len() - 2], fast[fast.len() - 1]);
+    let (slow_prev, slow_cur) = (slow[slow.len() - 2], slow[slow.len() - 1]);
+    if fast_prev <= slow_prev && fast_cur > slow_cur {
+        Some(Cross::Golden)
+    } else if fast_prev >= slow_prev && fast_cur < slow_cur {
+        Some(Cross::Death)
+    } else {
+        None
+    }
+}
+
+fn split_hlc(bars: &[Ohlcv]) -> (Vec<f64>, Vec<f64>, Vec<f64>) {
+    let highs = bars.iter().map(|b| b.high).collect();
+    let lows = bars.iter().map(|b| b.low).collect();
+    let closes = bars.iter().map(|b| b.close).collect();
+    (highs, lows, closes)
+}
+
 /// Builder pattern for creating indicator combinations
 /// 
 /// # Example
@@ -490,7 +578,8 @@ mod tests {
         
         // Test EMA
         let ema_values = ema(&prices, 3).unwrap();
-        assert_eq!(ema_values.len(), 10);
+        assert_eq!(ema_values.len(), 8); // SMA seeded
+        assert_relative_eq!(ema_values[0], 2.0);
         
         // Test RSI
         let rsi_values = rsi(&prices, 3).unwrap();
@@ -513,4 +602,32 @@ mod tests {
         // Should have results for the last few updates
         assert!(!all_results.is_empty());
     }
+
+    #[test]
+    fn test_ohlcv_matches_slices() {
+        let highs = vec![10.5, 11.0, 11.2, 10.8, 11.5, 11.8];
+        let lows = vec![10.0, 10.3, 10.8, 10.2, 10.9, 11.1];
+        let closes = vec![10.2, 10.8, 11.0, 10.5, 11.2, 11.6];
+        let volumes = vec![1000.0, 1500.0, 800.0, 2000.0, 1200.0, 900.0];
+        let bars: Vec<Ohlcv> = (0..closes.len())
+            .map(|i| Ohlcv { open: closes[i], high: highs[i], low: lows[i], close: closes[i], volume: volumes[i] })
+            .collect();
+
+        assert_eq!(atr_ohlcv(&bars, 3).unwrap(), atr(&highs, &lows, &closes, 3).unwrap());
+        assert_eq!(kdj_ohlcv(&bars, 3, 3, 3).unwrap(), kdj(&highs, &lows, &closes, 3, 3, 3).unwrap());
+        assert_eq!(sar_ohlcv(&bars, 0.02, 0.2).unwrap(), sar(&highs, &lows, 0.02, 0.2).unwrap());
+        assert_eq!(obv_ohlcv(&bars).unwrap(), obv(&closes, &volumes).unwrap());
+    }
+
+    #[test]
+    fn test_last_cross() {
+        let slow = vec![5.0, 5.0, 5.0, 5.0];
+        assert_eq!(last_cross(&[4.0, 6.0], &slow), Some(Cross::Golden));
+        assert_eq!(last_cross(&[5.0, 6.0], &slow), Some(Cross::Golden));
+        assert_eq!(last_cross(&[6.0, 4.0], &slow), Some(Cross::Death));
+        assert_eq!(last_cross(&[4.0, 4.5], &slow), None);
+        // Crossed on an earlier bar only
+        assert_eq!(last_cross(&[4.0, 6.0, 7.0], &slow), None);
+        assert_eq!(last_cross(&[6.0], &slow), None);
+    }
 }
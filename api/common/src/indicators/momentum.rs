@@ -5,28 +5,48 @@
 use super::{Indicator, IndicatorResult, IndicatorError};
 use std::collections::VecDeque;
 
+/// How RSI averages gains and losses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RsiMethod {
+    /// Plain average of the last `period` gains/losses (Cutler's RSI).
+    /// Only the window matters, so values don't depend on where the series starts.
+    Simple,
+    /// Wilder's smoothing: SMA of the first `period` changes, then `avg = (avg * (period - 1) + x) / period`.
+    /// Used by TA-Lib, StockCharts, TradingView and most Western charting platforms.
+    /// 通达信/同花顺 `SMA(X, N, 1)` is the same recursion, but seeded differently, so early values differ.
+    #[default]
+    Wilder,
+}
+
 /// Relative Strength Index (RSI)
 ///
 /// A momentum oscillator that measures the speed and change of price movements.
 #[derive(Debug, Clone)]
 pub struct RSI {
     period: usize,
-    prices: VecDeque<f64>,
+    method: RsiMethod,
+    changes: VecDeque<f64>,
     avg_gain: f64,
     avg_loss: f64,
     prev_price: Option<f64>,
 }
 
 impl RSI {
-    /// Creates a new RSI indicator with the given period
+    /// Creates a new RSI indicator with the given period, using Wilder's smoothing
     pub fn new(period: usize) -> IndicatorResult<Self> {
+        Self::new_with_method(period, RsiMethod::default())
+    }
+
+    /// Creates a new RSI indicator with the given period and averaging method
+    pub fn new_with_method(period: usize, method: RsiMethod) -> IndicatorResult<Self> {
         if period < 2 {
             return Err(IndicatorError::InvalidParameter("Period must be at least 2".to_string()));
         }
         
         Ok(Self {
             period,
-            prices: VecDeque::with_capacity(period + 1),
+            method,
+            changes: VecDeque::with_capacity(period + 1),
             avg_gain: 0.0,
             avg_loss: 0.0,
             prev_price: None,
@@ -44,23 +64,32 @@ impl Indicator for RSI {
             let gain = if change > 0.0 { change } else { 0.0 };
             let loss = if change < 0.0 { -change } else { 0.0 };
             
-            if self.prices.len() < self.period {
-                self.avg_gain = (self.avg_gain * (self.prices.len() as f64) + gain) / (self.prices.len() as f64 + 1.0);
-                self.avg_loss = (self.avg_loss * (self.prices.len() as f64) + loss) / (self.prices.len() as f64 + 1.0);
+            if self.changes.len() < self.period {
+                self.avg_gain = (self.avg_gain * (self.changes.len() as f64) + gain) / (self.changes.len() as f64 + 1.0);
+                self.avg_loss = (self.avg_loss * (self.changes.len() as f64) + loss) / (self.changes.len() as f64 + 1.0);
             } else {
-                self.avg_gain = (self.avg_gain * (self.period - 1) as f64 + gain) / self.period as f64;
-                self.avg_loss = (self.avg_loss * (self.period - 1) as f64 + loss) / self.period as f64;
+                match self.method {
+                    RsiMethod::Wilder => {
+                        self.avg_gain = (self.avg_gain * (self.period - 1) as f64 + gain) / self.period as f64;
+                        self.avg_loss = (self.avg_loss * (self.period - 1) as f64 + loss) / self.period as f64;
+                    }
+                    RsiMethod::Simple => {
+                        let dropped = self.changes[0];
+                        self.avg_gain += (gain - dropped.max(0.0)) / self.period as f64;
+                        self.avg_loss += (loss - (-dropped).max(0.0)) / self.period as f64;
+                    }
+                }
             }
             
-            self.prices.push_back(price);
-            if self.prices.len() > self.period {
-                self.prices.pop_front();
+            self.changes.push_back(change);
+            if self.changes.len() > self.period {
+                self.changes.pop_front();
             }
         }
         
         self.prev_price = Some(price);
         
-        if self.prices.len() < self.period {
+        if self.changes.len() < self.period {
             return Err(IndicatorError::NotEnoughData);
         }
         
@@ -75,7 +104,7 @@ impl Indicator for RSI {
     }
     
     fn reset(&mut self) {
-        self.prices.clear();
+        self.changes.clear();
         self.avg_gain = 0.0;
         self.avg_loss = 0.0;
         self.prev_price = None;
@@ -94,8 +123,13 @@ pub struct MACD {
 }
 
 impl MACD {
-    /// Creates a new MACD indicator with the given parameters
+    /// Creates a new MACD indicator with the given parameters, EMAs are SMA seeded (TA-Lib convention)
     pub fn new(fast_period: usize, slow_period: usize, signal_period: usize) -> IndicatorResult<Self> {
+        Self::with_seed(fast_period, slow_period, signal_period, EmaSeed::default())
+    }
+
+    /// Creates a new MACD indicator whose EMAs use the given seed convention
+    pub fn with_seed(fast_period: usize, slow_period: usize, signal_period: usize, seed: EmaSeed) -> IndicatorResult<Self> {
         if fast_period >= slow_period {
             return Err(IndicatorError::InvalidParameter(
                 "Fast period must be less than slow period".to_string(),
@@ -103,9 +137,9 @@ impl MACD {
         }
         
         Ok(Self {
-            fast_ema: EMA::new(fast_period)?,
-            slow_ema: EMA::new(slow_period)?,
-            signal_ema: EMA::new(signal_period)?,
+            fast_ema: EMA::with_seed(fast_period, seed)?,
+            slow_ema: EMA::with_seed(slow_period, seed)?,
+            signal_ema: EMA::with_seed(signal_period, seed)?,
             initialized: false,
         })
     }
@@ -129,8 +163,10 @@ impl Indicator for MACD {
     type Output = (f64, f64, f64); // (MACD line, Signal line, Histogram)
     
     fn update(&mut self, price: Self::Input) -> IndicatorResult<Self::Output> {
-        let fast_ema = self.fast_ema.update(price)?;
-        let slow_ema = self.slow_ema.update(price)?;
+        // Both EMAs must see every price, even while one of them is still warming up
+        let fast_ema = self.fast_ema.update(price);
+        let slow_ema = self.slow_ema.update(price);
+        let (fast_ema, slow_ema) = (fast_ema?, slow_ema?);
         
         let macd_line = fast_ema - slow_ema;
         let signal_line = self.signal_ema.update(macd_line)?;
@@ -303,20 +339,57 @@ mod tests {
         }
     }
     
+    #[test]
+    fn test_rsi_methods() {
+        // Closes from the StockCharts RSI example; their table rounds intermediate values, so it is ~0.07 off full precision
+        let prices = [
+            44.34, 44.09, 44.15, 43.61, 44.33, 44.83, 45.10, 45.42, 45.84, 46.08, 45.89,
+            46.03, 45.61, 46.28, 46.28, 46.00, 46.03, 46.41, 46.22, 45.64, 46.21, 46.25,
+            45.71, 46.45, 45.78, 45.35, 44.03, 44.18, 44.22, 44.57, 43.42, 42.66, 43.13,
+        ];
+        let wilder = [
+            70.46, 66.25, 66.48, 69.35, 66.29, 57.92, 62.88, 63.21, 56.01, 62.34,
+            54.67, 50.39, 40.02, 41.49, 41.90, 45.50, 37.32, 33.09, 37.79,
+        ];
+        let simple = [
+            70.46, 70.02, 69.83, 80.57, 73.33, 59.81, 62.53, 60.00, 48.48, 53.88,
+            48.95, 43.86, 37.73, 32.26, 32.72, 38.14, 31.75, 25.10, 30.22,
+        ];
+        for (method, expected) in [(RsiMethod::Wilder, wilder), (RsiMethod::Simple, simple)] {
+            let mut rsi = RSI::new_with_method(14, method).unwrap();
+            let values = prices.iter().filter_map(|p| rsi.update(*p).ok()).collect::<Vec<f64>>();
+            assert_eq!(values.len(), expected.len());
+            for (value, expected) in values.iter().zip(expected) {
+                assert_relative_eq!(*value, expected, epsilon = 0.005);
+            }
+        }
+    }
+    
     #[test]
     fn test_macd() {
         let mut macd = MACD::new(12, 26, 9).unwrap();
         
-        // Test with increasing prices
+        // Test with accelerating prices
         for i in 1..50 {
-            let price = i as f64;
-            let (macd_line, signal_line, _) = macd.update(price).unwrap();
+            let price = (i * i) as f64;
+            let result = macd.update(price);
             
-            if i >= 26 { // Need at least slow period to get valid values
-                assert!(macd_line > signal_line); // In an uptrend, MACD should be above signal
+            // Slow EMA needs 26 prices, then the signal EMA needs 9 MACD values: 26 + 9 - 1 = 34
+            if i >= 34 {
+                let (macd_line, signal_line, _) = result.unwrap();
+                assert!(macd_line > signal_line); // MACD keeps rising, so it stays above its signal
+            } else {
+                assert!(result.is_err());
             }
         }
     }
+
+    #[test]
+    fn test_macd_first_value_seed() {
+        let mut macd = MACD::with_seed(12, 26, 9, EmaSeed::FirstValue).unwrap();
+        let (macd_line, signal_line, histogram) = macd.update(10.0).unwrap();
+        assert_eq!((macd_line, signal_line, histogram), (0.0, 0.0, 0.0));
+    }
     
     #[test]
     fn test_kdj() {
@@ -345,5 +418,5 @@ mod tests {
 }
 
 // Import EMA from the trend module
-use super::trend::EMA;
+use super::trend::{EmaSeed, EMA};
 
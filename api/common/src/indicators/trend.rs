@@ -86,20 +86,42 @@ impl Indicator for SMA {
     }
 }
 
+/// How an EMA produces its first value
+///
+/// The seed only matters during warm-up; after enough data points both conventions converge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EmaSeed {
+    /// First value is the SMA of the first `period` inputs, no output before that.
+    /// Used by TA-Lib (default compatibility mode) and StockCharts.
+    #[default]
+    Sma,
+    /// First value is the first input itself, output from the first data point.
+    /// Used by 通达信/同花顺 and pandas `ewm(adjust=False)`.
+    FirstValue,
+}
+
 /// Exponential Moving Average (EMA)
 ///
 /// A type of moving average that places a greater weight on recent data points.
+/// Seeded with an SMA of the first `period` values by default, see [`EmaSeed`].
 #[derive(Debug, Clone)]
 pub struct EMA {
     period: usize,
     multiplier: f64,
+    seed: EmaSeed,
     current: Option<f64>,
-    initialized: bool,
+    warmup_sum: f64,
+    warmup_count: usize,
 }
 
 impl EMA {
-    /// Creates a new EMA indicator with the given period
+    /// Creates a new EMA indicator with the given period, seeded with an SMA (TA-Lib convention)
     pub fn new(period: usize) -> IndicatorResult<Self> {
+        Self::with_seed(period, EmaSeed::default())
+    }
+
+    /// Creates a new EMA indicator with the given period and seed convention
+    pub fn with_seed(period: usize, seed: EmaSeed) -> IndicatorResult<Self> {
         if period < 2 {
             return Err(IndicatorError::InvalidParameter("Period must be at least 2".to_string()));
         }
@@ -107,8 +129,10 @@ impl EMA {
         Ok(Self {
             period,
             multiplier: 2.0 / (period as f64 + 1.0),
+            seed,
             current: None,
-            initialized: false,
+            warmup_sum: 0.0,
+            warmup_count: 0,
         })
     }
     
@@ -123,12 +147,19 @@ impl Indicator for EMA {
     type Output = f64;
     
     fn update(&mut self, input: Self::Input) -> IndicatorResult<Self::Output> {
-        if !self.initialized {
-            self.current = Some(input);
-            self.initialized = true;
-        } else {
-            let prev = self.current.unwrap_or(input);
+        if let Some(prev) = self.current {
             self.current = Some((input - prev) * self.multiplier + prev);
+        } else {
+            match self.seed {
+                EmaSeed::FirstValue => self.current = Some(input),
+                EmaSeed::Sma => {
+                    self.warmup_sum += input;
+                    self.warmup_count += 1;
+                    if self.warmup_count == self.period {
+                        self.current = Some(self.warmup_sum / self.period as f64);
+                    }
+                }
+            }
         }
         
         self.current.ok_or(IndicatorError::NotEnoughData)
@@ -136,7 +167,8 @@ impl Indicator for EMA {
     
     fn reset(&mut self) {
         self.current = None;
-        self.initialized = false;
+        self.warmup_sum = 0.0;
+        self.warmup_count = 0;
     }
 }
 
@@ -261,6 +293,17 @@ mod tests {
     #[test]
     fn test_ema() {
         let mut ema = EMA::new(3).unwrap();
+        assert!(ema.update(1.0).is_err()); // Not enough data
+        assert!(ema.update(2.0).is_err());
+        assert_relative_eq!(ema.update(3.0).unwrap(), 2.0); // SMA seed: (1+2+3)/3
+        assert_relative_eq!(ema.update(4.0).unwrap(), 3.0); // (4-2)*0.5+2
+        ema.reset();
+        assert!(ema.update(1.0).is_err());
+    }
+
+    #[test]
+    fn test_ema_first_value_seed() {
+        let mut ema = EMA::with_seed(3, EmaSeed::FirstValue).unwrap();
         let multiplier = 2.0 / (3.0 + 1.0);
         
         let first = 1.0;
@@ -274,4 +317,24 @@ mod tests {
         let expected = third * multiplier + expected * (1.0 - multiplier);
         assert_relative_eq!(ema.update(third).unwrap(), expected);
     }
+
+    #[test]
+    fn test_ema_matches_talib() {
+        // 10-period EMA, reference values from TA-Lib default mode (same as the StockCharts example), rounded to 2 decimals
+        let prices = [
+            22.27, 22.19, 22.08, 22.17, 22.18, 22.13, 22.23, 22.43, 22.24, 22.29,
+            22.15, 22.39, 22.38, 22.61, 23.36, 24.05, 23.75, 23.83, 23.95, 23.63,
+            23.82, 23.87, 23.65, 23.19, 23.10, 23.33, 22.68, 23.10, 22.40, 22.17,
+        ];
+        let expected = [
+            22.22, 22.21, 22.24, 22.27, 22.33, 22.52, 22.80, 22.97, 23.13, 23.28,
+            23.34, 23.43, 23.51, 23.53, 23.47, 23.40, 23.39, 23.26, 23.23, 23.08, 22.92,
+        ];
+        let mut ema = EMA::new(10).unwrap();
+        let values = prices.iter().filter_map(|p| ema.update(*p).ok()).collect::<Vec<f64>>();
+        assert_eq!(values.len(), expected.len());
+        for (value, expected) in values.iter().zip(expected) {
+            assert_relative_eq!((value * 100.0).round() / 100.0, expected);
+        }
+    }
 }
@@ -1,7 +1,10 @@
-use std::collections::HashMap;
 use anyhow::bail;
 use serde::{Deserialize, Serialize};
-use crate::http;
+
+pub mod provider;
+pub mod usage;
+pub use provider::{LlmProvider, OpenAiCompatibleProvider};
+pub use usage::{usage_report, ModelUsage, UsageReport};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatMessage {
@@ -90,17 +93,44 @@ pub struct USStock {
     pub sector: String,
 }
 
-pub async fn chat(request: &ChatRequest) -> anyhow::Result<ChatResponse>{
-    let request = serde_json::to_string(&request)?;
-    let key = "sk-47b29c3eac324b2a8a137b4a7838a93b";
-    let mut headers = HashMap::new();
-    headers.insert("Content-Type".into(), "application/json".into());
-    headers.insert("Authorization".into(), format!("Bearer {}", key));
-    let res = http::post("https://api.deepseek.com/chat/completions", Some(request), Some(&headers)).await?;
-    Ok(res.json().await?)
+/// 默认的 DeepSeek 接口地址
+pub const DEFAULT_BASE_URL: &str = "https://api.deepseek.com";
+/// 默认模型
+pub const DEFAULT_MODEL: &str = "deepseek-chat";
+
+/// 单次调用的接口覆盖项, 为 `None` 时使用默认值
+///
+/// 可指向自建或其他 OpenAI 兼容的接口, 用于对比不同模型的效果
+#[derive(Debug, Clone, Default)]
+pub struct ChatOptions {
+    /// 接口地址, 如 `http://localhost:8000/v1`, 请求发往 `{base_url}/chat/completions`
+    pub base_url: Option<String>,
+    /// 模型名, 覆盖请求体中的 `model`
+    pub model: Option<String>,
+}
+
+impl ChatOptions {
+    pub fn new(base_url: Option<String>, model: Option<String>) -> Self {
+        Self { base_url, model }
+    }
+
+    fn completions_url(&self) -> String {
+        let base_url = self.base_url.as_deref().unwrap_or(DEFAULT_BASE_URL);
+        format!("{}/chat/completions", base_url.trim_end_matches('/'))
+    }
+}
+
+#[deprecated(note = "use `LlmProvider::chat_completion` (e.g. `OpenAiCompatibleProvider`) instead")]
+pub async fn chat(request: &ChatRequest, options: &ChatOptions) -> anyhow::Result<ChatResponse>{
+    OpenAiCompatibleProvider::new(options.clone()).chat_completion(request).await
 }
 
 pub async fn translate_finance_eng(eng: &str) -> anyhow::Result<String> {
+    translate_finance_eng_with(&OpenAiCompatibleProvider::default(), eng).await
+}
+
+/// 同 [`translate_finance_eng`], 使用指定的 provider
+pub async fn translate_finance_eng_with(provider: &dyn LlmProvider, eng: &str) -> anyhow::Result<String> {
     let req = r#"
         {
         "model": "deepseek-chat",
@@ -111,12 +141,17 @@ pub async fn translate_finance_eng(eng: &str) -> anyhow::Result<String> {
         "stream": false
       }
         "#.replace("{eng}", eng);
-    chat_str_result(&req).await
+    chat_str_result(provider, &req).await
 }
 
 
 
 pub async fn calculate_stock_similarity(cn_stock: &CNStock, us_stock: &USStock) -> anyhow::Result<String> {
+    calculate_stock_similarity_with(&OpenAiCompatibleProvider::default(), cn_stock, us_stock).await
+}
+
+/// 同 [`calculate_stock_similarity`], 使用指定的 provider, 如 `OpenAiCompatibleProvider::new(ChatOptions { .. })` 切换接口和模型
+pub async fn calculate_stock_similarity_with(provider: &dyn LlmProvider, cn_stock: &CNStock, us_stock: &USStock) -> anyhow::Result<String> {
     let cn_symbol = "";
     let cn_main_business = &cn_stock.main_business;
     let cn_business_scope = &cn_stock.business_scope;
@@ -182,12 +217,12 @@ A股：
         "stream": false
       }
         "#.replace("{promote}", &promote);
-    chat_str_result(&req).await
+    chat_str_result(provider, &req).await
 }
 
-async fn chat_str_result(promote: &str) -> anyhow::Result<String> {
+async fn chat_str_result(provider: &dyn LlmProvider, promote: &str) -> anyhow::Result<String> {
     let req = serde_json::from_str::<ChatRequest>(&promote)?;
-    let res = chat(&req).await?;
+    let res = provider.chat_completion(&req).await?;
     res.choices
         .and_then(|c| c.first().cloned())
         .and_then(|choice| choice.message)
@@ -196,13 +231,62 @@ async fn chat_str_result(promote: &str) -> anyhow::Result<String> {
 }
 
 
+#[cfg(test)]
 mod tests {
-    use crate::http;
-    use crate::llm::{chat, ChatRequest, translate_finance_eng};
+    use std::sync::Mutex;
+    use async_trait::async_trait;
+    use crate::llm::{ChatChoice, ChatMessage, ChatRequest, ChatResponse, LlmProvider, Usage, translate_finance_eng, translate_finance_eng_with};
 
     #[tokio::test]
     async fn test() {
       let txt = translate_finance_eng("EVI Industries Inc is a value-added distributor and service provider in the commercial laundry industry. It sells and leases commercial laundry equipment, specializing in washing, drying, finishing, material handling, water heating, power generation, and water reuse applications. The company supports its equipment offerings with installation, maintenance, and repair services through a large network of trained technicians. It serves a wide range of customers, including commercial, industrial, institutional, government, and retail sectors. Geographically, the company serves various countries including United States, Canada, the Caribbean, and Latin America.").await.unwrap();
       println!("{}", txt);
     }
-}
\ No newline at end of file
+
+    /// 记录请求并返回固定回复的 provider
+    struct MockProvider {
+        reply: String,
+        model: String,
+        usage: Option<Usage>,
+        requests: Mutex<Vec<ChatRequest>>,
+    }
+
+    impl MockProvider {
+        fn new(reply: &str, model: &str, usage: Option<Usage>) -> Self {
+            Self { reply: reply.into(), model: model.into(), usage, requests: Mutex::new(vec![]) }
+        }
+    }
+
+    #[async_trait]
+    impl LlmProvider for MockProvider {
+        async fn chat_completion(&self, request: &ChatRequest) -> anyhow::Result<ChatResponse> {
+            self.requests.lock().unwrap().push(request.clone());
+            Ok(ChatResponse {
+                id: None,
+                object: None,
+                created: None,
+                model: Some(self.model.clone()),
+                choices: Some(vec![ChatChoice {
+                    index: Some(0),
+                    message: Some(ChatMessage { content: self.reply.clone(), role: "assistant".into() }),
+                    logprobs: None,
+                    finish_reason: Some("stop".into()),
+                }]),
+                usage: self.usage.clone(),
+                system_fingerprint: None,
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_translate_finance_eng_with_mock_provider() {
+        let provider = MockProvider::new("商业洗衣设备分销商", "deepseek-chat", None);
+        let txt = translate_finance_eng_with(&provider, "commercial laundry distributor").await.unwrap();
+        assert_eq!(txt, "商业洗衣设备分销商");
+
+        let requests = provider.requests.lock().unwrap();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].messages[1].role, "user");
+        assert_eq!(requests[0].messages[1].content, "commercial laundry distributor");
+    }
+}
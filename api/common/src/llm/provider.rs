@@ -0,0 +1,227 @@
+//! LLM 调用抽象
+//!
+//! 上层函数 (`translate_finance_eng`, `calculate_stock_similarity` 等) 只依赖 [`LlmProvider`],
+//! 便于切换接口或在测试中替换为 mock 实现
+
+use std::collections::HashMap;
+use async_trait::async_trait;
+use crate::http;
+use super::{usage, ChatOptions, ChatRequest, ChatResponse};
+
+#[async_trait]
+pub trait LlmProvider: Send + Sync {
+    /// 发送一次非流式的 chat completion 请求, 实现需在返回前用 [`usage::record`] 记录响应中的 token 用量
+    async fn chat_completion(&self, request: &ChatRequest) -> anyhow::Result<ChatResponse>;
+}
+
+/// OpenAI 兼容接口 (默认 DeepSeek)
+///
+/// 各接口的差异:
+/// - DeepSeek: `usage` 额外返回 `prompt_cache_hit_tokens`/`prompt_cache_miss_tokens`, 缓存命中也会写入
+///   `prompt_tokens_details.cached_tokens`
+/// - OpenAI: 响应中 `model` 为带日期的快照名 (如 `gpt-4o-mini-2024-07-18`), 与请求中的别名不同;
+///   消息额外带 `refusal`/`annotations`, `usage` 额外带 `completion_tokens_details`, 这些字段均被忽略
+/// - 两者在 tool call 响应中 `message.content` 可能为 `null`, 当前 `ChatMessage` 不支持, 只适用于纯文本对话
+#[derive(Debug, Clone, Default)]
+pub struct OpenAiCompatibleProvider {
+    options: ChatOptions,
+}
+
+impl OpenAiCompatibleProvider {
+    pub fn new(options: ChatOptions) -> Self {
+        Self { options }
+    }
+}
+
+#[async_trait]
+impl LlmProvider for OpenAiCompatibleProvider {
+    async fn chat_completion(&self, request: &ChatRequest) -> anyhow::Result<ChatResponse> {
+        let mut request = request.clone();
+        if let Some(model) = &self.options.model {
+            request.model = model.clone();
+        }
+        let body = serde_json::to_string(&request)?;
+        let key = "sk-47b29c3eac324b2a8a137b4a7838a93b";
+        let mut headers = HashMap::new();
+        headers.insert("Content-Type".into(), "application/json".into());
+        headers.insert("Authorization".into(), format!("Bearer {}", key));
+        let res = http::post(&self.options.completions_url(), Some(body), Some(&headers)).await?;
+        let res: ChatResponse = res.json().await?;
+        if let Some(token_usage) = &res.usage {
+            // 响应中的模型名更准确, 如 OpenAI 返回带日期的快照名
+            usage::record(res.model.as_deref().unwrap_or(&request.model), token_usage);
+        }
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::ChatMessage;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    fn request(model: &str) -> ChatRequest {
+        ChatRequest {
+            messages: vec![ChatMessage { content: "hi".into(), role: "user".into() }],
+            model: model.into(),
+            thinking: None,
+            frequency_penalty: None,
+            max_tokens: None,
+            presence_penalty: None,
+            response_format: None,
+            stop: None,
+            stream: Some(false),
+            stream_options: None,
+            temperature: None,
+            top_p: None,
+            tools: None,
+            tool_choice: None,
+            logprobs: None,
+            top_logprobs: None,
+        }
+    }
+
+    /// 只处理一次请求的本地 mock 服务, 返回 (请求行, 请求体)
+    async fn serve_once(listener: TcpListener, response_body: &str) -> (String, String) {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut buf = Vec::new();
+        let mut chunk = [0u8; 4096];
+        let (header_end, content_length) = loop {
+            let n = socket.read(&mut chunk).await.unwrap();
+            buf.extend_from_slice(&chunk[..n]);
+            let text = String::from_utf8_lossy(&buf);
+            if let Some(pos) = text.find("\r\n\r\n") {
+                let content_length = text[..pos]
+                    .lines()
+                    .find_map(|line| line.to_ascii_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse::<usize>().unwrap()))
+                    .unwrap_or(0);
+                break (pos + 4, content_length);
+            }
+        };
+        while buf.len() < header_end + content_length {
+            let n = socket.read(&mut chunk).await.unwrap();
+            buf.extend_from_slice(&chunk[..n]);
+        }
+        let text = String::from_utf8_lossy(&buf).to_string();
+        let request_line = text.lines().next().unwrap().to_string();
+        let body = text[header_end..].to_string();
+
+        let response = format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            response_body.len(),
+            response_body
+        );
+        socket.write_all(response.as_bytes()).await.unwrap();
+        (request_line, body)
+    }
+
+    #[tokio::test]
+    async fn test_chat_uses_override_url_and_model() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(serve_once(
+            listener,
+            r#"{"choices":[{"index":0,"message":{"role":"assistant","content":"ok"},"finish_reason":"stop"}]}"#,
+        ));
+
+        let request = request("deepseek-chat");
+        let options = ChatOptions::new(Some(format!("http://{addr}/v1/")), Some("qwen2.5-7b".into()));
+        let res = OpenAiCompatibleProvider::new(options).chat_completion(&request).await.unwrap();
+
+        let (request_line, body) = server.await.unwrap();
+        assert_eq!(request_line, "POST /v1/chat/completions HTTP/1.1");
+        let sent: ChatRequest = serde_json::from_str(&body).unwrap();
+        assert_eq!(sent.model, "qwen2.5-7b");
+        assert_eq!(res.choices.unwrap()[0].message.as_ref().unwrap().content, "ok");
+    }
+
+    /// 录制的接口响应及期望解析结果
+    struct Fixture {
+        name: &'static str,
+        request_model: &'static str,
+        response: &'static str,
+        model: &'static str,
+        content: &'static str,
+        prompt_tokens: u32,
+        completion_tokens: u32,
+    }
+
+    const FIXTURES: &[Fixture] = &[
+        Fixture {
+            name: "deepseek",
+            request_model: "deepseek-chat",
+            response: include_str!("../../testdata/llm/deepseek_chat_completion.json"),
+            model: "deepseek-chat",
+            content: "EVI Industries 是商业洗衣行业的增值分销商和服务提供商。",
+            prompt_tokens: 42,
+            completion_tokens: 18,
+        },
+        Fixture {
+            name: "openai",
+            request_model: "gpt-4o-mini",
+            response: include_str!("../../testdata/llm/openai_chat_completion.json"),
+            model: "gpt-4o-mini-2024-07-18",
+            content: "EVI Industries 是一家商业洗衣设备分销及服务公司。",
+            prompt_tokens: 40,
+            completion_tokens: 21,
+        },
+    ];
+
+    /// 用录制的响应驱动 provider, 检查请求路径/模型及响应解析
+    async fn assert_conformance(fixture: &Fixture) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = tokio::spawn(serve_once(listener, fixture.response));
+
+        let provider = OpenAiCompatibleProvider::new(ChatOptions::new(Some(format!("http://{addr}")), None));
+        let res = provider.chat_completion(&request(fixture.request_model)).await.unwrap();
+
+        let (request_line, body) = server.await.unwrap();
+        assert_eq!(request_line, "POST /chat/completions HTTP/1.1", "{}", fixture.name);
+        let sent: ChatRequest = serde_json::from_str(&body).unwrap();
+        assert_eq!(sent.model, fixture.request_model, "{}", fixture.name);
+        assert_eq!(sent.stream, Some(false), "{}", fixture.name);
+
+        assert_eq!(res.model.as_deref(), Some(fixture.model), "{}", fixture.name);
+        let choice = &res.choices.unwrap()[0];
+        assert_eq!(choice.message.as_ref().unwrap().content, fixture.content, "{}", fixture.name);
+        assert_eq!(choice.finish_reason.as_deref(), Some("stop"), "{}", fixture.name);
+        let usage = res.usage.unwrap();
+        assert_eq!(usage.prompt_tokens, Some(fixture.prompt_tokens), "{}", fixture.name);
+        assert_eq!(usage.completion_tokens, Some(fixture.completion_tokens), "{}", fixture.name);
+    }
+
+    #[tokio::test]
+    async fn test_usage_accumulates_across_calls() {
+        // 使用独立的模型名, 避免与其他测试共享的全局统计互相影响
+        let model = "mock-usage-model";
+        for (prompt, completion) in [(100, 20), (50, 5)] {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let response = format!(
+                r#"{{"model":"{model}","choices":[{{"index":0,"message":{{"role":"assistant","content":"ok"}},"finish_reason":"stop"}}],"usage":{{"prompt_tokens":{prompt},"completion_tokens":{completion}}}}}"#
+            );
+            let server = tokio::spawn(async move { serve_once(listener, &response).await });
+            let provider = OpenAiCompatibleProvider::new(ChatOptions::new(Some(format!("http://{addr}")), None));
+            provider.chat_completion(&request("deepseek-chat")).await.unwrap();
+            server.await.unwrap();
+        }
+
+        let report = usage::usage_report();
+        let model_usage = report.models.iter().find(|m| m.model == model).unwrap();
+        assert_eq!(model_usage.calls, 2);
+        assert_eq!(model_usage.prompt_tokens, 150);
+        assert_eq!(model_usage.completion_tokens, 25);
+        assert_eq!(model_usage.estimated_cost, 0.0);
+        assert!(report.total_prompt_tokens >= 150);
+    }
+
+    #[tokio::test]
+    async fn test_provider_conformance_with_fixtures() {
+        for fixture in FIXTURES {
+            assert_conformance(fixture).await;
+        }
+    }
+}
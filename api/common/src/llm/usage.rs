@@ -0,0 +1,76 @@
+//! LLM token 用量统计
+//!
+//! 每次调用按模型累计 prompt/completion tokens, 并按价格表估算费用
+
+use dashmap::DashMap;
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use super::Usage;
+
+static USAGE: Lazy<DashMap<String, ModelUsage>> = Lazy::new(DashMap::new);
+
+/// 模型价格 (元/百万 tokens): (模型, 输入价格, 输出价格)
+///
+/// 按官网标价估算, 未命中缓存的输入价格; 不在表中的模型费用记为 0
+const MODEL_PRICES: &[(&str, f64, f64)] = &[
+    ("deepseek-chat", 2.0, 8.0),
+    ("deepseek-reasoner", 4.0, 16.0),
+];
+
+/// 单个模型的累计用量
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ModelUsage {
+    pub model: String,
+    /// 调用次数
+    pub calls: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    /// 估算费用, 单位: 元
+    pub estimated_cost: f64,
+}
+
+/// 用量报告
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UsageReport {
+    /// 按模型名排序
+    pub models: Vec<ModelUsage>,
+    pub total_calls: u64,
+    pub total_prompt_tokens: u64,
+    pub total_completion_tokens: u64,
+    pub total_estimated_cost: f64,
+}
+
+/// 记录一次调用的用量
+pub fn record(model: &str, usage: &Usage) {
+    let prompt_tokens = usage.prompt_tokens.unwrap_or(0) as u64;
+    let completion_tokens = usage.completion_tokens.unwrap_or(0) as u64;
+    let mut entry = USAGE.entry(model.to_string()).or_insert_with(|| ModelUsage {
+        model: model.to_string(),
+        ..Default::default()
+    });
+    entry.calls += 1;
+    entry.prompt_tokens += prompt_tokens;
+    entry.completion_tokens += completion_tokens;
+    entry.estimated_cost += estimate_cost(model, prompt_tokens, completion_tokens);
+}
+
+/// 当前进程内的累计用量
+pub fn usage_report() -> UsageReport {
+    let mut models: Vec<ModelUsage> = USAGE.iter().map(|e| e.value().clone()).collect();
+    models.sort_by(|a, b| a.model.cmp(&b.model));
+    UsageReport {
+        total_calls: models.iter().map(|m| m.calls).sum(),
+        total_prompt_tokens: models.iter().map(|m| m.prompt_tokens).sum(),
+        total_completion_tokens: models.iter().map(|m| m.completion_tokens).sum(),
+        total_estimated_cost: models.iter().map(|m| m.estimated_cost).sum(),
+        models,
+    }
+}
+
+fn estimate_cost(model: &str, prompt_tokens: u64, completion_tokens: u64) -> f64 {
+    MODEL_PRICES
+        .iter()
+        .find(|(name, _, _)| *name == model)
+        .map(|(_, input, output)| (prompt_tokens as f64 * input + completion_tokens as f64 * output) / 1_000_000.0)
+        .unwrap_or(0.0)
+}
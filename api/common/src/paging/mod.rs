@@ -1,3 +1,4 @@
+use serde::{Deserialize, Serialize};
 
 /// 分页
 ///
@@ -10,4 +11,25 @@ pub fn get_paging_data<T: Clone>(datas: &[T], page: usize, page_size: usize) ->
     let start = (page - 1) * page_size;
     let end = (start + page_size).min(datas.len());
     datas[start..end].to_vec()
-}
\ No newline at end of file
+}
+/// 分页结果
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Page<T> {
+    /// 当前页数据
+    pub data: Vec<T>,
+    /// 总数量
+    pub total: u64,
+    /// 当前页码，从1开始
+    pub page: u64,
+    /// 每页大小
+    pub page_size: u64,
+    /// 总页数
+    pub total_pages: u64,
+}
+
+impl<T> Page<T> {
+    pub fn new(data: Vec<T>, total: u64, page: u64, page_size: u64) -> Self {
+        let total_pages = if page_size == 0 { 0 } else { total.div_ceil(page_size) };
+        Self { data, total, page, page_size, total_pages }
+    }
+}
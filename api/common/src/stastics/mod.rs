@@ -25,46 +25,18 @@ pub struct IncDecInfo {
 }
 
 impl From<&Vec<f64>> for IncDecInfo {
+    /// `datas` 按时间正序; 连涨/连跌数为截止最后一天的连续上涨/下跌天数, 平盘既不算涨也不算跌, 且会中断连涨/连跌
     fn from(datas: &Vec<f64>) -> Self {
-        let (mut cinc_num, mut cdec_num, mut inc_num, mut dec_num) = (0, 0, 0, 0);
-
-        if datas.is_empty() {
-            return Self {
-                consecutive_inc: cinc_num,
-                consecutive_dec: cdec_num,
-                inc: inc_num,
-                dec: dec_num,
-            };
-        }
-        let datas = datas.iter().rev().collect::<Vec<&f64>>();
-        let mut current = *datas[0];
-        let remains = &datas[1..datas.len()];
-        let mut calc_cinc_num = true;
-        let mut calc_cdec_num = true;
-
-        for data in remains {
-            let data = **data;
-            if current >= data {
-                inc_num += 1;
-                if calc_cinc_num {
-                    cinc_num += 1;
-                    calc_cdec_num = false;
-                }
-            }
-            if current <= data {
-                dec_num += 1;
-                if calc_cdec_num {
-                    cdec_num += 1;
-                    calc_cinc_num = false;
-                }
-            }
-            current = data;
-        }
+        let changes: Vec<Ordering> = datas
+            .windows(2)
+            .map(|pair| pair[1].partial_cmp(&pair[0]).unwrap_or(Ordering::Equal))
+            .collect();
+        let trailing = |ord: Ordering| changes.iter().rev().take_while(|c| **c == ord).count() as u64;
         Self {
-            consecutive_inc: cinc_num,
-            consecutive_dec: cdec_num,
-            inc: inc_num,
-            dec: dec_num,
+            consecutive_inc: trailing(Ordering::Greater),
+            consecutive_dec: trailing(Ordering::Less),
+            inc: changes.iter().filter(|c| **c == Ordering::Greater).count() as u64,
+            dec: changes.iter().filter(|c| **c == Ordering::Less).count() as u64,
         }
     }
 }
@@ -98,6 +70,28 @@ pub fn calc_stastics(data: &mut Vec<f64>) -> Option<Stastics> {
     }
 }
 
+/// 一年的交易日数, 用于日收益率年化
+pub const TRADING_DAYS_PER_YEAR: f64 = 252.0;
+
+/// 年化波动率: 日收益率的样本标准差 * sqrt(252), 少于 2 个收益率时返回 None
+/// # Arguments
+/// - `daily_returns` 日收益率, 如 0.01 表示 1%
+pub fn annualized_volatility(daily_returns: &[f64]) -> Option<f64> {
+    calc_standard_deviation(daily_returns).map(|std| std * TRADING_DAYS_PER_YEAR.sqrt())
+}
+
+/// 年化夏普比率: (日均收益率 - 日无风险收益率) / 日收益率标准差 * sqrt(252)
+///
+/// 少于 2 个收益率或收益率没有波动时返回 None
+/// # Arguments
+/// - `daily_returns` 日收益率
+/// - `annual_risk_free` 年化无风险收益率, 如 0.02 表示 2%
+pub fn sharpe_ratio(daily_returns: &[f64], annual_risk_free: f64) -> Option<f64> {
+    let std = calc_standard_deviation(daily_returns).filter(|std| *std > 0f64)?;
+    let mean = daily_returns.iter().sum::<f64>() / daily_returns.len() as f64;
+    Some((mean - annual_risk_free / TRADING_DAYS_PER_YEAR) / std * TRADING_DAYS_PER_YEAR.sqrt())
+}
+
 fn calc_standard_deviation(data: &[f64]) -> Option<f64> {
     let n = data.len() as f64;
     if n <= 1.0 {
@@ -121,8 +115,35 @@ fn calc_median(data: &Vec<f64>) -> f64 {
     }
 }
 
+#[cfg(test)]
 mod test {
-    use crate::stastics::calc_median;
+    use crate::stastics::{annualized_volatility, calc_median, sharpe_ratio, IncDecInfo, TRADING_DAYS_PER_YEAR};
+
+    #[test]
+    fn test_volatility_and_sharpe() {
+        let returns = [0.01, -0.01, 0.01, -0.01];
+        // 样本标准差 = sqrt(4 * 0.0001 / 3)
+        let std = (0.0004f64 / 3.0).sqrt();
+        assert!((annualized_volatility(&returns).unwrap() - std * TRADING_DAYS_PER_YEAR.sqrt()).abs() < 1e-12);
+        assert!(sharpe_ratio(&returns, 0.0).unwrap().abs() < 1e-12);
+        assert!(sharpe_ratio(&[0.02, 0.01, 0.03], 0.0).unwrap() > 0.0);
+
+        assert_eq!(annualized_volatility(&[0.01]), None);
+        assert_eq!(sharpe_ratio(&[0.01, 0.01], 0.0), None);
+    }
+
+    #[test]
+    fn test_inc_dec_info() {
+        let info = IncDecInfo::from(&vec![10.0, 9.0, 9.0, 9.5, 10.0, 11.0]);
+        assert_eq!((info.consecutive_inc, info.consecutive_dec, info.inc, info.dec), (3, 0, 3, 1));
+
+        let info = IncDecInfo::from(&vec![10.0, 11.0, 12.0, 11.0]);
+        assert_eq!((info.consecutive_inc, info.consecutive_dec, info.inc, info.dec), (0, 1, 2, 1));
+
+        let info = IncDecInfo::from(&vec![10.0, 10.0]);
+        assert_eq!((info.consecutive_inc, info.consecutive_dec, info.inc, info.dec), (0, 0, 0, 0));
+        assert_eq!(IncDecInfo::from(&vec![]).inc, 0);
+    }
 
     #[test]
     fn test_gen_median() {
@@ -1,3 +1,6 @@
+use std::collections::btree_map::Entry;
+use std::collections::BTreeMap;
+
 use anyhow::{anyhow, bail};
 use chrono::{DateTime, Datelike, IsoWeek, Local, NaiveDate, NaiveDateTime, Days};
 use itertools::Itertools;
@@ -78,3 +81,41 @@ pub fn get_start_end_from_now(days_ago: u64) -> anyhow::Result<(NaiveDate, Naive
     let start = end.checked_sub_days(Days::new(days_ago)).ok_or(anyhow!("can't get start date"))?;
     Ok((start, end))
 }
+
+/// 按自然月重采样, 每个月只保留最后一个交易日的数据, 结果按日期升序
+///
+/// `trade_date` 返回 `%Y%m%d` 格式的日期, 输入顺序不限, 日期无法解析的数据会被丢弃
+pub fn month_end<T, F>(items: Vec<T>, trade_date: F) -> Vec<T>
+where
+    F: Fn(&T) -> &str,
+{
+    let mut months: BTreeMap<(i32, u32), T> = BTreeMap::new();
+    for item in items {
+        let Ok(date) = NaiveDate::parse_from_str(trade_date(&item), "%Y%m%d") else {
+            continue;
+        };
+        match months.entry((date.year(), date.month())) {
+            Entry::Vacant(e) => {
+                e.insert(item);
+            }
+            Entry::Occupied(mut e) => {
+                if trade_date(&item) > trade_date(e.get()) {
+                    e.insert(item);
+                }
+            }
+        }
+    }
+    months.into_values().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_month_end() {
+        let dates = vec!["20240131", "20240102", "20240229", "20240115", "20240301", "bad"];
+        let month_ends = month_end(dates, |d| *d);
+        assert_eq!(month_ends, vec!["20240131", "20240229", "20240301"]);
+    }
+}
@@ -1,3 +1,6 @@
+use anyhow::{anyhow, bail};
+use scraper::{ElementRef, Html, Selector};
+
 pub struct HtmlParser {
     html: String,
 }
@@ -12,3 +15,67 @@ impl HtmlParser {
 }
 
 pub struct Element {}
+
+/// 提取所有匹配 selector 的元素文本(去除首尾空白)
+pub fn extract_text(html: &str, selector: &str) -> anyhow::Result<Vec<String>> {
+    let document = Html::parse_document(html);
+    let css = parse_selector(selector)?;
+    let texts = document.select(&css).map(element_text).collect::<Vec<String>>();
+    if texts.is_empty() {
+        bail!("no element matches selector: {}", selector);
+    }
+    Ok(texts)
+}
+
+/// 将第一个匹配 table_selector 的表格解析为行/单元格(th 和 td), 跳过没有单元格的行
+pub fn extract_table(html: &str, table_selector: &str) -> anyhow::Result<Vec<Vec<String>>> {
+    let document = Html::parse_document(html);
+    let css = parse_selector(table_selector)?;
+    let table = document
+        .select(&css)
+        .next()
+        .ok_or(anyhow!("no table matches selector: {}", table_selector))?;
+    let row_selector = parse_selector("tr")?;
+    let cell_selector = parse_selector("th, td")?;
+    let rows = table
+        .select(&row_selector)
+        .map(|row| row.select(&cell_selector).map(element_text).collect::<Vec<String>>())
+        .filter(|cells| !cells.is_empty())
+        .collect();
+    Ok(rows)
+}
+
+fn parse_selector(selector: &str) -> anyhow::Result<Selector> {
+    Selector::parse(selector).map_err(|e| anyhow!("invalid selector: {}, error: {:?}", selector, e))
+}
+
+fn element_text(element: ElementRef) -> String {
+    element.text().collect::<String>().trim().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TABLE_HTML: &str = include_str!("../../testdata/eastmoney_table.html");
+
+    #[test]
+    fn test_extract_text() {
+        let titles = extract_text(TABLE_HTML, "h3.title").unwrap();
+        assert_eq!(titles, vec!["主要指标"]);
+
+        let err = extract_text(TABLE_HTML, "div.missing").unwrap_err();
+        assert!(err.to_string().contains("div.missing"));
+    }
+
+    #[test]
+    fn test_extract_table() {
+        let rows = extract_table(TABLE_HTML, "table.main-indicator").unwrap();
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[0], vec!["指标", "2024-12-31", "2023-12-31"]);
+        assert_eq!(rows[2], vec!["归属净利润(元)", "88.80亿", "--"]);
+
+        let err = extract_table(TABLE_HTML, "table.missing").unwrap_err();
+        assert!(err.to_string().contains("table.missing"));
+    }
+}
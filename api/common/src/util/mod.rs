@@ -7,7 +7,7 @@ pub mod math_util;
 pub mod pdf_util;
 pub mod compress_util;
 pub mod csv_util;
-mod rate_limit;
+pub mod rate_limit;
 
 pub fn to_result<T>(option: Option<T>) -> anyhow::Result<T> {
     option.to_result()
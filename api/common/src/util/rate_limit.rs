@@ -1,3 +1,121 @@
-trait RateLimit {
-    fn limit(&self);
-}
\ No newline at end of file
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::anyhow;
+use reqwest::Url;
+
+use tokio::sync::Mutex;
+use tokio::time::{sleep_until, Instant};
+
+/// 滑动窗口限流器: 任意 `window` 时间内最多放行 `max_requests` 次请求
+///
+/// clone 后共享同一个窗口, 可在多个 client / task 间复用
+#[derive(Clone, Debug)]
+pub struct RateLimiter {
+    max_requests: usize,
+    window: Duration,
+    history: Arc<Mutex<VecDeque<Instant>>>,
+}
+
+impl RateLimiter {
+    pub fn new(max_requests: usize, window: Duration) -> Self {
+        assert!(max_requests > 0, "max_requests must be greater than 0");
+        Self {
+            max_requests,
+            window,
+            history: Arc::new(Mutex::new(VecDeque::with_capacity(max_requests))),
+        }
+    }
+
+    /// 两次请求之间至少间隔 `interval`
+    pub fn with_min_interval(interval: Duration) -> Self {
+        Self::new(1, interval)
+    }
+
+    /// 获取一个许可, 窗口内请求数已满时等待最早的请求滑出窗口
+    pub async fn acquire(&self) {
+        let mut history = self.history.lock().await;
+        loop {
+            let now = Instant::now();
+            while history.front().is_some_and(|t| now.duration_since(*t) >= self.window) {
+                history.pop_front();
+            }
+            if history.len() < self.max_requests {
+                history.push_back(now);
+                return;
+            }
+            // 持有锁等待, 保证许可按到达顺序发放
+            let earliest = *history.front().expect("history is not empty");
+            sleep_until(earliest + self.window).await;
+        }
+    }
+}
+
+/// 按 url host 分别限流, 同一个 host 的两次请求至少间隔 `min_interval`
+#[derive(Clone, Debug)]
+pub struct HostRateLimiter {
+    min_interval: Duration,
+    limiters: Arc<std::sync::Mutex<HashMap<String, RateLimiter>>>,
+}
+
+impl HostRateLimiter {
+    pub fn new(min_interval: Duration) -> Self {
+        Self {
+            min_interval,
+            limiters: Arc::new(std::sync::Mutex::new(HashMap::new())),
+        }
+    }
+
+    pub fn min_interval(&self) -> Duration {
+        self.min_interval
+    }
+
+    /// 等待直到可以请求 url 所在的 host
+    pub async fn acquire(&self, url: &str) -> anyhow::Result<()> {
+        let host = Url::parse(url)?
+            .host_str()
+            .ok_or(anyhow!("url has no host: {}", url))?
+            .to_string();
+        let limiter = self
+            .limiters
+            .lock()
+            .map_err(|_| anyhow!("host rate limiter poisoned"))?
+            .entry(host)
+            .or_insert_with(|| RateLimiter::with_min_interval(self.min_interval))
+            .clone();
+        limiter.acquire().await;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_acquire_blocks_within_window() {
+        let window = Duration::from_millis(200);
+        let limiter = RateLimiter::new(2, window);
+        let start = Instant::now();
+        limiter.acquire().await;
+        limiter.clone().acquire().await;
+        assert!(start.elapsed() < window);
+
+        limiter.acquire().await;
+        assert!(start.elapsed() >= window);
+    }
+
+    #[tokio::test]
+    async fn test_host_rate_limiter_spaces_same_host() {
+        let interval = Duration::from_millis(150);
+        let limiter = HostRateLimiter::new(interval);
+        let start = Instant::now();
+        limiter.acquire("https://xueqiu.com/S/SZ300063").await.unwrap();
+        limiter.acquire("https://www.futunn.com/stock/IRDM-US").await.unwrap();
+        assert!(start.elapsed() < interval);
+
+        limiter.acquire("https://xueqiu.com/S/SH600000").await.unwrap();
+        assert!(start.elapsed() >= interval);
+    }
+}
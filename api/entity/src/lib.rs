@@ -46,17 +46,25 @@ pub mod us_company_info;
 pub mod block_trade;
 pub mod cn_security_info;
 pub mod limit_list_d;
+pub mod hk_hold;
+pub mod dividend;
+pub mod adj_factor;
 
 pub mod portfolio;
 
 pub mod holding;
+pub mod holding_trade;
+pub mod portfolio_valuation;
 
 pub mod task_run;
 pub mod task_state;
+pub mod task_dead_letter;
 pub mod hm_detail;
 pub mod us_main_indicator;
 
 pub mod stock_strategy_profile;
+pub mod stock_peer_map;
+pub mod security_change;
 
 pub mod scheduled_task;
 pub mod task_execution;
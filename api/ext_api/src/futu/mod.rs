@@ -1,14 +1,12 @@
 use anyhow::{Context, Result};
+use common::util::html_util;
 use reqwest::header;
-use scraper::{Html, Selector};
 
 /// Simple HTTP-based implementation (works for static content)
 pub async fn get() -> Result<String> {
     let url = "https://www.futunn.com/stock/IRDM-US/company";
     let css_selector = "#view-page  div.stock-page.router-page  section  div  section";
     // let css_selector = "#view-page > div.stock-page.router-page > section > div > section > div:nth-child(1) > div.company-info > div:nth-child(3) > span";
-    let selector = Selector::parse(css_selector)
-        .map_err(|e| anyhow::anyhow!("Invalid CSS selector: {}", e))?;
     
     let mut headers = header::HeaderMap::new();
     headers.insert(
@@ -20,14 +18,11 @@ pub async fn get() -> Result<String> {
         .default_headers(headers)
         .build()?;
         
+    crate::SCRAPE_RATE_LIMITER.acquire(url).await?;
     let html = client.get(url).send().await?.text().await?;
     println!("html: {}", html);
-    let document = Html::parse_document(&html);
-    
-    match document.select(&selector).next() {
-        Some(element) => Ok(element.text().collect::<String>().trim().to_string()),
-        None => Err(anyhow::anyhow!("Element not found with selector: {}", css_selector))
-    }
+    let texts = html_util::extract_text(&html, css_selector)?;
+    Ok(texts[0].clone())
 }
 
 #[cfg(test)]
@@ -1,11 +1,18 @@
+use std::time::Duration;
+
 use anyhow::anyhow;
+use common::util::rate_limit::HostRateLimiter;
+use once_cell::sync::Lazy;
 use reqwest::Response;
 
 pub mod tushare;
 pub mod mstar;
 pub mod dongcai;
 mod futu;
-mod xueqiu;
+pub mod xueqiu;
+
+/// 网页/非官方接口抓取共用的按 host 限流, 避免请求过快被封 IP
+static SCRAPE_RATE_LIMITER: Lazy<HostRateLimiter> = Lazy::new(|| HostRateLimiter::new(Duration::from_secs(1)));
 
 async fn resp_to_string(resp: Response) -> anyhow::Result<String> {
     String::from_utf8(resp.bytes().await?.as_ref().to_vec()).map_err(|e| anyhow!(e))
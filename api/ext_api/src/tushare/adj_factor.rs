@@ -0,0 +1,17 @@
+use chrono::NaiveDate;
+use entity::adj_factor::Model as AdjFactor;
+use tushare_api::{Api, fields, params, request, TushareRequest};
+use crate::tushare::call_api_as;
+
+/// 复权因子 https://tushare.pro/document/2?doc_id=28
+pub async fn adj_factor(trade_date: &NaiveDate) -> anyhow::Result<Vec<AdjFactor>> {
+    let trade_date = trade_date.format("%Y%m%d").to_string();
+    let res = call_api_as::<AdjFactor>(request!(Api::Custom("adj_factor".into()), {
+        "trade_date" => trade_date.as_str(),
+    }, [
+        "ts_code",
+        "trade_date",
+        "adj_factor",
+    ])).await?;
+    Ok(res.items)
+}
@@ -4,8 +4,10 @@ use tushare_api::{Api, fields, LogLevel, params, request, TushareRequest};
 use crate::tushare::call_api_as;
 
 /// 资产负债表
-pub async fn balancesheet(ts_code: &str) -> anyhow::Result<Vec<balancesheet::Model>> {
-    let res = call_api_as::<balancesheet::Model>( request!(Api::Balancesheet,
+///
+/// `start_date` 为公告开始日期(YYYYMMDD), 为 None 时抓取全部报告期
+pub async fn balancesheet(ts_code: &str, start_date: Option<&str>) -> anyhow::Result<Vec<balancesheet::Model>> {
+    let mut req = request!(Api::Balancesheet,
                        {"ts_code" => ts_code},
                         [
                             "ts_code",
@@ -164,7 +166,11 @@ pub async fn balancesheet(ts_code: &str) -> anyhow::Result<Vec<balancesheet::Mod
                             "oth_rcv_total",
                             "fix_assets_total",
                             "update_flag"
-                        ])).await?;
+                        ]);
+    if let Some(start_date) = start_date {
+        req.params.insert("start_date".to_string(), start_date.to_string());
+    }
+    let res = call_api_as::<balancesheet::Model>(req).await?;
     return Ok(res.items)
 
 }
\ No newline at end of file
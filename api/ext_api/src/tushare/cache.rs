@@ -0,0 +1,124 @@
+use std::any::Any;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use anyhow::anyhow;
+use tushare_api::{Api, TushareRequest};
+
+/// (接口名, 参数及字段的哈希)
+type CacheKey = (String, u64);
+/// 缓存写入时间和类型擦除后的响应结果
+type CacheEntries = HashMap<CacheKey, (Instant, Arc<dyn Any + Send + Sync>)>;
+
+/// tushare 接口的响应缓存, 按 (接口, 参数) 缓存结果, 每个接口单独设置有效期
+///
+/// 未设置有效期的接口不缓存; 命中缓存时不发起请求, 也不占用限流额度
+pub struct ResponseCache {
+    ttls: HashMap<String, Duration>,
+    entries: Mutex<CacheEntries>,
+}
+
+impl ResponseCache {
+    pub fn new() -> Self {
+        Self { ttls: HashMap::new(), entries: Mutex::new(HashMap::new()) }
+    }
+
+    /// 设置 `api` 的缓存有效期
+    pub fn with_ttl(mut self, api: Api, ttl: Duration) -> Self {
+        self.ttls.insert(api_key(&api), ttl);
+        self
+    }
+
+    /// 缓存有效时直接返回缓存结果, 否则调用 `fetch` 并缓存其结果
+    pub async fn get_or_fetch<T, F, Fut>(&self, request: &TushareRequest, fetch: F) -> anyhow::Result<Vec<T>>
+    where
+        T: Clone + Send + Sync + 'static,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = anyhow::Result<Vec<T>>>,
+    {
+        let api = api_key(&request.api_name);
+        let Some(ttl) = self.ttls.get(&api).copied() else {
+            return fetch().await;
+        };
+        let key = (api, request_hash(request));
+        if let Some(items) = self.lookup::<T>(&key, ttl)? {
+            return Ok(items);
+        }
+
+        let items = fetch().await?;
+        self.entries
+            .lock()
+            .map_err(|_| anyhow!("tushare response cache poisoned"))?
+            .insert(key, (Instant::now(), Arc::new(items.clone())));
+        Ok(items)
+    }
+
+    fn lookup<T: Clone + 'static>(&self, key: &CacheKey, ttl: Duration) -> anyhow::Result<Option<Vec<T>>> {
+        let entries = self.entries.lock().map_err(|_| anyhow!("tushare response cache poisoned"))?;
+        let items = entries
+            .get(key)
+            .filter(|(at, _)| at.elapsed() < ttl)
+            .and_then(|(_, items)| items.downcast_ref::<Vec<T>>())
+            .cloned();
+        Ok(items)
+    }
+}
+
+impl Default for ResponseCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn api_key(api: &Api) -> String {
+    format!("{:?}", api)
+}
+
+/// 参数按名称排序后计算哈希, 与参数插入顺序无关
+fn request_hash(request: &TushareRequest) -> u64 {
+    let params: BTreeMap<String, String> = request.params.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    let mut hasher = DefaultHasher::new();
+    params.hash(&mut hasher);
+    for field in request.fields.iter() {
+        field.to_string().hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tushare_api::{fields, params, request};
+
+    #[tokio::test]
+    async fn test_cached_within_ttl() {
+        let cache = ResponseCache::new().with_ttl(Api::StockBasic, Duration::from_secs(60));
+        let requests = AtomicUsize::new(0);
+        let fetch = || async {
+            requests.fetch_add(1, Ordering::SeqCst);
+            Ok(vec!["600000.SH".to_string()])
+        };
+
+        let stock_basic = request!(Api::StockBasic, {"list_status" => "L"}, ["ts_code"]);
+        let first = cache.get_or_fetch(&stock_basic, fetch).await.unwrap();
+        let second = cache.get_or_fetch(&stock_basic, fetch).await.unwrap();
+        assert_eq!(first, second);
+        assert_eq!(requests.load(Ordering::SeqCst), 1);
+
+        // 参数不同, 重新请求
+        let delisted = request!(Api::StockBasic, {"list_status" => "D"}, ["ts_code"]);
+        cache.get_or_fetch(&delisted, fetch).await.unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 2);
+
+        // 未设置有效期的接口不缓存
+        let daily = request!(Api::Daily, {"trade_date" => "20240102"}, ["ts_code"]);
+        cache.get_or_fetch(&daily, fetch).await.unwrap();
+        cache.get_or_fetch(&daily, fetch).await.unwrap();
+        assert_eq!(requests.load(Ordering::SeqCst), 4);
+    }
+}
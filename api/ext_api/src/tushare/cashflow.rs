@@ -4,8 +4,11 @@ use entity::cashflow;
 use tushare_api::{Api, fields, LogLevel, params, request, TushareRequest};
 use crate::tushare::call_api_as;
 
-pub async fn cashflow(ts_code: &str) -> anyhow::Result<Vec<cashflow::Model>> {
-    let res = call_api_as::<cashflow::Model>(request!(Api::Cashflow,
+/// 现金流量表
+///
+/// `start_date` 为公告开始日期(YYYYMMDD), 为 None 时抓取全部报告期
+pub async fn cashflow(ts_code: &str, start_date: Option<&str>) -> anyhow::Result<Vec<cashflow::Model>> {
+    let mut req = request!(Api::Cashflow,
                         {"ts_code" => ts_code},
                         [
                             "ts_code",
@@ -106,6 +109,10 @@ pub async fn cashflow(ts_code: &str) -> anyhow::Result<Vec<cashflow::Model>> {
                             "beg_bal_cash_equ",
                             "update_flag"
                         ]
-    )).await?;
+    );
+    if let Some(start_date) = start_date {
+        req.params.insert("start_date".to_string(), start_date.to_string());
+    }
+    let res = call_api_as::<cashflow::Model>(req).await?;
     return Ok(res.items)
 }
\ No newline at end of file
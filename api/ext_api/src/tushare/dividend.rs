@@ -0,0 +1,26 @@
+use entity::dividend::Model as Dividend;
+use tushare_api::{Api, fields, params, request, TushareRequest};
+use crate::tushare::call_api_as;
+
+/// 分红送股 https://tushare.pro/document/2?doc_id=103
+pub async fn dividend(ts_code: &str) -> anyhow::Result<Vec<Dividend>> {
+    let res = call_api_as::<Dividend>(request!(Api::Custom("dividend".into()), {
+        "ts_code" => ts_code,
+    }, [
+        "ts_code",
+        "end_date",
+        "ann_date",
+        "div_proc",
+        "stk_div",
+        "stk_bo_rate",
+        "stk_co_rate",
+        "cash_div",
+        "cash_div_tax",
+        "record_date",
+        "ex_date",
+        "pay_date",
+        "div_listdate",
+        "imp_ann_date",
+    ])).await?;
+    Ok(res.items)
+}
@@ -0,0 +1,23 @@
+use chrono::NaiveDate;
+use entity::hk_hold::Model as HkHold;
+use tushare_api::{Api, fields, params, request, TushareRequest};
+use crate::tushare::call_api_as;
+
+/// 沪深港通持股明细 https://tushare.pro/document/2?doc_id=188
+/// - `exchange`: SH 沪股通, SZ 深股通, HK 港股通
+pub async fn hk_hold(trade_date: &NaiveDate, exchange: &str) -> anyhow::Result<Vec<HkHold>> {
+    let trade_date = trade_date.format("%Y%m%d").to_string();
+    let res = call_api_as::<HkHold>(request!(Api::Custom("hk_hold".into()), {
+        "trade_date" => trade_date.as_str(),
+        "exchange" => exchange,
+    }, [
+        "code",
+        "trade_date",
+        "ts_code",
+        "name",
+        "vol",
+        "ratio",
+        "exchange",
+    ])).await?;
+    Ok(res.items)
+}
@@ -12,8 +12,10 @@ use entity::income;
 use entity::sea_orm::prelude::{BigDecimal, Decimal};
 
 /// 利润表
-pub async fn income(ts_code: &str) -> anyhow::Result<Vec<income::Model>> {
-    let incomes =  call_api_as::<income::Model>(request!(Api::Cashflow,
+///
+/// `start_date` 为公告开始日期(YYYYMMDD), 为 None 时抓取全部报告期
+pub async fn income(ts_code: &str, start_date: Option<&str>) -> anyhow::Result<Vec<income::Model>> {
+    let mut req = request!(Api::Income,
                         {"ts_code" => ts_code},
                                                         [
                                                   "ts_code",
@@ -110,6 +112,10 @@ pub async fn income(ts_code: &str) -> anyhow::Result<Vec<income::Model>> {
                                                   "continued_net_profit",
                                                   "end_net_profit",
                                                   "update_flag"
-                                              ])).await?;
+                                              ]);
+    if let Some(start_date) = start_date {
+        req.params.insert("start_date".to_string(), start_date.to_string());
+    }
+    let incomes = call_api_as::<income::Model>(req).await?;
     Ok(incomes.items)
 }
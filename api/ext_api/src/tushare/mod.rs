@@ -11,7 +11,6 @@ use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use anyhow::anyhow;
-use async_rate_limit::sliding_window::SlidingWindowRateLimiter;
 use once_cell::sync::Lazy;
 use reqwest::{Response, StatusCode};
 use serde::de::DeserializeOwned;
@@ -21,8 +20,10 @@ use tushare_api::client_ex::RetryConfig;
 use tushare_api::{FromTushareData, LogConfig, LogLevel, TushareClient, TushareClientEx, Api, TushareEntityList, TushareRequest, TushareResult};
 
 pub use balancesheet::*;
+pub use cache::ResponseCache;
 pub use cashflow::*;
 use common::http;
+use common::util::rate_limit::RateLimiter;
 pub use daily::*;
 pub use daily_basic::*;
 pub use fina_indicator::*;
@@ -55,8 +56,12 @@ pub use dc_member::*;
 pub use block_trade::*;
 pub use hm_detail::*;
 pub use limit_list_d::*;
+pub use hk_hold::*;
+pub use dividend::*;
+pub use adj_factor::*;
 
 mod balancesheet;
+mod cache;
 mod cashflow;
 mod daily;
 mod daily_basic;
@@ -92,6 +97,9 @@ mod dc_member;
 mod block_trade;
 mod hm_detail;
 mod limit_list_d;
+mod hk_hold;
+mod dividend;
+mod adj_factor;
 
 static TUSHARE_TOKEN: Lazy<String> = Lazy::new(|| {
     common::config::AppConfig::new()
@@ -118,6 +126,7 @@ static TUSHARE_CLIENT: Lazy<TushareClientEx> = Lazy::new(|| {
     .with_api_min_interval(Api::MoneyflowIndustryThs, Duration::from_millis(500))
     .with_api_min_interval(Api::Custom("stk_holdertrade".into()), Duration::from_millis(500))
     .with_api_min_interval(Api::StkHoldernumber, Duration::from_millis(500))
+    .with_api_min_interval(Api::UsDaily, Duration::from_millis(200))
     .with_retry_config(RetryConfig {
         max_retries: 3,
         base_delay: Duration::from_millis(200),
@@ -126,7 +135,44 @@ static TUSHARE_CLIENT: Lazy<TushareClientEx> = Lazy::new(|| {
     client_ex
 });
 
+/// 所有 tushare 接口共享的全局限流, 频次在 `[tushare.rate_limit]` 中配置, 单个接口的最小间隔仍由 TushareClientEx 控制
+static TUSHARE_RATE_LIMITER: Lazy<RateLimiter> = Lazy::new(|| {
+    let limit = common::config::AppConfig::new()
+        .expect("failed to get config")
+        .tushare_rate_limit();
+    RateLimiter::new(limit.max_requests, Duration::from_secs(limit.window_secs))
+});
+
+/// 缓存的接口(按 tushare 接口名)及默认有效期(秒), 日线等行情接口不缓存
+fn cached_apis() -> Vec<(&'static str, Api, u64)> {
+    vec![("stock_basic", Api::StockBasic, 12 * 60 * 60), ("trade_cal", Api::TradeCal, 24 * 60 * 60)]
+}
+
+/// 很少变化的接口的响应缓存, 有效期可在 `[tushare.cache_ttl]` 中按接口名覆盖
+static TUSHARE_CACHE: Lazy<ResponseCache> = Lazy::new(|| {
+    let configured = common::config::AppConfig::new()
+        .expect("failed to get config")
+        .tushare_cache_ttl();
+    cached_apis().into_iter().fold(ResponseCache::new(), |cache, (name, api, default_secs)| {
+        let secs = configured.get(name).copied().unwrap_or(default_secs);
+        cache.with_ttl(api, Duration::from_secs(secs))
+    })
+});
+
+/// 同 `call_api_as`, 但优先使用响应缓存, 命中时不请求 tushare 也不经过限流
+pub async fn call_api_cached<T>(request: TushareRequest) -> anyhow::Result<Vec<T>>
+where
+    T: FromTushareData + std::fmt::Debug + Clone + Send + Sync + 'static,
+{
+    TUSHARE_CACHE
+        .get_or_fetch(&request, || async {
+            let res = call_api_as::<T>(request.clone()).await?;
+            Ok(res.items)
+        })
+        .await
+}
 
 pub async fn call_api_as<T>(request: TushareRequest) -> TushareResult<TushareEntityList<T>> where T: FromTushareData + std::fmt::Debug {
+     TUSHARE_RATE_LIMITER.acquire().await;
      TUSHARE_CLIENT.call_api_as(&request).await
 }
@@ -1,12 +1,26 @@
 use tushare_api::{Api, fields, params, request, TushareRequest};
-use crate::tushare::call_api_as;
+use crate::tushare::call_api_cached;
 use entity::stock::Model as Stock;
 
 
-/// 获取基础信息数据，包括股票代码、名称、上市日期、退市日期等
+/// 全部上市状态: 上市、暂停上市、退市
+const LIST_STATUSES: [&str; 3] = ["L", "P", "D"];
+
+/// 获取基础信息数据，包括股票代码、名称、上市日期、退市日期等, 包含上市、暂停上市和退市的股票
+///
+/// 接口默认只返回上市股票, 这里按上市状态分别查询后合并
 pub async fn stock_basic() -> anyhow::Result<Vec<Stock>> {
+    let mut stocks = vec![];
+    for list_status in LIST_STATUSES {
+        stocks.extend(stock_basic_by_status(list_status).await?);
+    }
+    Ok(stocks)
+}
+
+/// 获取指定上市状态的股票基础信息, `list_status`: L上市 P暂停上市 D退市
+pub async fn stock_basic_by_status(list_status: &str) -> anyhow::Result<Vec<Stock>> {
     let req = request!(Api::StockBasic, {
-            "list_status" => "L",
+            "list_status" => list_status,
         }, [
             "ts_code",
             "symbol",
@@ -26,6 +40,5 @@ pub async fn stock_basic() -> anyhow::Result<Vec<Stock>> {
             "act_name",
             "act_ent_type",
         ]);
-    let res = call_api_as::<Stock>(req.clone()).await?;
-    Ok(res.items)
+    call_api_cached::<Stock>(req).await
 }
\ No newline at end of file
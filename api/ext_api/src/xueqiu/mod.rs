@@ -12,4 +12,7 @@
 /// 
 
 pub mod user_timeline;
-pub mod show;
\ No newline at end of file
+pub mod show;
+pub mod stock_page;
+
+pub use stock_page::{parse_stock_page, XueqiuQuote};
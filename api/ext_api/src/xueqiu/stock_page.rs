@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+
+use anyhow::anyhow;
+use serde::Serialize;
+
+use common::util::html_util::{extract_table, extract_text};
+
+/// 雪球个股页 `/S/<code>` 的行情摘要
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct XueqiuQuote {
+    pub name: Option<String>,  // 天龙集团(SZ:300063)
+    pub current: f64,
+    pub change: Option<f64>,
+    pub pct_chg: Option<f64>, // x%100
+    pub followers: Option<u64>,
+    pub high: Option<f64>,
+    pub low: Option<f64>,
+    pub open: Option<f64>,
+    pub pre_close: Option<f64>,
+    pub stats: BTreeMap<String, String>, // 行情表中的全部指标, 如 换手 -> 12.27%
+}
+
+// 页面改版时类名会变化, 每个字段按顺序尝试多个选择器
+const NAME_SELECTORS: &[&str] = &[".stock-name", ".quote-container h1", "[class*=stock-name]"];
+const CURRENT_SELECTORS: &[&str] = &[".stock-current strong", ".stock-current", "[class*=stock-current]"];
+const CHANGE_SELECTORS: &[&str] = &[".stock-change", "[class*=stock-change]"];
+const FOLLOWER_SELECTORS: &[&str] = &[".stock-follower__count", ".stock-follower", "[class*=follower]"];
+const STATS_TABLE_SELECTORS: &[&str] = &["table.quote-info", ".quote-container table", "table"];
+
+/// 从抓取到的雪球个股页 html 中解析行情, 找不到当前价时返回错误(通常是验证页或页面未加载完)
+pub fn parse_stock_page(html: &str) -> anyhow::Result<XueqiuQuote> {
+    let current = first_text(html, CURRENT_SELECTORS)
+        .and_then(|v| parse_number(&v))
+        .ok_or_else(|| anyhow!("current price not found in xueqiu stock page"))?;
+    let (change, pct_chg) = first_text(html, CHANGE_SELECTORS).map(|v| parse_change(&v)).unwrap_or((None, None));
+    let followers = first_text(html, FOLLOWER_SELECTORS).and_then(|v| parse_count(&v));
+    let stats = parse_stats(html);
+    let stat = |label: &str| stats.get(label).and_then(|v| parse_number(v));
+    Ok(XueqiuQuote {
+        name: first_text(html, NAME_SELECTORS),
+        current,
+        change,
+        pct_chg,
+        followers,
+        high: stat("最高"),
+        low: stat("最低"),
+        open: stat("今开"),
+        pre_close: stat("昨收"),
+        stats,
+    })
+}
+
+fn first_text(html: &str, selectors: &[&str]) -> Option<String> {
+    selectors
+        .iter()
+        .filter_map(|selector| extract_text(html, selector).ok())
+        .flatten()
+        .find(|text| !text.is_empty())
+}
+
+/// 行情表的单元格形如 `最高：6.48`
+fn parse_stats(html: &str) -> BTreeMap<String, String> {
+    let Some(rows) = STATS_TABLE_SELECTORS.iter().find_map(|selector| extract_table(html, selector).ok()) else {
+        return BTreeMap::new();
+    };
+    rows.iter()
+        .flatten()
+        .filter_map(|cell| cell.split_once('：').or_else(|| cell.split_once(':')))
+        .map(|(label, value)| (label.trim().to_string(), value.trim().to_string()))
+        .collect()
+}
+
+/// 提取文本中的第一个数字, 如 `¥6.35` -> 6.35, `+4.44%` -> 4.44
+fn parse_number(text: &str) -> Option<f64> {
+    let start = text.find(|c: char| c.is_ascii_digit() || c == '-' || c == '+')?;
+    let number: String = text[start..]
+        .chars()
+        .enumerate()
+        .take_while(|(i, c)| c.is_ascii_digit() || *c == '.' || (*i == 0 && (*c == '-' || *c == '+')))
+        .map(|(_, c)| c)
+        .collect();
+    number.parse().ok()
+}
+
+/// `+0.27  +4.44%` -> (涨跌额, 涨跌幅)
+fn parse_change(text: &str) -> (Option<f64>, Option<f64>) {
+    let mut parts = text.split_whitespace();
+    let change = parts.next().and_then(parse_number);
+    let pct_chg = parts.next().and_then(parse_number);
+    (change, pct_chg)
+}
+
+/// 关注人数, 支持 `3.62万` 这样的单位
+fn parse_count(text: &str) -> Option<u64> {
+    let value = parse_number(text)?;
+    let multiplier = if text.contains('亿') {
+        1e8
+    } else if text.contains('万') {
+        1e4
+    } else {
+        1f64
+    };
+    Some((value * multiplier).round() as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STOCK_PAGE_HTML: &str = include_str!("../../testdata/xueqiu_SZ300063.html");
+
+    #[test]
+    fn test_parse_stock_page() {
+        let quote = parse_stock_page(STOCK_PAGE_HTML).unwrap();
+        assert_eq!(quote.name.as_deref(), Some("天龙集团(SZ:300063)"));
+        assert_eq!(quote.current, 6.35);
+        assert_eq!((quote.change, quote.pct_chg), (Some(0.27), Some(4.44)));
+        assert_eq!(quote.followers, Some(36_200));
+        assert_eq!((quote.high, quote.low, quote.open, quote.pre_close), (Some(6.48), Some(6.05), Some(6.10), Some(6.08)));
+        assert_eq!(quote.stats.get("换手").map(|v| v.as_str()), Some("12.27%"));
+        assert_eq!(quote.stats.get("市盈率(TTM)").map(|v| v.as_str()), Some("亏损"));
+    }
+
+    #[test]
+    fn test_layout_variation_and_block_page() {
+        // 旧版页面: 当前价不在 strong 中, 没有关注数
+        let html = r#"<div class="quote-container"><h1>天龙集团</h1><div class="stock-current">¥6.35</div><div class="stock-change">-0.27 -4.08%</div></div>"#;
+        let quote = parse_stock_page(html).unwrap();
+        assert_eq!(quote.name.as_deref(), Some("天龙集团"));
+        assert_eq!(quote.current, 6.35);
+        assert_eq!(quote.pct_chg, Some(-4.08));
+        assert_eq!(quote.followers, None);
+
+        assert!(parse_stock_page("<html><body>请完成安全验证</body></html>").is_err());
+    }
+}
@@ -1,4 +1,4 @@
-use crate::task::{Task, us, fetch_fina_mainbz_task};
+use crate::task::fetch_fina_mainbz_task;
 use crate::task::fetch_balancesheet_task::FetchBalancesheetTask;
 use crate::task::fetch_cashflow_task::FetchCashflowTask;
 use crate::task::fetch_dc_index_task::FetchDcIndexTask;
@@ -26,6 +26,8 @@ use crate::task::fetch_ths_daily_task::FetchThsDailyTask;
 use crate::task::fetch_ths_index_task::FetchThsIndexTask;
 use crate::task::fetch_ths_member_task::FetchThsMemberTask;
 use crate::task::fetch_trade_calendar_task::FetchTradeCalendarTask;
+use crate::task::export_history_task::ExportHistoryTask;
+use crate::task::portfolio_valuation_task::PortfolioValuationTask;
 use task::us::fetch_us_basic_task::FetchUsBasicTask;
 use task::us::fetch_us_daily_task::FetchUsDailyTask;
 use entity::sea_orm::DatabaseConnection;
@@ -40,18 +42,24 @@ use crate::task::fetch_basic_org_info_task::FetchBasicOrgInfoTask;
 use crate::task::fetch_eng_translate_task::FetchEngTranslateTask;
 
 mod task_manager;
-pub use task_manager::{TaskListItem, TaskManager, TaskStateView, TaskInfo};
+pub use task_manager::{TaskAdminItem, TaskListItem, TaskManager, TaskRunResult, TaskStateView, TaskInfo};
 use crate::task::fetch_hm_detail_task::FetchHmDetailTask;
 use crate::task::fetch_limit_list_d_task::FetchLimitListDTask;
 
 mod task;
+pub use task::{run_with_lookback, set_fetch_windows, set_finance_full_refresh, set_lookback_days, Task};
+pub use task::us::fetch_us_daily_task::set_us_daily_fetch;
+
+mod task_registry;
+pub use task_registry::{create_task, task_names};
 
 pub async fn create_task_manager(conn: DatabaseConnection) -> anyhow::Result<TaskManager> {
     let tasks = get_schedule_jobs(conn.clone());
     TaskManager::new(conn, tasks).await
 }
 
-pub async fn start_schedule(conn: DatabaseConnection) -> Result<(), Box<dyn Error>> {
+/// 依次运行全部调度任务, 经由 `task_manager` 运行, 与管理接口的手动触发互斥
+pub async fn start_schedule(task_manager: TaskManager, conn: DatabaseConnection) -> Result<(), Box<dyn Error>> {
     let tasks = get_schedule_jobs(conn);
     for task in tasks {
         // tokio::spawn(async move {
@@ -61,7 +69,7 @@ pub async fn start_schedule(conn: DatabaseConnection) -> Result<(), Box<dyn Erro
         //     }
         // });
         info!("begin run task...");
-        let result = task.run().await;
+        let result = task_manager.run_task(task).await;
         if let Err(e) = result {
             error!("Task executed failed: {:?}", e);
         }
@@ -71,22 +79,24 @@ pub async fn start_schedule(conn: DatabaseConnection) -> Result<(), Box<dyn Erro
 }
 
 /// https://www.dongaigc.com/p/mvniekerk/tokio-cron-scheduler
-pub async fn start_schedule_tmp(conn: DatabaseConnection) -> Result<(), Box<dyn Error>> {
+pub async fn start_schedule_tmp(task_manager: TaskManager, conn: DatabaseConnection) -> Result<(), Box<dyn Error>> {
     let sched = JobScheduler::new().await?;
     let tasks = get_schedule_jobs(conn);
     for task in tasks {
         let schedule = task.get_schedule();
         let task_clone = task.clone();
+        let job_manager = task_manager.clone();
         let job = Job::new_async(schedule.as_str(), move |_uuid, _lock| {
             let task = task_clone.clone();
+            let task_manager = job_manager.clone();
             Box::pin(async move {
-                if let Err(e) = task.run().await {
+                if let Err(e) = task_manager.run_task(task).await {
                     error!("Task failed: {:?}", e);
                 }
             })
         })?;
         sched.add(job).await?;
-        task.run().await?;
+        task_manager.run_task(task).await?;
     }
     sched.start().await?;
     Ok(())
@@ -130,6 +140,9 @@ fn get_schedule_jobs(conn: DatabaseConnection) -> Vec<Arc<dyn Task>> {
     Arc::new(FetchMarginTask::new(conn.clone())),
         Arc::new(FetchMarginDetailTask::new(conn.clone())),
 
+        Arc::new(PortfolioValuationTask::new(conn.clone())),
+        Arc::new(ExportHistoryTask::new(conn.clone())),
+
         // Arc::new(FetchIndexDailyTask::new(conn.clone())),
         // Arc::new(FetchStockMonthlyTask::new(conn.clone())),
 
@@ -152,7 +165,7 @@ fn get_schedule_jobs(conn: DatabaseConnection) -> Vec<Arc<dyn Task>> {
     ];
     let us: Vec<Arc<dyn Task>> = vec![
         // Arc::new(FetchUsBasicTask::new(conn.clone())),
-        Arc::new(FetchUsDailyTask::new(conn.clone())),
+        Arc::new(FetchUsDailyTask::from_config(conn.clone())),
     ];
     let others: Vec<Arc<dyn Task>> = vec![
         //  Arc::new(FetchStockHolderNumberTask::new(conn.clone())),
@@ -0,0 +1,41 @@
+use std::path::Path;
+
+use async_trait::async_trait;
+use tracing::{error, info};
+use entity::sea_orm::DatabaseConnection;
+use service::export_service::{self, ExportFormat};
+use crate::task::Task;
+
+/// 每周六凌晨把 `[export]` 中配置的股票日线历史全量导出为 CSV 或 Parquet, 也可以通过 run_task 或管理接口手动运行
+pub struct ExportHistoryTask(DatabaseConnection);
+
+impl ExportHistoryTask {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self(db)
+    }
+}
+
+#[async_trait]
+impl Task for ExportHistoryTask {
+    fn get_schedule(&self) -> String {
+        "0 0 5 * * 6".to_string() // every Saturday at 05:00
+    }
+
+    async fn run(&self) -> anyhow::Result<()> {
+        let export = common::config::AppConfig::new()?.export();
+        if export.ts_codes.is_empty() {
+            info!("no ts_codes configured in [export], skip export");
+            return Ok(());
+        }
+        let format: ExportFormat = export.format.parse()?;
+        std::fs::create_dir_all(&export.dir)?;
+        for ts_code in &export.ts_codes {
+            let path = Path::new(&export.dir).join(format!("{}.{}", ts_code, format.extension()));
+            match export_service::export_history(ts_code, format, &path, None, None, &self.0).await {
+                Ok(count) => info!("exported {} rows of {} to {}", count, ts_code, path.display()),
+                Err(e) => error!("export history failed, ts_code: {}, error: {:?}", ts_code, e),
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,56 @@
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use tracing::{error, info};
+use common::db::get_entity_update_columns;
+use entity::adj_factor;
+use entity::sea_orm::{DatabaseConnection, EntityTrait, TransactionTrait};
+use crate::task::Task;
+
+/// 默认回溯的自然日数
+const DAYS_AGO: u64 = 30;
+
+/// 按交易日抓取全市场股票的复权因子
+pub struct FetchAdjFactorTask(DatabaseConnection);
+
+impl FetchAdjFactorTask {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self(conn)
+    }
+
+    async fn fetch_data_by_date(&self, date: &NaiveDate) -> anyhow::Result<usize> {
+        let factors = ext_api::tushare::adj_factor(date).await?;
+        let tx = self.0.begin().await?;
+        for factor in &factors {
+            let active_model = adj_factor::ActiveModel { ..factor.clone().into() };
+            let pks = [adj_factor::Column::TsCode, adj_factor::Column::TradeDate];
+            let update_columns = get_entity_update_columns::<adj_factor::Entity>(&pks);
+            let on_conflict = entity::sea_orm::sea_query::OnConflict::columns(pks)
+                .update_columns(update_columns)
+                .to_owned();
+            if let Err(e) = adj_factor::Entity::insert(active_model).on_conflict(on_conflict).exec(&tx).await {
+                error!("insert adj_factor failed, ts_code: {}, trade_date: {}, error: {:?}", factor.ts_code, factor.trade_date, e);
+            }
+        }
+        tx.commit().await?;
+        Ok(factors.len())
+    }
+}
+
+#[async_trait]
+impl Task for FetchAdjFactorTask {
+    fn get_schedule(&self) -> String {
+        "0 15 23 * * *".to_string()
+    }
+
+    async fn run(&self) -> anyhow::Result<()> {
+        let dates = super::get_calendar_dates(super::fetch_window_days("adj_factor", DAYS_AGO), &self.0).await?;
+        for date in &dates {
+            match self.fetch_data_by_date(date).await {
+                Ok(total) => info!("insert adj_factor complete, trade_date: {}, total: {}", date, total),
+                Err(e) => error!("fetch adj_factor failed, trade_date: {}, error: {:?}", date, e),
+            }
+        }
+        info!("fetch adj_factor task complete");
+        Ok(())
+    }
+}
@@ -6,6 +6,8 @@ use entity::stock;
 use crate::task::Task;
 
 use entity::sea_orm::EntityTrait;
+use entity::sea_orm::{ColumnTrait, QueryFilter};
+use crate::task::finance_diff::{changed_statements, fetch_start_date, full_refresh};
 use entity::sea_orm::ActiveModelTrait;
 use common::db::get_entity_update_columns;
 use entity::sea_orm::TransactionTrait;
@@ -28,13 +30,24 @@ impl Task for FetchBalancesheetTask {
         let stocks: Vec<stock::Model> = stock::Entity::find().all(&self.0).await?;
         let mut curr = 0;
         for stock in &stocks {
-            let balancesheet = ext_api::tushare::balancesheet(&stock.ts_code).await;
+            // 增量模式下只抓取已入库最新报告期之后公告的记录
+            let stored = if full_refresh() {
+                vec![]
+            } else {
+                entity::balancesheet::Entity::find()
+                    .filter(ColumnTrait::eq(&entity::balancesheet::Column::TsCode, &stock.ts_code))
+                    .all(&self.0)
+                    .await?
+            };
+            let start_date = fetch_start_date(&stored);
+            let balancesheet = ext_api::tushare::balancesheet(&stock.ts_code, start_date.as_deref()).await;
             if let Err(e) = balancesheet {
                 warn!("failed to fetch balancesheet for {}, {:?}", stock.ts_code, e);
                 continue;
             }
+            let changed = changed_statements(&stored, balancesheet?);
             let tx = self.0.begin().await?;
-            for balance in balancesheet? {
+            for balance in &changed {
                 let active_model = entity::balancesheet::ActiveModel { ..balance.clone().into() };
                 // ts_code  ann_date f_ann_date  end_date report_type comp_type
                 let pks = [
@@ -57,7 +70,7 @@ impl Task for FetchBalancesheetTask {
             }
             tx.commit().await?;
             curr += 1;
-            info!("insert balancesheet complete, ts_code: {}, progress: {}/{}", stock.ts_code, curr, stocks.len());
+            info!("insert balancesheet complete, ts_code: {}, changed: {}, progress: {}/{}", stock.ts_code, changed.len(), curr, stocks.len());
         }
         info!("fetch balancesheet task complete");
         Ok(())
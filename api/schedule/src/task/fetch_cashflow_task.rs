@@ -9,6 +9,8 @@ use ext_api::tushare;
 use ext_api::tushare::cashflow;
 
 use entity::sea_orm::EntityTrait;
+use entity::sea_orm::{ColumnTrait, QueryFilter};
+use crate::task::finance_diff::{changed_statements, fetch_start_date, full_refresh};
 use entity::sea_orm::ActiveModelTrait;
 
 pub struct FetchCashflowTask(DatabaseConnection);
@@ -29,13 +31,24 @@ impl Task for FetchCashflowTask {
         let stocks: Vec<stock::Model> = stock::Entity::find().all(&self.0).await?;
         let mut curr = 0;
         for stock in &stocks {
-            let cashflow = cashflow(&stock.ts_code).await;
+            // 增量模式下只抓取已入库最新报告期之后公告的记录
+            let stored = if full_refresh() {
+                vec![]
+            } else {
+                entity::cashflow::Entity::find()
+                    .filter(ColumnTrait::eq(&entity::cashflow::Column::TsCode, &stock.ts_code))
+                    .all(&self.0)
+                    .await?
+            };
+            let start_date = fetch_start_date(&stored);
+            let cashflow = cashflow(&stock.ts_code, start_date.as_deref()).await;
             if let Err(e) = cashflow {
                 error!("fetch cashflow failed, ts_code: {}, error: {:?}", stock.ts_code, e);
                 continue;
             }
+            let changed = changed_statements(&stored, cashflow?);
             let tx = self.0.begin().await?;
-            for cash in cashflow? {
+            for cash in &changed {
                 let active_model = entity::cashflow::ActiveModel { ..cash.clone().into() };
                 // ts_code  ann_date f_ann_date  end_date report_type comp_type
                 let pks = [
@@ -58,7 +71,7 @@ impl Task for FetchCashflowTask {
             }
             tx.commit().await?;
             curr += 1;
-            info!("insert cashflow complete, ts_code: {}, progress: {}/{}", stock.ts_code, curr, stocks.len());
+            info!("insert cashflow complete, ts_code: {}, changed: {}, progress: {}/{}", stock.ts_code, changed.len(), curr, stocks.len());
         }
         info!("fetch cashflow task complete");
         Ok(())
@@ -0,0 +1,57 @@
+use async_trait::async_trait;
+use tracing::{error, info, warn};
+use common::db::get_entity_update_columns;
+use entity::sea_orm::{DatabaseConnection, EntityTrait, TransactionTrait};
+use entity::{dividend, stock};
+use crate::task::Task;
+
+pub struct FetchDividendTask(DatabaseConnection);
+
+impl FetchDividendTask {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self(db)
+    }
+}
+
+#[async_trait]
+impl Task for FetchDividendTask {
+    fn get_schedule(&self) -> String {
+        "0 0 2 * * 6".to_string()
+    }
+
+    async fn run(&self) -> anyhow::Result<()> {
+        let stocks: Vec<stock::Model> = stock::Entity::find().all(&self.0).await?;
+        let mut curr = 0;
+        for stock in &stocks {
+            let dividends = match ext_api::tushare::dividend(&stock.ts_code).await {
+                Ok(dividends) => dividends,
+                Err(e) => {
+                    warn!("failed to fetch dividend for {}, {:?}", stock.ts_code, e);
+                    continue;
+                }
+            };
+            let tx = self.0.begin().await?;
+            for dividend in dividends {
+                let active_model = dividend::ActiveModel { ..dividend.clone().into() };
+                let pks = [
+                    dividend::Column::TsCode,
+                    dividend::Column::EndDate,
+                    dividend::Column::AnnDate,
+                    dividend::Column::DivProc,
+                ];
+                let update_columns = get_entity_update_columns::<dividend::Entity>(&pks);
+                let on_conflict = entity::sea_orm::sea_query::OnConflict::columns(pks)
+                    .update_columns(update_columns)
+                    .to_owned();
+                if let Err(e) = dividend::Entity::insert(active_model).on_conflict(on_conflict).exec(&tx).await {
+                    error!("insert dividend failed, ts_code: {}, end_date: {}, error: {:?}", stock.ts_code, dividend.end_date, e);
+                }
+            }
+            tx.commit().await?;
+            curr += 1;
+            info!("insert dividend complete, ts_code: {}, progress: {}/{}", stock.ts_code, curr, stocks.len());
+        }
+        info!("fetch dividend task complete");
+        Ok(())
+    }
+}
@@ -25,12 +25,12 @@ impl FetchFundDailyTask {
 #[async_trait]
 impl Task for FetchFundDailyTask {
     fn get_schedule(&self) -> String {
-        todo!()
+        "0 5 23 * * *".to_string()
     }
 
     async fn run(&self) -> anyhow::Result<()> {
         let funds: Vec<etf::Model> = etf::Entity::find().all(&self.0).await?;
-        let (start_date, end_date) = super::get_start_end_date_from_now(250)?;
+        let (start_date, end_date) = super::get_start_end_date_from_now(super::fetch_window_days("fund_daily", 250))?;
         let mut curr = 0;
         for fund in &funds {
             let res = ext_api::tushare::fund_daily(&fund.ts_code, &start_date, &end_date).await;
@@ -23,7 +23,7 @@ impl FetchFundPortfolioTask {
 #[async_trait]
 impl Task for FetchFundPortfolioTask {
     fn get_schedule(&self) -> String {
-        todo!()
+        "0 0 0 1 * * *".to_string()
     }
 
     async fn run(&self) -> anyhow::Result<()> {
@@ -0,0 +1,61 @@
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use tracing::{error, info};
+use common::db::get_entity_update_columns;
+use entity::hk_hold;
+use entity::sea_orm::{DatabaseConnection, EntityTrait, TransactionTrait};
+use crate::task::Task;
+
+/// 默认回溯的自然日数
+const DAYS_AGO: u64 = 30;
+/// 沪股通、深股通, 港股通(HK)持股为港股, 不保存
+const EXCHANGES: [&str; 2] = ["SH", "SZ"];
+
+/// 按交易日抓取北向资金持股快照
+pub struct FetchHkHoldTask(DatabaseConnection);
+
+impl FetchHkHoldTask {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self(conn)
+    }
+
+    async fn fetch_data_by_date(&self, date: &NaiveDate) -> anyhow::Result<usize> {
+        let mut holds = vec![];
+        for exchange in EXCHANGES {
+            holds.extend(ext_api::tushare::hk_hold(date, exchange).await?);
+        }
+        let tx = self.0.begin().await?;
+        for hold in &holds {
+            let active_model = hk_hold::ActiveModel { ..hold.clone().into() };
+            let pks = [hk_hold::Column::TsCode, hk_hold::Column::TradeDate];
+            let update_columns = get_entity_update_columns::<hk_hold::Entity>(&pks);
+            let on_conflict = entity::sea_orm::sea_query::OnConflict::columns(pks)
+                .update_columns(update_columns)
+                .to_owned();
+            if let Err(e) = hk_hold::Entity::insert(active_model).on_conflict(on_conflict).exec(&tx).await {
+                error!("insert hk_hold failed, ts_code: {}, trade_date: {}, error: {:?}", hold.ts_code, hold.trade_date, e);
+            }
+        }
+        tx.commit().await?;
+        Ok(holds.len())
+    }
+}
+
+#[async_trait]
+impl Task for FetchHkHoldTask {
+    fn get_schedule(&self) -> String {
+        "0 30 23 * * *".to_string()
+    }
+
+    async fn run(&self) -> anyhow::Result<()> {
+        let dates = super::get_calendar_dates(super::fetch_window_days("hk_hold", DAYS_AGO), &self.0).await?;
+        for date in &dates {
+            match self.fetch_data_by_date(date).await {
+                Ok(total) => info!("insert hk_hold complete, trade_date: {}, total: {}", date, total),
+                Err(e) => error!("fetch hk_hold failed, trade_date: {}, error: {:?}", date, e),
+            }
+        }
+        info!("fetch hk_hold task complete");
+        Ok(())
+    }
+}
@@ -6,6 +6,8 @@ use chrono::{Local, NaiveDate};
 use tracing::{error, info};
 use common::db::get_entity_update_columns;
 use entity::sea_orm::EntityTrait;
+use entity::sea_orm::{ColumnTrait, QueryFilter};
+use crate::task::finance_diff::{changed_statements, fetch_start_date, full_refresh};
 use entity::sea_orm::ActiveModelTrait;
 use crate::task::fetch_balancesheet_task::FetchBalancesheetTask;
 
@@ -25,13 +27,24 @@ impl Task for FetchIncomeTask {
         let stocks: Vec<stock::Model> = stock::Entity::find().all(&self.0).await?;
         let mut curr = 0;
         for stock in &stocks {
-            let incomes = ext_api::tushare::income(&stock.ts_code).await;
+            // 增量模式下只抓取已入库最新报告期之后公告的记录
+            let stored = if full_refresh() {
+                vec![]
+            } else {
+                entity::income::Entity::find()
+                    .filter(ColumnTrait::eq(&entity::income::Column::TsCode, &stock.ts_code))
+                    .all(&self.0)
+                    .await?
+            };
+            let start_date = fetch_start_date(&stored);
+            let incomes = ext_api::tushare::income(&stock.ts_code, start_date.as_deref()).await;
             if let Err(e) = incomes {
                 error!("fetch income failed, ts_code: {}, error: {:?}", stock.ts_code, e);
                 continue;
             }
+            let changed = changed_statements(&stored, incomes?);
             let tx = self.0.begin().await?;
-            for income in incomes? {
+            for income in &changed {
                 let active_model = entity::income::ActiveModel { ..income.clone().into() };
                 // ts_code  ann_date f_ann_date  end_date report_type comp_type
                 let pks = [
@@ -54,7 +67,7 @@ impl Task for FetchIncomeTask {
             }
             tx.commit().await?;
             curr += 1;
-            info!("insert income complete, ts_code: {}, progress: {}/{}", stock.ts_code, curr, stocks.len());
+            info!("insert income complete, ts_code: {}, changed: {}, progress: {}/{}", stock.ts_code, changed.len(), curr, stocks.len());
         }
         info!("fetch income task complete");
         Ok(())
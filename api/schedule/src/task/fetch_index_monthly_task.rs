@@ -20,12 +20,12 @@ impl FetchIndexMonthlyTask {
 #[async_trait]
 impl Task for FetchIndexMonthlyTask {
     fn get_schedule(&self) -> String {
-        todo!()
+        "0 0 0 1 * * *".to_string()
     }
 
     async fn run(&self) -> anyhow::Result<()> {
         let indexes: Vec<index::Model> = index::Entity::find().all(&self.0).await?;
-        let (start_date, end_date) = super::get_start_end_date_from_default()?;
+        let (start_date, end_date) = super::get_start_end_date_from_default("index_monthly")?;
         let mut curr = 0;
         for index in &indexes {
             let res = ext_api::tushare::index_monthly(&index.ts_code, &start_date, &end_date).await;
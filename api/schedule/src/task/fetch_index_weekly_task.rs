@@ -20,7 +20,7 @@ impl FetchIndexWeeklyTask {
 #[async_trait]
 impl Task for FetchIndexWeeklyTask {
     fn get_schedule(&self) -> String {
-        todo!()
+        "0 5 23 * * Fri".to_string()
     }
 
     async fn run(&self) -> anyhow::Result<()> {
@@ -20,7 +20,7 @@ impl FetchMarginDetailTask {
 #[async_trait]
 impl Task for FetchMarginDetailTask {
     fn get_schedule(&self) -> String {
-        todo!()
+        "0 5 23 * * *".to_string()
     }
 
     async fn run(&self) -> anyhow::Result<()> {
@@ -21,7 +21,7 @@ impl FetchMarginTask {
 #[async_trait]
 impl Task for FetchMarginTask {
     fn get_schedule(&self) -> String {
-        todo!()
+        "0 5 23 * * *".to_string()
     }
 
     async fn run(&self) -> anyhow::Result<()> {
@@ -19,7 +19,7 @@ impl FetchMoneyflowTask {
 #[async_trait]
 impl Task for FetchMoneyflowTask{
     fn get_schedule(&self) -> String {
-        todo!()
+        "0 5 23 * * *".to_string()
     }
 
     async fn run(&self) -> anyhow::Result<()> {
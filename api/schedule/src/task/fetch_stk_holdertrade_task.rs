@@ -22,7 +22,7 @@ impl FetchStkHoldertradeTask {
 #[async_trait]
 impl Task for FetchStkHoldertradeTask {
     fn get_schedule(&self) -> String {
-        todo!()
+        "0 0 0 1 * * *".to_string()
     }
 
     async fn run(&self) -> anyhow::Result<()> {
@@ -86,7 +86,7 @@ impl Task for FetchStockDailyBasicTask {
 
 
     async fn run(&self) -> anyhow::Result<()> {
-        let dates = super::get_calendar_dates(DAYS_AGO, &self.0).await?;
+        let dates = super::get_calendar_dates(super::fetch_window_days("stock_daily_basic", DAYS_AGO), &self.0).await?;
         for date in &dates {
             let res = self.fetch_data_by_date(date).await;
             if let Err(e) = res {
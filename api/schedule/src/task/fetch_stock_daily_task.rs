@@ -18,7 +18,7 @@ use entity::sea_orm::EntityOrSelect;
 use tokio::sync::{mpsc, Semaphore};
 use std::sync::Arc;
 use tokio::sync::mpsc::Receiver;
-use common::db::get_entity_update_columns;
+use common::db::upsert_or_dead_letter;
 use entity::sea_orm::prelude::Decimal;
 
 const DAYS_AGO: u64 = 250;
@@ -38,24 +38,11 @@ impl FetchStockDailyTask {
             if let Some(list_date) = &stock.list_date {
                 let list_date = NaiveDate::parse_from_str(list_date, "%Y%m%d")?;
                 let dailys = tushare::daily(Some(&stock.ts_code), &list_date, &date).await?;
-                for daily in &dailys {
-                     let active_model = entity::stock_daily::ActiveModel { ..daily.clone().into() };
-                // ts_code  ann_date f_ann_date  end_date report_type comp_type
-                    let pks = [
-                        entity::stock_daily::Column::TsCode,
-                        entity::stock_daily::Column::TradeDate,
-                    ];
-                    let update_columns = get_entity_update_columns::<entity::stock_daily::Entity>(&pks);
-                    let on_conflict = entity::sea_orm::sea_query::OnConflict::columns(pks)
-                        .update_columns(update_columns)
-                        .to_owned();
-
-                    if let Err(e) = entity::stock_daily::Entity::insert(active_model)
-                        .on_conflict(on_conflict)
-                        .exec(&tx)
-                        .await {
-                        error!("insert stock_daily failed, ts_code: {}, error: {:?}", stock.ts_code, e);
-                    }
+                // 单行写入失败时记入死信, 不影响该股票的其它日线
+                let pks = [stock_daily::Column::TsCode, stock_daily::Column::TradeDate];
+                let summary = upsert_or_dead_letter::<stock_daily::Entity, _>(&tx, "FetchStockDailyTask", dailys, &pks).await;
+                if summary.dead_lettered > 0 {
+                    warn!("insert stock_daily partially failed, ts_code: {}, dead lettered: {}", stock.ts_code, summary.dead_lettered);
                 }
             }
             curr += 1;
@@ -69,24 +56,11 @@ impl FetchStockDailyTask {
 
         let tx = self.0.begin().await?;
         let total = stock_dailys.len();
-        let mut curr = 0;
-        for stock_daily_data in stock_dailys {
-            let active_model = entity::stock_daily::ActiveModel { ..stock_daily_data.clone().into() };
-            let pks = [
-                stock_daily::Column::TsCode,
-                stock_daily::Column::TradeDate
-            ];
-            let update_columns = get_entity_update_columns::<entity::stock_daily::Entity>(&pks);
-            let on_conflict = entity::sea_orm::sea_query::OnConflict::columns(pks)
-                .update_columns(update_columns)
-                .to_owned();
-
-            if let Err(e) = entity::stock_daily::Entity::insert(active_model)
-                .on_conflict(on_conflict)
-                .exec(&tx)
-                .await {
-                error!("insert stock_daily failed, ts code: {}, trade date: {}, error: {:?}", stock_daily_data.ts_code, stock_daily_data.trade_date, e);
-            }
+        // 单行写入失败时记入死信, 不影响当天其它股票
+        let pks = [stock_daily::Column::TsCode, stock_daily::Column::TradeDate];
+        let summary = upsert_or_dead_letter::<stock_daily::Entity, _>(&tx, "FetchStockDailyTask", stock_dailys, &pks).await;
+        if summary.dead_lettered > 0 {
+            warn!("insert stock_daily partially failed, trade_date: {}, dead lettered: {}", date, summary.dead_lettered);
         }
         info!("insert stock_daily complete, trade_date: {}, total: {}", date, total);
         tx.commit().await?;
@@ -101,7 +75,7 @@ impl Task for FetchStockDailyTask {
     }
 
     async fn run(&self) -> anyhow::Result<()> {
-        let dates = super::get_calendar_dates(DAYS_AGO, &self.0).await?;
+        let dates = super::get_calendar_dates(super::fetch_window_days("stock_daily", DAYS_AGO), &self.0).await?;
         info!("fetch    all s   tock_daily tasks run..., start = {}, end = {}", dates[0], dates[dates.len() - 1]);
         for date in &dates {
             let res = self.fetch_data_by_date(date).await;
@@ -8,6 +8,7 @@ use entity::stock::Model as Stock;
 use entity::sea_orm::ActiveModelTrait;
 use entity::sea_orm::EntityTrait;
 use common::db::get_entity_update_columns;
+use service::security::security_change_service;
 
 
 pub struct FetchStockListTask(DatabaseConnection);
@@ -27,6 +28,10 @@ impl Task for FetchStockListTask {
 
     async fn run(&self) -> anyhow::Result<()> {
         let mut stocks = tushare::stock_basic().await?;
+        match security_change_service::record_stock_list_changes(&stocks, &self.0).await {
+            Ok(changes) => info!("stock list changes: {}", changes.len()),
+            Err(e) => error!("record stock list changes failed, error: {:?}", e),
+        }
         let tx = self.0.begin().await?;
         let total = stocks.len();
         let mut curr = 0;
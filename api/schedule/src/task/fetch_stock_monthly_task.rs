@@ -37,7 +37,7 @@ impl Task for FetchStockMonthlyTask {
 
     async fn run(&self) -> anyhow::Result<()> {
         let stocks: Vec<stock::Model> = stock::Entity::find().all(&self.0).await?;
-        let (start_date, end_date) = super::get_start_end_date_from_default()?;
+        let (start_date, end_date) = super::get_start_end_date_from_default("stock_monthly")?;
         let mut curr = 0;
         for stock in &stocks {
             let res = ext_api::tushare::monthly(&stock.ts_code, &start_date, &end_date).await;
@@ -0,0 +1,112 @@
+//! 财务报表(利润表、现金流量表、资产负债表)的增量抓取
+//!
+//! 只抓取已入库最新报告期之后公告的记录, 与已入库记录对比后只写入新增和被更正的记录
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use anyhow::anyhow;
+use entity::{balancesheet, cashflow, income};
+
+/// 手动补数据时改为全量抓取, 只能设置一次
+static FULL_REFRESH: OnceLock<bool> = OnceLock::new();
+
+pub fn set_finance_full_refresh() -> anyhow::Result<()> {
+    FULL_REFRESH.set(true).map_err(|_| anyhow!("finance full refresh already set"))
+}
+
+pub(crate) fn full_refresh() -> bool {
+    FULL_REFRESH.get().copied().unwrap_or(false)
+}
+
+/// 一条财务报表记录
+pub(crate) trait FinanceStatement: Clone + PartialEq {
+    /// 与入库时冲突判断使用的主键一致: (ts_code, report_type, ann_date, f_ann_date)
+    fn key(&self) -> (&str, Option<&str>, Option<&str>, Option<&str>);
+    /// 报告期
+    fn end_date(&self) -> Option<&str>;
+}
+
+impl FinanceStatement for income::Model {
+    fn key(&self) -> (&str, Option<&str>, Option<&str>, Option<&str>) {
+        (&self.ts_code, Some(&self.report_type), Some(&self.ann_date), Some(&self.f_ann_date))
+    }
+
+    fn end_date(&self) -> Option<&str> {
+        self.end_date.as_deref()
+    }
+}
+
+impl FinanceStatement for cashflow::Model {
+    fn key(&self) -> (&str, Option<&str>, Option<&str>, Option<&str>) {
+        (&self.ts_code, self.report_type.as_deref(), self.ann_date.as_deref(), self.f_ann_date.as_deref())
+    }
+
+    fn end_date(&self) -> Option<&str> {
+        Some(&self.end_date)
+    }
+}
+
+impl FinanceStatement for balancesheet::Model {
+    fn key(&self) -> (&str, Option<&str>, Option<&str>, Option<&str>) {
+        (&self.ts_code, Some(&self.report_type), Some(&self.ann_date), Some(&self.f_ann_date))
+    }
+
+    fn end_date(&self) -> Option<&str> {
+        Some(&self.end_date)
+    }
+}
+
+/// 增量抓取的公告起始日: 已入库的最新报告期, 新报告期和对旧报告期的更正都在其之后公告; 没有数据时全量抓取
+pub(crate) fn fetch_start_date<T: FinanceStatement>(stored: &[T]) -> Option<String> {
+    stored.iter().filter_map(|v| v.end_date()).max().map(|v| v.to_string())
+}
+
+/// 抓取结果中需要写入的记录: 新增的记录, 以及与已入库记录主键相同但数值不同(被更正)的记录
+pub(crate) fn changed_statements<T: FinanceStatement>(stored: &[T], fetched: Vec<T>) -> Vec<T> {
+    let stored: HashMap<_, &T> = stored.iter().map(|v| (v.key(), v)).collect();
+    fetched
+        .into_iter()
+        .filter(|v| stored.get(&v.key()).is_none_or(|old| *old != v))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use entity::sea_orm::prelude::Decimal;
+
+    fn income(end_date: &str, ann_date: &str, n_income: i64) -> income::Model {
+        let mut model: income::Model = serde_json::from_value(serde_json::json!({
+            "ts_code": "600000.SH",
+            "ann_date": ann_date,
+            "f_ann_date": ann_date,
+            "end_date": end_date,
+            "report_type": "1",
+        }))
+        .unwrap();
+        model.n_income = Some(Decimal::from(n_income));
+        model
+    }
+
+    #[test]
+    fn test_changed_statements() {
+        let stored = vec![income("20231231", "20240330", 100), income("20240331", "20240428", 30)];
+        assert_eq!(fetch_start_date(&stored).as_deref(), Some("20240331"));
+
+        let fetched = vec![
+            // 未变化, 跳过
+            income("20240331", "20240428", 30),
+            // 更正了净利润
+            income("20231231", "20240330", 90),
+            // 新报告期
+            income("20240630", "20240830", 60),
+        ];
+        let changed = changed_statements(&stored, fetched);
+        let changed: Vec<(Option<&str>, Option<Decimal>)> = changed.iter().map(|v| (v.end_date(), v.n_income)).collect();
+        assert_eq!(changed, vec![(Some("20231231"), Some(Decimal::from(90))), (Some("20240630"), Some(Decimal::from(60)))]);
+
+        // 没有已入库数据时全量抓取
+        assert_eq!(fetch_start_date::<income::Model>(&[]), None);
+    }
+}
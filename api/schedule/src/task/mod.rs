@@ -1,3 +1,6 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
 use anyhow::anyhow;
 use async_trait::async_trait;
 use chrono::{Days, Local, NaiveDate};
@@ -42,28 +45,92 @@ pub mod fetch_eng_translate_task;
 pub mod fetch_hm_detail_task;
 pub mod fetch_limit_list_d_task;
 pub mod fetch_fina_mainbz_task;
+pub mod fetch_hk_hold_task;
+pub mod fetch_dividend_task;
+pub mod fetch_adj_factor_task;
+pub mod verify_adj_factor_task;
+pub mod portfolio_valuation_task;
+pub mod export_history_task;
+pub(crate) mod finance_diff;
+
+pub use finance_diff::set_finance_full_refresh;
 
 #[async_trait]
 pub trait Task: Send + Sync {
     fn get_schedule(&self) -> String;
     async fn run(&self) -> anyhow::Result<()>;
+
+    /// 任务名, 默认为实现类型的名称(如 `FetchStockDailyTask`), 与任务注册表中的名称一致
+    fn get_name(&self) -> String {
+        let full = std::any::type_name::<Self>();
+        full.rsplit("::").next().unwrap_or(full).to_string()
+    }
+}
+
+/// 手动补数据时覆盖各任务默认的回溯天数, 只能设置一次
+static LOOKBACK_DAYS: OnceLock<u64> = OnceLock::new();
+
+pub fn set_lookback_days(days: u64) -> anyhow::Result<()> {
+    LOOKBACK_DAYS.set(days).map_err(|_| anyhow!("lookback days already set"))
+}
+
+/// 配置文件 `[schedule.windows]` 中各任务的回溯天数, 按任务的配置名(如 `stock_daily`)索引, 启动时设置一次
+static FETCH_WINDOWS: OnceLock<HashMap<String, u64>> = OnceLock::new();
+
+pub fn set_fetch_windows(windows: HashMap<String, u64>) -> anyhow::Result<()> {
+    FETCH_WINDOWS.set(windows).map_err(|_| anyhow!("fetch windows already set"))
+}
+
+/// 任务 `key` 的默认回溯天数: 配置了 `[schedule.windows] <key>` 时取配置值, 否则为 `default_days`;
+/// 手动运行时指定的天数仍优先于这里的值
+fn fetch_window_days(key: &str, default_days: u64) -> u64 {
+    FETCH_WINDOWS.get().map_or(default_days, |windows| window_from(windows, key, default_days))
 }
 
-fn get_start_end_date_from_default() -> anyhow::Result<(NaiveDate, NaiveDate)> {
+/// `windows` 中任务 `key` 的回溯天数, 未配置时为 `default_days`
+fn window_from(windows: &HashMap<String, u64>, key: &str, default_days: u64) -> u64 {
+    windows.get(key).copied().unwrap_or(default_days)
+}
+
+tokio::task_local! {
+    /// 单次手动运行覆盖的回溯天数, 只在 `run_with_lookback` 的作用域内生效
+    static RUN_LOOKBACK_DAYS: u64;
+}
+
+/// 运行一次任务, `days` 不为 None 时覆盖本次运行的回溯天数, 不影响同时运行的其它任务
+///
+/// 覆盖只对任务自身的 future 生效, 任务内部 spawn 出去的子任务仍使用默认回溯天数
+pub async fn run_with_lookback(task: &dyn Task, days: Option<u64>) -> anyhow::Result<()> {
+    match days {
+        Some(days) => RUN_LOOKBACK_DAYS.scope(days, task.run()).await,
+        None => task.run().await,
+    }
+}
+
+fn lookback_days(default_days: u64) -> u64 {
+    RUN_LOOKBACK_DAYS
+        .try_with(|days| *days)
+        .ok()
+        .or_else(|| LOOKBACK_DAYS.get().copied())
+        .unwrap_or(default_days)
+}
+
+/// 默认回溯 10 天, 可通过 `[schedule.windows] <key>` 调整
+fn get_start_end_date_from_default(key: &str) -> anyhow::Result<(NaiveDate, NaiveDate)> {
     let today = Local::now();
-    let start = Local::now().checked_sub_days(Days::new(10)).ok_or(anyhow!("failed to sub days"))?;
+    let start = Local::now().checked_sub_days(Days::new(lookback_days(fetch_window_days(key, 10)))).ok_or(anyhow!("failed to sub days"))?;
     Ok((start.date_naive(), today.date_naive()))
 }
 
 fn get_start_end_date_from_now(days_num_before_today: u64) -> anyhow::Result<(NaiveDate, NaiveDate)> {
     let today = Local::now();
-    let start = Local::now().checked_sub_days(Days::new(days_num_before_today)).ok_or(anyhow!("failed to sub days"))?;
+    let start = Local::now().checked_sub_days(Days::new(lookback_days(days_num_before_today))).ok_or(anyhow!("failed to sub days"))?;
     Ok((start.date_naive(), today.date_naive()))
 }
 
 fn get_start_end_date(days_num_before_today: u64) -> anyhow::Result<(String, String)> {
     let today = Local::now().format("%Y%m%d").to_string();
-    let start = Local::now().checked_sub_days(Days::new(days_num_before_today)).ok_or(anyhow!("date is none"))?.format("%Y%m%d").to_string();
+    let start = Local::now().checked_sub_days(Days::new(lookback_days(days_num_before_today))).ok_or(anyhow!("date is none"))?.format("%Y%m%d").to_string();
     Ok((start, today))
 }
 
@@ -81,4 +148,41 @@ async fn get_calendar_dates(days_num_before_today: u64, conn: &DatabaseConnectio
         .await?;
     let dates = dates.iter().map(|v| NaiveDate::parse_from_str(&v.cal_date, "%Y%m%d").unwrap()).collect();
     Ok(dates)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    struct LookbackProbe(Mutex<Vec<u64>>);
+
+    #[async_trait]
+    impl Task for LookbackProbe {
+        fn get_schedule(&self) -> String {
+            "0 0 * * * *".into()
+        }
+
+        async fn run(&self) -> anyhow::Result<()> {
+            self.0.lock().unwrap().push(lookback_days(10));
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_with_lookback() {
+        let probe = LookbackProbe(Mutex::new(Vec::new()));
+        run_with_lookback(&probe, Some(30)).await.unwrap();
+        run_with_lookback(&probe, None).await.unwrap();
+        assert_eq!(*probe.0.lock().unwrap(), vec![30, 10]);
+    }
+
+    #[test]
+    fn test_window_from() {
+        let windows = HashMap::from([("stock_daily".to_string(), 5)]);
+        assert_eq!(window_from(&windows, "stock_daily", 250), 5);
+        // 未配置的任务使用默认值
+        assert_eq!(window_from(&windows, "hk_hold", 30), 30);
+        assert_eq!(window_from(&HashMap::new(), "stock_daily", 250), 250);
+    }
+}
@@ -0,0 +1,29 @@
+use async_trait::async_trait;
+use chrono::Local;
+use tracing::info;
+use entity::sea_orm::DatabaseConnection;
+use crate::task::Task;
+
+/// 每日收盘数据入库后记录全部组合当天的估值, 供时间加权收益率计算使用
+pub struct PortfolioValuationTask(DatabaseConnection);
+
+impl PortfolioValuationTask {
+    pub fn new(db: DatabaseConnection) -> Self {
+        Self(db)
+    }
+}
+
+#[async_trait]
+impl Task for PortfolioValuationTask {
+    fn get_schedule(&self) -> String {
+        // 在 FetchStockDailyTask(23:05) 之后运行
+        "0 30 23 * * *".to_string()
+    }
+
+    async fn run(&self) -> anyhow::Result<()> {
+        let today = Local::now().date_naive();
+        let count = service::portfolio_service::snapshot_all_valuations(&self.0, &today).await?;
+        info!("portfolio valuation complete, date: {}, portfolios: {}", today, count);
+        Ok(())
+    }
+}
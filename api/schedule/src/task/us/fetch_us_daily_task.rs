@@ -14,17 +14,147 @@ use entity::sea_orm::QueryFilter;
 use entity::sea_orm::ColumnTrait;
 
 use entity::sea_orm::EntityOrSelect;
-use std::sync::Arc;
+use std::future::Future;
+use std::sync::{Arc, OnceLock};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use common::config::UsDailyFetch;
 use common::db::get_entity_update_columns;
+use common::task_runner::run_with_limit;
 use entity::sea_orm::prelude::Decimal;
 use entity::sea_orm::sea_query::OnConflict;
 
-pub struct FetchUsDailyTask(DatabaseConnection);
+/// 默认并发数
+const DEFAULT_CONCURRENCY: usize = 8;
+/// 单个 symbol 的默认超时时间
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+/// 默认每完成多少个 symbol 打印一次进度
+const DEFAULT_PROGRESS_INTERVAL: usize = 200;
+
+/// 配置文件 `[schedule.us_daily]` 中的并发参数, 启动时设置一次
+static US_DAILY_FETCH: OnceLock<UsDailyFetch> = OnceLock::new();
+
+pub fn set_us_daily_fetch(config: UsDailyFetch) -> anyhow::Result<()> {
+    US_DAILY_FETCH.set(config).map_err(|_| anyhow!("us daily fetch config already set"))
+}
+
+/// 拉取美股最近 3 个月日线
+///
+/// 以有限并发拉取, 请求经过 tushare 的全局限流器和 us_daily 接口的最小间隔限制;
+/// 单个 symbol 超时后放弃, 不会阻塞整批任务
+pub struct FetchUsDailyTask {
+    conn: DatabaseConnection,
+    concurrency: usize,
+    timeout: Duration,
+    progress_interval: usize,
+}
 
 impl FetchUsDailyTask {
     pub fn new(connection: DatabaseConnection) -> Self {
-        FetchUsDailyTask(connection)
+        FetchUsDailyTask {
+            conn: connection,
+            concurrency: DEFAULT_CONCURRENCY,
+            timeout: DEFAULT_TIMEOUT,
+            progress_interval: DEFAULT_PROGRESS_INTERVAL,
+        }
+    }
+
+    /// 按 `[schedule.us_daily]` 配置创建, 未配置的项使用默认值
+    pub fn from_config(connection: DatabaseConnection) -> Self {
+        let config = US_DAILY_FETCH.get().copied().unwrap_or_default();
+        let mut task = Self::new(connection);
+        if let Some(concurrency) = config.concurrency {
+            task = task.with_concurrency(concurrency);
+        }
+        if let Some(timeout_secs) = config.timeout_secs {
+            task = task.with_timeout(Duration::from_secs(timeout_secs));
+        }
+        if let Some(progress_interval) = config.progress_interval {
+            task = task.with_progress_interval(progress_interval);
+        }
+        task
     }
+
+    /// 设置最大并发数, 至少为 1
+    pub fn with_concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// 设置单个 symbol 的请求超时时间
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// 设置每完成多少个 symbol 打印一次进度, 至少为 1
+    pub fn with_progress_interval(mut self, progress_interval: usize) -> Self {
+        self.progress_interval = progress_interval.max(1);
+        self
+    }
+
+    async fn save(conn: &DatabaseConnection, datas: &[UsDaily]) -> anyhow::Result<()> {
+        let tx = conn.begin().await?;
+        for data in datas {
+            let pks = [
+                us_daily::Column::TsCode,
+                us_daily::Column::TradeDate,
+            ];
+            let update_columns = get_entity_update_columns::<us_daily::Entity>(&pks);
+            let on_conflict = OnConflict::columns(pks)
+                .update_columns(update_columns)
+                .to_owned();
+            let am = us_daily::ActiveModel { ..data.clone().into() };
+            if let Err(e) = us_daily::Entity::insert(am)
+                .on_conflict(on_conflict)
+                .exec(&tx)
+                .await {
+                error!("insert us_daily failed err: {:?}",  e);
+            }
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+/// 以最多 `concurrency` 个并发对每个 symbol 调用 `fetch`, 超过 `timeout` 的请求视为失败;
+/// 每个 symbol 完成(成功/失败/超时)后调用一次 `on_result`, 每完成 `progress_interval` 个打印一次进度
+async fn fetch_concurrently<T, F, Fut, H, HF>(
+    symbols: Vec<String>,
+    concurrency: usize,
+    timeout: Duration,
+    progress_interval: usize,
+    fetch: F,
+    on_result: H,
+)
+where
+    T: Send + 'static,
+    F: Fn(String) -> Fut + Send + Sync,
+    Fut: Future<Output = anyhow::Result<T>> + Send + 'static,
+    H: Fn(String, anyhow::Result<T>) -> HF + Send + Sync,
+    HF: Future<Output = ()> + Send + 'static,
+{
+    let total = symbols.len();
+    let completed = AtomicUsize::new(0);
+    run_with_limit(
+        concurrency,
+        symbols,
+        |symbol| {
+            let fut = fetch(symbol);
+            async move {
+                tokio::time::timeout(timeout, fut)
+                    .await
+                    .unwrap_or_else(|_| Err(anyhow!("timeout after {:?}", timeout)))
+            }
+        },
+        |symbol, result| {
+            let done = completed.fetch_add(1, Ordering::SeqCst) + 1;
+            if done % progress_interval == 0 || done == total {
+                info!("fetch us_daily progress: {}/{}", done, total);
+            }
+            on_result(symbol, result)
+        },
+    ).await;
 }
 
 #[async_trait]
@@ -37,45 +167,94 @@ impl Task for FetchUsDailyTask {
         let us_stocks = us_stock::Entity::find()
             // .select_only()
             // .column(us_stock::Column::Symbol)
-            .all(&self.0)
+            .all(&self.conn)
             .await?;
         info!("us stock size: {}", us_stocks.len());
 
         let end_date = Local::now().naive_local();
         let start_date = end_date.checked_sub_months(Months::new(3)).unwrap().format("%Y%m%d").to_string();
         let end_date = end_date.format("%Y%m%d").to_string();
-        let mut curr = 0;
-        for stock in &us_stocks {
-            curr += 1;
-            let datas = match tushare::us_daily(&stock.symbol, &start_date, &end_date).await {
-                Ok(data) => data,
-                Err(e) => {
-                    error!("fetch us_daily failed, stock: {:?}, err: {:?}", stock.symbol, e);
-                    continue;
+        let symbols: Vec<String> = us_stocks.into_iter().map(|stock| stock.symbol).collect();
+        fetch_concurrently(
+            symbols,
+            self.concurrency,
+            self.timeout,
+            self.progress_interval,
+            |symbol| {
+                let (start_date, end_date) = (start_date.clone(), end_date.clone());
+                async move { tushare::us_daily(&symbol, &start_date, &end_date).await }
+            },
+            |symbol, result| {
+                let conn = self.conn.clone();
+                async move {
+                    let datas = match result {
+                        Ok(datas) => datas,
+                        Err(e) => {
+                            error!("fetch us_daily failed, stock: {:?}, err: {:?}", symbol, e);
+                            return;
+                        }
+                    };
+                    match Self::save(&conn, &datas).await {
+                        Ok(()) => info!("insert us_daily complete, stock: {:?}, total: {}", symbol, datas.len()),
+                        Err(e) => error!("insert us_daily failed, stock: {:?}, err: {:?}", symbol, e),
+                    }
                 }
-            };
-            let tx = self.0.begin().await?;
-            for data in &datas {
-                let pks = [
-                    us_daily::Column::TsCode,
-                    us_daily::Column::TradeDate,
-                ];
-                let update_columns = get_entity_update_columns::<us_daily::Entity>(&pks);
-                let on_conflict = OnConflict::columns(pks)
-                    .update_columns(update_columns)
-                    .to_owned();
-                let am = us_daily::ActiveModel { ..data.clone().into() };
-                if let Err(e) = us_daily::Entity::insert(am)
-                    .on_conflict(on_conflict)
-                    .exec(&tx)
-                    .await {
-                    error!("insert us_daily failed err: {:?}",  e);
-                }
-            }
-            tx.commit().await?;
-            info!("insert us_daily complete, stock: {:?}, total: {}", stock.symbol, datas.len());
-        }
+            },
+        ).await;
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+    use tokio::time::Instant;
+
+    /// 请求结束(包括超时被取消)时减少在途计数
+    struct InFlightGuard(Arc<AtomicUsize>);
+
+    impl Drop for InFlightGuard {
+        fn drop(&mut self) {
+            self.0.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slow_symbol_does_not_stall_batch() {
+        let symbols: Vec<String> = (0..10).map(|i| format!("S{i}")).collect();
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let max_in_flight = Arc::new(AtomicUsize::new(0));
+        let results = Arc::new(Mutex::new(vec![]));
+
+        let start = Instant::now();
+        fetch_concurrently(
+            symbols,
+            3,
+            Duration::from_millis(100),
+            4,
+            |symbol| {
+                let (in_flight, max_in_flight) = (in_flight.clone(), max_in_flight.clone());
+                async move {
+                    let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_in_flight.fetch_max(now, Ordering::SeqCst);
+                    let _guard = InFlightGuard(in_flight);
+                    let delay = if symbol == "S0" { Duration::from_secs(30) } else { Duration::from_millis(20) };
+                    tokio::time::sleep(delay).await;
+                    Ok(symbol)
+                }
+            },
+            |symbol, result| {
+                results.lock().unwrap().push((symbol, result.is_ok()));
+                async {}
+            },
+        ).await;
+
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert!(max_in_flight.load(Ordering::SeqCst) <= 3);
+        let results = results.lock().unwrap();
+        assert_eq!(results.len(), 10);
+        assert_eq!(results.iter().filter(|(_, ok)| !ok).map(|(s, _)| s.as_str()).collect::<Vec<_>>(), vec!["S0"]);
+    }
+}
@@ -0,0 +1,42 @@
+use async_trait::async_trait;
+use chrono::{Days, Local};
+use tracing::{info, warn};
+use entity::sea_orm::DatabaseConnection;
+use service::data_quality_service;
+use crate::task::Task;
+
+/// 检查最近多少个自然日内的除权除息
+const DAYS_AGO: u64 = 90;
+/// 每次最多抽查的股票数
+const SAMPLE_SIZE: usize = 50;
+
+/// 抽查近期有除权除息的股票, 检查除权除息日前后的复权因子, 异常记录到日志
+pub struct VerifyAdjFactorTask(DatabaseConnection);
+
+impl VerifyAdjFactorTask {
+    pub fn new(conn: DatabaseConnection) -> Self {
+        Self(conn)
+    }
+}
+
+#[async_trait]
+impl Task for VerifyAdjFactorTask {
+    fn get_schedule(&self) -> String {
+        // 在 FetchDividendTask(周六 02:00) 之后运行
+        "0 0 4 * * 6".to_string()
+    }
+
+    async fn run(&self) -> anyhow::Result<()> {
+        let today = Local::now().date_naive();
+        let since = today - Days::new(DAYS_AGO);
+        let anomalies = data_quality_service::verify_adj_factors(&since, &today, SAMPLE_SIZE, &self.0).await?;
+        for anomaly in &anomalies {
+            warn!(
+                "adj_factor anomaly, ts_code: {}, trade_date: {}, pct_chg: {:.2}, adj_pct_chg: {:.2}",
+                anomaly.ts_code, anomaly.trade_date, anomaly.pct_chg, anomaly.adj_pct_chg
+            );
+        }
+        info!("verify adj_factor task complete, since: {}, anomalies: {}", since, anomalies.len());
+        Ok(())
+    }
+}
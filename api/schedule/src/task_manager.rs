@@ -1,16 +1,17 @@
 use anyhow::{anyhow, Context};
-use chrono::Local;
-use entity::sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, Set};
+use chrono::{DateTime, Local};
+use entity::sea_orm::{ActiveModelTrait, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set};
 use entity::{task_run, task_state};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::sync::Arc;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
 use tokio::sync::RwLock;
 use tracing::{error, info};
 
 use entity::sea_orm::sea_query::Expr;
 
-use crate::task::Task;
+use crate::task::{run_with_lookback, Task};
+use crate::task_registry;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TaskInfo {
@@ -37,22 +38,58 @@ pub struct TaskListItem {
     pub state: TaskStateView,
 }
 
+/// 管理后台的任务概览, 合并任务注册表、`task_state` 和最近一次 `task_run`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskAdminItem {
+    pub name: String,
+    pub schedule: Option<String>, // 只能手动运行(未加入调度)的任务为 None
+    pub enabled: bool, // 未暂停且未停止
+    pub last_run_at: Option<String>,
+    pub last_status: Option<String>,
+    pub last_duration_ms: Option<i64>, // 仍在运行时为 None
+}
+
+/// 单次运行的结果, 与写入 `task_run` 的记录一致
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskRunResult {
+    pub run_id: i64,
+    pub name: String,
+    pub status: String, // success / error
+    pub started_at: String,
+    pub ended_at: String,
+    pub duration_ms: Option<i64>,
+    pub error: Option<String>,
+}
+
 #[derive(Clone)]
 pub struct TaskManager {
     conn: DatabaseConnection,
     tasks: Arc<RwLock<HashMap<String, Arc<dyn Task>>>>,
+    running: Arc<Mutex<HashSet<String>>>,
+}
+
+/// 任务运行期间持有, 释放时把任务从运行中的集合移除
+struct RunningGuard {
+    running: Arc<Mutex<HashSet<String>>>,
+    task_name: String,
+}
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        self.running.lock().unwrap().remove(&self.task_name);
+    }
 }
 
 impl TaskManager {
     pub async fn new(conn: DatabaseConnection, tasks: Vec<Arc<dyn Task>>) -> anyhow::Result<Self> {
         let mut map: HashMap<String, Arc<dyn Task>> = HashMap::new();
         for t in tasks {
-            let name = task_name(&t);
-            map.insert(name, t);
+            map.insert(t.get_name(), t);
         }
         let mgr = Self {
             conn,
             tasks: Arc::new(RwLock::new(map)),
+            running: Arc::new(Mutex::new(HashSet::new())),
         };
 
         mgr.ensure_states_exist().await?;
@@ -99,7 +136,7 @@ impl TaskManager {
             items.push(TaskListItem {
                 info: TaskInfo {
                     name: name.clone(),
-                    schedule: safe_get_schedule(task),
+                    schedule: Some(task.get_schedule()),
                 },
                 state,
             });
@@ -108,13 +145,76 @@ impl TaskManager {
         Ok(items)
     }
 
+    /// 所有已注册任务(包括只能按名称手动运行的任务)的启用状态和最近一次运行情况, 按名称排序
+    ///
+    /// 只读, 不会为没有状态记录的任务创建 `task_state`
+    pub async fn admin_list(&self) -> anyhow::Result<Vec<TaskAdminItem>> {
+        let tasks = self.tasks.read().await;
+        let mut names: Vec<String> = task_registry::task_names().into_iter().map(str::to_string).collect();
+        names.extend(tasks.keys().cloned());
+        names.sort();
+        names.dedup();
+
+        let mut items = Vec::with_capacity(names.len());
+        for name in names {
+            let state = task_state::Entity::find_by_id(name.clone()).one(&self.conn).await?;
+            let last_run = task_run::Entity::find()
+                .filter(task_run::Column::TaskName.eq(name.clone()))
+                .order_by_desc(task_run::Column::Id)
+                .one(&self.conn)
+                .await?;
+            items.push(TaskAdminItem {
+                schedule: tasks.get(&name).map(|task| task.get_schedule()),
+                enabled: state.as_ref().is_none_or(|s| !s.paused && !s.stopped),
+                last_duration_ms: last_run.as_ref().and_then(run_duration_ms),
+                last_run_at: last_run.as_ref().map(|r| r.started_at.clone()),
+                last_status: last_run.map(|r| r.status),
+                name,
+            });
+        }
+        Ok(items)
+    }
+
     pub async fn run_now(&self, task_name: &str) -> anyhow::Result<()> {
         let task = {
             let tasks = self.tasks.read().await;
             tasks.get(task_name).cloned()
         }
         .ok_or_else(|| anyhow!("task not found: {}", task_name))?;
+        self.execute(task_name, task, None).await?;
+        Ok(())
+    }
+
+    /// 手动运行一次任务, 先在已调度的任务中查找, 找不到时按名称从任务注册表创建
+    ///
+    /// 同名任务正在运行时直接返回错误, 不会重复运行; `days` 覆盖本次运行的回溯天数
+    pub async fn run_registered(&self, task_name: &str, days: Option<u64>) -> anyhow::Result<TaskRunResult> {
+        let task = {
+            let tasks = self.tasks.read().await;
+            tasks.get(task_name).cloned()
+        };
+        let task = match task {
+            Some(task) => task,
+            None => task_registry::create_task(task_name, self.conn.clone())?,
+        };
+        self.execute(task_name, task, days).await
+    }
 
+    /// 调度器运行任务的入口, 与手动触发共用运行中检查和运行记录, 同名任务不会重叠运行
+    pub async fn run_task(&self, task: Arc<dyn Task>) -> anyhow::Result<TaskRunResult> {
+        self.execute(&task.get_name(), task, None).await
+    }
+
+    fn try_start(&self, task_name: &str) -> anyhow::Result<RunningGuard> {
+        let mut running = self.running.lock().unwrap();
+        if !running.insert(task_name.to_string()) {
+            return Err(anyhow!("task is already running: {}", task_name));
+        }
+        Ok(RunningGuard { running: self.running.clone(), task_name: task_name.to_string() })
+    }
+
+    async fn execute(&self, task_name: &str, task: Arc<dyn Task>, days: Option<u64>) -> anyhow::Result<TaskRunResult> {
+        let _guard = self.try_start(task_name)?;
         let st = self.get_state(task_name).await?;
         if st.stopped {
             return Err(anyhow!("task is stopped: {}", task_name));
@@ -126,10 +226,10 @@ impl TaskManager {
         self.set_running(task_name).await?;
 
         let run_id = self.create_run_row(task_name).await?;
-        info!("[task] run_now start task={} run_id={}", task_name, run_id);
+        info!("[task] run_now start task={} run_id={} days={:?}", task_name, run_id, days);
 
         let started = now_str();
-        let res = task.run().await;
+        let res = run_with_lookback(task.as_ref(), days).await;
         let ended = now_str();
 
         let (status, success_count, fail_count, err_msg) = match res {
@@ -145,7 +245,15 @@ impl TaskManager {
         self.update_last_run_state(task_name, &status, &started, &ended, success_count, fail_count)
             .await?;
 
-        Ok(())
+        Ok(TaskRunResult {
+            run_id,
+            name: task_name.to_string(),
+            duration_ms: duration_ms(&started, &ended),
+            status,
+            started_at: started,
+            ended_at: ended,
+            error: err_msg,
+        })
     }
 
     pub async fn pause(&self, task_name: &str) -> anyhow::Result<()> {
@@ -292,13 +400,119 @@ fn now_str() -> String {
     Local::now().format("%Y-%m-%dT%H:%M:%S%.6f%:z").to_string()
 }
 
-fn safe_get_schedule(_task: &Arc<dyn Task>) -> Option<String> {
-    // 当前项目中大量 task.get_schedule() 仍是 todo!()。
-    // 为避免管理模块因为 panic 失效，这里先不调用 get_schedule。
-    None
+fn run_duration_ms(run: &task_run::Model) -> Option<i64> {
+    duration_ms(&run.started_at, run.ended_at.as_deref()?)
 }
 
-fn task_name(task: &Arc<dyn Task>) -> String {
-    let full = std::any::type_name_of_val(task.as_ref());
-    full.rsplit("::").next().unwrap_or(full).to_string()
+fn duration_ms(started_at: &str, ended_at: &str) -> Option<i64> {
+    let started = DateTime::parse_from_rfc3339(started_at).ok()?;
+    let ended = DateTime::parse_from_rfc3339(ended_at).ok()?;
+    Some((ended - started).num_milliseconds())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use common::db::test_util;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct NoopTask;
+
+    #[async_trait]
+    impl Task for NoopTask {
+        fn get_schedule(&self) -> String {
+            "0 0 * * * *".into()
+        }
+
+        async fn run(&self) -> anyhow::Result<()> {
+            Ok(())
+        }
+    }
+
+    /// 记录运行次数, 运行期间短暂等待以便测试并发触发
+    struct CountingTask(Arc<AtomicUsize>);
+
+    #[async_trait]
+    impl Task for CountingTask {
+        fn get_schedule(&self) -> String {
+            "0 0 * * * *".into()
+        }
+
+        async fn run(&self) -> anyhow::Result<()> {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+            Ok(())
+        }
+    }
+
+    async fn memory_db() -> DatabaseConnection {
+        let conn = test_util::memory_db().await;
+        test_util::create_table(&conn, task_state::Entity).await;
+        test_util::create_table(&conn, task_run::Entity).await;
+        conn
+    }
+
+    #[tokio::test]
+    async fn test_admin_list_after_run() {
+        let conn = memory_db().await;
+        let mgr = TaskManager::new(conn, vec![Arc::new(NoopTask)]).await.unwrap();
+        let before = Local::now();
+        mgr.run_now("NoopTask").await.unwrap();
+
+        let items = mgr.admin_list().await.unwrap();
+        let noop = items.iter().find(|i| i.name == "NoopTask").unwrap();
+        assert!(noop.enabled);
+        assert_eq!(noop.last_status.as_deref(), Some("success"));
+        assert_eq!(noop.schedule.as_deref(), Some("0 0 * * * *"));
+        // 只有注册表和调度中的任务, 没有按 trait 名称生成的条目
+        assert!(items.iter().all(|i| i.name != "Task"));
+        let last_run_at = DateTime::parse_from_rfc3339(noop.last_run_at.as_deref().unwrap()).unwrap();
+        assert!(last_run_at >= before - chrono::Duration::seconds(1));
+        assert!(noop.last_duration_ms.unwrap() >= 0);
+
+        // 注册表中未运行过的任务
+        let never_run = items.iter().find(|i| i.name == "FetchStockListTask").unwrap();
+        assert!(never_run.enabled);
+        assert!(never_run.last_run_at.is_none() && never_run.last_status.is_none());
+        // 未加入调度, 只能手动运行
+        assert!(never_run.schedule.is_none());
+
+        mgr.pause("NoopTask").await.unwrap();
+        let items = mgr.admin_list().await.unwrap();
+        assert!(!items.iter().find(|i| i.name == "NoopTask").unwrap().enabled);
+    }
+
+    #[tokio::test]
+    async fn test_run_registered_once() {
+        let conn = memory_db().await;
+        let runs = Arc::new(AtomicUsize::new(0));
+        let mgr = TaskManager::new(conn, vec![Arc::new(CountingTask(runs.clone()))]).await.unwrap();
+
+        // 同时触发两次, 第二次在第一次运行期间被拒绝
+        let (first, second) = tokio::join!(mgr.run_registered("CountingTask", Some(5)), mgr.run_registered("CountingTask", Some(5)));
+        let (ok, err) = if first.is_ok() { (first, second) } else { (second, first) };
+        let result = ok.unwrap();
+        assert!(err.unwrap_err().to_string().contains("already running"));
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert_eq!(result.name, "CountingTask");
+        assert_eq!(result.status, "success");
+        assert!(result.duration_ms.unwrap() >= 50);
+
+        // 运行结束后可以再次触发
+        mgr.run_registered("CountingTask", None).await.unwrap();
+        assert_eq!(runs.load(Ordering::SeqCst), 2);
+
+        assert!(mgr.run_registered("NoSuchTask", None).await.unwrap_err().to_string().contains("unknown task"));
+
+        // 调度运行期间手动触发同样被拒绝
+        let scheduled: Arc<dyn Task> = Arc::new(CountingTask(runs.clone()));
+        let (scheduled, manual) = tokio::join!(mgr.run_task(scheduled), async {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+            mgr.run_registered("CountingTask", None).await
+        });
+        assert_eq!(scheduled.unwrap().status, "success");
+        assert!(manual.unwrap_err().to_string().contains("already running"));
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+    }
 }
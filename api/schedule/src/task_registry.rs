@@ -0,0 +1,83 @@
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use entity::sea_orm::DatabaseConnection;
+
+use crate::task::*;
+
+type TaskFactory = fn(DatabaseConnection) -> Arc<dyn Task>;
+
+/// 所有可按名称单独运行的任务, 名称为任务的类型名
+const TASKS: &[(&str, TaskFactory)] = &[
+    ("FetchStockListTask", |conn| Arc::new(fetch_stock_list_task::FetchStockListTask::new(conn))),
+    ("FetchTradeCalendarTask", |conn| Arc::new(fetch_trade_calendar_task::FetchTradeCalendarTask::new(conn))),
+    ("FetchStockDailyTask", |conn| Arc::new(fetch_stock_daily_task::FetchStockDailyTask::new(conn))),
+    ("FetchStockDailyBasicTask", |conn| Arc::new(fetch_stock_daily_basic_task::FetchStockDailyBasicTask::new(conn))),
+    ("FetchStockMonthlyTask", |conn| Arc::new(fetch_stock_monthly_task::FetchStockMonthlyTask::new(conn))),
+    ("FetchStockHolderNumberTask", |conn| Arc::new(fetch_stock_holder_number_task::FetchStockHolderNumberTask::new(conn))),
+    ("FetchIndexTask", |conn| Arc::new(fetch_index_task::FetchIndexTask::new(conn))),
+    ("FetchIndexDailyTask", |conn| Arc::new(fetch_index_daily_task::FetchIndexDailyTask::new(conn))),
+    ("FetchIndexWeeklyTask", |conn| Arc::new(fetch_index_weekly_task::FetchIndexWeeklyTask::new(conn))),
+    ("FetchIndexMonthlyTask", |conn| Arc::new(fetch_index_monthly_task::FetchIndexMonthlyTask::new(conn))),
+    ("FetchFundTask", |conn| Arc::new(fetch_fund_task::FetchFundTask::new(conn))),
+    ("FetchFundDailyTask", |conn| Arc::new(fetch_fund_daily_task::FetchFundDailyTask::new(conn))),
+    ("FetchFundPortfolioTask", |conn| Arc::new(fetch_fund_portfolio_task::FetchFundPortfolioTask::new(conn))),
+    ("FetchEtfTask", |conn| Arc::new(fetch_etf_task::FetchEtfTask::new(conn))),
+    ("FetchIncomeTask", |conn| Arc::new(fetch_income_task::FetchIncomeTask::new(conn))),
+    ("FetchBalancesheetTask", |conn| Arc::new(fetch_balancesheet_task::FetchBalancesheetTask::new(conn))),
+    ("FetchCashflowTask", |conn| Arc::new(fetch_cashflow_task::FetchCashflowTask::new(conn))),
+    ("FetchFinanceIndicatorTask", |conn| Arc::new(fetch_finance_indicator_task::FetchFinanceIndicatorTask::new(conn))),
+    ("FetchFinaMainbzTask", |conn| Arc::new(fetch_fina_mainbz_task::FetchFinaMainbzTask::new(conn))),
+    ("FetchMoneyflowTask", |conn| Arc::new(fetch_moneyflow_task::FetchMoneyflowTask::new(conn))),
+    ("FetchMarginTask", |conn| Arc::new(fetch_margin_task::FetchMarginTask::new(conn))),
+    ("FetchMarginDetailTask", |conn| Arc::new(fetch_margin_detail_task::FetchMarginDetailTask::new(conn))),
+    ("FetchThsIndexTask", |conn| Arc::new(fetch_ths_index_task::FetchThsIndexTask::new(conn))),
+    ("FetchThsMemberTask", |conn| Arc::new(fetch_ths_member_task::FetchThsMemberTask::new(conn))),
+    ("FetchThsDailyTask", |conn| Arc::new(fetch_ths_daily_task::FetchThsDailyTask::new(conn))),
+    ("FetchDcIndexTask", |conn| Arc::new(fetch_dc_index_task::FetchDcIndexTask::new(conn))),
+    ("FetchDcMemberTask", |conn| Arc::new(fetch_dc_member_task::FetchDcMemberTask::new(conn))),
+    ("FetchStkHoldertradeTask", |conn| Arc::new(fetch_stk_holdertrade_task::FetchStkHoldertradeTask::new(conn))),
+    ("FetchBlockTradeTask", |conn| Arc::new(fetch_block_trade_task::FetchBlockTradeTask::new(conn))),
+    ("FetchBasicOrgInfoTask", |conn| Arc::new(fetch_basic_org_info_task::FetchBasicOrgInfoTask::new(conn))),
+    ("FetchEngTranslateTask", |conn| Arc::new(fetch_eng_translate_task::FetchEngTranslateTask::new(conn))),
+    ("FetchHmDetailTask", |conn| Arc::new(fetch_hm_detail_task::FetchHmDetailTask::new(conn))),
+    ("FetchLimitListDTask", |conn| Arc::new(fetch_limit_list_d_task::FetchLimitListDTask::new(conn))),
+    ("FetchHkHoldTask", |conn| Arc::new(fetch_hk_hold_task::FetchHkHoldTask::new(conn))),
+    ("FetchDividendTask", |conn| Arc::new(fetch_dividend_task::FetchDividendTask::new(conn))),
+    ("FetchAdjFactorTask", |conn| Arc::new(fetch_adj_factor_task::FetchAdjFactorTask::new(conn))),
+    ("VerifyAdjFactorTask", |conn| Arc::new(verify_adj_factor_task::VerifyAdjFactorTask::new(conn))),
+    ("PortfolioValuationTask", |conn| Arc::new(portfolio_valuation_task::PortfolioValuationTask::new(conn))),
+    ("ExportHistoryTask", |conn| Arc::new(export_history_task::ExportHistoryTask::new(conn))),
+    ("FetchUsBasicTask", |conn| Arc::new(us::fetch_us_basic_task::FetchUsBasicTask::new(conn))),
+    ("FetchUsStockTask", |conn| Arc::new(us::fetch_us_stock_task::FetchUsStockTask::new(conn))),
+    ("FetchUsDailyTask", |conn| Arc::new(us::fetch_us_daily_task::FetchUsDailyTask::from_config(conn))),
+    ("FetchUsCompanyInfoTask", |conn| Arc::new(us::fetch_us_company_info_task::FetchUsCompanyInfoTask::new(conn))),
+    ("FetchUsMainIndicatorTask", |conn| Arc::new(us::fetch_main_indictor_task::FetchUsMainIndicatorTask::new(conn))),
+];
+
+/// 所有已注册的任务名
+pub fn task_names() -> Vec<&'static str> {
+    TASKS.iter().map(|(name, _)| *name).collect()
+}
+
+/// 按名称创建任务, 名称不存在时返回错误
+pub fn create_task(name: &str, conn: DatabaseConnection) -> anyhow::Result<Arc<dyn Task>> {
+    let (_, factory) = TASKS
+        .iter()
+        .find(|(task_name, _)| *task_name == name)
+        .ok_or_else(|| anyhow!("unknown task: {}, available tasks: {}", name, task_names().join(", ")))?;
+    Ok(factory(conn))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_create_task() {
+        for name in task_names() {
+            assert!(create_task(name, DatabaseConnection::Disconnected).is_ok());
+        }
+        assert!(create_task("FetchNothingTask", DatabaseConnection::Disconnected).is_err());
+    }
+}
@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+use anyhow::bail;
+use num_traits::ToPrimitive;
+use serde::Serialize;
+
+use entity::sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use entity::{stock_daily, trade_calendar};
+
+/// 计算站上均线比例所用的均线周期
+const MA_PERIOD: usize = 50;
+
+/// 全市场宽度
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Breadth {
+    pub trade_date: String,
+    pub advancers: usize,
+    pub decliners: usize,
+    pub unchanged: usize,
+    pub advance_decline: i64,   // 上涨家数 - 下跌家数, 即腾落线(A/D line)当日增量
+    pub ma_sample_count: usize, // 有足够历史计算 MA50 的股票数
+    pub above_ma_count: usize,
+    pub pct_above_ma: Option<f64>, // 收盘价站上 MA50 的股票占比 x%100
+}
+
+/// 统计 `trade_date` 当日全市场的涨跌家数以及收盘价站上 50 日均线的股票占比
+pub async fn market_breadth(trade_date: &str, conn: &DatabaseConnection) -> anyhow::Result<Breadth> {
+    let dates: Vec<String> = trade_calendar::Entity::find()
+        .filter(ColumnTrait::eq(&trade_calendar::Column::Exchange, "SSE"))
+        .filter(ColumnTrait::eq(&trade_calendar::Column::IsOpen, 1))
+        .filter(trade_calendar::Column::CalDate.lte(trade_date))
+        .order_by_desc(trade_calendar::Column::CalDate)
+        .limit(MA_PERIOD as u64)
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|d| d.cal_date)
+        .collect();
+    if dates.first().map(|d| d.as_str()) != Some(trade_date) {
+        bail!("{} is not a trade date", trade_date);
+    }
+    let start = dates.last().expect("dates is not empty");
+
+    let prices = stock_daily::Entity::find()
+        .filter(stock_daily::Column::TradeDate.gte(start))
+        .filter(stock_daily::Column::TradeDate.lte(trade_date))
+        .order_by_asc(stock_daily::Column::TradeDate)
+        .all(conn)
+        .await?;
+    Ok(calc_breadth(trade_date, &prices))
+}
+
+/// `prices` 为截止 `trade_date` 最近 `MA_PERIOD` 个交易日的全市场日线, 日期按正序排序
+fn calc_breadth(trade_date: &str, prices: &[stock_daily::Model]) -> Breadth {
+    let mut by_code: HashMap<&str, Vec<&stock_daily::Model>> = HashMap::new();
+    for price in prices {
+        by_code.entry(price.ts_code.as_str()).or_default().push(price);
+    }
+
+    let (mut advancers, mut decliners, mut unchanged) = (0, 0, 0);
+    let (mut ma_sample_count, mut above_ma_count) = (0, 0);
+    for prices in by_code.values() {
+        // 当日停牌的股票不参与统计
+        let Some(latest) = prices.last().filter(|p| p.trade_date == trade_date) else {
+            continue;
+        };
+        let change = latest
+            .pre_close
+            .map(|pre_close| latest.close - pre_close)
+            .or(latest.change)
+            .and_then(|v| v.to_f64());
+        match change {
+            Some(v) if v > 0f64 => advancers += 1,
+            Some(v) if v < 0f64 => decliners += 1,
+            Some(_) => unchanged += 1,
+            None => {}
+        }
+
+        if prices.len() < MA_PERIOD {
+            continue;
+        }
+        let closes: Option<Vec<f64>> = prices[prices.len() - MA_PERIOD..].iter().map(|p| p.close.to_f64()).collect();
+        let Some(closes) = closes else {
+            continue;
+        };
+        let ma = closes.iter().sum::<f64>() / MA_PERIOD as f64;
+        ma_sample_count += 1;
+        if closes[MA_PERIOD - 1] > ma {
+            above_ma_count += 1;
+        }
+    }
+
+    Breadth {
+        trade_date: trade_date.to_string(),
+        advancers,
+        decliners,
+        unchanged,
+        advance_decline: advancers as i64 - decliners as i64,
+        ma_sample_count,
+        above_ma_count,
+        pct_above_ma: (ma_sample_count > 0).then(|| above_ma_count as f64 / ma_sample_count as f64 * 100f64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use rust_decimal::Decimal;
+
+    fn price(ts_code: &str, day: usize, close: i64, pre_close: i64) -> stock_daily::Model {
+        stock_daily::Model {
+            pre_close: Some(Decimal::from(pre_close)),
+            ..test_util::daily(ts_code, &format!("2024{:04}", day), close)
+        }
+    }
+
+    #[test]
+    fn test_calc_breadth() {
+        let mut prices = vec![];
+        for day in 1..=MA_PERIOD {
+            // 持续上涨, 站上均线
+            prices.push(price("000001.SZ", day, 10 + day as i64, 9 + day as i64));
+            // 持续下跌, 跌破均线
+            prices.push(price("000002.SZ", day, 100 - day as i64, 101 - day as i64));
+            // 横盘, 收盘价等于均线
+            prices.push(price("000003.SZ", day, 20, 20));
+        }
+        // 历史不足 50 天的新股, 当日上涨
+        prices.push(price("301999.SZ", MA_PERIOD, 30, 25));
+        // 当日停牌
+        prices.push(price("000004.SZ", MA_PERIOD - 1, 8, 9));
+        prices.sort_by(|a, b| a.trade_date.cmp(&b.trade_date));
+
+        let breadth = calc_breadth(&format!("2024{:04}", MA_PERIOD), &prices);
+        assert_eq!((breadth.advancers, breadth.decliners, breadth.unchanged), (2, 1, 1));
+        assert_eq!(breadth.advance_decline, 1);
+        assert_eq!((breadth.ma_sample_count, breadth.above_ma_count), (3, 1));
+        assert!((breadth.pct_above_ma.unwrap() - 100f64 / 3f64).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,156 @@
+use num_traits::ToPrimitive;
+use serde::Serialize;
+
+use common::db::find_latest_n;
+use common::indicators::{kdj, last_cross, ma, macd, Cross};
+use entity::sea_orm::DatabaseConnection;
+use entity::stock_daily;
+
+use crate::scan::market_scan;
+use crate::universe::Universe;
+
+const MA_FAST: usize = 5;
+const MA_SLOW: usize = 20;
+const MACD_PARAMS: (usize, usize, usize) = (12, 26, 9);
+const KDJ_PARAMS: (usize, usize, usize) = (9, 3, 3);
+/// 每只股票加载的K线数, 让 MACD 的 DEA 已经稳定
+const LOOKBACK_BARS: usize = 120;
+/// 扫描的最大并发查询数
+const SCAN_CONCURRENCY: usize = 8;
+
+/// 发生交叉的指标
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CrossIndicator {
+    Ma,   // MA5 与 MA20
+    Macd, // DIF 与 DEA
+    Kdj,  // K 与 D
+}
+
+/// 最新交易日发生的指标交叉
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CrossoverAlert {
+    pub ts_code: String,
+    pub trade_date: String,
+    pub indicator: CrossIndicator,
+    pub cross: Cross,
+}
+
+/// 股票池中在最新交易日发生 MA(5/20) 金叉/死叉、MACD 金叉/死叉、KDJ 金叉/死叉的股票
+///
+/// 最新交易日取股票池中最晚的日线日期, 停牌股票的最后一根K线不在当天, 不会产生提醒;
+/// 单只股票查询失败或数据不足时跳过. 结果按股票代码排序
+pub async fn crossover_alerts(universe: &Universe, conn: &DatabaseConnection) -> anyhow::Result<Vec<CrossoverAlert>> {
+    let ts_codes = universe.resolve(conn).await?;
+    let results = market_scan(ts_codes, SCAN_CONCURRENCY, |ts_code| async move {
+        find_latest_n::<stock_daily::Entity>(conn, stock_daily::Column::TsCode, stock_daily::Column::TradeDate, &ts_code, LOOKBACK_BARS).await
+    })
+    .await;
+    let prices: Vec<(String, Vec<stock_daily::Model>)> = results
+        .into_iter()
+        .filter_map(|(ts_code, prices)| prices.ok().filter(|p| !p.is_empty()).map(|p| (ts_code, p)))
+        .collect();
+
+    let Some(latest_trade_date) = prices.iter().filter_map(|(_, p)| p.last()).map(|p| p.trade_date.clone()).max() else {
+        return Ok(vec![]);
+    };
+    let mut alerts: Vec<CrossoverAlert> = prices
+        .iter()
+        .filter(|(_, p)| p.last().is_some_and(|p| p.trade_date == latest_trade_date))
+        .flat_map(|(ts_code, p)| detect_crossovers(ts_code, p))
+        .collect();
+    alerts.sort_by(|a, b| a.ts_code.cmp(&b.ts_code));
+    Ok(alerts)
+}
+
+/// `prices` 按日期正序, 返回最后一根K线上发生的交叉
+fn detect_crossovers(ts_code: &str, prices: &[stock_daily::Model]) -> Vec<CrossoverAlert> {
+    let Some(last) = prices.last() else {
+        return vec![];
+    };
+    let closes: Vec<f64> = prices.iter().map(|p| p.close.to_f64().unwrap_or_default()).collect();
+    let highs: Vec<f64> = prices.iter().map(|p| p.high.to_f64().unwrap_or_default()).collect();
+    let lows: Vec<f64> = prices.iter().map(|p| p.low.to_f64().unwrap_or_default()).collect();
+
+    let mut crosses = vec![];
+    if let (Ok(fast), Ok(slow)) = (ma(&closes, MA_FAST), ma(&closes, MA_SLOW)) {
+        crosses.push((CrossIndicator::Ma, last_cross(&fast, &slow)));
+    }
+    let (fast, slow, signal) = MACD_PARAMS;
+    if let Ok(values) = macd(&closes, fast, slow, signal) {
+        let (dif, dea): (Vec<f64>, Vec<f64>) = values.iter().map(|v| (v.0, v.1)).unzip();
+        crosses.push((CrossIndicator::Macd, last_cross(&dif, &dea)));
+    }
+    let (k_period, d_period, j_period) = KDJ_PARAMS;
+    if let Ok(values) = kdj(&highs, &lows, &closes, k_period, d_period, j_period) {
+        let (k, d): (Vec<f64>, Vec<f64>) = values.iter().map(|v| (v.0, v.1)).unzip();
+        crosses.push((CrossIndicator::Kdj, last_cross(&k, &d)));
+    }
+
+    crosses
+        .into_iter()
+        .filter_map(|(indicator, cross)| {
+            cross.map(|cross| CrossoverAlert { ts_code: ts_code.to_string(), trade_date: last.trade_date.clone(), indicator, cross })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use chrono::{Duration, NaiveDate};
+    use rust_decimal::Decimal;
+
+    /// 从 20240101 起每天一根K线
+    fn prices(ts_code: &str, closes: &[f64]) -> Vec<stock_daily::Model> {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, close)| {
+                let close = Decimal::from_f64_retain(*close).unwrap();
+                stock_daily::Model {
+                    open: close,
+                    high: close + Decimal::ONE,
+                    low: close - Decimal::ONE,
+                    close,
+                    vol: Decimal::ONE,
+                    amount: Decimal::ONE,
+                    ..test_util::daily(ts_code, &(start + Duration::days(i as i64)).format("%Y%m%d").to_string(), 0)
+                }
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_crossover_alerts() {
+        let conn = test_util::memory_db().await;
+        // 持续下跌, MA5 在 MA20 下方; 最后一天大涨, MA5 上穿 MA20
+        let mut golden: Vec<f64> = (0..40).map(|i| 100.0 - i as f64 * 0.5).collect();
+        golden.push(golden[39] + 30.0);
+        let falling: Vec<f64> = (0..41).map(|i| 100.0 - i as f64 * 0.5).collect();
+        let dailies = [
+            prices("600000.SH", &golden),
+            prices("601166.SH", &falling),
+            // 同样的走势但早一天结束, 即最新交易日停牌, 交叉不在最新交易日
+            prices("000001.SZ", &golden[1..]),
+        ]
+        .concat();
+        test_util::seed(&conn, stock_daily::Entity, dailies).await;
+
+        let universe = Universe::List(vec!["600000.SH".to_string(), "601166.SH".to_string(), "000001.SZ".to_string()]);
+        let alerts = crossover_alerts(&universe, &conn).await.unwrap();
+        let ma_alerts: Vec<&CrossoverAlert> = alerts.iter().filter(|a| a.indicator == CrossIndicator::Ma).collect();
+        assert_eq!(
+            ma_alerts,
+            vec![&CrossoverAlert {
+                ts_code: "600000.SH".to_string(),
+                trade_date: "20240210".to_string(),
+                indicator: CrossIndicator::Ma,
+                cross: Cross::Golden,
+            }]
+        );
+        assert!(alerts.iter().all(|a| a.ts_code != "000001.SZ" && a.trade_date == "20240210"));
+    }
+}
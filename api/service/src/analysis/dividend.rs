@@ -0,0 +1,147 @@
+use anyhow::anyhow;
+use chrono::{Months, NaiveDate};
+use num_traits::ToPrimitive;
+use serde::Serialize;
+
+use entity::sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+use entity::{dividend, stock_daily};
+
+/// 已实施的分红方案
+const DIV_PROC_DONE: &str = "实施";
+
+/// 一次已实施的分红
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DividendPayout {
+    pub end_date: String, // 分红年度
+    pub ex_date: String,  // 除权除息日
+    pub pay_date: Option<String>,
+    pub cash_div_tax: f64, // 每股分红(税前), 元
+    pub stk_div: f64,      // 每股送转
+}
+
+/// 股息率和分红历史
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DividendInfo {
+    pub ts_code: String,
+    pub trade_date: String,
+    pub close: f64,
+    pub dps_ttm: f64,                 // 最近 12 个月每股分红(税前), 元
+    pub yield_ttm: f64,               // 股息率 x%100
+    pub history: Vec<DividendPayout>, // 按除权除息日倒序
+}
+
+/// 以最新收盘价计算的股息率(TTM), 即除权除息日在最近 12 个月内的已实施现金分红之和 / 收盘价
+///
+/// 从未分红的股票股息率为 0, 没有日线数据时返回错误
+pub async fn dividend_yield(ts_code: &str, conn: &DatabaseConnection) -> anyhow::Result<DividendInfo> {
+    let latest = stock_daily::Entity::find()
+        .filter(ColumnTrait::eq(&stock_daily::Column::TsCode, ts_code))
+        .order_by_desc(stock_daily::Column::TradeDate)
+        .one(conn)
+        .await?
+        .ok_or_else(|| anyhow!("stock daily of {} not found", ts_code))?;
+    let close = latest.close.to_f64().ok_or_else(|| anyhow!("invalid close price of {}", ts_code))?;
+    let dividends = dividend::Entity::find()
+        .filter(ColumnTrait::eq(&dividend::Column::TsCode, ts_code))
+        .filter(ColumnTrait::eq(&dividend::Column::DivProc, DIV_PROC_DONE))
+        .all(conn)
+        .await?;
+    calc_dividend_info(ts_code, &latest.trade_date, close, &dividends)
+}
+
+fn calc_dividend_info(ts_code: &str, trade_date: &str, close: f64, dividends: &[dividend::Model]) -> anyhow::Result<DividendInfo> {
+    let date = NaiveDate::parse_from_str(trade_date, common::date::FORMAT)?;
+    let start = date
+        .checked_sub_months(Months::new(12))
+        .ok_or_else(|| anyhow!("invalid trade date {}", trade_date))?
+        .format(common::date::FORMAT)
+        .to_string();
+
+    let mut history: Vec<DividendPayout> = dividends
+        .iter()
+        .filter(|d| d.div_proc == DIV_PROC_DONE)
+        .filter_map(|d| {
+            Some(DividendPayout {
+                end_date: d.end_date.clone(),
+                ex_date: d.ex_date.clone()?,
+                pay_date: d.pay_date.clone(),
+                cash_div_tax: d.cash_div_tax.and_then(|v| v.to_f64()).unwrap_or_default(),
+                stk_div: d.stk_div.and_then(|v| v.to_f64()).unwrap_or_default(),
+            })
+        })
+        .collect();
+    history.sort_by(|a, b| b.ex_date.cmp(&a.ex_date));
+
+    let dps_ttm: f64 = history
+        .iter()
+        .filter(|p| p.ex_date.as_str() > start.as_str() && p.ex_date.as_str() <= trade_date)
+        .map(|p| p.cash_div_tax)
+        .sum();
+    let yield_ttm = if close > 0f64 { dps_ttm / close * 100f64 } else { 0f64 };
+    Ok(DividendInfo {
+        ts_code: ts_code.to_string(),
+        trade_date: trade_date.to_string(),
+        close,
+        dps_ttm,
+        yield_ttm,
+        history,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    fn dividend(end_date: &str, div_proc: &str, ex_date: Option<&str>, cash_div_tax: &str) -> dividend::Model {
+        dividend::Model {
+            ts_code: "600000.SH".to_string(),
+            end_date: end_date.to_string(),
+            ann_date: end_date.to_string(),
+            div_proc: div_proc.to_string(),
+            stk_div: None,
+            stk_bo_rate: None,
+            stk_co_rate: None,
+            cash_div: None,
+            cash_div_tax: Some(Decimal::from_str(cash_div_tax).unwrap()),
+            record_date: None,
+            ex_date: ex_date.map(str::to_string),
+            pay_date: None,
+            div_listdate: None,
+            imp_ann_date: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_dividend_yield() {
+        let conn = test_util::memory_db().await;
+        let daily = stock_daily::Model {
+            vol: Decimal::ONE,
+            amount: Decimal::ONE,
+            ..test_util::daily("600000.SH", "20240628", 10)
+        };
+        test_util::seed(&conn, stock_daily::Entity, vec![daily]).await;
+        let dividends = vec![
+            // 超过 12 个月
+            dividend("20221231", "实施", Some("20230620"), "0.30"),
+            dividend("20230630", "实施", Some("20231020"), "0.20"),
+            dividend("20231231", "实施", Some("20240612"), "0.30"),
+            // 未实施
+            dividend("20231231", "预案", None, "0.30"),
+        ];
+        test_util::seed(&conn, dividend::Entity, dividends).await;
+
+        let info = dividend_yield("600000.SH", &conn).await.unwrap();
+        assert!((info.dps_ttm - 0.5).abs() < 1e-9);
+        assert!((info.yield_ttm - 5f64).abs() < 1e-9);
+        let ex_dates: Vec<&str> = info.history.iter().map(|p| p.ex_date.as_str()).collect();
+        assert_eq!(ex_dates, vec!["20240612", "20231020", "20230620"]);
+
+        // 从未分红
+        let info = calc_dividend_info("000001.SZ", "20240628", 10f64, &[]).unwrap();
+        assert_eq!((info.dps_ttm, info.yield_ttm), (0f64, 0f64));
+        assert!(info.history.is_empty());
+    }
+}
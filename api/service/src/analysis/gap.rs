@@ -0,0 +1,130 @@
+use chrono::NaiveDate;
+use num_traits::ToPrimitive;
+use serde::Serialize;
+
+use common::finance::pct_chg;
+use entity::sea_orm::DatabaseConnection;
+use entity::stock_daily;
+
+use crate::stock::stock_price_service;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub enum GapDirection {
+    Up,
+    Down,
+}
+
+/// 跳空缺口
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct GapEvent {
+    pub trade_date: String,
+    pub direction: GapDirection,
+    pub gap_pct: f64, // 开盘价相对昨收的涨跌幅 x%100
+    pub pre_close: f64,
+    pub open: f64,
+    pub filled: bool, // 当天盘中是否回补缺口(跳空高开最低价回到昨收, 跳空低开最高价回到昨收)
+}
+
+/// 检测 [start, end] 内开盘价相对昨收涨跌幅绝对值不小于 `min_gap_pct`(x%100) 的跳空缺口
+///
+/// 昨收优先使用日线的 `pre_close`(除权除息后的昨收), 没有时使用上一交易日收盘价.
+/// 只分析单只股票, 不是全市场筛选, 因此不接受 `Universe`
+pub async fn detect_gaps(
+    ts_code: &str,
+    min_gap_pct: f64,
+    start: &NaiveDate,
+    end: &NaiveDate,
+    conn: &DatabaseConnection,
+) -> anyhow::Result<Vec<GapEvent>> {
+    let mut prices = stock_price_service::get_stock_prices(ts_code, start, end, conn).await?;
+    prices.reverse();
+    Ok(find_gaps(&prices, min_gap_pct))
+}
+
+/// `prices` 日期按正序排序
+fn find_gaps(prices: &[stock_daily::Model], min_gap_pct: f64) -> Vec<GapEvent> {
+    let mut gaps = vec![];
+    for (i, price) in prices.iter().enumerate() {
+        let pre_close = price
+            .pre_close
+            .or_else(|| i.checked_sub(1).map(|prev| prices[prev].close))
+            .and_then(|v| v.to_f64());
+        let (Some(pre_close), Some(open), Some(high), Some(low)) =
+            (pre_close, price.open.to_f64(), price.high.to_f64(), price.low.to_f64())
+        else {
+            continue;
+        };
+        if pre_close <= 0f64 {
+            continue;
+        }
+        let gap_pct = pct_chg(pre_close, open);
+        if gap_pct.abs() < min_gap_pct {
+            continue;
+        }
+        let (direction, filled) = if gap_pct > 0f64 {
+            (GapDirection::Up, low <= pre_close)
+        } else {
+            (GapDirection::Down, high >= pre_close)
+        };
+        gaps.push(GapEvent {
+            trade_date: price.trade_date.clone(),
+            direction,
+            gap_pct,
+            pre_close,
+            open,
+            filled,
+        });
+    }
+    gaps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use rust_decimal::Decimal;
+    use rust_decimal::prelude::FromPrimitive;
+
+    fn price(date: &str, pre_close: Option<f64>, open: f64, high: f64, low: f64, close: f64) -> stock_daily::Model {
+        let dec = |v: f64| Decimal::from_f64(v).unwrap();
+        stock_daily::Model {
+            open: dec(open),
+            high: dec(high),
+            low: dec(low),
+            close: dec(close),
+            pre_close: pre_close.map(dec),
+            ..test_util::daily("000001.SZ", date, 0)
+        }
+    }
+
+    #[test]
+    fn test_gap_up_filled() {
+        let prices = vec![
+            price("20240102", None, 10.0, 10.2, 9.9, 10.0),
+            // 高开 5%, 盘中最低回到昨收
+            price("20240103", None, 10.5, 10.8, 9.95, 10.1),
+        ];
+        let gaps = find_gaps(&prices, 3.0);
+        assert_eq!(gaps.len(), 1);
+        assert_eq!(gaps[0].direction, GapDirection::Up);
+        assert!((gaps[0].gap_pct - 5.0).abs() < 1e-9);
+        assert!(gaps[0].filled);
+    }
+
+    #[test]
+    fn test_gap_not_filled() {
+        let prices = vec![
+            price("20240102", None, 10.0, 10.2, 9.9, 10.0),
+            // 高开未回补
+            price("20240103", Some(10.0), 10.6, 11.0, 10.4, 10.9),
+            // 小幅高开, 不到阈值
+            price("20240104", Some(10.9), 11.0, 11.1, 10.8, 10.9),
+            // 除权后昨收为 5.45, 低开 10% 且未回补
+            price("20240105", Some(5.45), 4.905, 5.2, 4.8, 5.0),
+        ];
+        let gaps = find_gaps(&prices, 3.0);
+        assert_eq!(gaps.len(), 2);
+        assert_eq!((gaps[0].trade_date.as_str(), gaps[0].direction, gaps[0].filled), ("20240103", GapDirection::Up, false));
+        assert_eq!((gaps[1].trade_date.as_str(), gaps[1].direction, gaps[1].filled), ("20240105", GapDirection::Down, false));
+    }
+}
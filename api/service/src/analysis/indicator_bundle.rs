@@ -0,0 +1,285 @@
+use std::collections::BTreeMap;
+
+use num_traits::ToPrimitive;
+use serde::{Deserialize, Serialize};
+
+use common::db::{find_latest_n, BoundedSelectExt};
+use common::indicators::{boll, kdj, ma, macd, rsi};
+use entity::sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+use entity::{stock, stock_daily};
+
+use super::result_cache::cached_by_date;
+use crate::diagnosis::stock_diagnosis::align_to_dates;
+use crate::diagnosis::MaSeries;
+use crate::scan::market_scan;
+use crate::stock::get_stock;
+
+const MA_PERIODS: [usize; 4] = [5, 10, 20, 60];
+const MACD_PARAMS: (usize, usize, usize) = (12, 26, 9);
+const RSI_PERIOD: usize = 14;
+const KDJ_PARAMS: (usize, usize, usize) = (9, 3, 3);
+const BOLL_PARAMS: (usize, f64) = (20, 2.0);
+/// 窗口之前额外加载的K线数, 让窗口第一天的 MA60、MACD 等指标已经形成
+const WARMUP_BARS: usize = 60;
+/// 计算行业均值时的最大并发查询数
+const SECTOR_CONCURRENCY: usize = 8;
+
+/// 一只股票最近一段时间的全部常用指标序列, 每个序列都与 `trade_dates` 一一对应,
+/// 历史数据不足以计算的位置为 `None`
+#[derive(Debug, Clone, Serialize)]
+pub struct IndicatorBundle {
+    pub ts_code: String,
+    pub trade_dates: Vec<String>,
+    pub close: Vec<f64>,
+    pub ma: Vec<MaSeries>,
+    pub macd_dif: Vec<Option<f64>>,
+    pub macd_dea: Vec<Option<f64>>,
+    pub macd_hist: Vec<Option<f64>>,
+    pub rsi: Vec<Option<f64>>,
+    pub kdj_k: Vec<Option<f64>>,
+    pub kdj_d: Vec<Option<f64>>,
+    pub kdj_j: Vec<Option<f64>>,
+    pub boll_upper: Vec<Option<f64>>,
+    pub boll_mid: Vec<Option<f64>>,
+    pub boll_lower: Vec<Option<f64>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sector: Option<SectorOverlay>,
+}
+
+/// 所属行业的指标均值, 用于叠加在个股指标上观察相对位置, 与 `IndicatorBundle::trade_dates` 一一对应
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SectorOverlay {
+    pub industry: String,
+    pub members: usize,        // 参与计算的成分股数(含本股), 为 1 时均值即本股自身
+    pub rsi: Vec<Option<f64>>, // 当天有 RSI 的成分股的 RSI 均值, 没有成分股有值时为 None
+}
+
+/// 行业 RSI 均值, 按 (行业, 最新交易日) 缓存
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct SectorRsi {
+    members: usize,
+    rsi: BTreeMap<String, f64>, // trade_date -> RSI 均值
+}
+
+/// 计算最近 `window` 个交易日的 MA(5/10/20/60)、MACD(12,26,9)、RSI(14)、KDJ(9,3,3)、BOLL(20,2) 序列
+///
+/// 会额外加载窗口之前的 60 根K线用于预热
+pub async fn indicator_bundle(ts_code: &str, window: usize, conn: &DatabaseConnection) -> anyhow::Result<IndicatorBundle> {
+    let prices = find_latest_n::<stock_daily::Entity>(
+        conn,
+        stock_daily::Column::TsCode,
+        stock_daily::Column::TradeDate,
+        ts_code,
+        window + WARMUP_BARS,
+    )
+    .await?;
+    if prices.is_empty() {
+        anyhow::bail!("stock daily of {} not found", ts_code);
+    }
+    Ok(calc_bundle(ts_code, &prices, window))
+}
+
+/// 同 [`indicator_bundle`], 同时计算所属行业全部股票的 RSI 均值叠加在 `sector` 中
+///
+/// 股票没有行业或行业内没有可计算的股票时 `sector` 为 None
+pub async fn indicator_bundle_with_sector(ts_code: &str, window: usize, conn: &DatabaseConnection) -> anyhow::Result<IndicatorBundle> {
+    let mut bundle = indicator_bundle(ts_code, window, conn).await?;
+    let Some(industry) = get_stock(ts_code, conn).await?.industry else {
+        return Ok(bundle);
+    };
+    let Some(latest_trade_date) = bundle.trade_dates.last() else {
+        return Ok(bundle);
+    };
+    let key = format!("sector_rsi:{}:{}", industry, window);
+    let sector: SectorRsi = cached_by_date(&key, latest_trade_date, conn, || sector_rsi(&industry, window, conn)).await?;
+    if sector.members > 0 {
+        let rsi = bundle.trade_dates.iter().map(|date| sector.rsi.get(date).copied()).collect();
+        bundle.sector = Some(SectorOverlay { industry, members: sector.members, rsi });
+    }
+    Ok(bundle)
+}
+
+/// 行业内每只股票最近 `window` 个交易日的 RSI 按交易日求均值, 单只股票查询失败或数据不足时跳过
+async fn sector_rsi(industry: &str, window: usize, conn: &DatabaseConnection) -> anyhow::Result<SectorRsi> {
+    let ts_codes: Vec<String> = stock::Entity::find()
+        .filter(ColumnTrait::eq(&stock::Column::Industry, industry))
+        .bounded()
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|s| s.ts_code)
+        .collect();
+    let results = market_scan(ts_codes, SECTOR_CONCURRENCY, |ts_code| async move { indicator_bundle(&ts_code, window, conn).await }).await;
+
+    let mut members = 0;
+    let mut sums: BTreeMap<String, (f64, usize)> = BTreeMap::new();
+    for bundle in results.into_iter().filter_map(|(_, result)| result.ok()) {
+        let mut counted = false;
+        for (date, rsi) in bundle.trade_dates.into_iter().zip(bundle.rsi) {
+            if let Some(rsi) = rsi {
+                let sum = sums.entry(date).or_default();
+                sum.0 += rsi;
+                sum.1 += 1;
+                counted = true;
+            }
+        }
+        if counted {
+            members += 1;
+        }
+    }
+    let rsi = sums.into_iter().map(|(date, (sum, count))| (date, sum / count as f64)).collect();
+    Ok(SectorRsi { members, rsi })
+}
+
+/// `prices` 按日期正序, 指标在全部数据上计算, 只返回最后 `window` 根
+fn calc_bundle(ts_code: &str, prices: &[stock_daily::Model], window: usize) -> IndicatorBundle {
+    let len = prices.len();
+    let closes: Vec<f64> = prices.iter().map(|p| p.close.to_f64().unwrap_or_default()).collect();
+    let highs: Vec<f64> = prices.iter().map(|p| p.high.to_f64().unwrap_or_default()).collect();
+    let lows: Vec<f64> = prices.iter().map(|p| p.low.to_f64().unwrap_or_default()).collect();
+
+    let (fast, slow, signal) = MACD_PARAMS;
+    let macd = macd(&closes, fast, slow, signal).ok();
+    let (k_period, d_period, j_period) = KDJ_PARAMS;
+    let kdj = kdj(&highs, &lows, &closes, k_period, d_period, j_period).ok();
+    let (boll_period, boll_std) = BOLL_PARAMS;
+    let boll = boll(&closes, boll_period, boll_std).ok();
+
+    // 先与全部数据对齐, 再截取窗口
+    let start = len.saturating_sub(window);
+    let series = |values: Option<Vec<f64>>| align_to_dates(values, len).split_off(start);
+    let pick3 = |values: &Option<Vec<(f64, f64, f64)>>, f: fn(&(f64, f64, f64)) -> f64| values.as_ref().map(|v| v.iter().map(f).collect());
+    let pick_boll = |f: fn(&(f64, f64, f64, f64, f64)) -> f64| boll.as_ref().map(|v| v.iter().map(f).collect());
+
+    IndicatorBundle {
+        ts_code: ts_code.to_string(),
+        trade_dates: prices[start..].iter().map(|p| p.trade_date.clone()).collect(),
+        close: closes[start..].to_vec(),
+        ma: MA_PERIODS
+            .iter()
+            .map(|&period| MaSeries { period, values: series(ma(&closes, period).ok()) })
+            .collect(),
+        macd_dif: series(pick3(&macd, |v| v.0)),
+        macd_dea: series(pick3(&macd, |v| v.1)),
+        macd_hist: series(pick3(&macd, |v| v.2)),
+        rsi: series(rsi(&closes, RSI_PERIOD).ok()),
+        kdj_k: series(pick3(&kdj, |v| v.0)),
+        kdj_d: series(pick3(&kdj, |v| v.1)),
+        kdj_j: series(pick3(&kdj, |v| v.2)),
+        boll_upper: series(pick_boll(|v| v.1)),
+        boll_mid: series(pick_boll(|v| v.0)),
+        boll_lower: series(pick_boll(|v| v.2)),
+        sector: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use entity::cache_data;
+    use rust_decimal::Decimal;
+
+    fn prices(days: usize) -> Vec<stock_daily::Model> {
+        prices_of("600000.SH", days, 3)
+    }
+
+    /// 锯齿上涨, 避免 RSI/KDJ 分母为 0; `cycle` 不同的股票 RSI 不同
+    fn prices_of(ts_code: &str, days: usize, cycle: usize) -> Vec<stock_daily::Model> {
+        (0..days)
+            .map(|i| {
+                let close = Decimal::from(100 + i as i64 + (i % cycle) as i64);
+                stock_daily::Model {
+                    open: close,
+                    high: close + Decimal::ONE,
+                    low: close - Decimal::ONE,
+                    close,
+                    vol: Decimal::ONE,
+                    amount: Decimal::ONE,
+                    ..test_util::daily(ts_code, &format!("{}", 20240000 + i), 0)
+                }
+            })
+            .collect()
+    }
+
+    fn first_some(values: &[Option<f64>]) -> Option<usize> {
+        values.iter().position(|v| v.is_some())
+    }
+
+    #[test]
+    fn test_warmup_alignment() {
+        // 数据不足以预热: 前面的位置为 None
+        let bundle = calc_bundle("600000.SH", &prices(30), 30);
+        assert_eq!(bundle.trade_dates.len(), 30);
+        for values in [&bundle.macd_dif, &bundle.rsi, &bundle.kdj_k, &bundle.boll_mid].into_iter().chain(bundle.ma.iter().map(|m| &m.values)) {
+            assert_eq!(values.len(), 30);
+        }
+        assert_eq!(first_some(&bundle.ma[0].values), Some(4));
+        assert_eq!(first_some(&bundle.ma[2].values), Some(19));
+        assert_eq!(first_some(&bundle.ma[3].values), None);
+        assert_eq!(first_some(&bundle.boll_mid), Some(19));
+        assert!((bundle.boll_mid[29].unwrap() - bundle.ma[2].values[29].unwrap()).abs() < 1e-9);
+        assert!(bundle.boll_upper[29].unwrap() > bundle.boll_mid[29].unwrap());
+
+        // 有足够的预热数据: 窗口内每个指标都已形成, 且与全量计算的最后 30 个值一致
+        let all = prices(90);
+        let bundle = calc_bundle("600000.SH", &all, 30);
+        assert_eq!(bundle.trade_dates.first().map(|d| d.as_str()), Some(all[60].trade_date.as_str()));
+        assert_eq!(bundle.close.len(), 30);
+        for values in [&bundle.macd_dif, &bundle.macd_dea, &bundle.rsi, &bundle.kdj_j, &bundle.boll_lower].into_iter().chain(bundle.ma.iter().map(|m| &m.values)) {
+            assert_eq!(values.len(), 30);
+            assert!(values.iter().all(|v| v.is_some()));
+        }
+        let closes: Vec<f64> = all.iter().map(|p| p.close.to_f64().unwrap()).collect();
+        let ma60 = ma(&closes, 60).unwrap();
+        assert_eq!(bundle.ma[3].values.last().copied().flatten(), ma60.last().copied());
+    }
+
+    fn stock(ts_code: &str, industry: Option<&str>) -> stock::Model {
+        stock::Model {
+            industry: industry.map(str::to_string),
+            ..test_util::stock(ts_code)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_sector_overlay() {
+        let conn = test_util::memory_db().await;
+        let stocks = vec![
+            stock("600000.SH", Some("银行")),
+            stock("601166.SH", Some("银行")),
+            stock("600519.SH", Some("白酒")),
+            stock("000001.SZ", None),
+        ];
+        test_util::seed(&conn, stock::Entity, stocks).await;
+        let dailies = [prices_of("600000.SH", 40, 3), prices_of("601166.SH", 40, 4), prices_of("600519.SH", 40, 5), prices_of("000001.SZ", 40, 3)].concat();
+        test_util::seed(&conn, stock_daily::Entity, dailies).await;
+        test_util::create_table(&conn, cache_data::Entity).await;
+
+        let bundle = indicator_bundle_with_sector("600000.SH", 20, &conn).await.unwrap();
+        let peer = indicator_bundle("601166.SH", 20, &conn).await.unwrap();
+        let sector = bundle.sector.as_ref().unwrap();
+        assert_eq!(sector.industry, "银行");
+        assert_eq!(sector.members, 2);
+        assert_eq!(sector.rsi.len(), bundle.trade_dates.len());
+        for i in 0..sector.rsi.len() {
+            let expected = (bundle.rsi[i].unwrap() + peer.rsi[i].unwrap()) / 2.0;
+            assert!((sector.rsi[i].unwrap() - expected).abs() < 1e-9);
+        }
+        assert_ne!(sector.rsi, bundle.rsi);
+
+        // 第二次读取缓存, 结果一致
+        let cached = indicator_bundle_with_sector("600000.SH", 20, &conn).await.unwrap();
+        assert_eq!(cached.sector, bundle.sector);
+        assert_eq!(cache_data::Entity::find().all(&conn).await.unwrap().len(), 1);
+
+        // 只有一只成分股的行业: 均值即自身
+        let single = indicator_bundle_with_sector("600519.SH", 20, &conn).await.unwrap();
+        let sector = single.sector.unwrap();
+        assert_eq!(sector.members, 1);
+        assert_eq!(sector.rsi, single.rsi);
+
+        // 没有行业
+        assert!(indicator_bundle_with_sector("000001.SZ", 20, &conn).await.unwrap().sector.is_none());
+    }
+}
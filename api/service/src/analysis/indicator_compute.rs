@@ -0,0 +1,360 @@
+use std::collections::BTreeMap;
+
+use anyhow::{anyhow, bail};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
+
+use common::indicators::{atr, boll, ema, kdj, macd, obv, rsi, sma, IndicatorError};
+
+use crate::diagnosis::stock_diagnosis::align_to_dates;
+
+/// 单次请求最多的K线数
+const MAX_BARS: usize = 5000;
+/// 单次请求最多的指标数
+const MAX_INDICATORS: usize = 20;
+
+/// 调用方上传的K线, 按字段分列存放(与逐根K线的 `common::data_type::Ohlcv` 不同),
+/// 各数组按日期正序且长度一致; 只有需要的指标才要求提供 high/low/volume
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct OhlcvSeries {
+    #[serde(default)]
+    pub open: Option<Vec<f64>>,
+    #[serde(default)]
+    pub high: Option<Vec<f64>>,
+    #[serde(default)]
+    pub low: Option<Vec<f64>>,
+    pub close: Vec<f64>,
+    #[serde(default)]
+    pub volume: Option<Vec<f64>>,
+}
+
+/// 要计算的指标及参数, 例如 `{"name": "sma", "period": 5}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, EnumIter)]
+#[serde(tag = "name", rename_all = "snake_case")]
+pub enum IndicatorSpec {
+    Sma { period: usize },
+    Ema { period: usize },
+    Rsi { period: usize },
+    Macd { fast: usize, slow: usize, signal: usize },
+    Boll { period: usize, std_dev: f64 },
+    Kdj { k_period: usize, d_period: usize, j_period: usize },
+    Atr { period: usize },
+    Obv,
+}
+
+/// 指标的一个参数, 与 `IndicatorSpec` 中同名字段对应
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct ParamDescriptor {
+    pub name: &'static str,
+    pub default: f64,
+    pub min: f64,
+    pub integer: bool, // 周期类参数只能是整数
+}
+
+/// 可计算的指标, `inputs` 为需要上传的K线数组, 只需要 close 的为价格类指标
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct IndicatorDescriptor {
+    pub name: &'static str,
+    pub inputs: &'static [&'static str],
+    pub params: &'static [ParamDescriptor],
+}
+
+const fn param(name: &'static str, default: f64, min: f64) -> ParamDescriptor {
+    ParamDescriptor { name, default, min, integer: true }
+}
+
+const fn float_param(name: &'static str, default: f64, min: f64) -> ParamDescriptor {
+    ParamDescriptor { name, default, min, integer: false }
+}
+
+const CLOSE: &[&str] = &["close"];
+const HLC: &[&str] = &["high", "low", "close"];
+
+const SMA: IndicatorDescriptor = IndicatorDescriptor { name: "sma", inputs: CLOSE, params: &[param("period", 5.0, 1.0)] };
+const EMA: IndicatorDescriptor = IndicatorDescriptor { name: "ema", inputs: CLOSE, params: &[param("period", 12.0, 1.0)] };
+const RSI: IndicatorDescriptor = IndicatorDescriptor { name: "rsi", inputs: CLOSE, params: &[param("period", 14.0, 2.0)] };
+const MACD: IndicatorDescriptor = IndicatorDescriptor {
+    name: "macd",
+    inputs: CLOSE,
+    params: &[param("fast", 12.0, 1.0), param("slow", 26.0, 2.0), param("signal", 9.0, 1.0)],
+};
+const BOLL: IndicatorDescriptor =
+    IndicatorDescriptor { name: "boll", inputs: CLOSE, params: &[param("period", 20.0, 2.0), float_param("std_dev", 2.0, 0.1)] };
+const KDJ: IndicatorDescriptor = IndicatorDescriptor {
+    name: "kdj",
+    inputs: HLC,
+    params: &[param("k_period", 9.0, 1.0), param("d_period", 3.0, 1.0), param("j_period", 3.0, 1.0)],
+};
+const ATR: IndicatorDescriptor = IndicatorDescriptor { name: "atr", inputs: HLC, params: &[param("period", 14.0, 1.0)] };
+const OBV: IndicatorDescriptor = IndicatorDescriptor { name: "obv", inputs: &["close", "volume"], params: &[] };
+
+impl IndicatorSpec {
+    /// 指标的描述, 新增变体时必须在这里补充, 否则无法编译
+    pub fn descriptor(&self) -> &'static IndicatorDescriptor {
+        match self {
+            IndicatorSpec::Sma { .. } => &SMA,
+            IndicatorSpec::Ema { .. } => &EMA,
+            IndicatorSpec::Rsi { .. } => &RSI,
+            IndicatorSpec::Macd { .. } => &MACD,
+            IndicatorSpec::Boll { .. } => &BOLL,
+            IndicatorSpec::Kdj { .. } => &KDJ,
+            IndicatorSpec::Atr { .. } => &ATR,
+            IndicatorSpec::Obv => &OBV,
+        }
+    }
+
+    /// 按描述中的最小值校验参数
+    fn check_params(&self) -> anyhow::Result<()> {
+        let descriptor = self.descriptor();
+        let values = serde_json::to_value(self)?;
+        for param in descriptor.params {
+            // NaN 序列化为 null, 交给各指标自己校验
+            let Some(value) = values.get(param.name).and_then(|v| v.as_f64()) else {
+                continue;
+            };
+            if value < param.min {
+                bail!("{} of {} must be at least {}, got {}", param.name, descriptor.name, param.min, value);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 支持的指标, 由 `IndicatorSpec` 的全部变体生成, 前端据此生成参数表单
+pub static INDICATORS: Lazy<Vec<IndicatorDescriptor>> = Lazy::new(|| IndicatorSpec::iter().map(|spec| *spec.descriptor()).collect());
+
+impl IndicatorDescriptor {
+    /// 使用全部默认参数的指标
+    pub fn default_spec(&self) -> anyhow::Result<IndicatorSpec> {
+        let mut spec = serde_json::Map::new();
+        spec.insert("name".to_string(), self.name.into());
+        for param in self.params {
+            // 整数参数要写成整数, 否则无法反序列化为 usize
+            let value = if param.integer {
+                serde_json::Value::from(param.default as u64)
+            } else {
+                serde_json::Value::from(param.default)
+            };
+            spec.insert(param.name.to_string(), value);
+        }
+        Ok(serde_json::from_value(serde_json::Value::Object(spec))?)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComputeRequest {
+    pub bars: OhlcvSeries,
+    pub indicators: Vec<IndicatorSpec>,
+}
+
+/// 单个指标的计算结果, `series` 按输出名称索引(如 MACD 的 dif/dea/hist),
+/// 每个序列都与输入的K线一一对应, 数据不足以计算的位置为 `None`
+#[derive(Debug, Clone, Serialize)]
+pub struct ComputedIndicator {
+    pub name: String,
+    pub series: BTreeMap<String, Vec<Option<f64>>>,
+}
+
+/// 在调用方上传的K线上计算指标, 不访问数据库
+///
+/// 数组长度不一致、缺少指标需要的数组、参数不合法或数据不足时返回错误, 错误信息可直接返回给调用方
+pub fn compute_indicators(req: &ComputeRequest) -> anyhow::Result<Vec<ComputedIndicator>> {
+    let len = req.bars.close.len();
+    if len == 0 {
+        bail!("close must not be empty");
+    }
+    if len > MAX_BARS {
+        bail!("too many bars: {}, max: {}", len, MAX_BARS);
+    }
+    if req.indicators.is_empty() {
+        bail!("indicators must not be empty");
+    }
+    if req.indicators.len() > MAX_INDICATORS {
+        bail!("too many indicators: {}, max: {}", req.indicators.len(), MAX_INDICATORS);
+    }
+    let bars = &req.bars;
+    for (field, values) in [("open", &bars.open), ("high", &bars.high), ("low", &bars.low), ("volume", &bars.volume)] {
+        if let Some(values) = values.as_ref().filter(|v| v.len() != len) {
+            bail!("length of {} ({}) does not match length of close ({})", field, values.len(), len);
+        }
+    }
+    if bars.close.iter().any(|v| !v.is_finite()) {
+        bail!("close contains non-finite values");
+    }
+
+    req.indicators.iter().map(|spec| compute_one(bars, spec)).collect()
+}
+
+fn compute_one(bars: &OhlcvSeries, spec: &IndicatorSpec) -> anyhow::Result<ComputedIndicator> {
+    let len = bars.close.len();
+    let close = &bars.close;
+    let required = |field: &str, values: &Option<Vec<f64>>| -> anyhow::Result<Vec<f64>> {
+        values.clone().ok_or_else(|| anyhow!("{} is required by {}", field, spec_name(spec)))
+    };
+    let check_period = |period: usize| -> anyhow::Result<()> {
+        if period == 0 || period > len {
+            bail!("period of {} must be between 1 and {}, got {}", spec_name(spec), len, period);
+        }
+        Ok(())
+    };
+    let wrap = |e: IndicatorError| anyhow!("{} failed: {}", spec_name(spec), e);
+    spec.check_params()?;
+
+    let series: Vec<(&str, Vec<f64>)> = match *spec {
+        IndicatorSpec::Sma { period } => {
+            check_period(period)?;
+            vec![("value", sma(close, period).map_err(wrap)?)]
+        }
+        IndicatorSpec::Ema { period } => {
+            check_period(period)?;
+            vec![("value", ema(close, period).map_err(wrap)?)]
+        }
+        IndicatorSpec::Rsi { period } => {
+            check_period(period)?;
+            vec![("value", rsi(close, period).map_err(wrap)?)]
+        }
+        IndicatorSpec::Macd { fast, slow, signal } => {
+            if fast >= slow {
+                bail!("fast period of macd must be less than slow period, got {} and {}", fast, slow);
+            }
+            check_period(slow)?;
+            check_period(signal)?;
+            let values = macd(close, fast, slow, signal).map_err(wrap)?;
+            vec![
+                ("dif", values.iter().map(|v| v.0).collect()),
+                ("dea", values.iter().map(|v| v.1).collect()),
+                ("hist", values.iter().map(|v| v.2).collect()),
+            ]
+        }
+        IndicatorSpec::Boll { period, std_dev } => {
+            check_period(period)?;
+            if std_dev.is_nan() || std_dev <= 0f64 {
+                bail!("std_dev of boll must be greater than 0, got {}", std_dev);
+            }
+            let values = boll(close, period, std_dev).map_err(wrap)?;
+            vec![
+                ("upper", values.iter().map(|v| v.1).collect()),
+                ("mid", values.iter().map(|v| v.0).collect()),
+                ("lower", values.iter().map(|v| v.2).collect()),
+            ]
+        }
+        IndicatorSpec::Kdj { k_period, d_period, j_period } => {
+            check_period(k_period)?;
+            check_period(d_period)?;
+            check_period(j_period)?;
+            let (high, low) = (required("high", &bars.high)?, required("low", &bars.low)?);
+            let values = kdj(&high, &low, close, k_period, d_period, j_period).map_err(wrap)?;
+            vec![
+                ("k", values.iter().map(|v| v.0).collect()),
+                ("d", values.iter().map(|v| v.1).collect()),
+                ("j", values.iter().map(|v| v.2).collect()),
+            ]
+        }
+        IndicatorSpec::Atr { period } => {
+            check_period(period)?;
+            let (high, low) = (required("high", &bars.high)?, required("low", &bars.low)?);
+            vec![("value", atr(&high, &low, close, period).map_err(wrap)?)]
+        }
+        IndicatorSpec::Obv => {
+            let volume = required("volume", &bars.volume)?;
+            vec![("value", obv(close, &volume).map_err(wrap)?)]
+        }
+    };
+
+    Ok(ComputedIndicator {
+        name: spec_name(spec),
+        series: series.into_iter().map(|(key, values)| (key.to_string(), align_to_dates(Some(values), len))).collect(),
+    })
+}
+
+/// 指标名称及参数, 如 `sma_5`、`macd_12_26_9`
+fn spec_name(spec: &IndicatorSpec) -> String {
+    match spec {
+        IndicatorSpec::Sma { period } => format!("sma_{}", period),
+        IndicatorSpec::Ema { period } => format!("ema_{}", period),
+        IndicatorSpec::Rsi { period } => format!("rsi_{}", period),
+        IndicatorSpec::Macd { fast, slow, signal } => format!("macd_{}_{}_{}", fast, slow, signal),
+        IndicatorSpec::Boll { period, std_dev } => format!("boll_{}_{}", period, std_dev),
+        IndicatorSpec::Kdj { k_period, d_period, j_period } => format!("kdj_{}_{}_{}", k_period, d_period, j_period),
+        IndicatorSpec::Atr { period } => format!("atr_{}", period),
+        IndicatorSpec::Obv => "obv".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(close: Vec<f64>, indicators: Vec<IndicatorSpec>) -> ComputeRequest {
+        ComputeRequest { bars: OhlcvSeries { close, ..Default::default() }, indicators }
+    }
+
+    #[test]
+    fn test_compute_sma_aligned() {
+        let req = request(vec![1.0, 2.0, 3.0, 4.0, 5.0], vec![IndicatorSpec::Sma { period: 3 }]);
+        let result = compute_indicators(&req).unwrap();
+        assert_eq!(result[0].name, "sma_3");
+        assert_eq!(result[0].series["value"], vec![None, None, Some(2.0), Some(3.0), Some(4.0)]);
+    }
+
+    #[test]
+    fn test_compute_validation() {
+        let mut req = request(vec![1.0, 2.0, 3.0], vec![IndicatorSpec::Atr { period: 2 }]);
+        assert!(compute_indicators(&req).unwrap_err().to_string().contains("high is required"));
+
+        req.bars.high = Some(vec![1.0, 2.0]);
+        assert!(compute_indicators(&req).unwrap_err().to_string().contains("length of high"));
+
+        let req = request(vec![1.0, 2.0, 3.0], vec![IndicatorSpec::Sma { period: 4 }]);
+        assert!(compute_indicators(&req).unwrap_err().to_string().contains("period of sma_4"));
+
+        let req = request(vec![1.0, 2.0, 3.0], vec![IndicatorSpec::Macd { fast: 3, slow: 2, signal: 1 }]);
+        assert!(compute_indicators(&req).is_err());
+    }
+
+    #[test]
+    fn test_registry_defaults_compute() {
+        let rsi = INDICATORS.iter().find(|d| d.name == "rsi").unwrap();
+        assert_eq!(rsi.inputs, ["close"]);
+        assert_eq!(rsi.default_spec().unwrap(), IndicatorSpec::Rsi { period: 14 });
+        let boll = INDICATORS.iter().find(|d| d.name == "boll").unwrap();
+        assert_eq!(boll.default_spec().unwrap(), IndicatorSpec::Boll { period: 20, std_dev: 2.0 });
+
+        // 每个登记的指标都能用默认参数计算
+        let close: Vec<f64> = (0..60).map(|i| 10.0 + (i as f64 * 0.7).sin()).collect();
+        let bars = OhlcvSeries {
+            high: Some(close.iter().map(|c| c + 0.5).collect()),
+            low: Some(close.iter().map(|c| c - 0.5).collect()),
+            volume: Some(vec![1000.0; close.len()]),
+            close,
+            ..Default::default()
+        };
+        let indicators: Vec<IndicatorSpec> = INDICATORS.iter().map(|d| d.default_spec().unwrap()).collect();
+        let result = compute_indicators(&ComputeRequest { bars, indicators }).unwrap();
+        assert_eq!(result.len(), INDICATORS.len());
+    }
+
+    #[test]
+    fn test_registry_covers_every_variant() {
+        // 每个变体都登记了描述, 且默认参数能还原出同一变体
+        assert_eq!(INDICATORS.len(), IndicatorSpec::iter().count());
+        for spec in IndicatorSpec::iter() {
+            let descriptor = spec.descriptor();
+            assert_eq!(descriptor.default_spec().unwrap().descriptor(), descriptor);
+        }
+        let names: std::collections::BTreeSet<_> = INDICATORS.iter().map(|d| d.name).collect();
+        assert_eq!(names.len(), INDICATORS.len());
+    }
+
+    #[test]
+    fn test_compute_checks_param_min() {
+        let req = request(vec![1.0, 2.0, 3.0], vec![IndicatorSpec::Rsi { period: 1 }]);
+        let err = compute_indicators(&req).unwrap_err().to_string();
+        assert!(err.contains("period of rsi must be at least 2"), "{}", err);
+
+        let req = request(vec![1.0, 2.0, 3.0], vec![IndicatorSpec::Boll { period: 2, std_dev: 0.05 }]);
+        assert!(compute_indicators(&req).unwrap_err().to_string().contains("std_dev of boll"));
+    }
+}
@@ -0,0 +1,80 @@
+use anyhow::bail;
+use num_traits::ToPrimitive;
+
+use entity::sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use entity::stock_daily;
+
+/// 按成交额估算的单日净流入, 单位: 万元
+///
+/// 没有逐笔数据时的粗略替代: 上涨日整日成交额计为流入, 下跌日计为流出, 平盘计 0。
+/// `amount` 为日线成交额, 单位: 千元
+pub fn estimated_daily_inflow(pct_chg: f64, amount: f64) -> f64 {
+    let amount = amount / 10f64;
+    if pct_chg > 0f64 {
+        amount
+    } else if pct_chg < 0f64 {
+        -amount
+    } else {
+        0f64
+    }
+}
+
+/// 最近 `window` 个交易日按成交额估算的累计净流入(估算值, 非真实资金流向), 单位: 万元
+///
+/// 用于 `moneyflow` 缺失时的替代, 即上涨日成交额之和减去下跌日成交额之和
+pub async fn estimated_inflow(ts_code: &str, window: usize, conn: &DatabaseConnection) -> anyhow::Result<f64> {
+    if window == 0 {
+        bail!("window must be greater than 0");
+    }
+    let prices = stock_daily::Entity::find()
+        .filter(ColumnTrait::eq(&stock_daily::Column::TsCode, ts_code))
+        .order_by_desc(stock_daily::Column::TradeDate)
+        .limit(window as u64)
+        .all(conn)
+        .await?;
+    if prices.is_empty() {
+        bail!("stock daily of {} not found", ts_code);
+    }
+    Ok(sum_estimated_inflow(&prices))
+}
+
+fn sum_estimated_inflow(prices: &[stock_daily::Model]) -> f64 {
+    prices
+        .iter()
+        .filter_map(|p| {
+            let pct_chg = p.pct_chg.and_then(|v| v.to_f64())?;
+            Some(estimated_daily_inflow(pct_chg, p.amount.to_f64()?))
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use rust_decimal::Decimal;
+
+    fn price(trade_date: &str, pct_chg: i64, amount: i64) -> stock_daily::Model {
+        stock_daily::Model {
+            pct_chg: Some(Decimal::from(pct_chg)),
+            vol: Decimal::ONE,
+            amount: Decimal::from(amount),
+            ..test_util::daily("600000.SH", trade_date, 10)
+        }
+    }
+
+    #[test]
+    fn test_up_days_dominate() {
+        let prices = vec![
+            price("20240102", 2, 50_000),
+            price("20240103", 3, 80_000),
+            price("20240104", -1, 40_000),
+            price("20240105", 0, 30_000),
+            price("20240108", 1, 20_000),
+        ];
+        // (50000 + 80000 - 40000 + 20000) 千元 = 11000 万元
+        let inflow = sum_estimated_inflow(&prices);
+        assert!(inflow > 0f64);
+        assert!((inflow - 11_000f64).abs() < 1e-9);
+    }
+}
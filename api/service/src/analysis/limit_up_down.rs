@@ -1,30 +1,160 @@
-pub type Price = f64;
+use std::collections::HashMap;
 
-pub fn get_continues<F>(prices: &[Price], pred: F) -> usize where F: Fn(&Price, &Price) -> bool {
-    if prices.is_empty() {
-        return 0;
-    }
-    if prices.len() == 1 {
-        return 1;
+use anyhow::bail;
+use num_traits::ToPrimitive;
+use serde::Serialize;
+
+use common::finance::stock::is_limit_up_close;
+use entity::sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use entity::{stock_daily, trade_calendar};
+
+use crate::universe::Universe;
+
+/// 计算连板数时最多回看的交易日数, 超过的连板按此数计
+const STREAK_LOOKBACK: usize = 60;
+
+/// 连板榜单中的一只股票
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LimitUpStreak {
+    pub ts_code: String,
+    pub close: f64,
+    pub streak: u32, // 截止当日的连续涨停天数
+}
+
+/// 股票截止最新交易日的连续涨停天数, 最新交易日未涨停则为 0
+///
+/// 涨停按昨收和所属板块的涨跌幅限制判断, 停牌日不打断连板
+pub async fn limit_up_streak(ts_code: &str, conn: &DatabaseConnection) -> anyhow::Result<u32> {
+    let prices = stock_daily::Entity::find()
+        .filter(ColumnTrait::eq(&stock_daily::Column::TsCode, ts_code))
+        .order_by_desc(stock_daily::Column::TradeDate)
+        .limit(STREAK_LOOKBACK as u64)
+        .all(conn)
+        .await?;
+    Ok(trailing_streak(prices.iter().rev()))
+}
+
+/// `trade_date` 当日 `universe` 范围内涨停的股票, 按连板数从高到低排序
+pub async fn limit_up_leaderboard(trade_date: &str, universe: &Universe, conn: &DatabaseConnection) -> anyhow::Result<Vec<LimitUpStreak>> {
+    let dates: Vec<String> = trade_calendar::Entity::find()
+        .filter(ColumnTrait::eq(&trade_calendar::Column::Exchange, "SSE"))
+        .filter(ColumnTrait::eq(&trade_calendar::Column::IsOpen, 1))
+        .filter(trade_calendar::Column::CalDate.lte(trade_date))
+        .order_by_desc(trade_calendar::Column::CalDate)
+        .limit(STREAK_LOOKBACK as u64)
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|d| d.cal_date)
+        .collect();
+    if dates.first().map(|d| d.as_str()) != Some(trade_date) {
+        bail!("{} is not a trade date", trade_date);
     }
-    let mut curr_price = &prices[0];
-    let mut count = 0usize;
-    for price in &prices[1..] {
-        if pred(price, curr_price) {
-            count += 1;
-        } else {
-            break;
-        }
-        curr_price = price;
+    let start = dates.last().expect("dates is not empty");
+
+    let universe = universe.resolve_set(conn).await?;
+    let prices: Vec<stock_daily::Model> = stock_daily::Entity::find()
+        .filter(stock_daily::Column::TradeDate.gte(start))
+        .filter(stock_daily::Column::TradeDate.lte(trade_date))
+        .order_by_asc(stock_daily::Column::TradeDate)
+        .all(conn)
+        .await?
+        .into_iter()
+        .filter(|p| universe.contains(&p.ts_code))
+        .collect();
+    Ok(rank_streaks(trade_date, &prices))
+}
+
+/// `prices` 为多只股票的日线, 日期按正序排序
+fn rank_streaks(trade_date: &str, prices: &[stock_daily::Model]) -> Vec<LimitUpStreak> {
+    let mut by_code: HashMap<&str, Vec<&stock_daily::Model>> = HashMap::new();
+    for price in prices {
+        by_code.entry(price.ts_code.as_str()).or_default().push(price);
     }
-    count
+
+    let mut streaks: Vec<LimitUpStreak> = by_code
+        .into_iter()
+        .filter_map(|(ts_code, prices)| {
+            // 当日停牌的股票不上榜
+            let latest = prices.last().filter(|p| p.trade_date == trade_date)?;
+            let streak = trailing_streak(prices.iter().copied());
+            (streak > 0).then(|| LimitUpStreak {
+                ts_code: ts_code.to_string(),
+                close: latest.close.to_f64().unwrap_or_default(),
+                streak,
+            })
+        })
+        .collect();
+    streaks.sort_by(|a, b| b.streak.cmp(&a.streak).then_with(|| a.ts_code.cmp(&b.ts_code)));
+    streaks
 }
 
+/// 从最后一天往前数连续涨停的天数, `prices` 为同一只股票按日期正序排序的日线
+fn trailing_streak<'a>(prices: impl DoubleEndedIterator<Item = &'a stock_daily::Model>) -> u32 {
+    prices.rev().take_while(|p| is_limit_up(p)).count() as u32
+}
 
-pub fn get_continues_limitup<F>(prices: &[Price]) -> usize {
-    get_continues(prices, |price, curr_price| price > curr_price)
+fn is_limit_up(price: &stock_daily::Model) -> bool {
+    match (price.pre_close.and_then(|v| v.to_f64()), price.close.to_f64()) {
+        (Some(pre_close), Some(close)) if pre_close > 0f64 => is_limit_up_close(&price.ts_code, pre_close, close),
+        _ => false,
+    }
 }
 
-pub fn get_continues_limitdown<F>(prices: &[Price]) -> usize {
-    get_continues(prices, |price, curr_price| price < curr_price)
-}
\ No newline at end of file
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    fn price(ts_code: &str, trade_date: &str, pre_close: &str, close: &str) -> stock_daily::Model {
+        let (pre_close, close) = (Decimal::from_str(pre_close).unwrap(), Decimal::from_str(close).unwrap());
+        stock_daily::Model {
+            open: close,
+            high: close,
+            low: close,
+            close,
+            pre_close: Some(pre_close),
+            change: Some(close - pre_close),
+            vol: Decimal::ONE,
+            ..test_util::daily(ts_code, trade_date, 0)
+        }
+    }
+
+    fn three_limit_ups(ts_code: &str) -> Vec<stock_daily::Model> {
+        vec![
+            price(ts_code, "20240102", "10.00", "10.20"),
+            price(ts_code, "20240103", "10.20", "11.22"),
+            price(ts_code, "20240104", "11.22", "12.34"),
+            price(ts_code, "20240105", "12.34", "13.57"),
+        ]
+    }
+
+    #[tokio::test]
+    async fn test_limit_up_streak_resets_on_normal_day() {
+        let conn = test_util::memory_db().await;
+        let mut prices = three_limit_ups("600000.SH");
+        test_util::seed(&conn, stock_daily::Entity, prices.clone()).await;
+        assert_eq!(limit_up_streak("600000.SH", &conn).await.unwrap(), 3);
+
+        prices.push(price("600000.SH", "20240108", "13.57", "13.80"));
+        assert_eq!(trailing_streak(prices.iter()), 0);
+    }
+
+    #[test]
+    fn test_rank_streaks() {
+        let mut prices = three_limit_ups("600000.SH");
+        // 创业板 20% 涨停, 首板
+        prices.push(price("300750.SZ", "20240104", "100.00", "110.00"));
+        prices.push(price("300750.SZ", "20240105", "110.00", "132.00"));
+        // 主板涨 10% 但收盘价不是涨停价
+        prices.push(price("000001.SZ", "20240105", "10.05", "11.05"));
+        // 当日停牌
+        prices.push(price("000002.SZ", "20240104", "10.00", "11.00"));
+        prices.sort_by(|a, b| a.trade_date.cmp(&b.trade_date));
+
+        let streaks: Vec<(String, u32)> = rank_streaks("20240105", &prices).into_iter().map(|s| (s.ts_code, s.streak)).collect();
+        assert_eq!(streaks, vec![("600000.SH".to_string(), 3), ("300750.SZ".to_string(), 1)]);
+    }
+}
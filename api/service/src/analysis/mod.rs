@@ -1 +1,31 @@
-mod limit_up_down;
\ No newline at end of file
+mod limit_up_down;
+mod gap;
+mod inflow;
+mod indicator_bundle;
+mod indicator_compute;
+mod breadth;
+mod crossover;
+mod valuation;
+mod vwap;
+mod top_movers;
+mod similar_movers;
+mod northbound;
+mod dividend;
+mod moneyflow_buckets;
+mod result_cache;
+
+pub use breadth::{market_breadth, Breadth};
+pub use crossover::{crossover_alerts, CrossIndicator, CrossoverAlert};
+pub use dividend::{dividend_yield, DividendInfo, DividendPayout};
+pub use gap::{detect_gaps, GapDirection, GapEvent};
+pub use indicator_bundle::{indicator_bundle, indicator_bundle_with_sector, IndicatorBundle, SectorOverlay};
+pub use indicator_compute::{compute_indicators, ComputeRequest, ComputedIndicator, IndicatorDescriptor, IndicatorSpec, OhlcvSeries, ParamDescriptor, INDICATORS};
+pub use inflow::{estimated_daily_inflow, estimated_inflow};
+pub use limit_up_down::{limit_up_leaderboard, limit_up_streak, LimitUpStreak};
+pub use moneyflow_buckets::{moneyflow_buckets, BucketFlow, BucketSeries, FlowBucket};
+pub use result_cache::{cached_analysis, cached_by_date, AnalysisKind};
+pub use northbound::{northbound_trend, NorthboundPoint, NorthboundTrend};
+pub use valuation::{valuation_percentile, ValuationPercentile};
+pub use similar_movers::similar_movers;
+pub use top_movers::{top_movers, Mover, TopMovers};
+pub use vwap::{window_vwap, VwapPosition, WindowVwap};
@@ -0,0 +1,164 @@
+use anyhow::bail;
+use num_traits::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use common::db::find_latest_n;
+use entity::moneyflow;
+use entity::sea_orm::DatabaseConnection;
+
+/// 按单笔成交金额划分的资金档位, 与 Tushare `moneyflow` 的划分一致
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlowBucket {
+    Small,      // 小单, 5 万以下
+    Medium,     // 中单, 5 万 ~ 20 万
+    Large,      // 大单, 20 万 ~ 100 万
+    ExtraLarge, // 特大单, 100 万以上
+}
+
+/// 单个档位的每日净流入, 与 `BucketFlow::trade_dates` 一一对应
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BucketSeries {
+    pub bucket: FlowBucket,
+    pub net_inflow: Vec<Option<f64>>, // 买入额 - 卖出额, 单位: 万元; 买入或卖出缺失的交易日为 None
+    pub cumulative: Vec<Option<f64>>, // 窗口内累计净流入, 单位: 万元; 第一个有数据的交易日之前为 None
+    pub total: Option<f64>,           // 窗口内净流入合计, 单位: 万元; 整个窗口都缺失时为 None
+}
+
+/// 最近一段时间按资金档位拆分的净流入
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BucketFlow {
+    pub ts_code: String,
+    pub trade_dates: Vec<String>, // 按日期正序
+    pub buckets: Vec<BucketSeries>,
+    pub dominant: Option<FlowBucket>, // 窗口内净流入合计绝对值最大的档位, 即主导本轮走势的资金
+}
+
+/// 最近 `window` 个交易日每个资金档位(小单/中单/大单/特大单)的净流入及累计趋势
+///
+/// 用于判断上涨或下跌主要由大资金还是散户推动; 个别档位的买卖额缺失时只影响该档位当天的数据
+pub async fn moneyflow_buckets(ts_code: &str, window: usize, conn: &DatabaseConnection) -> anyhow::Result<BucketFlow> {
+    if window == 0 {
+        bail!("window must be greater than 0");
+    }
+    let flows = find_latest_n::<moneyflow::Entity>(conn, moneyflow::Column::TsCode, moneyflow::Column::TradeDate, ts_code, window).await?;
+    if flows.is_empty() {
+        bail!("moneyflow of {} not found", ts_code);
+    }
+    Ok(build_bucket_flow(ts_code, &flows))
+}
+
+/// `flows` 按日期正序
+fn build_bucket_flow(ts_code: &str, flows: &[moneyflow::Model]) -> BucketFlow {
+    let buckets: Vec<BucketSeries> = [FlowBucket::Small, FlowBucket::Medium, FlowBucket::Large, FlowBucket::ExtraLarge]
+        .into_iter()
+        .map(|bucket| {
+            // 用 Decimal 计算净流入和累计值, 转换为 f64 只在输出时进行, 避免累计误差
+            let net_inflow: Vec<Option<Decimal>> = flows.iter().map(|flow| bucket_net_inflow(flow, bucket)).collect();
+            let mut sum: Option<Decimal> = None;
+            let cumulative = net_inflow
+                .iter()
+                .map(|v| {
+                    if let Some(v) = v {
+                        sum = Some(sum.unwrap_or(Decimal::ZERO) + v);
+                    }
+                    to_f64(sum)
+                })
+                .collect();
+            let net_inflow = net_inflow.into_iter().map(to_f64).collect();
+            BucketSeries { bucket, net_inflow, cumulative, total: to_f64(sum) }
+        })
+        .collect();
+    let dominant = buckets
+        .iter()
+        .filter_map(|b| b.total.map(|total| (b.bucket, total.abs())))
+        .filter(|(_, total)| *total > 0f64)
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+        .map(|(bucket, _)| bucket);
+    BucketFlow {
+        ts_code: ts_code.to_string(),
+        trade_dates: flows.iter().map(|f| f.trade_date.clone()).collect(),
+        buckets,
+        dominant,
+    }
+}
+
+fn to_f64(value: Option<Decimal>) -> Option<f64> {
+    value.and_then(|v| v.to_f64())
+}
+
+fn bucket_net_inflow(flow: &moneyflow::Model, bucket: FlowBucket) -> Option<Decimal> {
+    let (buy, sell) = match bucket {
+        FlowBucket::Small => (flow.buy_sm_amount, flow.sell_sm_amount),
+        FlowBucket::Medium => (flow.buy_md_amount, flow.sell_md_amount),
+        FlowBucket::Large => (flow.buy_lg_amount, flow.sell_lg_amount),
+        FlowBucket::ExtraLarge => (flow.buy_elg_amount, flow.sell_elg_amount),
+    };
+    Some(buy? - sell?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    /// 各档位 (买入, 卖出) 额, 单位: 万元
+    fn flow(trade_date: &str, sm: (i64, i64), md: Option<(i64, i64)>, lg: (i64, i64), elg: (i64, i64)) -> moneyflow::Model {
+        moneyflow::Model {
+            ts_code: "600000.SH".to_string(),
+            trade_date: trade_date.to_string(),
+            buy_sm_vol: None,
+            buy_sm_amount: Some(Decimal::from(sm.0)),
+            sell_sm_vol: None,
+            sell_sm_amount: Some(Decimal::from(sm.1)),
+            buy_md_vol: None,
+            buy_md_amount: md.map(|md| Decimal::from(md.0)),
+            sell_md_vol: None,
+            sell_md_amount: md.map(|md| Decimal::from(md.1)),
+            buy_lg_vol: None,
+            buy_lg_amount: Some(Decimal::from(lg.0)),
+            sell_lg_vol: None,
+            sell_lg_amount: Some(Decimal::from(lg.1)),
+            buy_elg_vol: None,
+            buy_elg_amount: Some(Decimal::from(elg.0)),
+            sell_elg_vol: None,
+            sell_elg_amount: Some(Decimal::from(elg.1)),
+            net_mf_vol: None,
+            net_mf_amount: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_moneyflow_buckets() {
+        let conn = test_util::memory_db().await;
+        let flows = vec![
+            // 窗口之外
+            flow("20240102", (100, 100), Some((100, 100)), (100, 100), (9000, 0)),
+            flow("20240103", (300, 500), None, (800, 300), (2000, 500)),
+            flow("20240104", (200, 400), Some((300, 200)), (600, 400), (1500, 500)),
+            flow("20240105", (100, 300), None, (500, 500), (1200, 200)),
+        ];
+        test_util::seed(&conn, moneyflow::Entity, flows).await;
+
+        let result = moneyflow_buckets("600000.SH", 3, &conn).await.unwrap();
+        assert_eq!(result.trade_dates, vec!["20240103", "20240104", "20240105"]);
+        let series = |bucket: FlowBucket| result.buckets.iter().find(|b| b.bucket == bucket).unwrap();
+
+        let small = series(FlowBucket::Small);
+        assert_eq!(small.net_inflow, vec![Some(-200.0), Some(-200.0), Some(-200.0)]);
+        assert_eq!(small.cumulative, vec![Some(-200.0), Some(-400.0), Some(-600.0)]);
+
+        // 中单缺失的交易日不计入累计
+        let medium = series(FlowBucket::Medium);
+        assert_eq!(medium.net_inflow, vec![None, Some(100.0), None]);
+        assert_eq!(medium.cumulative, vec![None, Some(100.0), Some(100.0)]);
+        assert_eq!(medium.total, Some(100.0));
+
+        assert_eq!(series(FlowBucket::Large).total, Some(700.0));
+        assert_eq!(series(FlowBucket::ExtraLarge).total, Some(3500.0));
+        assert_eq!(result.dominant, Some(FlowBucket::ExtraLarge));
+
+        assert!(moneyflow_buckets("601166.SH", 3, &conn).await.is_err());
+    }
+}
@@ -0,0 +1,108 @@
+use num_traits::ToPrimitive;
+use serde::Serialize;
+
+use entity::hk_hold;
+use entity::sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+
+/// 返回的持股快照数
+const TREND_WINDOW: usize = 120;
+/// 近期变化使用的快照间隔
+const SHORT_CHANGE_DAYS: usize = 5;
+const LONG_CHANGE_DAYS: usize = 20;
+
+/// 单个交易日的北向资金持股
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NorthboundPoint {
+    pub trade_date: String,
+    pub ratio: f64,       // 持股占已发行股份 x%100
+    pub vol: Option<f64>, // 持股数量, 股
+}
+
+/// 北向资金(沪深股通)持股趋势
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NorthboundTrend {
+    pub ts_code: String,
+    pub points: Vec<NorthboundPoint>, // 按日期正序
+    pub ratio_chg_5d: Option<f64>,    // 最新持股占比较 5 个快照前的变化, 百分点
+    pub ratio_chg_20d: Option<f64>,   // 最新持股占比较 20 个快照前的变化, 百分点
+}
+
+/// 最近 120 个交易日的北向资金持股占比及其近期变化
+///
+/// 不在沪深股通标的范围内(没有持股快照)的股票返回空序列, 变化为 None
+pub async fn northbound_trend(ts_code: &str, conn: &DatabaseConnection) -> anyhow::Result<NorthboundTrend> {
+    let holds = hk_hold::Entity::find()
+        .filter(ColumnTrait::eq(&hk_hold::Column::TsCode, ts_code))
+        .order_by_desc(hk_hold::Column::TradeDate)
+        .limit(TREND_WINDOW as u64)
+        .all(conn)
+        .await?;
+    Ok(build_trend(ts_code, &holds))
+}
+
+/// `holds` 按日期倒序
+fn build_trend(ts_code: &str, holds: &[hk_hold::Model]) -> NorthboundTrend {
+    let points: Vec<NorthboundPoint> = holds
+        .iter()
+        .rev()
+        .filter_map(|h| {
+            Some(NorthboundPoint {
+                trade_date: h.trade_date.clone(),
+                ratio: h.ratio.and_then(|v| v.to_f64())?,
+                vol: h.vol.and_then(|v| v.to_f64()),
+            })
+        })
+        .collect();
+    let ratio_chg = |days: usize| {
+        let latest = points.last()?;
+        let prev = points.len().checked_sub(days + 1).map(|i| &points[i])?;
+        Some(latest.ratio - prev.ratio)
+    };
+    NorthboundTrend {
+        ts_code: ts_code.to_string(),
+        ratio_chg_5d: ratio_chg(SHORT_CHANGE_DAYS),
+        ratio_chg_20d: ratio_chg(LONG_CHANGE_DAYS),
+        points,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use rust_decimal::Decimal;
+
+    fn hold(trade_date: &str, ratio: Decimal) -> hk_hold::Model {
+        hk_hold::Model {
+            ts_code: "600519.SH".to_string(),
+            trade_date: trade_date.to_string(),
+            code: Some("90000".to_string()),
+            name: Some("贵州茅台".to_string()),
+            vol: Some(Decimal::from(1_000_000)),
+            ratio: Some(ratio),
+            exchange: Some("SH".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_northbound_trend() {
+        let conn = test_util::memory_db().await;
+        // 持股占比从 6.00% 每天增加 0.05 个百分点
+        let holds = (0..8)
+            .map(|i| hold(&format!("202401{:02}", i + 2), Decimal::new(600 + 5 * i, 2)))
+            .collect();
+        test_util::seed(&conn, hk_hold::Entity, holds).await;
+
+        let trend = northbound_trend("600519.SH", &conn).await.unwrap();
+        assert_eq!(trend.points.len(), 8);
+        assert_eq!(trend.points[0].trade_date, "20240102");
+        assert!((trend.points[7].ratio - 6.35).abs() < 1e-9);
+        assert!((trend.ratio_chg_5d.unwrap() - 0.25).abs() < 1e-9);
+        assert_eq!(trend.ratio_chg_20d, None);
+
+        // 非沪深股通标的
+        let trend = northbound_trend("430047.BJ", &conn).await.unwrap();
+        assert!(trend.points.is_empty());
+        assert_eq!((trend.ratio_chg_5d, trend.ratio_chg_20d), (None, None));
+    }
+}
@@ -0,0 +1,155 @@
+use std::future::Future;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tracing::warn;
+
+use entity::sea_orm::{ActiveValue, ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use entity::{cache_data, stock_daily};
+
+/// 可缓存的单只证券分析结果
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnalysisKind {
+    Diagnosis,
+    DiagnosisDetailed,
+    Overview,
+}
+
+impl AnalysisKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            AnalysisKind::Diagnosis => "diagnosis",
+            AnalysisKind::DiagnosisDetailed => "diagnosis_detailed",
+            AnalysisKind::Overview => "overview",
+        }
+    }
+}
+
+/// 按 `(ts_code, 最新交易日, 分析类型)` 缓存分析结果, 结果序列化后存入 `cache_data`
+///
+/// 当日收盘数据入库后结果不再变化, 同一交易日内重复请求直接读取缓存; 新交易日的数据入库后
+/// 最新交易日变化, 缓存自然失效并在下次请求时重新计算. 缓存读写失败只记录日志, 不影响返回结果
+pub async fn cached_analysis<T, F, Fut>(ts_code: &str, kind: AnalysisKind, conn: &DatabaseConnection, compute: F) -> anyhow::Result<T>
+where
+    T: Serialize + DeserializeOwned,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = anyhow::Result<T>>,
+{
+    let Some(latest_trade_date) = latest_trade_date(ts_code, conn).await? else {
+        // 没有行情数据时无法确定缓存是否过期, 直接计算
+        return compute().await;
+    };
+    cached_by_date(&cache_key(ts_code, kind), &latest_trade_date, conn, compute).await
+}
+
+/// 按 `(key, date)` 缓存计算结果, `date` 变化后旧结果被替换; 用于不属于单只证券的结果, 如行业均值
+pub async fn cached_by_date<T, F, Fut>(key: &str, date: &str, conn: &DatabaseConnection, compute: F) -> anyhow::Result<T>
+where
+    T: Serialize + DeserializeOwned,
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = anyhow::Result<T>>,
+{
+    let cached = cache_data::Entity::find()
+        .filter(ColumnTrait::eq(&cache_data::Column::Type, key))
+        .filter(ColumnTrait::eq(&cache_data::Column::Date, date))
+        .one(conn)
+        .await;
+    match cached {
+        Ok(Some(cached)) => match serde_json::from_value(cached.data) {
+            Ok(result) => return Ok(result),
+            // 结果结构变化后旧缓存无法解析, 重新计算并覆盖
+            Err(e) => warn!("invalid cached {}, recompute: {:?}", key, e),
+        },
+        Ok(None) => {}
+        Err(e) => warn!("read cached {} failed: {:?}", key, e),
+    }
+
+    let result = compute().await?;
+    if let Err(e) = save(key, date, &result, conn).await {
+        warn!("save cached {} failed: {:?}", key, e);
+    }
+    Ok(result)
+}
+
+fn cache_key(ts_code: &str, kind: AnalysisKind) -> String {
+    format!("{}:{}", kind.as_str(), ts_code)
+}
+
+async fn latest_trade_date(ts_code: &str, conn: &DatabaseConnection) -> anyhow::Result<Option<String>> {
+    let trade_date = stock_daily::Entity::find()
+        .select_only()
+        .column(stock_daily::Column::TradeDate)
+        .filter(ColumnTrait::eq(&stock_daily::Column::TsCode, ts_code))
+        .order_by_desc(stock_daily::Column::TradeDate)
+        .into_tuple::<String>()
+        .one(conn)
+        .await?;
+    Ok(trade_date)
+}
+
+/// 写入新结果, 同时删除同一 key 之前日期的缓存
+async fn save<T: Serialize>(key: &str, trade_date: &str, result: &T, conn: &DatabaseConnection) -> anyhow::Result<()> {
+    let data = serde_json::to_value(result)?;
+    cache_data::Entity::delete_many().filter(ColumnTrait::eq(&cache_data::Column::Type, key)).exec(conn).await?;
+    let model = cache_data::ActiveModel {
+        r#type: ActiveValue::Set(key.to_string()),
+        date: ActiveValue::Set(trade_date.to_string()),
+        data: ActiveValue::Set(data),
+        id: ActiveValue::NotSet,
+    };
+    cache_data::Entity::insert(model).exec_without_returning(conn).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use entity::sea_orm::IntoActiveModel;
+    use rust_decimal::Decimal;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn daily(trade_date: &str) -> stock_daily::Model {
+        stock_daily::Model {
+            vol: Decimal::ONE,
+            amount: Decimal::ONE,
+            ..test_util::daily("000001.SZ", trade_date, 10)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cached_analysis_recompute_once_per_trade_date() {
+        let conn = test_util::memory_db().await;
+        test_util::seed(&conn, stock_daily::Entity, vec![daily("20240104"), daily("20240105")]).await;
+        test_util::create_table(&conn, cache_data::Entity).await;
+
+        let computed = AtomicUsize::new(0);
+        let counter = &computed;
+        let diagnosis = move || async move {
+            let n = counter.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok::<_, anyhow::Error>(format!("diagnosis #{}", n))
+        };
+
+        let first: String = cached_analysis("000001.SZ", AnalysisKind::Diagnosis, &conn, diagnosis).await.unwrap();
+        let second: String = cached_analysis("000001.SZ", AnalysisKind::Diagnosis, &conn, diagnosis).await.unwrap();
+        assert_eq!(first, "diagnosis #1");
+        assert_eq!(second, "diagnosis #1");
+        assert_eq!(computed.load(Ordering::SeqCst), 1);
+
+        // 不同分析类型互不影响
+        let overview: String = cached_analysis("000001.SZ", AnalysisKind::Overview, &conn, diagnosis).await.unwrap();
+        assert_eq!(overview, "diagnosis #2");
+
+        // 新交易日的数据入库后重新计算, 并替换旧缓存
+        stock_daily::Entity::insert(daily("20240108").into_active_model()).exec(&conn).await.unwrap();
+        let third: String = cached_analysis("000001.SZ", AnalysisKind::Diagnosis, &conn, diagnosis).await.unwrap();
+        assert_eq!(third, "diagnosis #3");
+        let cached = cache_data::Entity::find()
+            .filter(ColumnTrait::eq(&cache_data::Column::Type, "diagnosis:000001.SZ"))
+            .all(&conn)
+            .await
+            .unwrap();
+        assert_eq!(cached.len(), 1);
+        assert_eq!(cached[0].date, "20240108");
+    }
+}
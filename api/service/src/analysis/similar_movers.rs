@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail};
+use num_traits::ToPrimitive;
+use tracing::warn;
+
+use common::data_type::TsCode;
+use common::stastics::correlation::pearson_correlation;
+use entity::sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use entity::{stock, stock_daily};
+
+use crate::scan::market_scan;
+use crate::universe::Universe;
+
+/// 同时加载候选股票日线的并发数
+const SCAN_CONCURRENCY: usize = 8;
+/// 计算相关系数至少需要的共同交易日数
+const MIN_OVERLAP: usize = 3;
+
+/// 与 `ts_code` 最近 `window` 个交易日的日收益率相关性最高的 `top_n` 只同行业股票, 按相关系数从高到低
+///
+/// 收益率按交易日对齐, 只用双方都有收益率的日期计算; 共同交易日不足目标股票一半的候选股票不参与排序.
+/// 候选股票限定在 `universe` 范围内, 目标股票本身不需要在范围内
+pub async fn similar_movers(
+    ts_code: &str,
+    window: usize,
+    top_n: usize,
+    universe: &Universe,
+    conn: &DatabaseConnection,
+) -> anyhow::Result<Vec<(TsCode, f64)>> {
+    if window < MIN_OVERLAP {
+        bail!("window must be at least {}", MIN_OVERLAP);
+    }
+    let target = stock::Entity::find_by_id(ts_code)
+        .one(conn)
+        .await?
+        .ok_or_else(|| anyhow!("stock {} not found", ts_code))?;
+    let Some(industry) = target.industry else {
+        bail!("industry of {} not found", ts_code);
+    };
+    let candidates: Vec<String> = stock::Entity::find()
+        .select_only()
+        .column(stock::Column::TsCode)
+        .filter(ColumnTrait::eq(&stock::Column::Industry, industry))
+        .filter(ColumnTrait::ne(&stock::Column::TsCode, ts_code))
+        .into_tuple()
+        .all(conn)
+        .await?;
+    let universe = universe.resolve_set(conn).await?;
+    let candidates: Vec<String> = candidates.into_iter().filter(|c| universe.contains(c)).collect();
+
+    // 多取一天, 用于计算第一天的收益率
+    let mut prices = stock_daily::Entity::find()
+        .filter(ColumnTrait::eq(&stock_daily::Column::TsCode, ts_code))
+        .order_by_desc(stock_daily::Column::TradeDate)
+        .limit(window as u64 + 1)
+        .all(conn)
+        .await?;
+    prices.reverse();
+    let (Some(start), Some(end)) = (prices.first().map(|p| p.trade_date.clone()), prices.last().map(|p| p.trade_date.clone())) else {
+        bail!("stock daily of {} not found", ts_code);
+    };
+    let target_returns = daily_returns(&prices);
+
+    let (start, end) = (start.as_str(), end.as_str());
+    let results = market_scan(candidates, SCAN_CONCURRENCY, |ts_code| async move {
+        let prices = stock_daily::Entity::find()
+            .filter(ColumnTrait::eq(&stock_daily::Column::TsCode, &ts_code))
+            .filter(stock_daily::Column::TradeDate.gte(start))
+            .filter(stock_daily::Column::TradeDate.lte(end))
+            .order_by_asc(stock_daily::Column::TradeDate)
+            .all(conn)
+            .await?;
+        Ok(daily_returns(&prices))
+    })
+    .await;
+
+    let candidates = results
+        .into_iter()
+        .filter_map(|(ts_code, result)| match result {
+            Ok(returns) => Some((ts_code, returns)),
+            Err(e) => {
+                warn!("load stock daily failed, ts_code: {}, error: {:?}", ts_code, e);
+                None
+            }
+        })
+        .collect();
+    Ok(rank_similar(&target_returns, candidates, top_n))
+}
+
+fn rank_similar(target: &HashMap<String, f64>, candidates: Vec<(String, HashMap<String, f64>)>, top_n: usize) -> Vec<(TsCode, f64)> {
+    let min_overlap = MIN_OVERLAP.max(target.len().div_ceil(2));
+    let mut ranked: Vec<(TsCode, f64)> = candidates
+        .into_iter()
+        .filter_map(|(ts_code, returns)| {
+            let (x, y): (Vec<f64>, Vec<f64>) = target
+                .iter()
+                .filter_map(|(date, r)| returns.get(date).map(|c| (*r, *c)))
+                .unzip();
+            if x.len() < min_overlap {
+                return None;
+            }
+            pearson_correlation(&x, &y).ok().map(|corr| (ts_code, corr))
+        })
+        .collect();
+    ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    ranked.truncate(top_n);
+    ranked
+}
+
+/// 按交易日索引的日收益率, `prices` 为同一只股票按日期正序排序的日线, 第一天没有收益率
+fn daily_returns(prices: &[stock_daily::Model]) -> HashMap<String, f64> {
+    prices
+        .windows(2)
+        .filter_map(|pair| {
+            let (prev, curr) = (pair[0].close.to_f64()?, pair[1].close.to_f64()?);
+            (prev > 0f64).then(|| (pair[1].trade_date.clone(), curr / prev - 1f64))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use rust_decimal::Decimal;
+
+    fn prices(ts_code: &str, closes: &[f64]) -> Vec<stock_daily::Model> {
+        closes
+            .iter()
+            .enumerate()
+            .map(|(i, close)| {
+                let close = Decimal::try_from(*close).unwrap();
+                stock_daily::Model {
+                    open: close,
+                    high: close,
+                    low: close,
+                    close,
+                    vol: Decimal::ONE,
+                    ..test_util::daily(ts_code, &format!("202401{:02}", i + 2), 0)
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rank_similar() {
+        let closes = [10.0, 10.5, 10.2, 10.8, 11.0, 10.6, 11.2];
+        let target = daily_returns(&prices("600000.SH", &closes));
+
+        // 价格是目标的 3 倍, 收益率完全相同
+        let tripled: Vec<f64> = closes.iter().map(|c| c * 3.0).collect();
+        let candidates = vec![
+            ("601166.SH".to_string(), daily_returns(&prices("601166.SH", &[20.0, 19.5, 20.1, 19.6, 19.4, 19.9, 19.3]))),
+            ("600036.SH".to_string(), daily_returns(&prices("600036.SH", &tripled))),
+            ("601398.SH".to_string(), daily_returns(&prices("601398.SH", &[5.0, 5.1, 5.0, 5.2, 5.3, 5.2, 5.3]))),
+            // 共同交易日不足
+            ("601988.SH".to_string(), daily_returns(&prices("601988.SH", &[4.0, 4.1]))),
+        ];
+
+        let ranked = rank_similar(&target, candidates, 2);
+        assert_eq!(ranked.len(), 2);
+        assert_eq!(ranked[0].0, "600036.SH");
+        assert!((ranked[0].1 - 1.0).abs() < 1e-9);
+        assert_eq!(ranked[1].0, "601398.SH");
+        assert!(ranked[1].1 < 1.0);
+    }
+}
@@ -0,0 +1,140 @@
+use std::cmp::Ordering;
+
+use anyhow::Context;
+use chrono::NaiveDate;
+use num_traits::ToPrimitive;
+use serde::Serialize;
+
+use common::finance::stock::{is_limit_down_close, is_limit_up_close};
+use entity::sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter};
+use entity::stock_daily;
+
+use crate::stock::stock_price_service;
+use crate::universe::Universe;
+
+/// 单只股票当日涨跌幅
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Mover {
+    pub ts_code: String,
+    pub close: f64,
+    pub pct_chg: f64, // x%100
+    pub limit_up: bool,
+    pub limit_down: bool,
+}
+
+/// 当日涨幅榜和跌幅榜
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct TopMovers {
+    pub trade_date: String,
+    pub gainers: Vec<Mover>, // 按涨幅从高到低
+    pub losers: Vec<Mover>,  // 按跌幅从高到低
+}
+
+/// `trade_date` 当日 `universe` 范围内涨幅、跌幅前 `top_n` 的股票
+///
+/// 停牌(成交量为 0)的股票不参与排序; 收盘涨停或跌停的股票仍在榜内, 通过 `limit_up`/`limit_down` 标记,
+/// 涨跌停价按昨收和所属板块的涨跌幅限制计算, 不区分 ST 股. `trade_date` 不是交易日时取之前最近一个交易日,
+/// 结果中的 `trade_date` 为实际使用的交易日
+pub async fn top_movers(trade_date: &str, top_n: usize, universe: &Universe, conn: &DatabaseConnection) -> anyhow::Result<TopMovers> {
+    let date = NaiveDate::parse_from_str(trade_date, common::date::FORMAT).with_context(|| format!("invalid trade_date: {}", trade_date))?;
+    let Some(trade_date) = stock_price_service::trade_date_on_or_before(&date, conn).await? else {
+        return Ok(rank_movers(trade_date, top_n, &[]));
+    };
+    let universe = universe.resolve_set(conn).await?;
+    let prices: Vec<stock_daily::Model> = stock_daily::Entity::find()
+        .filter(ColumnTrait::eq(&stock_daily::Column::TradeDate, &trade_date))
+        .all(conn)
+        .await?
+        .into_iter()
+        .filter(|p| universe.contains(&p.ts_code))
+        .collect();
+    Ok(rank_movers(&trade_date, top_n, &prices))
+}
+
+fn rank_movers(trade_date: &str, top_n: usize, prices: &[stock_daily::Model]) -> TopMovers {
+    let mut movers: Vec<Mover> = prices.iter().filter(|p| !p.vol.is_zero()).filter_map(to_mover).collect();
+    movers.sort_by(|a, b| b.pct_chg.partial_cmp(&a.pct_chg).unwrap_or(Ordering::Equal));
+
+    let gainers = movers.iter().filter(|m| m.pct_chg > 0f64).take(top_n).cloned().collect();
+    let losers = movers.iter().rev().filter(|m| m.pct_chg < 0f64).take(top_n).cloned().collect();
+    TopMovers { trade_date: trade_date.to_string(), gainers, losers }
+}
+
+fn to_mover(price: &stock_daily::Model) -> Option<Mover> {
+    let close = price.close.to_f64()?;
+    let pre_close = price.pre_close.and_then(|v| v.to_f64()).filter(|v| *v > 0f64);
+    let pct_chg = price
+        .pct_chg
+        .and_then(|v| v.to_f64())
+        .or_else(|| pre_close.map(|pre_close| (close / pre_close - 1f64) * 100f64))?;
+    let (limit_up, limit_down) = match pre_close {
+        Some(pre_close) => (
+            is_limit_up_close(&price.ts_code, pre_close, close),
+            is_limit_down_close(&price.ts_code, pre_close, close),
+        ),
+        None => (false, false),
+    };
+    Some(Mover { ts_code: price.ts_code.clone(), close, pct_chg, limit_up, limit_down })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use rust_decimal::Decimal;
+    use std::str::FromStr;
+
+    fn price(ts_code: &str, pre_close: &str, close: &str, vol: i64) -> stock_daily::Model {
+        let (pre_close, close) = (Decimal::from_str(pre_close).unwrap(), Decimal::from_str(close).unwrap());
+        stock_daily::Model {
+            open: close,
+            high: close,
+            low: close,
+            close,
+            pre_close: Some(pre_close),
+            change: Some(close - pre_close),
+            vol: Decimal::from(vol),
+            ..test_util::daily(ts_code, "20240102", 0)
+        }
+    }
+
+    #[test]
+    fn test_rank_movers() {
+        let prices = vec![
+            // 主板涨停 +10%
+            price("600000.SH", "10.00", "11.00", 100),
+            // 创业板 +15%, 未涨停
+            price("300750.SZ", "100.00", "115.00", 100),
+            // 创业板涨停 +20%
+            price("301236.SZ", "10.00", "12.00", 100),
+            price("000001.SZ", "10.00", "10.30", 100),
+            price("000002.SZ", "10.00", "10.00", 100),
+            price("000004.SZ", "10.00", "9.50", 100),
+            // 主板跌停 -10%
+            price("000005.SZ", "10.05", "9.05", 100),
+            // 停牌
+            price("000006.SZ", "10.00", "13.00", 0),
+        ];
+
+        let movers = rank_movers("20240102", 3, &prices);
+        let gainers: Vec<(&str, bool)> = movers.gainers.iter().map(|m| (m.ts_code.as_str(), m.limit_up)).collect();
+        assert_eq!(gainers, vec![("301236.SZ", true), ("300750.SZ", false), ("600000.SH", true)]);
+        let losers: Vec<(&str, bool)> = movers.losers.iter().map(|m| (m.ts_code.as_str(), m.limit_down)).collect();
+        assert_eq!(losers, vec![("000005.SZ", true), ("000004.SZ", false)]);
+        assert!((movers.gainers[1].pct_chg - 15f64).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_top_movers_within_universe() {
+        let conn = test_util::memory_db().await;
+        test_util::seed(&conn, entity::stock::Entity, vec![test_util::stock("600000.SH"), test_util::stock("000001.SZ")]).await;
+        let prices = vec![price("600000.SH", "10.00", "11.00", 100), price("000001.SZ", "10.00", "10.50", 100)];
+        test_util::seed(&conn, stock_daily::Entity, prices).await;
+
+        let all = top_movers("20240102", 3, &Universe::All, &conn).await.unwrap();
+        assert_eq!(all.gainers.len(), 2);
+        let listed = top_movers("20240102", 3, &Universe::List(vec!["000001.SZ".to_string()]), &conn).await.unwrap();
+        let gainers: Vec<&str> = listed.gainers.iter().map(|m| m.ts_code.as_str()).collect();
+        assert_eq!(gainers, vec!["000001.SZ"]);
+    }
+}
@@ -0,0 +1,141 @@
+use anyhow::anyhow;
+use num_traits::ToPrimitive;
+use serde::Serialize;
+
+use entity::sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+use entity::{stock, stock_daily_basic};
+
+/// 个股估值在所属行业中的分位
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ValuationPercentile {
+    pub ts_code: String,
+    pub industry: String,
+    pub trade_date: String,
+    pub pe: Option<f64>,
+    pub pe_percentile: Option<f64>, // 行业内 PE 低于该股的同行占比 x%100, 越小越便宜; 该股 PE 为负或缺失时为 None
+    pub pe_peer_count: usize,       // 参与 PE 分位计算的股票数(含自身, 不含 PE 为负的同行)
+    pub pb: Option<f64>,
+    pub pb_percentile: Option<f64>, // 行业内 PB 低于该股的同行占比 x%100
+    pub pb_peer_count: usize,
+}
+
+/// 使用最新一个交易日的 `stock_daily_basic`, 计算个股 PE/PB 在 `stock.industry` 同行中的分位
+pub async fn valuation_percentile(ts_code: &str, conn: &DatabaseConnection) -> anyhow::Result<ValuationPercentile> {
+    let stock = stock::Entity::find_by_id(ts_code)
+        .one(conn)
+        .await?
+        .ok_or_else(|| anyhow!("stock {} not found", ts_code))?;
+    let industry = stock.industry.ok_or_else(|| anyhow!("stock {} has no industry", ts_code))?;
+
+    let latest = stock_daily_basic::Entity::find()
+        .filter(ColumnTrait::eq(&stock_daily_basic::Column::TsCode, ts_code))
+        .order_by_desc(stock_daily_basic::Column::TradeDate)
+        .one(conn)
+        .await?
+        .ok_or_else(|| anyhow!("no daily basic for {}", ts_code))?;
+
+    let peers: Vec<String> = stock::Entity::find()
+        .filter(ColumnTrait::eq(&stock::Column::Industry, &industry))
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|s| s.ts_code)
+        .collect();
+    let basics = stock_daily_basic::Entity::find()
+        .filter(ColumnTrait::eq(&stock_daily_basic::Column::TradeDate, &latest.trade_date))
+        .filter(stock_daily_basic::Column::TsCode.is_in(peers))
+        .all(conn)
+        .await?;
+
+    Ok(compute_valuation_percentile(ts_code, &industry, &latest.trade_date, &basics))
+}
+
+/// `basics` 为同一交易日同行业的每日指标, 应包含目标股票
+fn compute_valuation_percentile(
+    ts_code: &str,
+    industry: &str,
+    trade_date: &str,
+    basics: &[stock_daily_basic::Model],
+) -> ValuationPercentile {
+    let target = basics.iter().find(|b| b.ts_code == ts_code);
+    let pe = target.and_then(|b| b.pe).and_then(|v| v.to_f64());
+    let pb = target.and_then(|b| b.pb).and_then(|v| v.to_f64());
+
+    let pes: Vec<f64> = basics.iter().filter_map(|b| b.pe.and_then(|v| v.to_f64())).filter(|v| *v > 0f64).collect();
+    let pbs: Vec<f64> = basics.iter().filter_map(|b| b.pb.and_then(|v| v.to_f64())).collect();
+
+    ValuationPercentile {
+        ts_code: ts_code.to_string(),
+        industry: industry.to_string(),
+        trade_date: trade_date.to_string(),
+        pe,
+        pe_percentile: pe.filter(|v| *v > 0f64).and_then(|v| percentile(v, &pes)),
+        pe_peer_count: pes.len(),
+        pb,
+        pb_percentile: pb.and_then(|v| percentile(v, &pbs)),
+        pb_peer_count: pbs.len(),
+    }
+}
+
+/// `values` 中(含 `value` 自身)除自身外严格小于 `value` 的占比 x%100, 少于 2 个值时无意义
+fn percentile(value: f64, values: &[f64]) -> Option<f64> {
+    if values.len() < 2 {
+        return None;
+    }
+    let below = values.iter().filter(|v| **v < value).count();
+    Some(below as f64 / (values.len() - 1) as f64 * 100f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+    use rust_decimal::prelude::FromPrimitive;
+
+    fn basic(ts_code: &str, pe: Option<f64>, pb: Option<f64>) -> stock_daily_basic::Model {
+        let dec = |v: f64| Decimal::from_f64(v).unwrap();
+        stock_daily_basic::Model {
+            ts_code: ts_code.to_string(),
+            trade_date: "20240105".to_string(),
+            close: None,
+            turnover_rate: None,
+            turnover_rate_f: None,
+            volume_ratio: None,
+            pe: pe.map(dec),
+            pe_ttm: None,
+            pb: pb.map(dec),
+            ps: None,
+            ps_ttm: None,
+            dv_ratio: None,
+            dv_ttm: None,
+            total_share: None,
+            float_share: None,
+            free_share: None,
+            total_mv: None,
+            circ_mv: None,
+        }
+    }
+
+    #[test]
+    fn test_valuation_percentile_in_industry() {
+        let basics = vec![
+            basic("000001.SZ", Some(10.0), Some(1.0)),
+            basic("000002.SZ", Some(20.0), Some(2.0)),
+            basic("000003.SZ", Some(30.0), Some(1.5)),
+            basic("000004.SZ", Some(40.0), Some(4.0)),
+            basic("000005.SZ", Some(50.0), Some(5.0)),
+            // 亏损股, 不参与 PE 分位
+            basic("000006.SZ", Some(-8.0), Some(0.5)),
+        ];
+        let result = compute_valuation_percentile("000003.SZ", "银行", "20240105", &basics);
+        assert_eq!(result.pe_peer_count, 5);
+        assert_eq!(result.pe_percentile, Some(50.0));
+        assert_eq!(result.pb_peer_count, 6);
+        // PB 1.5 高于 0.5 和 1.0
+        assert_eq!(result.pb_percentile, Some(40.0));
+
+        let loss = compute_valuation_percentile("000006.SZ", "银行", "20240105", &basics);
+        assert_eq!(loss.pe_percentile, None);
+        assert_eq!(loss.pb_percentile, Some(0.0));
+    }
+}
@@ -0,0 +1,118 @@
+use anyhow::{anyhow, bail};
+use num_traits::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use entity::sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use entity::stock_daily;
+
+use crate::security::{sum_decimal, SecurityPriceDecimal};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub enum VwapPosition {
+    Above,
+    Below,
+    At,
+}
+
+/// N 日成交量加权均价(成交额 / 成交量), 可作为动态支撑/压力线
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct WindowVwap {
+    pub ts_code: String,
+    pub trade_date: String, // 最新交易日
+    pub window: usize,
+    pub vwap: f64,
+    pub close: f64, // 最新收盘价
+    pub position: VwapPosition, // 最新收盘价相对 vwap 的位置
+}
+
+/// 最近 `window` 个交易日的成交量加权均价, 成交量为 0 的交易日(停牌)不参与计算
+pub async fn window_vwap(ts_code: &str, window: usize, conn: &DatabaseConnection) -> anyhow::Result<WindowVwap> {
+    if window == 0 {
+        bail!("window must be greater than 0");
+    }
+    let prices = stock_daily::Entity::find()
+        .filter(ColumnTrait::eq(&stock_daily::Column::TsCode, ts_code))
+        .order_by_desc(stock_daily::Column::TradeDate)
+        .limit(window as u64)
+        .all(conn)
+        .await?;
+    calc_window_vwap(ts_code, window, &prices)
+}
+
+/// `prices` 日期按倒序排序, 第一条为最新交易日
+fn calc_window_vwap(ts_code: &str, window: usize, prices: &[stock_daily::Model]) -> anyhow::Result<WindowVwap> {
+    let latest = prices.first().ok_or_else(|| anyhow!("no daily price for {}", ts_code))?;
+    let close = latest.close.to_f64().ok_or_else(|| anyhow!("invalid close for {}", ts_code))?;
+
+    // 用 Decimal 求和, 窗口较长时不会累积浮点误差
+    let traded: Vec<SecurityPriceDecimal> = prices
+        .iter()
+        .cloned()
+        .map(SecurityPriceDecimal::from_stock_daily)
+        .filter(|p| p.vol.is_some_and(|vol| vol > Decimal::ZERO))
+        .collect();
+    let amount = sum_decimal(&traded, |p| p.amount);
+    let vol = sum_decimal(&traded, |p| p.vol);
+    if vol <= Decimal::ZERO {
+        bail!("no trading volume for {} in last {} days", ts_code, window);
+    }
+    // amount 单位千元, vol 单位手(100股)
+    let vwap = (amount * Decimal::from(10) / vol).to_f64().ok_or_else(|| anyhow!("invalid vwap for {}", ts_code))?;
+    let position = if close > vwap {
+        VwapPosition::Above
+    } else if close < vwap {
+        VwapPosition::Below
+    } else {
+        VwapPosition::At
+    };
+    Ok(WindowVwap {
+        ts_code: ts_code.to_string(),
+        trade_date: latest.trade_date.clone(),
+        window,
+        vwap,
+        close,
+        position,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use rust_decimal::Decimal;
+    use rust_decimal::prelude::FromPrimitive;
+
+    fn price(date: &str, close: f64, vol: f64, amount: f64) -> stock_daily::Model {
+        let dec = |v: f64| Decimal::from_f64(v).unwrap();
+        stock_daily::Model {
+            open: dec(close),
+            high: dec(close),
+            low: dec(close),
+            close: dec(close),
+            vol: dec(vol),
+            amount: dec(amount),
+            ..test_util::daily("000001.SZ", date, 0)
+        }
+    }
+
+    #[test]
+    fn test_window_vwap() {
+        let prices = vec![
+            // 1000手 @ 12.0 = 1200千元
+            price("20240105", 12.0, 1000.0, 1200.0),
+            // 停牌
+            price("20240104", 11.0, 0.0, 0.0),
+            // 3000手 @ 10.0 = 3000千元
+            price("20240103", 10.0, 3000.0, 3000.0),
+        ];
+        let vwap = calc_window_vwap("000001.SZ", 3, &prices).unwrap();
+        // (1200 + 3000) * 1000 / ((1000 + 3000) * 100) = 10.5
+        assert!((vwap.vwap - 10.5).abs() < 1e-9);
+        assert_eq!(vwap.position, VwapPosition::Above);
+        assert_eq!(vwap.trade_date, "20240105");
+
+        let suspended = vec![price("20240105", 12.0, 0.0, 0.0)];
+        assert!(calc_window_vwap("000001.SZ", 1, &suspended).is_err());
+    }
+}
@@ -0,0 +1,223 @@
+use anyhow::bail;
+use futures::future::join_all;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use entity::sea_orm::DatabaseConnection;
+
+use crate::analysis::{indicator_bundle, indicator_bundle_with_sector, top_movers};
+use crate::diagnosis::{diagnosis, diagnosis_detailed};
+use crate::margin_service::short_interest;
+use crate::stock::quote_cache::QuoteCache;
+use crate::stock::stock_overview_service::stock_overview;
+use crate::universe::Universe;
+
+/// 单次批量请求最多的操作数
+const MAX_BATCH_OPS: usize = 20;
+/// `indicator_bundle` 最多返回的交易日数, 约一年
+const MAX_WINDOW: usize = 250;
+/// `top_movers` 每个方向最多返回的股票数
+const MAX_TOP_N: usize = 100;
+/// 批量请求支持的操作, 只开放只读的分析接口
+pub const SUPPORTED_OPS: [&str; 5] = ["stock_overview", "stock_diagnosis", "indicator_bundle", "top_movers", "short_interest"];
+
+/// 批量请求中的单个操作, `params` 与对应单独接口的参数一致
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchOp {
+    pub op: String,
+    #[serde(default)]
+    pub params: Value,
+}
+
+/// 单个操作的结果, 执行失败时 `data` 为 None, `error` 为失败原因
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchOpResult {
+    pub op: String,
+    pub data: Option<Value>,
+    pub error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TsCodeParams {
+    ts_code: String,
+}
+
+#[derive(Deserialize)]
+struct DiagnosisParams {
+    ts_code: String,
+    #[serde(default)]
+    detailed: bool,
+    #[serde(default)]
+    moneyflow_period: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct IndicatorBundleParams {
+    ts_code: String,
+    window: usize,
+    #[serde(default)]
+    sector: bool,
+}
+
+#[derive(Deserialize)]
+struct TopMoversParams {
+    trade_date: String,
+    top_n: usize,
+    #[serde(default)]
+    universe: Universe,
+}
+
+/// 并发执行一组分析操作, 结果顺序与 `ops` 一致
+///
+/// 单个操作失败(包括不支持的操作、参数错误)只记录在对应结果的 `error` 中, 不影响其它操作
+pub async fn run_batch(ops: Vec<BatchOp>, quotes: &QuoteCache, conn: &DatabaseConnection) -> anyhow::Result<Vec<BatchOpResult>> {
+    if ops.len() > MAX_BATCH_OPS {
+        bail!("too many ops: {}, max: {}", ops.len(), MAX_BATCH_OPS);
+    }
+    let results = join_all(ops.into_iter().map(|op| async move {
+        match run_op(&op, quotes, conn).await {
+            Ok(data) => BatchOpResult { op: op.op, data: Some(data), error: None },
+            Err(e) => BatchOpResult { op: op.op, data: None, error: Some(e.to_string()) },
+        }
+    }))
+    .await;
+    Ok(results)
+}
+
+async fn run_op(op: &BatchOp, quotes: &QuoteCache, conn: &DatabaseConnection) -> anyhow::Result<Value> {
+    let data = match op.op.as_str() {
+        "stock_overview" => {
+            let params: TsCodeParams = parse_params(&op.params)?;
+            serde_json::to_value(stock_overview(&params.ts_code, quotes, conn).await?)?
+        }
+        "stock_diagnosis" => {
+            let params: DiagnosisParams = parse_params(&op.params)?;
+            if params.detailed {
+                serde_json::to_value(diagnosis_detailed(&params.ts_code, params.moneyflow_period, conn).await?)?
+            } else {
+                serde_json::to_value(diagnosis(&params.ts_code, params.moneyflow_period, conn).await?)?
+            }
+        }
+        "indicator_bundle" => {
+            let params: IndicatorBundleParams = parse_params(&op.params)?;
+            in_range("window", params.window, 1, MAX_WINDOW)?;
+            if params.sector {
+                serde_json::to_value(indicator_bundle_with_sector(&params.ts_code, params.window, conn).await?)?
+            } else {
+                serde_json::to_value(indicator_bundle(&params.ts_code, params.window, conn).await?)?
+            }
+        }
+        "top_movers" => {
+            let params: TopMoversParams = parse_params(&op.params)?;
+            in_range("top_n", params.top_n, 1, MAX_TOP_N)?;
+            serde_json::to_value(top_movers(&params.trade_date, params.top_n, &params.universe, conn).await?)?
+        }
+        "short_interest" => {
+            let params: TsCodeParams = parse_params(&op.params)?;
+            serde_json::to_value(short_interest(&params.ts_code, conn).await?)?
+        }
+        other => bail!("unsupported op: {}, supported: {}", other, SUPPORTED_OPS.join(", ")),
+    };
+    Ok(data)
+}
+
+fn parse_params<T: DeserializeOwned>(params: &Value) -> anyhow::Result<T> {
+    serde_json::from_value(params.clone()).map_err(|e| anyhow::anyhow!("invalid params: {}", e))
+}
+
+/// 与单独接口的入参校验一致, 拒绝超出 `[min, max]` 的数值
+fn in_range(name: &str, value: usize, min: usize, max: usize) -> anyhow::Result<()> {
+    if value < min || value > max {
+        bail!("invalid {}: {}, must be between {} and {}", name, value, min, max);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use chrono::{Duration, Local};
+    use entity::{moneyflow, stock, stock_daily, stock_daily_basic};
+    use rust_decimal::Decimal;
+    use serde_json::json;
+
+    fn daily(trade_date: &str, close: i64) -> stock_daily::Model {
+        stock_daily::Model { vol: Decimal::ONE, amount: Decimal::ONE, ..test_util::daily("600000.SH", trade_date, close) }
+    }
+
+    fn basic(trade_date: &str) -> stock_daily_basic::Model {
+        stock_daily_basic::Model {
+            ts_code: "600000.SH".to_string(),
+            trade_date: trade_date.to_string(),
+            close: None,
+            turnover_rate: None,
+            turnover_rate_f: None,
+            volume_ratio: None,
+            pe: None,
+            pe_ttm: None,
+            pb: None,
+            ps: None,
+            ps_ttm: None,
+            dv_ratio: None,
+            dv_ttm: None,
+            total_share: None,
+            float_share: None,
+            free_share: None,
+            total_mv: None,
+            circ_mv: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_batch_overview_and_diagnosis() {
+        let conn = test_util::memory_db().await;
+        let stock = stock::Model {
+            name: Some("浦发银行".to_string()),
+            industry: Some("银行".to_string()),
+            ..test_util::stock("600000.SH")
+        };
+        test_util::seed(&conn, stock::Entity, vec![stock]).await;
+        // 诊断只取最近 90 天的数据
+        let today = Local::now().date_naive();
+        let dates: Vec<String> = (1..=3).rev().map(|i| (today - Duration::days(i)).format(common::date::FORMAT).to_string()).collect();
+        let dailies = dates.iter().enumerate().map(|(i, d)| daily(d, 10 + i as i64)).collect();
+        test_util::seed(&conn, stock_daily::Entity, dailies).await;
+        test_util::seed(&conn, stock_daily_basic::Entity, dates.iter().map(|d| basic(d)).collect()).await;
+        test_util::create_table(&conn, moneyflow::Entity).await;
+
+        let ops = vec![
+            BatchOp { op: "stock_overview".to_string(), params: json!({ "ts_code": "600000.SH" }) },
+            BatchOp { op: "stock_diagnosis".to_string(), params: json!({ "ts_code": "600000.SH" }) },
+            BatchOp { op: "drop_table".to_string(), params: Value::Null },
+        ];
+        let results = run_batch(ops, &QuoteCache::default(), &conn).await.unwrap();
+        assert_eq!(results.len(), 3);
+
+        assert_eq!(results[0].op, "stock_overview");
+        let overview = results[0].data.as_ref().unwrap();
+        assert_eq!(overview["ts_code"], "600000.SH");
+        assert_eq!(overview["close"], 12f64);
+
+        assert_eq!(results[1].op, "stock_diagnosis");
+        assert!(results[1].error.is_none());
+        assert_eq!(results[1].data.as_ref().unwrap()["current_price"], 12f64);
+
+        assert_eq!(results[2].op, "drop_table");
+        assert!(results[2].data.is_none());
+        assert!(results[2].error.as_ref().unwrap().contains("unsupported op"));
+    }
+
+    #[tokio::test]
+    async fn test_run_batch_rejects_out_of_range_params() {
+        let conn = test_util::memory_db().await;
+        let ops = vec![
+            BatchOp { op: "indicator_bundle".to_string(), params: json!({ "ts_code": "600000.SH", "window": 0 }) },
+            BatchOp { op: "top_movers".to_string(), params: json!({ "trade_date": "20240102", "top_n": 100000 }) },
+        ];
+        let results = run_batch(ops, &QuoteCache::default(), &conn).await.unwrap();
+        assert_eq!(results[0].error.as_deref(), Some("invalid window: 0, must be between 1 and 250"));
+        assert_eq!(results[1].error.as_deref(), Some("invalid top_n: 100000, must be between 1 and 100"));
+    }
+}
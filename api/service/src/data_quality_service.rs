@@ -0,0 +1,369 @@
+use std::collections::{BTreeSet, HashMap};
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use anyhow::anyhow;
+use chrono::{Duration as DateDuration, Local, NaiveDate};
+use once_cell::sync::Lazy;
+use num_traits::ToPrimitive;
+use serde::Serialize;
+
+use common::db::find_between;
+use entity::sea_orm::{
+    ColumnTrait, DatabaseConnection, EntityTrait, Order, PaginatorTrait, QueryFilter, QueryOrder, QuerySelect,
+};
+use entity::{adj_factor, dividend, fund_daily, index_daily, margin_detail, moneyflow, stock_daily, stock_daily_basic, trade_calendar};
+
+/// 交易日历使用的交易所, A股各交易所交易日相同
+const CALENDAR_EXCHANGE: &str = "SSE";
+/// 报告缓存时间, 统计需要扫描多张大表
+const CACHE_TTL: Duration = Duration::from_secs(5 * 60);
+/// 复权涨跌幅与实际涨跌幅相差超过该值(百分点)视为复权因子异常
+const ADJ_FACTOR_TOLERANCE: f64 = 0.5;
+/// 抽查复权因子时除权除息日前后各取的自然日数
+const ADJ_CHECK_MARGIN_DAYS: i64 = 10;
+
+/// 单张行情表的数据质量
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EntityQuality {
+    pub entity: String,
+    pub last_trade_date: Option<String>, // 表中最新的交易日, 空表为 None
+    pub total_rows: u64,
+    pub stocks_with_gaps: usize, // 统计窗口内有缺失交易日的代码数
+}
+
+/// 数据质量报告
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DataQualityReport {
+    pub window_days: usize,
+    pub window_start: String,
+    pub window_end: String,
+    pub entities: Vec<EntityQuality>,
+}
+
+static REPORT_CACHE: Lazy<RwLock<HashMap<usize, (Instant, DataQualityReport)>>> = Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// 各行情表的最新交易日、总行数, 以及最近 `window_days` 个交易日内有缺失的代码数, 结果缓存 5 分钟
+///
+/// 代码在窗口内第一条与最后一条数据之间缺少某个交易日即视为有缺失, 停牌也会被计入;
+/// 窗口开始后才上市或窗口结束前退市的不计入
+pub async fn data_quality_report(window_days: usize, conn: &DatabaseConnection) -> anyhow::Result<DataQualityReport> {
+    let cached = REPORT_CACHE
+        .read()
+        .map_err(|_| anyhow!("data quality cache poisoned"))?
+        .get(&window_days)
+        .filter(|(at, _)| at.elapsed() < CACHE_TTL)
+        .map(|(_, report)| report.clone());
+    if let Some(report) = cached {
+        return Ok(report);
+    }
+
+    let report = build_report(window_days, conn).await?;
+    REPORT_CACHE
+        .write()
+        .map_err(|_| anyhow!("data quality cache poisoned"))?
+        .insert(window_days, (Instant::now(), report.clone()));
+    Ok(report)
+}
+
+async fn build_report(window_days: usize, conn: &DatabaseConnection) -> anyhow::Result<DataQualityReport> {
+    if window_days == 0 {
+        anyhow::bail!("window_days must be greater than 0");
+    }
+    let today = Local::now().date_naive().format(common::date::FORMAT).to_string();
+    let mut window: Vec<String> = trade_calendar::Entity::find()
+        .select_only()
+        .column(trade_calendar::Column::CalDate)
+        .filter(ColumnTrait::eq(&trade_calendar::Column::Exchange, CALENDAR_EXCHANGE))
+        .filter(ColumnTrait::eq(&trade_calendar::Column::IsOpen, 1))
+        .filter(trade_calendar::Column::CalDate.lte(&today))
+        .order_by_desc(trade_calendar::Column::CalDate)
+        .limit(window_days as u64)
+        .into_tuple::<String>()
+        .all(conn)
+        .await?;
+    window.reverse();
+    let (Some(window_start), Some(window_end)) = (window.first().cloned(), window.last().cloned()) else {
+        anyhow::bail!("trade calendar not found before {}", today);
+    };
+
+    let entities = vec![
+        entity_quality(stock_daily::Entity, stock_daily::Column::TsCode, stock_daily::Column::TradeDate, &window, conn).await?,
+        entity_quality(stock_daily_basic::Entity, stock_daily_basic::Column::TsCode, stock_daily_basic::Column::TradeDate, &window, conn).await?,
+        entity_quality(moneyflow::Entity, moneyflow::Column::TsCode, moneyflow::Column::TradeDate, &window, conn).await?,
+        entity_quality(margin_detail::Entity, margin_detail::Column::TsCode, margin_detail::Column::TradeDate, &window, conn).await?,
+        entity_quality(index_daily::Entity, index_daily::Column::TsCode, index_daily::Column::TradeDate, &window, conn).await?,
+        entity_quality(fund_daily::Entity, fund_daily::Column::TsCode, fund_daily::Column::TradeDate, &window, conn).await?,
+    ];
+    Ok(DataQualityReport { window_days, window_start, window_end, entities })
+}
+
+/// `window` 为统计窗口内的交易日, 按正序排序且不为空
+async fn entity_quality<E>(
+    entity: E,
+    ts_code_col: E::Column,
+    date_col: E::Column,
+    window: &[String],
+    conn: &DatabaseConnection,
+) -> anyhow::Result<EntityQuality>
+where
+    E: EntityTrait,
+    E::Model: Send + Sync,
+{
+    let total_rows = E::find().count(conn).await?;
+    let last_trade_date = E::find()
+        .select_only()
+        .column(date_col)
+        .order_by_desc(date_col)
+        .into_tuple::<String>()
+        .one(conn)
+        .await?;
+    let rows: Vec<(String, String)> = E::find()
+        .select_only()
+        .column(ts_code_col)
+        .column(date_col)
+        .filter(date_col.gte(&window[0]))
+        .filter(date_col.lte(&window[window.len() - 1]))
+        .into_tuple()
+        .all(conn)
+        .await?;
+    Ok(EntityQuality {
+        entity: entity.table_name().to_string(),
+        last_trade_date,
+        total_rows,
+        stocks_with_gaps: count_gapped(window, &rows),
+    })
+}
+
+/// 统计 `rows` (代码, 交易日) 中在首末两条数据之间缺少 `window` 内交易日的代码数
+fn count_gapped(window: &[String], rows: &[(String, String)]) -> usize {
+    let mut by_code: HashMap<&str, BTreeSet<&str>> = HashMap::new();
+    for (ts_code, trade_date) in rows {
+        by_code.entry(ts_code.as_str()).or_default().insert(trade_date.as_str());
+    }
+    by_code
+        .values()
+        .filter(|dates| {
+            let (Some(first), Some(last)) = (dates.first(), dates.last()) else {
+                return false;
+            };
+            window
+                .iter()
+                .filter(|d| d.as_str() >= *first && d.as_str() <= *last)
+                .any(|d| !dates.contains(d.as_str()))
+        })
+        .count()
+}
+
+/// 复权因子异常: 复权后的涨跌幅与按昨收(已除权)计算的实际涨跌幅不一致, 复权价在该日出现跳变
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AdjFactorAnomaly {
+    pub ts_code: String,
+    pub trade_date: String,
+    pub pct_chg: f64,     // 实际涨跌幅 x%100, close / pre_close
+    pub adj_pct_chg: f64, // 复权涨跌幅 x%100, 相邻两日复权收盘价之比
+}
+
+/// 检查复权因子: 相邻两个交易日的复权收盘价之比应与当日收盘价 / 昨收(除权除息后)一致,
+/// 除权除息日前后复权价连续; 不一致的交易日说明复权因子有误
+///
+/// `prices` 为同一只股票按日期正序排序的日线, `factors` 为交易日到复权因子的映射,
+/// 缺少昨收或复权因子的交易日跳过
+pub fn adj_factor_anomalies(prices: &[stock_daily::Model], factors: &HashMap<String, f64>) -> Vec<AdjFactorAnomaly> {
+    prices
+        .windows(2)
+        .filter_map(|pair| {
+            let (prev, curr) = (&pair[0], &pair[1]);
+            let (prev_factor, factor) = (*factors.get(&prev.trade_date)?, *factors.get(&curr.trade_date)?);
+            let (prev_close, close) = (prev.close.to_f64()?, curr.close.to_f64()?);
+            let pre_close = curr.pre_close.and_then(|v| v.to_f64())?;
+            if prev_close <= 0f64 || pre_close <= 0f64 || prev_factor <= 0f64 {
+                return None;
+            }
+            let pct_chg = (close / pre_close - 1f64) * 100f64;
+            let adj_pct_chg = (close * factor / (prev_close * prev_factor) - 1f64) * 100f64;
+            ((pct_chg - adj_pct_chg).abs() > ADJ_FACTOR_TOLERANCE).then(|| AdjFactorAnomaly {
+                ts_code: curr.ts_code.clone(),
+                trade_date: curr.trade_date.clone(),
+                pct_chg,
+                adj_pct_chg,
+            })
+        })
+        .collect()
+}
+
+/// 抽查 [since, until] 内有除权除息的股票, 检查除权除息日前后 10 个自然日的复权因子
+///
+/// 按除权除息日倒序取最近的 `sample_size` 只股票, 结果按代码和交易日排序
+pub async fn verify_adj_factors(since: &NaiveDate, until: &NaiveDate, sample_size: usize, conn: &DatabaseConnection) -> anyhow::Result<Vec<AdjFactorAnomaly>> {
+    let events: Vec<(String, String)> = dividend::Entity::find()
+        .select_only()
+        .column(dividend::Column::TsCode)
+        .column(dividend::Column::ExDate)
+        .filter(dividend::Column::ExDate.gte(since.format(common::date::FORMAT).to_string()))
+        .filter(dividend::Column::ExDate.lte(until.format(common::date::FORMAT).to_string()))
+        .order_by_desc(dividend::Column::ExDate)
+        .into_tuple()
+        .all(conn)
+        .await?;
+    let mut sampled: BTreeSet<&str> = BTreeSet::new();
+    let mut checks: BTreeSet<(&str, &str)> = BTreeSet::new();
+    for (ts_code, ex_date) in &events {
+        if sampled.len() >= sample_size && !sampled.contains(ts_code.as_str()) {
+            continue;
+        }
+        sampled.insert(ts_code.as_str());
+        checks.insert((ts_code.as_str(), ex_date.as_str()));
+    }
+
+    let mut anomalies = vec![];
+    for (ts_code, ex_date) in checks {
+        let ex_date = NaiveDate::parse_from_str(ex_date, common::date::FORMAT)?;
+        let start = (ex_date - DateDuration::days(ADJ_CHECK_MARGIN_DAYS)).format(common::date::FORMAT).to_string();
+        let end = (ex_date + DateDuration::days(ADJ_CHECK_MARGIN_DAYS)).format(common::date::FORMAT).to_string();
+        let prices = find_between::<stock_daily::Entity>(conn, stock_daily::Column::TsCode, stock_daily::Column::TradeDate, ts_code, &start, &end, Order::Asc).await?;
+        let factors: HashMap<String, f64> =
+            find_between::<adj_factor::Entity>(conn, adj_factor::Column::TsCode, adj_factor::Column::TradeDate, ts_code, &start, &end, Order::Asc)
+                .await?
+                .into_iter()
+                .filter_map(|f| Some((f.trade_date, f.adj_factor?.to_f64()?)))
+                .collect();
+        anomalies.extend(adj_factor_anomalies(&prices, &factors));
+    }
+    // 同一股票相邻两次除权除息的检查区间可能重叠
+    anomalies.sort_by(|a, b| (&a.ts_code, &a.trade_date).cmp(&(&b.ts_code, &b.trade_date)));
+    anomalies.dedup_by(|a, b| a.ts_code == b.ts_code && a.trade_date == b.trade_date);
+    Ok(anomalies)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use rust_decimal::Decimal;
+
+    fn daily(ts_code: &str, trade_date: &str) -> stock_daily::Model {
+        test_util::daily(ts_code, trade_date, 1)
+    }
+
+    #[tokio::test]
+    async fn test_data_quality_report_with_gap() {
+        let conn = test_util::memory_db().await;
+        let dates = ["20240102", "20240103", "20240104", "20240105", "20240108"];
+        let calendar = dates
+            .iter()
+            .map(|d| trade_calendar::Model {
+                exchange: CALENDAR_EXCHANGE.to_string(),
+                cal_date: d.to_string(),
+                is_open: 1,
+                pretrade_date: None,
+            })
+            .collect();
+        test_util::seed(&conn, trade_calendar::Entity, calendar).await;
+
+        let mut prices = vec![];
+        // 完整
+        prices.extend(dates.iter().map(|d| daily("000001.SZ", d)));
+        // 20240104 缺失
+        prices.extend(dates.iter().filter(|d| **d != "20240104").map(|d| daily("000002.SZ", d)));
+        // 窗口中途退市, 不算缺失
+        prices.extend(dates[..2].iter().map(|d| daily("000003.SZ", d)));
+        test_util::seed(&conn, stock_daily::Entity, prices).await;
+        test_util::create_table(&conn, stock_daily_basic::Entity).await;
+        test_util::create_table(&conn, moneyflow::Entity).await;
+        test_util::create_table(&conn, margin_detail::Entity).await;
+        test_util::create_table(&conn, index_daily::Entity).await;
+        test_util::create_table(&conn, fund_daily::Entity).await;
+
+        let report = build_report(5, &conn).await.unwrap();
+        assert_eq!((report.window_start.as_str(), report.window_end.as_str()), ("20240102", "20240108"));
+        let daily = &report.entities[0];
+        assert_eq!(daily.entity, "stock_daily");
+        assert_eq!(daily.last_trade_date.as_deref(), Some("20240108"));
+        assert_eq!(daily.total_rows, 11);
+        assert_eq!(daily.stocks_with_gaps, 1);
+
+        let basic = &report.entities[1];
+        assert_eq!((basic.last_trade_date.clone(), basic.total_rows, basic.stocks_with_gaps), (None, 0, 0));
+    }
+
+    #[test]
+    fn test_adj_factor_jump_flagged() {
+        // 20240104 除息 1 元: 昨收 10 -> 除息后 9, 收盘 9.18 实际上涨 2%
+        let bar = |trade_date: &str, close: &str, pre_close: &str| stock_daily::Model {
+            close: close.parse().unwrap(),
+            pre_close: Some(pre_close.parse().unwrap()),
+            ..daily("600000.SH", trade_date)
+        };
+        let prices = vec![
+            bar("20240102", "10.00", "10.00"),
+            bar("20240103", "10.00", "10.00"),
+            bar("20240104", "9.18", "9.00"),
+            bar("20240105", "9.18", "9.18"),
+        ];
+        // 除息日复权因子按 10 / 9 调整, 复权价连续
+        let factors: HashMap<String, f64> =
+            [("20240102", 1.0), ("20240103", 1.0), ("20240104", 10.0 / 9.0), ("20240105", 10.0 / 9.0)]
+                .into_iter()
+                .map(|(d, f)| (d.to_string(), f))
+                .collect();
+        assert!(adj_factor_anomalies(&prices, &factors).is_empty());
+
+        // 复权因子没有调整, 复权价在除息日跳空 8.2%
+        let mut bad = factors.clone();
+        bad.insert("20240104".to_string(), 1.0);
+        bad.insert("20240105".to_string(), 1.0);
+        let anomalies = adj_factor_anomalies(&prices, &bad);
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].trade_date, "20240104");
+        assert!((anomalies[0].pct_chg - 2f64).abs() < 1e-9);
+        assert!((anomalies[0].adj_pct_chg + 8.2).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_verify_adj_factors_around_ex_date() {
+        let conn = test_util::memory_db().await;
+        let dividend = |ts_code: &str, ex_date: &str| dividend::Model {
+            ts_code: ts_code.to_string(),
+            end_date: "20231231".to_string(),
+            ann_date: "20240101".to_string(),
+            div_proc: "实施".to_string(),
+            stk_div: None,
+            stk_bo_rate: None,
+            stk_co_rate: None,
+            cash_div: None,
+            cash_div_tax: None,
+            record_date: None,
+            ex_date: Some(ex_date.to_string()),
+            pay_date: None,
+            div_listdate: None,
+            imp_ann_date: None,
+        };
+        // 600000.SH 复权因子在除息日没有调整, 000001.SZ 除息日不在检查区间内
+        test_util::seed(&conn, dividend::Entity, vec![dividend("600000.SH", "20240104"), dividend("000001.SZ", "20230104")]).await;
+        let bar = |ts_code: &str, trade_date: &str, close: &str, pre_close: &str| stock_daily::Model {
+            close: close.parse().unwrap(),
+            pre_close: Some(pre_close.parse().unwrap()),
+            ..daily(ts_code, trade_date)
+        };
+        let mut prices = vec![];
+        let mut factors = vec![];
+        for ts_code in ["600000.SH", "000001.SZ"] {
+            prices.extend([bar(ts_code, "20240103", "10.00", "10.00"), bar(ts_code, "20240104", "9.18", "9.00")]);
+            factors.extend(["20240103", "20240104"].map(|trade_date| adj_factor::Model {
+                ts_code: ts_code.to_string(),
+                trade_date: trade_date.to_string(),
+                adj_factor: Some(Decimal::ONE),
+            }));
+        }
+        test_util::seed(&conn, stock_daily::Entity, prices).await;
+        test_util::seed(&conn, adj_factor::Entity, factors).await;
+
+        let since = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let until = NaiveDate::from_ymd_opt(2024, 3, 31).unwrap();
+        let anomalies = verify_adj_factors(&since, &until, 10, &conn).await.unwrap();
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!((anomalies[0].ts_code.as_str(), anomalies[0].trade_date.as_str()), ("600000.SH", "20240104"));
+        assert!(verify_adj_factors(&since, &until, 0, &conn).await.unwrap().is_empty());
+    }
+}
@@ -2,6 +2,7 @@ use anyhow::{Context, Result};
 
 use std::collections::HashMap;
 
+use chrono::NaiveDate;
 use entity::sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
 use entity::sea_orm::sea_query::Expr;
 use entity::sea_orm::prelude::Decimal;
@@ -9,6 +10,7 @@ use entity::{dc_index, dc_member, stock_daily};
 use serde::{Deserialize, Serialize};
 
 use crate::pct_chg::PeriodPctChg;
+use crate::stock::stock_price_service;
 
 pub async fn list_dc_index_latest(conn: &DatabaseConnection) -> Result<Vec<dc_index::Model>> {
     let pairs: Vec<(String, String)> = dc_index::Entity::find()
@@ -94,12 +96,17 @@ pub async fn list_dc_members_enriched_by_concept(
     let mut pct_latest_map: HashMap<String, Option<f64>> = HashMap::new();
     let mut closes_desc_map: HashMap<String, Vec<Decimal>> = HashMap::new();
 
-    let daily_rows = stock_daily::Entity::find()
-        .filter(ColumnTrait::eq(&stock_daily::Column::TradeDate, trade_date.to_string()))
-        .filter(stock_daily::Column::TsCode.is_in(cn_symbols.clone()))
-        .all(conn)
-        .await
-        .context("Failed to fetch stock_daily rows for selected trade_date")?;
+    // 选定日期不是交易日时取之前最近一个交易日的涨跌幅
+    let date = NaiveDate::parse_from_str(trade_date, common::date::FORMAT).with_context(|| format!("Invalid trade_date: {}", trade_date))?;
+    let daily_rows = match stock_price_service::trade_date_on_or_before(&date, conn).await? {
+        Some(day) => stock_daily::Entity::find()
+            .filter(ColumnTrait::eq(&stock_daily::Column::TradeDate, day))
+            .filter(stock_daily::Column::TsCode.is_in(cn_symbols.clone()))
+            .all(conn)
+            .await
+            .context("Failed to fetch stock_daily rows for selected trade_date")?,
+        None => vec![],
+    };
     for d in daily_rows {
         let v = d.pct_chg.and_then(|x| x.to_string().parse::<f64>().ok());
         pct_day_map.insert(d.ts_code.clone(), v);
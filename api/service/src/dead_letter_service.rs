@@ -0,0 +1,121 @@
+use std::fmt::Debug;
+
+use anyhow::{anyhow, bail};
+use chrono::Local;
+use serde::de::DeserializeOwned;
+
+use common::db::{truncate_error, upsert_one, DEAD_LETTER_PENDING, DEAD_LETTER_RETRIED};
+use entity::sea_orm::{
+    ActiveModelTrait, ActiveValue, ColumnTrait, DatabaseConnection, EntityTrait, IntoActiveModel, QueryFilter, QueryOrder,
+};
+use entity::{stock_daily, task_dead_letter};
+
+/// 按任务名、状态过滤死信, 按创建顺序倒序
+pub async fn list_dead_letters(
+    task_name: Option<&str>,
+    status: Option<&str>,
+    conn: &DatabaseConnection,
+) -> anyhow::Result<Vec<task_dead_letter::Model>> {
+    let mut query = task_dead_letter::Entity::find();
+    if let Some(task_name) = task_name {
+        query = query.filter(ColumnTrait::eq(&task_dead_letter::Column::TaskName, task_name));
+    }
+    if let Some(status) = status {
+        query = query.filter(ColumnTrait::eq(&task_dead_letter::Column::Status, status));
+    }
+    Ok(query.order_by_desc(task_dead_letter::Column::Id).all(conn).await?)
+}
+
+/// 重新写入一条死信中的数据, 成功后状态改为 retried; 失败时更新错误信息并返回错误, 死信保持 pending
+pub async fn retry_dead_letter(id: i64, conn: &DatabaseConnection) -> anyhow::Result<task_dead_letter::Model> {
+    let letter = task_dead_letter::Entity::find_by_id(id).one(conn).await?.ok_or_else(|| anyhow!("dead letter {} not found", id))?;
+    if letter.status == DEAD_LETTER_RETRIED {
+        bail!("dead letter {} has already been retried", id);
+    }
+    let result = match letter.target_table.as_str() {
+        // 接入死信的任务写入的表, 新任务接入时在这里登记
+        "stock_daily" => retry_payload::<stock_daily::Entity>(&letter.payload, &[stock_daily::Column::TsCode, stock_daily::Column::TradeDate], conn).await,
+        table => Err(anyhow!("retry of table {} is not supported", table)),
+    };
+
+    let retry_count = letter.retry_count + 1;
+    let mut active = letter.into_active_model();
+    active.retry_count = ActiveValue::Set(retry_count);
+    active.retried_at = ActiveValue::Set(Some(Local::now().format("%Y-%m-%d %H:%M:%S").to_string()));
+    match result {
+        Ok(()) => {
+            active.status = ActiveValue::Set(DEAD_LETTER_RETRIED.to_string());
+            Ok(active.update(conn).await?)
+        }
+        Err(e) => {
+            active.status = ActiveValue::Set(DEAD_LETTER_PENDING.to_string());
+            active.error = ActiveValue::Set(truncate_error(&e));
+            active.update(conn).await?;
+            Err(e.context(format!("retry dead letter {} failed", id)))
+        }
+    }
+}
+
+async fn retry_payload<E>(payload: &str, pks: &[E::Column], conn: &DatabaseConnection) -> anyhow::Result<()>
+where
+    E: EntityTrait,
+    E::Model: DeserializeOwned + IntoActiveModel<E::ActiveModel>,
+    E::ActiveModel: ActiveModelTrait<Entity = E> + Send,
+    E::Column: ColumnTrait + Clone + PartialEq + Debug,
+{
+    let model: E::Model = serde_json::from_str(payload)?;
+    upsert_one::<E, _>(conn, model, pks).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use rust_decimal::Decimal;
+
+    fn letter(id: i64, target_table: &str, payload: String) -> task_dead_letter::Model {
+        task_dead_letter::Model {
+            id,
+            task_name: "FetchStockDailyTask".to_string(),
+            target_table: target_table.to_string(),
+            payload,
+            error: "Data too long for column".to_string(),
+            status: DEAD_LETTER_PENDING.to_string(),
+            retry_count: 0,
+            created_at: "2024-01-05 23:05:00".to_string(),
+            retried_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_retry_dead_letter() {
+        let conn = test_util::memory_db().await;
+        test_util::create_table(&conn, stock_daily::Entity).await;
+        let daily = stock_daily::Model {
+            vol: Decimal::ONE,
+            amount: Decimal::ONE,
+            ..test_util::daily("000001.SZ", "20240105", 10)
+        };
+        let letters = vec![
+            letter(1, "stock_daily", serde_json::to_string(&daily).unwrap()),
+            letter(2, "stock_daily", "{\"ts_code\": \"000002.SZ\"}".to_string()),
+        ];
+        test_util::seed(&conn, task_dead_letter::Entity, letters).await;
+
+        assert_eq!(list_dead_letters(Some("FetchStockDailyTask"), Some(DEAD_LETTER_PENDING), &conn).await.unwrap().len(), 2);
+
+        let retried = retry_dead_letter(1, &conn).await.unwrap();
+        assert_eq!(retried.status, DEAD_LETTER_RETRIED);
+        assert_eq!(retried.retry_count, 1);
+        let written = stock_daily::Entity::find().all(&conn).await.unwrap();
+        assert_eq!(written, vec![daily]);
+        assert!(retry_dead_letter(1, &conn).await.is_err());
+
+        // 数据仍然不合法, 保持 pending 并记录新的错误
+        assert!(retry_dead_letter(2, &conn).await.is_err());
+        let pending = list_dead_letters(None, Some(DEAD_LETTER_PENDING), &conn).await.unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].retry_count, 1);
+        assert!(pending[0].error.contains("missing field"));
+    }
+}
@@ -101,6 +101,8 @@ pub enum IndicatorType {
     Rsi,
     /// KDJ指标
     Kdj,
+    /// 主力资金流向
+    MoneyFlow,
 }
 
 /// 指标详细数据
@@ -171,4 +173,64 @@ pub enum IndicatorDetails {
         /// KDJ信号
         kdj_signal: String,
     },
+    /// 主力资金流向分析详情
+    MoneyFlow {
+        /// 统计天数
+        days: usize,
+        /// 主力(大单+特大单)累计净流入, 单位: 万元
+        net_main_inflow: f64,
+        /// 主力净流入的天数
+        inflow_days: usize,
+        /// 资金流向信号
+        flow_signal: String,
+        /// 是否为缺少资金流向数据时按成交额估算的结果
+        #[serde(default)]
+        estimated: bool,
+    },
+}
+
+/// 带原始指标序列的诊股结果, 供前端绘图使用
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetailedDiagnosis {
+    /// 诊股结果, 序列化时与 `DiagnosisResult` 字段平铺
+    #[serde(flatten)]
+    pub diagnosis: DiagnosisResult,
+    /// 诊断所用的指标序列
+    pub series: IndicatorSeries,
+}
+
+/// 指标序列
+///
+/// 所有序列与 `trade_dates` 等长且按日期对齐, 指标尚未形成的日期为 `None`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct IndicatorSeries {
+    /// 交易日期 (YYYYMMDD)
+    pub trade_dates: Vec<String>,
+    /// 收盘价
+    pub close: Vec<f64>,
+    /// 收盘价均线
+    pub ma: Vec<MaSeries>,
+    /// MACD线
+    pub macd_line: Vec<Option<f64>>,
+    /// MACD信号线
+    pub macd_signal: Vec<Option<f64>>,
+    /// MACD柱状图
+    pub macd_histogram: Vec<Option<f64>>,
+    /// RSI
+    pub rsi: Vec<Option<f64>>,
+    /// KDJ K值
+    pub kdj_k: Vec<Option<f64>>,
+    /// KDJ D值
+    pub kdj_d: Vec<Option<f64>>,
+    /// KDJ J值
+    pub kdj_j: Vec<Option<f64>>,
+}
+
+/// 单条均线序列
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaSeries {
+    /// 均线周期
+    pub period: usize,
+    /// 均线值
+    pub values: Vec<Option<f64>>,
 }
@@ -9,5 +9,5 @@ pub mod stock_data_service;
 pub mod stock_diagnosis_service;
 
 pub use stock_diagnosis::StockDiagnosis;
-pub use diagnosis_result::{DiagnosisResult, DiagnosisLevel, IndicatorAnalysis, IndicatorDetails};
-pub use stock_diagnosis_service::diagnosis;
+pub use diagnosis_result::{DiagnosisResult, DetailedDiagnosis, DiagnosisLevel, IndicatorAnalysis, IndicatorDetails, IndicatorSeries, MaSeries};
+pub use stock_diagnosis_service::{diagnosis, diagnosis_detailed};
@@ -1,10 +1,13 @@
 //! 股票诊断核心模块
 
 use crate::strategy::traits::SecurityData;
-use super::diagnosis_result::{DiagnosisResult, DiagnosisLevel, IndicatorAnalysis, IndicatorType, IndicatorDetails};
+use super::diagnosis_result::{DiagnosisResult, DetailedDiagnosis, DiagnosisLevel, IndicatorAnalysis, IndicatorType, IndicatorDetails, IndicatorSeries, MaSeries};
 use super::technical_indicators::TechnicalIndicators;
+use crate::analysis::estimated_daily_inflow;
 use anyhow::Result;
 use chrono::NaiveDate;
+use entity::moneyflow;
+use rust_decimal::prelude::ToPrimitive;
 
 /// 股票诊断器
 pub struct StockDiagnosis {
@@ -22,6 +25,10 @@ pub struct StockDiagnosis {
     pub volume_ma_period: usize,
     /// 换手率分析周期
     pub turnover_period: usize,
+    /// 主力资金流向统计天数
+    pub moneyflow_period: usize,
+    /// 指标序列中输出的均线周期
+    pub ma_periods: Vec<usize>,
 }
 
 impl Default for StockDiagnosis {
@@ -36,6 +43,8 @@ impl Default for StockDiagnosis {
             kdj_d_period: 3,
             volume_ma_period: 20,
             turnover_period: 20,
+            moneyflow_period: 5,
+            ma_periods: vec![5, 10, 20],
         }
     }
 }
@@ -48,6 +57,13 @@ impl StockDiagnosis {
 
     /// 诊断股票
     pub fn diagnose(&self, data: &[SecurityData]) -> Result<DiagnosisResult> {
+        self.diagnose_with_moneyflow(data, &[])
+    }
+
+    /// 诊断股票, 并把主力资金流向作为一个维度计入综合评分
+    ///
+    /// `moneyflow` 按日期正序, 为空时按成交额估算资金流向, 成交额也缺失时不计入该维度
+    pub fn diagnose_with_moneyflow(&self, data: &[SecurityData], moneyflow: &[moneyflow::Model]) -> Result<DiagnosisResult> {
         if data.is_empty() {
             return Err(anyhow::anyhow!("数据为空"));
         }
@@ -104,6 +120,13 @@ impl StockDiagnosis {
             indicators.push(kdj_analysis);
         }
 
+        // 主力资金流向分析
+        if let Ok(moneyflow_analysis) = self.analyze_moneyflow(moneyflow).or_else(|_| self.analyze_estimated_inflow(data)) {
+            total_score += moneyflow_analysis.score as u32;
+            valid_indicators += 1;
+            indicators.push(moneyflow_analysis);
+        }
+
         if valid_indicators == 0 {
             return Err(anyhow::anyhow!("无法计算任何技术指标"));
         }
@@ -128,6 +151,52 @@ impl StockDiagnosis {
         })
     }
 
+    /// 诊断股票, 同时返回诊断所用的原始指标序列
+    pub fn diagnose_detailed(&self, data: &[SecurityData], moneyflow: &[moneyflow::Model]) -> Result<DetailedDiagnosis> {
+        let diagnosis = self.diagnose_with_moneyflow(data, moneyflow)?;
+        let series = self.indicator_series(data);
+        Ok(DetailedDiagnosis { diagnosis, series })
+    }
+
+    /// 计算MA/MACD/RSI/KDJ序列, 数据不足的指标输出全 `None`
+    pub fn indicator_series(&self, data: &[SecurityData]) -> IndicatorSeries {
+        let len = data.len();
+        let prices: Vec<f64> = data.iter().map(|d| d.close).collect();
+
+        let ma = self.ma_periods
+            .iter()
+            .map(|&period| MaSeries {
+                period,
+                values: align_to_dates(TechnicalIndicators::sma(&prices, period).ok(), len),
+            })
+            .collect();
+
+        let macd = TechnicalIndicators::macd(&prices, self.macd_fast_period, self.macd_slow_period, self.macd_signal_period).ok();
+        let (macd_line, macd_signal, macd_histogram) = match macd {
+            Some((line, signal, histogram)) => (Some(line), Some(signal), Some(histogram)),
+            None => (None, None, None),
+        };
+
+        let kdj = TechnicalIndicators::kdj(data, self.kdj_period, self.kdj_k_period, self.kdj_d_period).ok();
+        let (kdj_k, kdj_d, kdj_j) = match kdj {
+            Some((k, d, j)) => (Some(k), Some(d), Some(j)),
+            None => (None, None, None),
+        };
+
+        IndicatorSeries {
+            trade_dates: data.iter().map(|d| d.trade_date.clone()).collect(),
+            close: prices.clone(),
+            ma,
+            macd_line: align_to_dates(macd_line, len),
+            macd_signal: align_to_dates(macd_signal, len),
+            macd_histogram: align_to_dates(macd_histogram, len),
+            rsi: align_to_dates(TechnicalIndicators::rsi(&prices, self.rsi_period).ok(), len),
+            kdj_k: align_to_dates(kdj_k, len),
+            kdj_d: align_to_dates(kdj_d, len),
+            kdj_j: align_to_dates(kdj_j, len),
+        }
+    }
+
     /// 分析成交量
     fn analyze_volume(&self, data: &[SecurityData]) -> Result<IndicatorAnalysis> {
         if data.len() < self.volume_ma_period {
@@ -380,6 +449,73 @@ impl StockDiagnosis {
         })
     }
 
+    /// 分析主力资金流向, 持续净流入看多, 持续净流出看空
+    fn analyze_moneyflow(&self, moneyflow: &[moneyflow::Model]) -> Result<IndicatorAnalysis> {
+        let recent = &moneyflow[moneyflow.len().saturating_sub(self.moneyflow_period)..];
+        let net_inflows: Vec<f64> = recent.iter().filter_map(main_net_inflow).collect();
+        if net_inflows.is_empty() {
+            return Err(anyhow::anyhow!("缺少资金流向数据"));
+        }
+        Ok(self.moneyflow_analysis(&net_inflows, false))
+    }
+
+    /// 缺少资金流向数据时, 用成交额和涨跌幅估算主力资金流向, 结果标记为估算
+    fn analyze_estimated_inflow(&self, data: &[SecurityData]) -> Result<IndicatorAnalysis> {
+        let recent = &data[data.len().saturating_sub(self.moneyflow_period)..];
+        let net_inflows: Vec<f64> = recent
+            .iter()
+            .filter(|d| d.amount > 0.0)
+            .filter_map(|d| d.pct_change.map(|pct_chg| estimated_daily_inflow(pct_chg, d.amount)))
+            .collect();
+        if net_inflows.is_empty() {
+            return Err(anyhow::anyhow!("缺少成交额数据"));
+        }
+        Ok(self.moneyflow_analysis(&net_inflows, true))
+    }
+
+    /// 按每日净流入(万元)评估资金面
+    fn moneyflow_analysis(&self, net_inflows: &[f64], estimated: bool) -> IndicatorAnalysis {
+        let days = net_inflows.len();
+        let net_main_inflow: f64 = net_inflows.iter().sum();
+        let inflow_days = net_inflows.iter().filter(|v| **v > 0.0).count();
+        let outflow_days = net_inflows.iter().filter(|v| **v < 0.0).count();
+        // 持续: 至少 80% 的天数同向
+        let sustained = |n: usize| n * 5 >= days * 4;
+
+        let (score, level, description, flow_signal) = if net_main_inflow > 0.0 && sustained(inflow_days) {
+            (85, DiagnosisLevel::StrongBullish, "主力资金持续净流入，机构积极建仓", "持续流入")
+        } else if net_main_inflow > 0.0 && inflow_days * 2 >= days {
+            (65, DiagnosisLevel::Bullish, "主力资金整体净流入，资金面偏多", "净流入")
+        } else if net_main_inflow < 0.0 && sustained(outflow_days) {
+            (20, DiagnosisLevel::StrongBearish, "主力资金持续净流出，机构持续减仓", "持续流出")
+        } else if net_main_inflow < 0.0 && outflow_days * 2 >= days {
+            (35, DiagnosisLevel::Bearish, "主力资金整体净流出，资金面偏空", "净流出")
+        } else {
+            (50, DiagnosisLevel::Neutral, "主力资金进出分歧，方向不明", "分歧")
+        };
+
+        let (indicator_name, description) = if estimated {
+            ("主力资金(估算)".to_string(), format!("按成交额估算：{}", description))
+        } else {
+            ("主力资金".to_string(), description.to_string())
+        };
+        IndicatorAnalysis {
+            indicator_name,
+            indicator_type: IndicatorType::MoneyFlow,
+            current_value: Some(net_main_inflow),
+            score,
+            level,
+            description,
+            details: IndicatorDetails::MoneyFlow {
+                days,
+                net_main_inflow,
+                inflow_days,
+                flow_signal: flow_signal.to_string(),
+                estimated,
+            },
+        }
+    }
+
     /// 生成综合描述
     fn generate_overall_description(&self, level: &DiagnosisLevel, score: u8, indicators: &[IndicatorAnalysis]) -> String {
         let level_desc = level.description();
@@ -474,3 +610,118 @@ impl StockDiagnosis {
         }
     }
 }
+
+/// 把指标序列右对齐到交易日期上, 最后一个值对应最新交易日, 前面不足的部分补 `None`
+pub(crate) fn align_to_dates(values: Option<Vec<f64>>, len: usize) -> Vec<Option<f64>> {
+    let values = values.unwrap_or_default();
+    let values = &values[values.len().saturating_sub(len)..];
+    let mut aligned = vec![None; len - values.len()];
+    aligned.extend(values.iter().copied().map(Some));
+    aligned
+}
+
+/// 当日主力(大单+特大单)净流入, 单位: 万元
+fn main_net_inflow(flow: &moneyflow::Model) -> Option<f64> {
+    let amount = |v: &Option<rust_decimal::Decimal>| v.as_ref().and_then(|v| v.to_f64());
+    let buy = amount(&flow.buy_lg_amount)? + amount(&flow.buy_elg_amount)?;
+    let sell = amount(&flow.sell_lg_amount)? + amount(&flow.sell_elg_amount)?;
+    Some(buy - sell)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+
+    fn security_data(days: usize) -> Vec<SecurityData> {
+        (0..days)
+            .map(|i| SecurityData {
+                symbol: "600000.SH".to_string(),
+                trade_date: format!("202401{:02}", i + 1),
+                open: 10.0,
+                high: 10.5,
+                low: 9.5,
+                close: 10.0,
+                pct_change: Some(0.0),
+                volume: 1000.0,
+                turnover_rate: Some(2.0),
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn moneyflow(trade_date: &str, buy: i64, sell: i64) -> moneyflow::Model {
+        moneyflow::Model {
+            ts_code: "600000.SH".to_string(),
+            trade_date: trade_date.to_string(),
+            buy_sm_vol: None,
+            buy_sm_amount: None,
+            sell_sm_vol: None,
+            sell_sm_amount: None,
+            buy_md_vol: None,
+            buy_md_amount: None,
+            sell_md_vol: None,
+            sell_md_amount: None,
+            buy_lg_vol: None,
+            buy_lg_amount: Some(Decimal::from(buy)),
+            sell_lg_vol: None,
+            sell_lg_amount: Some(Decimal::from(sell)),
+            buy_elg_vol: None,
+            buy_elg_amount: Some(Decimal::ZERO),
+            sell_elg_vol: None,
+            sell_elg_amount: Some(Decimal::ZERO),
+            net_mf_vol: None,
+            net_mf_amount: None,
+        }
+    }
+
+    #[test]
+    fn test_moneyflow_inflow_raises_score() {
+        let data = security_data(30);
+        let diagnosis = StockDiagnosis::new();
+        let base = diagnosis.diagnose(&data).unwrap();
+        assert!(base.indicators.iter().all(|i| i.indicator_type != IndicatorType::MoneyFlow));
+
+        let flows: Vec<moneyflow::Model> = (20..30).map(|day| moneyflow(&format!("202401{:02}", day), 5000, 1000)).collect();
+        let result = diagnosis.diagnose_with_moneyflow(&data, &flows).unwrap();
+        let analysis = result.indicators.iter().find(|i| i.indicator_type == IndicatorType::MoneyFlow).unwrap();
+        assert_eq!(analysis.level, DiagnosisLevel::StrongBullish);
+        assert_eq!(analysis.current_value, Some(20000.0)); // 最近 5 天, 每天净流入 4000 万
+        assert!(result.overall_score > base.overall_score);
+    }
+
+    #[test]
+    fn test_estimated_inflow_fallback() {
+        let mut data = security_data(30);
+        for d in data.iter_mut() {
+            d.pct_change = Some(1.0);
+            d.amount = 10_000.0;
+        }
+        let result = StockDiagnosis::new().diagnose_with_moneyflow(&data, &[]).unwrap();
+        let analysis = result.indicators.iter().find(|i| i.indicator_type == IndicatorType::MoneyFlow).unwrap();
+        assert_eq!(analysis.indicator_name, "主力资金(估算)");
+        assert_eq!(analysis.level, DiagnosisLevel::StrongBullish);
+        assert_eq!(analysis.current_value, Some(5000.0)); // 最近 5 天, 每天成交 1000 万
+        assert!(matches!(analysis.details, IndicatorDetails::MoneyFlow { estimated: true, .. }));
+    }
+
+    #[test]
+    fn test_diagnose_detailed_includes_series() {
+        let data = security_data(30);
+        let detailed = StockDiagnosis::new().diagnose_detailed(&data, &[]).unwrap();
+
+        let series = &detailed.series;
+        assert_eq!(series.trade_dates.len(), 30);
+        assert_eq!(series.ma.len(), 3);
+        for values in series.ma.iter().map(|ma| &ma.values)
+            .chain([&series.macd_line, &series.macd_signal, &series.macd_histogram, &series.rsi, &series.kdj_k, &series.kdj_d, &series.kdj_j])
+        {
+            assert_eq!(values.len(), 30);
+            assert!(values.last().unwrap().is_some());
+        }
+        // MA5 前 4 天尚未形成
+        assert_eq!(series.ma[0].values[3], None);
+        assert_eq!(series.ma[0].values[4], Some(10.0));
+        assert_eq!(detailed.diagnosis.stock_code, "600000.SH");
+    }
+}
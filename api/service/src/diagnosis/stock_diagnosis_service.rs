@@ -1,19 +1,73 @@
-use crate::diagnosis::{DiagnosisResult, StockDiagnosis};
+use crate::analysis::{cached_analysis, AnalysisKind};
+use crate::diagnosis::{DetailedDiagnosis, DiagnosisResult, StockDiagnosis};
 use crate::strategy::traits::SecurityData;
 use anyhow::{anyhow, Result};
 use chrono::{Local, NaiveDate, Duration};
-use entity::{stock_daily, stock_daily_basic};
+use entity::{moneyflow, stock_daily, stock_daily_basic};
 use entity::sea_orm::{DatabaseConnection, EntityTrait, ColumnTrait, QueryFilter, QueryOrder};
 
 /// 获取股票诊断结果
 /// 
 /// # 参数
 /// * `tscode` - 股票代码
+/// * `moneyflow_period` - 主力资金流向统计天数, None 时使用默认值
 /// * `conn` - 数据库连接
 /// 
 /// # 返回
-/// 返回诊断结果或错误
-pub async fn diagnosis(tscode: &str, conn: &DatabaseConnection) -> Result<DiagnosisResult> {
+/// 返回诊断结果或错误, 使用默认参数时同一交易日内的结果会被缓存
+pub async fn diagnosis(tscode: &str, moneyflow_period: Option<usize>, conn: &DatabaseConnection) -> Result<DiagnosisResult> {
+    let diagnosis = diagnoser(moneyflow_period);
+    if !is_default(&diagnosis) {
+        return compute_diagnosis(tscode, &diagnosis, conn).await;
+    }
+    cached_analysis(tscode, AnalysisKind::Diagnosis, conn, || compute_diagnosis(tscode, &diagnosis, conn)).await
+}
+
+async fn compute_diagnosis(tscode: &str, diagnosis: &StockDiagnosis, conn: &DatabaseConnection) -> Result<DiagnosisResult> {
+    let (security_data, moneyflow) = load_diagnosis_data(tscode, conn).await?;
+
+    // 执行诊断
+    let result = diagnosis.diagnose_with_moneyflow(&security_data, &moneyflow)?;
+    
+    Ok(result)
+}
+
+/// 获取股票诊断结果, 同时返回诊断所用的 MA/MACD/RSI/KDJ 序列
+/// 
+/// # 参数
+/// * `tscode` - 股票代码
+/// * `moneyflow_period` - 主力资金流向统计天数, None 时使用默认值
+/// * `conn` - 数据库连接
+/// 
+/// # 返回
+/// 返回带指标序列的诊断结果或错误, 使用默认参数时同一交易日内的结果会被缓存
+pub async fn diagnosis_detailed(tscode: &str, moneyflow_period: Option<usize>, conn: &DatabaseConnection) -> Result<DetailedDiagnosis> {
+    let diagnosis = diagnoser(moneyflow_period);
+    if !is_default(&diagnosis) {
+        return compute_diagnosis_detailed(tscode, &diagnosis, conn).await;
+    }
+    cached_analysis(tscode, AnalysisKind::DiagnosisDetailed, conn, || compute_diagnosis_detailed(tscode, &diagnosis, conn)).await
+}
+
+async fn compute_diagnosis_detailed(tscode: &str, diagnosis: &StockDiagnosis, conn: &DatabaseConnection) -> Result<DetailedDiagnosis> {
+    let (security_data, moneyflow) = load_diagnosis_data(tscode, conn).await?;
+
+    diagnosis.diagnose_detailed(&security_data, &moneyflow)
+}
+
+/// 按请求参数创建诊断器, 未指定的参数使用默认值
+fn diagnoser(moneyflow_period: Option<usize>) -> StockDiagnosis {
+    let default = StockDiagnosis::new();
+    StockDiagnosis { moneyflow_period: moneyflow_period.unwrap_or(default.moneyflow_period), ..default }
+}
+
+/// 缓存中只保存默认参数的诊断结果
+fn is_default(diagnosis: &StockDiagnosis) -> bool {
+    diagnosis.moneyflow_period == StockDiagnosis::new().moneyflow_period
+}
+
+/// 加载最近90天的诊断数据: 行情数据和资金流向数据
+async fn load_diagnosis_data(tscode: &str, conn: &DatabaseConnection) -> Result<(Vec<SecurityData>, Vec<moneyflow::Model>)> {
     // 计算90天前的日期
     let end_date = Local::now().date_naive();
     let start_date = end_date - Duration::days(90);
@@ -35,11 +89,16 @@ pub async fn diagnosis(tscode: &str, conn: &DatabaseConnection) -> Result<Diagno
         return Err(anyhow!("无法构建股票 {} 的分析数据", tscode));
     }
     
-    // 执行诊断
-    let diagnosis = StockDiagnosis::new();
-    let result = diagnosis.diagnose(&security_data)?;
-    
-    Ok(result)
+    // 获取资金流向数据, 没有数据时诊断不计入资金维度
+    let moneyflow = moneyflow::Entity::find()
+        .filter(ColumnTrait::eq(&moneyflow::Column::TsCode, tscode))
+        .filter(moneyflow::Column::TradeDate.gte(start_date.format("%Y%m%d").to_string()))
+        .filter(moneyflow::Column::TradeDate.lte(end_date.format("%Y%m%d").to_string()))
+        .order_by_asc(moneyflow::Column::TradeDate)
+        .all(conn)
+        .await?;
+
+    Ok((security_data, moneyflow))
 }
 
 /// 获取股票日线数据和基本面数据（参考 stock_picker_service 的实现）
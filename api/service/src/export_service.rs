@@ -0,0 +1,223 @@
+use std::fs::File;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use arrow_array::{ArrayRef, Float64Array, RecordBatch, StringArray};
+use arrow_schema::{DataType, Field, Schema, SchemaRef};
+use chrono::NaiveDate;
+use futures::TryStreamExt;
+use num_traits::ToPrimitive;
+use parquet::arrow::ArrowWriter;
+use parquet::file::properties::WriterProperties;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use entity::sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+use entity::stock_daily;
+
+/// Parquet 每个 row group 的行数, 写满一组后落盘, 内存中最多缓存这么多行
+const PARQUET_ROW_GROUP_SIZE: usize = 8192;
+
+/// 导出文件格式
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Csv,
+    Parquet,
+}
+
+impl ExportFormat {
+    /// 文件扩展名
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Parquet => "parquet",
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(Self::Csv),
+            "parquet" => Ok(Self::Parquet),
+            _ => Err(anyhow!("Unknown export format: {}", s)),
+        }
+    }
+}
+
+/// 把股票日线按日期正序导出到 `path`, 返回导出的行数
+///
+/// 从数据库流式读取并写入文件, 不会把整段历史加载到内存; `start`/`end` 为空时不限制该端。
+/// CSV 的列与 `stock_daily` 表一致; Parquet 的列名相同, 价格、成交量等数值列保存为 f64
+pub async fn export_history(
+    ts_code: &str,
+    format: ExportFormat,
+    path: &Path,
+    start: Option<&NaiveDate>,
+    end: Option<&NaiveDate>,
+    conn: &DatabaseConnection,
+) -> anyhow::Result<usize> {
+    let mut query = stock_daily::Entity::find().filter(ColumnTrait::eq(&stock_daily::Column::TsCode, ts_code));
+    if let Some(start) = start {
+        query = query.filter(stock_daily::Column::TradeDate.gte(start.format(common::date::FORMAT).to_string()));
+    }
+    if let Some(end) = end {
+        query = query.filter(stock_daily::Column::TradeDate.lte(end.format(common::date::FORMAT).to_string()));
+    }
+    let mut rows = query.order_by_asc(stock_daily::Column::TradeDate).stream(conn).await?;
+
+    let file = File::create(path).map_err(|e| anyhow!("create {} failed: {}", path.display(), e))?;
+    let mut writer = HistoryWriter::new(format, file)?;
+    let mut count = 0;
+    while let Some(row) = rows.try_next().await? {
+        writer.write(row)?;
+        count += 1;
+    }
+    writer.finish()?;
+    Ok(count)
+}
+
+enum HistoryWriter {
+    Csv(csv::Writer<File>),
+    Parquet { writer: ArrowWriter<File>, rows: Vec<stock_daily::Model> },
+}
+
+impl HistoryWriter {
+    fn new(format: ExportFormat, file: File) -> anyhow::Result<Self> {
+        let writer = match format {
+            ExportFormat::Csv => Self::Csv(csv::Writer::from_writer(file)),
+            ExportFormat::Parquet => {
+                let props = WriterProperties::builder().set_max_row_group_size(PARQUET_ROW_GROUP_SIZE).build();
+                let writer = ArrowWriter::try_new(file, parquet_schema(), Some(props))?;
+                Self::Parquet { writer, rows: Vec::with_capacity(PARQUET_ROW_GROUP_SIZE) }
+            }
+        };
+        Ok(writer)
+    }
+
+    fn write(&mut self, row: stock_daily::Model) -> anyhow::Result<()> {
+        match self {
+            Self::Csv(writer) => writer.serialize(&row)?,
+            Self::Parquet { writer, rows } => {
+                rows.push(row);
+                if rows.len() >= PARQUET_ROW_GROUP_SIZE {
+                    writer.write(&to_record_batch(rows)?)?;
+                    rows.clear();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> anyhow::Result<()> {
+        match self {
+            Self::Csv(mut writer) => writer.flush()?,
+            Self::Parquet { mut writer, rows } => {
+                if !rows.is_empty() {
+                    writer.write(&to_record_batch(&rows)?)?;
+                }
+                writer.close()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parquet 中的数值列: 列名和取值
+type NumericColumn = (&'static str, fn(&stock_daily::Model) -> Option<Decimal>);
+
+const NUMERIC_COLUMNS: [NumericColumn; 9] = [
+    ("open", |d| Some(d.open)),
+    ("high", |d| Some(d.high)),
+    ("low", |d| Some(d.low)),
+    ("close", |d| Some(d.close)),
+    ("pre_close", |d| d.pre_close),
+    ("change", |d| d.change),
+    ("pct_chg", |d| d.pct_chg),
+    ("vol", |d| Some(d.vol)),
+    ("amount", |d| Some(d.amount)),
+];
+
+fn parquet_schema() -> SchemaRef {
+    let mut fields = vec![Field::new("ts_code", DataType::Utf8, false), Field::new("trade_date", DataType::Utf8, false)];
+    fields.extend(NUMERIC_COLUMNS.iter().map(|(name, _)| Field::new(*name, DataType::Float64, true)));
+    Arc::new(Schema::new(fields))
+}
+
+fn to_record_batch(rows: &[stock_daily::Model]) -> anyhow::Result<RecordBatch> {
+    let mut columns: Vec<ArrayRef> = vec![
+        Arc::new(StringArray::from_iter_values(rows.iter().map(|row| row.ts_code.as_str()))),
+        Arc::new(StringArray::from_iter_values(rows.iter().map(|row| row.trade_date.as_str()))),
+    ];
+    for (_, value) in NUMERIC_COLUMNS {
+        let array = rows.iter().map(|row| value(row).and_then(|v| v.to_f64())).collect::<Float64Array>();
+        columns.push(Arc::new(array));
+    }
+    Ok(RecordBatch::try_new(parquet_schema(), columns)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use parquet::arrow::arrow_reader::ParquetRecordBatchReaderBuilder;
+
+    fn daily(trade_date: &str, close: i64) -> stock_daily::Model {
+        stock_daily::Model {
+            pct_chg: Some(Decimal::new(125, 2)),
+            vol: Decimal::from(1000),
+            amount: Decimal::from(10 * close),
+            ..test_util::daily("600000.SH", trade_date, close)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_csv_round_trip() {
+        let conn = test_util::memory_db().await;
+        let prices = vec![daily("20240104", 12), daily("20240102", 10), daily("20240103", 11), daily("20240105", 13)];
+        test_util::seed(&conn, stock_daily::Entity, prices.clone()).await;
+
+        let path = std::env::temp_dir().join(format!("export_history_{}.csv", std::process::id()));
+        let start = NaiveDate::from_ymd_opt(2024, 1, 3).unwrap();
+        let count = export_history("600000.SH", ExportFormat::Csv, &path, Some(&start), None, &conn).await.unwrap();
+        assert_eq!(count, 3);
+
+        let rows: Vec<stock_daily::Model> = csv::Reader::from_path(&path).unwrap().deserialize().collect::<Result<_, _>>().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(rows, vec![prices[2].clone(), prices[0].clone(), prices[3].clone()]);
+
+        assert!("xlsx".parse::<ExportFormat>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_export_parquet_round_trip() {
+        let conn = test_util::memory_db().await;
+        let prices = vec![daily("20240103", 11), daily("20240102", 10), daily("20240104", 12)];
+        test_util::seed(&conn, stock_daily::Entity, prices).await;
+
+        let path = std::env::temp_dir().join(format!("export_history_{}.parquet", std::process::id()));
+        let end = NaiveDate::from_ymd_opt(2024, 1, 3).unwrap();
+        let count = export_history("600000.SH", ExportFormat::Parquet, &path, None, Some(&end), &conn).await.unwrap();
+        assert_eq!(count, 2);
+
+        let reader = ParquetRecordBatchReaderBuilder::try_new(File::open(&path).unwrap()).unwrap().build().unwrap();
+        let batches = reader.collect::<Result<Vec<_>, _>>().unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(batches.len(), 1);
+        let batch = &batches[0];
+        let column = |name: &str| batch.column_by_name(name).unwrap().clone();
+        let trade_dates = column("trade_date");
+        let trade_dates = trade_dates.as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(trade_dates.iter().flatten().collect::<Vec<_>>(), vec!["20240102", "20240103"]);
+        let close = column("close");
+        assert_eq!(close.as_any().downcast_ref::<Float64Array>().unwrap().values(), &[10f64, 11f64]);
+        let pct_chg = column("pct_chg");
+        assert_eq!(pct_chg.as_any().downcast_ref::<Float64Array>().unwrap().value(0), 1.25f64);
+        assert!(column("change").is_null(0));
+    }
+}
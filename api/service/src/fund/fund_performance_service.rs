@@ -0,0 +1,121 @@
+use anyhow::bail;
+use chrono::{Datelike, Days, Local, Months, NaiveDate};
+use num_traits::ToPrimitive;
+use serde::Serialize;
+
+use common::finance::{max_drawdown, pct_chg};
+use common::stastics::{annualized_volatility, sharpe_ratio};
+use entity::fund_daily;
+use entity::sea_orm::DatabaseConnection;
+
+use super::get_fund_daily;
+
+/// 加载的最长历史, 月
+const HISTORY_MONTHS: u32 = 36;
+/// 多加载的自然日, 保证窗口起点之前有一条数据作为基准
+const HISTORY_MARGIN_DAYS: u64 = 15;
+
+/// 基金基于净值(收盘价)的业绩指标, 均为 x%100
+///
+/// 收益率窗口超出基金历史时为 None; 波动率、最大回撤、夏普按最近至多 3 年的数据计算
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FundPerformance {
+    pub ts_code: String,
+    pub as_of: String, // 最新净值日期
+    pub ytd_return: Option<f64>,
+    pub return_1y: Option<f64>,
+    pub return_3y: Option<f64>,
+    pub annualized_volatility: Option<f64>,
+    pub max_drawdown: Option<f64>, // 为正数, 没有回撤时为 0
+    pub sharpe: Option<f64>,       // 无风险收益率按 0 计算
+}
+
+/// 基金今年以来、近 1 年、近 3 年收益率, 以及年化波动率、最大回撤和夏普比率
+pub async fn fund_performance(fund_code: &str, conn: &DatabaseConnection) -> anyhow::Result<FundPerformance> {
+    let today = Local::now().date_naive();
+    let start = today
+        .checked_sub_months(Months::new(HISTORY_MONTHS))
+        .and_then(|d| d.checked_sub_days(Days::new(HISTORY_MARGIN_DAYS)))
+        .unwrap_or(today);
+    let mut prices = get_fund_daily(fund_code, &start, &today, conn).await?;
+    prices.reverse();
+    let navs = to_navs(&prices);
+    if navs.is_empty() {
+        bail!("fund daily of {} not found", fund_code);
+    }
+    Ok(calc_performance(fund_code, &navs))
+}
+
+fn to_navs(prices: &[fund_daily::Model]) -> Vec<(NaiveDate, f64)> {
+    prices
+        .iter()
+        .filter_map(|p| {
+            let date = NaiveDate::parse_from_str(&p.trade_date, common::date::FORMAT).ok()?;
+            let close = p.close.to_f64().filter(|v| *v > 0f64)?;
+            Some((date, close))
+        })
+        .collect()
+}
+
+/// `navs` 按日期正序且不为空
+fn calc_performance(ts_code: &str, navs: &[(NaiveDate, f64)]) -> FundPerformance {
+    let (as_of, last) = navs[navs.len() - 1];
+    // 窗口起点当天或之前的最后一条净值作为基准, 历史不够时为 None
+    let return_since = |start: Option<NaiveDate>| {
+        let start = start?;
+        let (_, base) = navs.iter().rev().find(|(date, _)| *date <= start)?;
+        Some(pct_chg(*base, last))
+    };
+    let year_begin = NaiveDate::from_ymd_opt(as_of.year(), 1, 1).and_then(|d| d.pred_opt());
+
+    let closes: Vec<f64> = navs.iter().map(|(_, nav)| *nav).collect();
+    let daily_returns: Vec<f64> = closes.windows(2).map(|w| w[1] / w[0] - 1f64).collect();
+    FundPerformance {
+        ts_code: ts_code.to_string(),
+        as_of: as_of.format(common::date::FORMAT).to_string(),
+        ytd_return: return_since(year_begin),
+        return_1y: return_since(as_of.checked_sub_months(Months::new(12))),
+        return_3y: return_since(as_of.checked_sub_months(Months::new(36))),
+        annualized_volatility: annualized_volatility(&daily_returns).map(|v| v * 100f64),
+        max_drawdown: (closes.len() > 1).then(|| max_drawdown(&closes).map(|d| d.drawdown_pct).unwrap_or(0f64)),
+        sharpe: sharpe_ratio(&daily_returns, 0f64),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calc_performance() {
+        let date = |y, m, d| NaiveDate::from_ymd_opt(y, m, d).unwrap();
+        let mut navs = vec![];
+        let mut day = date(2022, 7, 1);
+        while day <= date(2024, 6, 28) {
+            let nav = if day <= date(2023, 6, 28) {
+                1.0
+            } else if day <= date(2023, 12, 31) {
+                1.2
+            } else if day <= date(2024, 3, 31) {
+                0.9
+            } else {
+                1.5
+            };
+            navs.push((day, nav));
+            day = day.succ_opt().unwrap();
+        }
+
+        let performance = calc_performance("000001.OF", &navs);
+        assert_eq!(performance.as_of, "20240628");
+        // 1.2 -> 1.5
+        assert!((performance.ytd_return.unwrap() - 25f64).abs() < 1e-9);
+        // 1.0 -> 1.5
+        assert!((performance.return_1y.unwrap() - 50f64).abs() < 1e-9);
+        // 历史不足 3 年
+        assert_eq!(performance.return_3y, None);
+        // 1.2 -> 0.9
+        assert!((performance.max_drawdown.unwrap() - 25f64).abs() < 1e-9);
+        assert!(performance.annualized_volatility.unwrap() > 0f64);
+        assert!(performance.sharpe.unwrap() > 0f64);
+    }
+}
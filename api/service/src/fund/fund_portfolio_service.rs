@@ -0,0 +1,142 @@
+use std::collections::HashMap;
+
+use anyhow::bail;
+use num_traits::ToPrimitive;
+use serde::Serialize;
+
+use entity::fund_portfolio;
+use entity::sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+
+/// 返回的前 N 大持仓
+const TOP_HOLDINGS: usize = 10;
+
+/// 基金在某个报告期的一只持仓股票
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct FundHolding {
+    pub symbol: String,
+    pub mkv: f64,    // 持有市值, 元
+    pub amount: f64, // 持有数量, 股
+    pub weight: f64, // 占股票市值比, x%100
+}
+
+/// 基金在报告期 `report_date`(YYYYMMDD, 如 20240630) 按持仓占比排序的前 10 大持仓
+///
+/// 同一报告期有多次公告(更正)时取最新公告; 没有该报告期数据时返回错误
+pub async fn fund_top_holdings(fund_code: &str, report_date: &str, conn: &DatabaseConnection) -> anyhow::Result<Vec<FundHolding>> {
+    let portfolio = fund_portfolio::Entity::find()
+        .filter(ColumnTrait::eq(&fund_portfolio::Column::TsCode, fund_code))
+        .filter(ColumnTrait::eq(&fund_portfolio::Column::EndDate, report_date))
+        .all(conn)
+        .await?;
+    if portfolio.is_empty() {
+        bail!("portfolio of fund {} not found for report date {}", fund_code, report_date);
+    }
+    let mut holdings = to_holdings(portfolio);
+    holdings.truncate(TOP_HOLDINGS);
+    Ok(holdings)
+}
+
+/// 两只基金最近一期持仓的重合度, x%100
+///
+/// 对两只基金都持有的股票取两者持仓占比的较小值求和, 完全相同的持仓为 100, 没有共同持仓为 0;
+/// 两只基金的最近报告期可能不同, 任一基金没有持仓数据时返回错误
+pub async fn fund_holdings_overlap(fund_a: &str, fund_b: &str, conn: &DatabaseConnection) -> anyhow::Result<f64> {
+    let a = latest_holdings(fund_a, conn).await?;
+    let b = latest_holdings(fund_b, conn).await?;
+    Ok(holdings_overlap(&a, &b))
+}
+
+async fn latest_holdings(fund_code: &str, conn: &DatabaseConnection) -> anyhow::Result<Vec<FundHolding>> {
+    let latest = fund_portfolio::Entity::find()
+        .filter(ColumnTrait::eq(&fund_portfolio::Column::TsCode, fund_code))
+        .order_by_desc(fund_portfolio::Column::EndDate)
+        .one(conn)
+        .await?;
+    let Some(latest) = latest else {
+        bail!("portfolio of fund {} not found", fund_code);
+    };
+    let portfolio = fund_portfolio::Entity::find()
+        .filter(ColumnTrait::eq(&fund_portfolio::Column::TsCode, fund_code))
+        .filter(ColumnTrait::eq(&fund_portfolio::Column::EndDate, &latest.end_date))
+        .all(conn)
+        .await?;
+    Ok(to_holdings(portfolio))
+}
+
+/// `portfolio` 为同一基金同一报告期的数据, 按占比从高到低排序; 缺少占比时按持有市值计算
+fn to_holdings(portfolio: Vec<fund_portfolio::Model>) -> Vec<FundHolding> {
+    let Some(ann_date) = portfolio.iter().map(|p| p.ann_date.clone()).max() else {
+        return vec![];
+    };
+    let portfolio: Vec<_> = portfolio.into_iter().filter(|p| p.ann_date == ann_date).collect();
+    let total_mkv: f64 = portfolio.iter().filter_map(|p| p.mkv.to_f64()).sum();
+    let mut holdings: Vec<FundHolding> = portfolio
+        .into_iter()
+        .map(|p| {
+            let mkv = p.mkv.to_f64().unwrap_or_default();
+            let weight = p
+                .stk_mkv_ratio
+                .and_then(|v| v.to_f64())
+                .unwrap_or_else(|| if total_mkv > 0f64 { mkv / total_mkv * 100f64 } else { 0f64 });
+            FundHolding { symbol: p.symbol, mkv, amount: p.amount.to_f64().unwrap_or_default(), weight }
+        })
+        .collect();
+    holdings.sort_by(|a, b| b.weight.total_cmp(&a.weight).then_with(|| a.symbol.cmp(&b.symbol)));
+    holdings
+}
+
+fn holdings_overlap(a: &[FundHolding], b: &[FundHolding]) -> f64 {
+    let b: HashMap<&str, f64> = b.iter().map(|h| (h.symbol.as_str(), h.weight)).collect();
+    a.iter()
+        .filter_map(|h| b.get(h.symbol.as_str()).map(|weight| h.weight.min(*weight)))
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use rust_decimal::Decimal;
+
+    fn holding(ts_code: &str, end_date: &str, symbol: &str, ratio: i64) -> fund_portfolio::Model {
+        fund_portfolio::Model {
+            ts_code: ts_code.to_string(),
+            ann_date: "20240720".to_string(),
+            end_date: end_date.to_string(),
+            symbol: symbol.to_string(),
+            mkv: Decimal::from(ratio * 1_000_000),
+            amount: Decimal::from(ratio * 10_000),
+            stk_mkv_ratio: Some(Decimal::from(ratio)),
+            stk_float_ratio: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fund_holdings_overlap() {
+        let conn = test_util::memory_db().await;
+        let portfolio = vec![
+            // 旧报告期不参与
+            holding("000001.OF", "20240331", "000858.SZ", 50),
+            holding("000001.OF", "20240630", "600519.SH", 30),
+            holding("000001.OF", "20240630", "000858.SZ", 20),
+            holding("000001.OF", "20240630", "300750.SZ", 10),
+            holding("000002.OF", "20240630", "600519.SH", 25),
+            holding("000002.OF", "20240630", "300750.SZ", 15),
+            holding("000002.OF", "20240630", "601318.SH", 40),
+        ];
+        test_util::seed(&conn, fund_portfolio::Entity, portfolio).await;
+
+        let top = fund_top_holdings("000001.OF", "20240630", &conn).await.unwrap();
+        let symbols: Vec<&str> = top.iter().map(|h| h.symbol.as_str()).collect();
+        assert_eq!(symbols, vec!["600519.SH", "000858.SZ", "300750.SZ"]);
+        assert_eq!(top[0].weight, 30f64);
+
+        // min(30, 25) + min(10, 15)
+        let overlap = fund_holdings_overlap("000001.OF", "000002.OF", &conn).await.unwrap();
+        assert!((overlap - 35f64).abs() < 1e-9);
+
+        let err = fund_top_holdings("000001.OF", "20231231", &conn).await.unwrap_err();
+        assert!(err.to_string().contains("not found"));
+        assert!(fund_holdings_overlap("000001.OF", "000003.OF", &conn).await.is_err());
+    }
+}
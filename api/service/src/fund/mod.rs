@@ -1,21 +1,24 @@
-use chrono::{Datelike, DateTime, NaiveDate, NaiveDateTime, Utc};
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
 use futures::StreamExt;
 use itertools::Itertools;
 
+use common::calc::PeriodKey;
+use common::data_type::TimePeriod;
+use common::util::date_util;
 use entity::fund_daily;
-use entity::sea_orm::{ColumnTrait, DatabaseConnection};
-use entity::sea_orm::{EntityTrait, QueryFilter, QueryOrder};
+use common::db::find_between;
+use entity::sea_orm::{DatabaseConnection, Order};
+
+pub use fund_performance_service::{fund_performance, FundPerformance};
+pub use fund_portfolio_service::{fund_holdings_overlap, fund_top_holdings, FundHolding};
+
+mod fund_performance_service;
+mod fund_portfolio_service;
 
 pub async fn get_fund_daily(ts_code: &str, start: &NaiveDate, end: &NaiveDate, conn: &DatabaseConnection) -> anyhow::Result<Vec<fund_daily::Model>> {
     let start = start.format("%Y%m%d").to_string();
     let end = end.format("%Y%m%d").to_string();
-    let fund_dailies = fund_daily::Entity::find()
-        .filter(ColumnTrait::eq(&fund_daily::Column::TsCode, ts_code))
-        .filter(fund_daily::Column::TradeDate.gte(&start))
-        .filter(fund_daily::Column::TradeDate.lte(&end))
-        .order_by_desc(fund_daily::Column::TradeDate)
-        .all(conn).await?;
-    Ok(fund_dailies)
+    find_between::<fund_daily::Entity>(conn, fund_daily::Column::TsCode, fund_daily::Column::TradeDate, ts_code, &start, &end, Order::Desc).await
 }
 
 pub async fn get_fund_weekly(ts_code: &str, start: &NaiveDate, end: &NaiveDate, conn: &DatabaseConnection) -> anyhow::Result<Vec<fund_daily::Model>> {
@@ -33,13 +36,14 @@ fn filter_week_end_data(prices: Vec<fund_daily::Model>) ->Vec<fund_daily::Model>
         .into_iter()
         .group_by(|price| {
             let date = parse_datetime(&price.trade_date);
-            (date.year(), date.iso_week().week())
+            PeriodKey::of(&date.date_naive(), TimePeriod::Weekly)
         });
 
     let mut filtered_prices = Vec::new();
     for (_, group) in &grouped_prices {
-        let last_price = group.last().unwrap();
-        filtered_prices.push(last_price.clone());
+        // 周内日期最晚的一条, 与输入的排序方向无关
+        let last_price = group.max_by(|a, b| a.trade_date.cmp(&b.trade_date)).unwrap();
+        filtered_prices.push(last_price);
     }
     filtered_prices
 }
@@ -52,23 +56,9 @@ fn parse_datetime(date: &str) -> DateTime<Utc> {
 }
 
 fn filter_month_end_data(prices: Vec<fund_daily::Model>) -> Vec<fund_daily::Model> {
-    let mut grouped_prices = prices
-        .into_iter()
-        .group_by(|price| {
-            let date = parse_datetime(&price.trade_date);
-            (date.year(), date.month())
-        });
-
-    let mut filtered_prices = Vec::new();
-    for (_, mut group) in &grouped_prices {
-        let last_price = group.next().unwrap();
-        filtered_prices.push(last_price.clone());
-    }
-    // while let Some((_, group)) = grouped_prices.next() {
-    //     let last_price = group.last().unwrap();
-    //     filtered_prices.push(last_price.clone());
-    // }
-
+    let mut filtered_prices = date_util::month_end(prices, |price| &price.trade_date);
+    // 与日线数据保持一致, 按日期倒序
+    filtered_prices.reverse();
     filtered_prices
 }
 
@@ -101,6 +91,18 @@ mod tests {
         // assert_eq!(filtered_data[1].close, dec!(1.1), "第一周收盘价应该是1.1");
     }
 
+    #[test]
+    fn test_filter_week_end_data_across_years() {
+        // 与 get_fund_daily 一致按日期倒序; 20241230 ~ 20250103 同属 2025 年第 1 周
+        let test_data = ["20250103", "20250102", "20241231", "20241230", "20240105", "20240102", "20230106", "20230103"]
+            .into_iter()
+            .map(create_fund_daily_data)
+            .collect();
+
+        let filtered: Vec<String> = filter_week_end_data(test_data).into_iter().map(|d| d.trade_date).collect();
+        assert_eq!(filtered, vec!["20250103", "20240105", "20230106"]);
+    }
+
     fn create_fund_daily_data(date: &str) -> fund_daily::Model {
         fund_daily::Model {
             ts_code: "000001.OF".to_string(),
@@ -1,8 +1,7 @@
 pub mod analysis;
 pub mod stock;
-mod trade_calendar_service;
+pub mod trade_calendar_service;
 pub mod stastic;
-mod stock_daily_service;
 pub mod security;
 pub mod fund;
 pub mod strategy;
@@ -23,3 +22,18 @@ pub mod finance_main_business_service;
 pub mod strategy_profile_service;
 
 pub mod task_scheduler_service;
+
+pub mod scan;
+
+pub mod universe;
+
+pub mod data_quality_service;
+
+pub mod export_service;
+
+pub mod batch_service;
+
+pub mod dead_letter_service;
+
+#[cfg(test)]
+mod test_util;
@@ -1,11 +1,18 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use chrono::NaiveDate;
 use entity::margin;
 use entity::margin_detail;
-use entity::sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder};
+use entity::stock_daily_basic;
+use entity::sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
 use rust_decimal::prelude::ToPrimitive;
+use serde::Serialize;
 use std::collections::BTreeMap;
 
+/// 融券余量占流通股本超过该比例(x%100)视为空头仓位较高
+const SQUEEZE_SHORT_RATIO: f64 = 2.0;
+/// 判断价格是否上涨的交易日数
+const SQUEEZE_PRICE_DAYS: usize = 5;
+
 #[derive(Clone, Debug)]
 pub struct MarginBalancePoint {
     pub date: String,
@@ -68,3 +75,145 @@ pub async fn get_stock_margin_balance(
         })
         .collect())
 }
+
+/// 个股最新一个交易日的融券(空头)情况
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ShortInterest {
+    pub ts_code: String,
+    pub trade_date: String,
+    pub short_balance: Option<f64>,     // 融券余额, 元
+    pub short_volume: Option<f64>,      // 融券余量, 股
+    pub short_ratio: Option<f64>,       // 融券余量占流通股本, x%100
+    pub short_balance_chg: Option<f64>, // 融券余额较上一交易日变化, x%100
+    pub price_chg: Option<f64>,         // 最近 5 个交易日涨跌幅, x%100
+    pub squeeze_risk: bool,             // 空头仓位较高且价格上涨, 有轧空风险
+}
+
+/// 个股最新的融券余额占流通股本比例、较上一交易日的变化, 以及轧空风险
+///
+/// 融券余量占流通股本不低于 2% 且最近 5 个交易日上涨时标记轧空风险;
+/// 流通股本或价格缺失时对应字段为 None, 不标记风险; 没有融资融券数据时返回错误
+pub async fn short_interest(ts_code: &str, conn: &DatabaseConnection) -> Result<ShortInterest> {
+    let margins = margin_detail::Entity::find()
+        .filter(ColumnTrait::eq(&margin_detail::Column::TsCode, ts_code))
+        .order_by_desc(margin_detail::Column::TradeDate)
+        .limit(2)
+        .all(conn)
+        .await?;
+    let Some(latest) = margins.first() else {
+        bail!("margin detail of {} not found", ts_code);
+    };
+    let basics = stock_daily_basic::Entity::find()
+        .filter(ColumnTrait::eq(&stock_daily_basic::Column::TsCode, ts_code))
+        .filter(stock_daily_basic::Column::TradeDate.lte(&latest.trade_date))
+        .order_by_desc(stock_daily_basic::Column::TradeDate)
+        .limit(SQUEEZE_PRICE_DAYS as u64 + 1)
+        .all(conn)
+        .await?;
+    Ok(calc_short_interest(&margins, &basics))
+}
+
+/// `margins` 和 `basics` 均按日期倒序, `margins` 不为空, `basics` 不晚于最新的融资融券日期
+fn calc_short_interest(margins: &[margin_detail::Model], basics: &[stock_daily_basic::Model]) -> ShortInterest {
+    let latest = &margins[0];
+    let short_balance = latest.rqye.and_then(|v| v.to_f64());
+    let short_volume = latest.rqyl.and_then(|v| v.to_f64());
+    // 流通股本单位为万股
+    let float_share = basics.first().and_then(|b| b.float_share).and_then(|v| v.to_f64()).filter(|v| *v > 0f64);
+    let short_ratio = short_volume.zip(float_share).map(|(volume, float)| volume / (float * 10000f64) * 100f64);
+    let short_balance_chg = margins
+        .get(1)
+        .and_then(|prev| prev.rqye)
+        .and_then(|v| v.to_f64())
+        .filter(|v| *v > 0f64)
+        .zip(short_balance)
+        .map(|(prev, curr)| (curr / prev - 1f64) * 100f64);
+
+    let closes: Vec<f64> = basics.iter().filter_map(|b| b.close.and_then(|v| v.to_f64())).collect();
+    let price_chg = (closes.len() > SQUEEZE_PRICE_DAYS && closes[SQUEEZE_PRICE_DAYS] > 0f64)
+        .then(|| (closes[0] / closes[SQUEEZE_PRICE_DAYS] - 1f64) * 100f64);
+    let squeeze_risk = short_ratio.is_some_and(|ratio| ratio >= SQUEEZE_SHORT_RATIO) && price_chg.is_some_and(|chg| chg > 0f64);
+
+    ShortInterest {
+        ts_code: latest.ts_code.clone(),
+        trade_date: latest.trade_date.clone(),
+        short_balance,
+        short_volume,
+        short_ratio,
+        short_balance_chg,
+        price_chg,
+        squeeze_risk,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use rust_decimal::Decimal;
+
+    fn margin(trade_date: &str, rqye: i64, rqyl: i64) -> margin_detail::Model {
+        margin_detail::Model {
+            trade_date: trade_date.to_string(),
+            ts_code: "600000.SH".to_string(),
+            name: None,
+            rzye: None,
+            rqye: Some(Decimal::from(rqye)),
+            rzmre: None,
+            rqyl: Some(Decimal::from(rqyl)),
+            rzche: None,
+            rqchl: None,
+            rqmcl: None,
+            rzrqye: None,
+        }
+    }
+
+    fn basic(trade_date: &str, close: i64) -> stock_daily_basic::Model {
+        stock_daily_basic::Model {
+            ts_code: "600000.SH".to_string(),
+            trade_date: trade_date.to_string(),
+            close: Some(Decimal::from(close)),
+            turnover_rate: None,
+            turnover_rate_f: None,
+            volume_ratio: None,
+            pe: None,
+            pe_ttm: None,
+            pb: None,
+            ps: None,
+            ps_ttm: None,
+            dv_ratio: None,
+            dv_ttm: None,
+            total_share: None,
+            // 1 亿股
+            float_share: Some(Decimal::from(10_000)),
+            free_share: None,
+            total_mv: None,
+            circ_mv: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_short_squeeze_risk() {
+        let conn = test_util::memory_db().await;
+        // 融券余量 300 万股, 占流通股本 3%
+        let margins = vec![margin("20240108", 24_000_000, 2_000_000), margin("20240109", 36_000_000, 3_000_000)];
+        test_util::seed(&conn, margin_detail::Entity, margins).await;
+        let dates = ["20240102", "20240103", "20240104", "20240105", "20240108", "20240109", "20240110"];
+        let basics = dates.iter().enumerate().map(|(i, d)| basic(d, 10 + i as i64)).collect();
+        test_util::seed(&conn, stock_daily_basic::Entity, basics).await;
+
+        let short = short_interest("600000.SH", &conn).await.unwrap();
+        assert_eq!(short.trade_date, "20240109");
+        assert!((short.short_ratio.unwrap() - 3f64).abs() < 1e-9);
+        assert!((short.short_balance_chg.unwrap() - 50f64).abs() < 1e-9);
+        // 不使用融资融券日期之后的价格: 10 -> 15
+        assert!((short.price_chg.unwrap() - 50f64).abs() < 1e-9);
+        assert!(short.squeeze_risk);
+
+        // 缺少流通股本和价格时不标记风险
+        let short = calc_short_interest(&[margin("20240109", 36_000_000, 3_000_000)], &[]);
+        assert_eq!((short.short_ratio, short.short_balance_chg, short.price_chg), (None, None, None));
+        assert!(!short.squeeze_risk);
+        assert!(short_interest("000001.SZ", &conn).await.is_err());
+    }
+}
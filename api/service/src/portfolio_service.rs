@@ -4,14 +4,25 @@ use entity::sea_orm::{
     DatabaseConnection, EntityTrait, ActiveModelTrait, Set, 
     TransactionTrait, QueryFilter, ColumnTrait, QueryOrder, QuerySelect
 };
-use entity::{portfolio, holding, us_stock, stock, stock_daily};
+use entity::{portfolio, holding, holding_trade, portfolio_valuation, us_stock, stock, stock_daily};
 use serde::{Deserialize, Serialize};
-use tracing::{info, error};
+use tracing::{info, error, warn};
 use entity::sea_orm::sea_query::ExprTrait;
 use entity::sea_orm::prelude::Decimal;
-use std::collections::HashMap;
+use entity::sea_orm::sea_query::OnConflict;
+use chrono::NaiveDate;
+use num_traits::ToPrimitive;
+use std::collections::{HashMap, VecDeque};
+use std::str::FromStr;
 
 use crate::pct_chg::PeriodPctChg;
+use crate::stock::quote_cache::QuoteCache;
+use common::data_type::TsCode;
+
+/// A股一手的股数
+const ROUND_LOT: i64 = 100;
+/// 目标权重之和与 1 的允许误差
+const TARGET_WEIGHT_TOLERANCE: f64 = 1e-3;
 
 enum StockDto {
     UsStock(us_stock::Model),
@@ -208,6 +219,24 @@ pub async fn delete_portfolio(
         .context("Failed to fetch portfolio")?
         .ok_or_else(|| anyhow::anyhow!("Portfolio not found: {}", portfolio_id))?;
     
+    let holding_ids: Vec<i32> = holding::Entity::find()
+        .select_only()
+        .column(holding::Column::Id)
+        .filter(holding::Column::PortfolioId.eq(portfolio_id))
+        .into_tuple()
+        .all(&txn)
+        .await
+        .context("Failed to fetch holdings")?;
+    holding_trade::Entity::delete_many()
+        .filter(holding_trade::Column::HoldingId.is_in(holding_ids))
+        .exec(&txn)
+        .await
+        .context("Failed to delete trades")?;
+    portfolio_valuation::Entity::delete_many()
+        .filter(ColumnTrait::eq(&portfolio_valuation::Column::PortfolioId, portfolio_id))
+        .exec(&txn)
+        .await
+        .context("Failed to delete portfolio valuations")?;
     holding::Entity::delete_many()
         .filter(holding::Column::PortfolioId.eq(portfolio_id))
         .exec(&txn)
@@ -318,6 +347,7 @@ pub async fn add_holding(
 
 pub async fn get_holdings(
     conn: &DatabaseConnection,
+    quotes: &QuoteCache,
     portfolio_id: i32,
 ) -> Result<Vec<HoldingResponse>> {
     info!("Getting holdings for portfolio: {}", portfolio_id);
@@ -355,19 +385,11 @@ pub async fn get_holdings(
             .await?;
 
         if let Some(latest_trade_date) = latest_trade_date {
-            let latest_dailies = stock_daily::Entity::find()
-                .filter(ColumnTrait::eq(&stock_daily::Column::TradeDate, latest_trade_date.clone()))
-                .filter(stock_daily::Column::TsCode.is_in(cn_symbols.clone()))
-                .all(conn)
-                .await?;
-
-            for d in latest_dailies {
-                let close_v = d.close.to_string().parse::<f64>().ok();
-                latest_price_map.insert(d.ts_code.clone(), close_v);
-                let v = d
-                    .pct_chg
-                    .and_then(|x| x.to_string().parse::<f64>().ok());
-                latest_pct_map.insert(d.ts_code.clone(), v);
+            // 停牌股票的最新收盘不在全市场最新交易日, 不展示当前价
+            let latest_closes = quotes.latest_closes(&cn_symbols, conn).await?;
+            for latest in latest_closes.into_values().filter(|q| q.trade_date == latest_trade_date) {
+                latest_price_map.insert(latest.ts_code.clone(), Some(latest.close));
+                latest_pct_map.insert(latest.ts_code, latest.pct_chg);
             }
 
             let last_60_dates: Vec<String> = stock_daily::Entity::find()
@@ -497,10 +519,663 @@ pub async fn remove_holding(
         bail!("Holding {} does not belong to portfolio {}", holding_id, portfolio_id);
     }
     
+    let txn = conn.begin().await.context("Failed to start transaction")?;
+    holding_trade::Entity::delete_many()
+        .filter(ColumnTrait::eq(&holding_trade::Column::HoldingId, holding_id))
+        .exec(&txn)
+        .await
+        .context("Failed to delete trades")?;
     let holding_active: holding::ActiveModel = holding.into();
-    holding_active.delete(conn).await
+    holding_active.delete(&txn).await
         .context("Failed to delete holding")?;
+    txn.commit().await.context("Failed to commit transaction")?;
+
+    // 已有估值包含被删除持仓的市值和现金流, 全部重新估值; 失败时由每日估值任务补齐
+    if let Err(e) = revalue_all(conn, portfolio_id).await {
+        error!("Failed to revalue portfolio {} after removing holding {}: {:?}", portfolio_id, holding_id, e);
+    }
     
     info!("Holding {} removed successfully", holding_id);
     Ok(())
 }
+
+/// 买卖方向, 数据库中保存为 `B` / `S`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum TradeSide {
+    Buy,
+    Sell,
+}
+
+impl TradeSide {
+    fn code(&self) -> &'static str {
+        match self {
+            TradeSide::Buy => "B",
+            TradeSide::Sell => "S",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AddTradeRequest {
+    pub side: TradeSide,
+    pub trade_date: String, // yyyyMMdd
+    pub quantity: Decimal,
+    pub price: Decimal,
+}
+
+/// 持仓成本计算方法
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum CostBasisMethod {
+    Fifo,        // 先进先出, 卖出依次冲减最早买入的批次
+    AverageCost, // 移动加权平均成本, 卖出不改变剩余持仓的平均成本
+}
+
+impl FromStr for CostBasisMethod {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "fifo" => Ok(CostBasisMethod::Fifo),
+            "average" | "average_cost" => Ok(CostBasisMethod::AverageCost),
+            _ => bail!("Unknown cost basis method: {}", s),
+        }
+    }
+}
+
+/// 尚未卖出的买入批次
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Lot {
+    pub trade_date: String,
+    pub quantity: Decimal,
+    pub price: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CostBasis {
+    pub holding_id: i32,
+    pub method: CostBasisMethod,
+    pub quantity: Decimal,         // 当前持有数量
+    pub total_cost: Decimal,       // 当前持仓总成本
+    pub avg_cost: Option<Decimal>, // 当前持仓平均成本, 已清仓时为 None
+    pub realized_pnl: Decimal,     // 已实现盈亏
+    pub lots: Vec<Lot>,            // 剩余批次, 仅 FIFO 有意义, 平均成本法为空
+}
+
+/// 记录持仓的一笔买入或卖出, 卖出数量不能超过当前持有数量
+pub async fn add_trade(
+    conn: &DatabaseConnection,
+    portfolio_id: i32,
+    holding_id: i32,
+    req: AddTradeRequest,
+) -> Result<holding_trade::Model> {
+    info!("Adding {:?} trade to holding {} in portfolio {}", req.side, holding_id, portfolio_id);
+
+    if req.quantity <= Decimal::ZERO || req.price < Decimal::ZERO {
+        bail!("Invalid quantity {} or price {}", req.quantity, req.price);
+    }
+    let trade_date = NaiveDate::parse_from_str(&req.trade_date, common::date::FORMAT)
+        .with_context(|| format!("Invalid trade_date: {}", req.trade_date))?;
+
+    find_holding(conn, portfolio_id, holding_id).await?;
+
+    let txn = conn.begin().await.context("Failed to start transaction")?;
+    let trade = holding_trade::ActiveModel {
+        holding_id: Set(holding_id),
+        side: Set(req.side.code().to_string()),
+        trade_date: Set(req.trade_date),
+        quantity: Set(req.quantity),
+        price: Set(req.price),
+        ..Default::default()
+    }
+    .insert(&txn)
+    .await
+    .context("Failed to insert trade")?;
+    // 回放全部成交, 插入的卖出记录早于后续买入或超过持仓时整体回滚
+    let trades = find_trades(&txn, holding_id).await?;
+    compute_cost_basis(holding_id, &trades, CostBasisMethod::Fifo)?;
+    txn.commit().await.context("Failed to commit transaction")?;
+
+    // 估值失败(如缺少行情)不影响已提交的成交, 之后由每日估值任务补齐
+    if let Err(e) = revalue_from(conn, portfolio_id, &trade_date).await {
+        error!("Failed to revalue portfolio {} from {}: {:?}", portfolio_id, trade_date, e);
+    }
+    Ok(trade)
+}
+
+/// 按指定方法计算持仓成本和已实现盈亏, 持仓不存在或不属于该组合时返回错误
+pub async fn cost_basis(conn: &DatabaseConnection, portfolio_id: i32, holding_id: i32, method: CostBasisMethod) -> Result<CostBasis> {
+    find_holding(conn, portfolio_id, holding_id).await?;
+    let trades = find_trades(conn, holding_id).await?;
+    compute_cost_basis(holding_id, &trades, method)
+}
+
+/// 查询组合下的持仓, 持仓不存在或不属于该组合时返回错误
+async fn find_holding(conn: &DatabaseConnection, portfolio_id: i32, holding_id: i32) -> Result<holding::Model> {
+    let holding = holding::Entity::find_by_id(holding_id)
+        .one(conn)
+        .await
+        .context("Failed to fetch holding")?
+        .ok_or_else(|| anyhow!("Holding not found: {}", holding_id))?;
+    if holding.portfolio_id != portfolio_id {
+        bail!("Holding {} does not belong to portfolio {}", holding_id, portfolio_id);
+    }
+    Ok(holding)
+}
+
+async fn find_trades<C: entity::sea_orm::ConnectionTrait>(conn: &C, holding_id: i32) -> Result<Vec<holding_trade::Model>> {
+    holding_trade::Entity::find()
+        .filter(ColumnTrait::eq(&holding_trade::Column::HoldingId, holding_id))
+        .order_by_asc(holding_trade::Column::TradeDate)
+        .order_by_asc(holding_trade::Column::Id)
+        .all(conn)
+        .await
+        .context("Failed to fetch trades")
+}
+
+/// `trades` 按成交日期正序
+fn compute_cost_basis(holding_id: i32, trades: &[holding_trade::Model], method: CostBasisMethod) -> Result<CostBasis> {
+    let mut lots: VecDeque<Lot> = VecDeque::new();
+    let mut quantity = Decimal::ZERO;
+    let mut total_cost = Decimal::ZERO;
+    let mut realized_pnl = Decimal::ZERO;
+
+    for trade in trades {
+        match trade.side.as_str() {
+            "B" => {
+                quantity += trade.quantity;
+                total_cost += trade.quantity * trade.price;
+                lots.push_back(Lot { trade_date: trade.trade_date.clone(), quantity: trade.quantity, price: trade.price });
+            }
+            "S" => {
+                if trade.quantity > quantity {
+                    bail!("Sell {} on {} exceeds position {} of holding {}", trade.quantity, trade.trade_date, quantity, holding_id);
+                }
+                let sold_cost = match method {
+                    CostBasisMethod::Fifo => {
+                        let mut remaining = trade.quantity;
+                        let mut sold_cost = Decimal::ZERO;
+                        while remaining > Decimal::ZERO {
+                            let lot = lots.front_mut().ok_or_else(|| anyhow!("No lot left for holding {}", holding_id))?;
+                            let take = remaining.min(lot.quantity);
+                            sold_cost += take * lot.price;
+                            lot.quantity -= take;
+                            remaining -= take;
+                            if lot.quantity.is_zero() {
+                                lots.pop_front();
+                            }
+                        }
+                        sold_cost
+                    }
+                    CostBasisMethod::AverageCost => total_cost * trade.quantity / quantity,
+                };
+                quantity -= trade.quantity;
+                total_cost -= sold_cost;
+                realized_pnl += trade.quantity * trade.price - sold_cost;
+            }
+            side => bail!("Unknown trade side {} of trade {}", side, trade.id),
+        }
+    }
+
+    let avg_cost = (!quantity.is_zero()).then(|| total_cost / quantity);
+    let lots = match method {
+        CostBasisMethod::Fifo => lots.into_iter().collect(),
+        CostBasisMethod::AverageCost => vec![],
+    };
+    Ok(CostBasis { holding_id, method, quantity, total_cost, avg_cost, realized_pnl, lots })
+}
+
+/// 调仓建议
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct RebalanceAction {
+    pub ts_code: TsCode,
+    pub side: TradeSide,
+    pub quantity: i64, // 买卖股数, 除清仓外为整手
+    pub price: f64,    // 最新收盘价
+    pub current_weight: f64,
+    pub target_weight: f64,
+}
+
+/// 按最新收盘价计算当前权重, 给出调整到目标权重所需的买卖股数(按整手向下取整)
+///
+/// - `targets` A股代码 -> 目标权重, 权重之和需为 1; 不在 `targets` 中的持仓视为目标权重 0, 全部卖出
+/// - 持有数量按持仓的买卖记录计算, 结果先列卖出再列买入
+pub async fn rebalance_plan(
+    conn: &DatabaseConnection,
+    quotes: &QuoteCache,
+    portfolio_id: i32,
+    targets: HashMap<TsCode, f64>,
+) -> Result<Vec<RebalanceAction>> {
+    validate_targets(&targets)?;
+
+    let holdings = holding::Entity::find()
+        .filter(holding::Column::PortfolioId.eq(portfolio_id))
+        .filter(ColumnTrait::eq(&holding::Column::ExchangeId, "cn"))
+        .all(conn)
+        .await
+        .context("Failed to fetch holdings")?;
+    let mut quantities: HashMap<TsCode, f64> = HashMap::new();
+    for h in &holdings {
+        let basis = cost_basis(conn, portfolio_id, h.id, CostBasisMethod::Fifo).await?;
+        let quantity = basis.quantity.to_f64().ok_or_else(|| anyhow!("Invalid quantity of holding {}", h.id))?;
+        *quantities.entry(h.symbol.clone()).or_default() += quantity;
+    }
+    for ts_code in targets.keys() {
+        quantities.entry(ts_code.clone()).or_default();
+    }
+
+    let ts_codes = quantities.keys().cloned().collect::<Vec<_>>();
+    let latest_closes = quotes.latest_closes(&ts_codes, conn).await?;
+    let mut positions = Vec::with_capacity(quantities.len());
+    for (ts_code, quantity) in quantities {
+        let latest = latest_closes.get(&ts_code).ok_or_else(|| anyhow!("No daily price for {}", ts_code))?;
+        let price = Some(latest.close).filter(|v| *v > 0f64).ok_or_else(|| anyhow!("Invalid close price of {}", ts_code))?;
+        positions.push((ts_code, quantity, price));
+    }
+    plan_rebalance(&positions, &targets)
+}
+
+fn validate_targets(targets: &HashMap<TsCode, f64>) -> Result<()> {
+    if let Some((ts_code, weight)) = targets.iter().find(|(_, weight)| !(0f64..=1f64).contains(*weight)) {
+        bail!("Invalid target weight {} of {}", weight, ts_code);
+    }
+    let sum = targets.values().sum::<f64>();
+    if (sum - 1f64).abs() > TARGET_WEIGHT_TOLERANCE {
+        bail!("Target weights must sum to 1, got {}", sum);
+    }
+    Ok(())
+}
+
+/// `positions` 为 (代码, 持有数量, 最新价)
+fn plan_rebalance(positions: &[(TsCode, f64, f64)], targets: &HashMap<TsCode, f64>) -> Result<Vec<RebalanceAction>> {
+    let total = positions.iter().map(|(_, quantity, price)| quantity * price).sum::<f64>();
+    if total <= 0f64 {
+        bail!("Portfolio has no position value to rebalance");
+    }
+    let mut actions = vec![];
+    for (ts_code, quantity, price) in positions {
+        let current_weight = quantity * price / total;
+        let target_weight = targets.get(ts_code).copied().unwrap_or_default();
+        let (side, shares) = if target_weight == 0f64 {
+            (TradeSide::Sell, *quantity as i64)
+        } else {
+            let delta = ((target_weight - current_weight) * total / price) as i64 / ROUND_LOT * ROUND_LOT;
+            if delta >= 0 { (TradeSide::Buy, delta) } else { (TradeSide::Sell, -delta) }
+        };
+        if shares == 0 {
+            continue;
+        }
+        actions.push(RebalanceAction { ts_code: ts_code.clone(), side, quantity: shares, price: *price, current_weight, target_weight });
+    }
+    actions.sort_by(|a, b| (a.side == TradeSide::Buy, &a.ts_code).cmp(&(b.side == TradeSide::Buy, &b.ts_code)));
+    Ok(actions)
+}
+
+/// 记录组合某日收盘后的市值和当日外部现金流(入金为正, 出金为负), 同一天重复记录时覆盖
+///
+/// `market_value` 已包含当日的现金流
+pub async fn record_valuation(
+    conn: &DatabaseConnection,
+    portfolio_id: i32,
+    trade_date: &NaiveDate,
+    market_value: Decimal,
+    cash_flow: Decimal,
+) -> Result<()> {
+    let model = portfolio_valuation::ActiveModel {
+        portfolio_id: Set(portfolio_id),
+        trade_date: Set(trade_date.format(common::date::FORMAT).to_string()),
+        market_value: Set(market_value),
+        cash_flow: Set(cash_flow),
+    };
+    let on_conflict = OnConflict::columns([portfolio_valuation::Column::PortfolioId, portfolio_valuation::Column::TradeDate])
+        .update_columns([portfolio_valuation::Column::MarketValue, portfolio_valuation::Column::CashFlow])
+        .to_owned();
+    portfolio_valuation::Entity::insert(model)
+        .on_conflict(on_conflict)
+        .exec(conn)
+        .await
+        .context("Failed to save portfolio valuation")?;
+    Ok(())
+}
+
+/// 按持仓的买卖记录和 `date` 当天(或之前最近一个交易日)的收盘价计算组合市值并记录估值
+///
+/// 组合没有现金账户, 当天的买入额视为入金, 卖出额视为出金. 目前只计算 A 股持仓,
+/// 美股持仓以美元计价, 跳过并记录警告, 其成交也不计入现金流
+pub async fn snapshot_valuation(conn: &DatabaseConnection, portfolio_id: i32, date: &NaiveDate) -> Result<Decimal> {
+    let day = date.format(common::date::FORMAT).to_string();
+    let holdings = holding::Entity::find()
+        .filter(holding::Column::PortfolioId.eq(portfolio_id))
+        .all(conn)
+        .await
+        .context("Failed to fetch holdings")?;
+
+    let mut market_value = Decimal::ZERO;
+    let mut cash_flow = Decimal::ZERO;
+    for h in &holdings {
+        if h.exchange_id != "cn" {
+            warn!("Skip valuation of holding {} on exchange {}, only cn holdings are supported", h.id, h.exchange_id);
+            continue;
+        }
+        let trades: Vec<holding_trade::Model> = find_trades(conn, h.id).await?.into_iter().filter(|t| t.trade_date <= day).collect();
+        if trades.is_empty() {
+            continue;
+        }
+        for t in trades.iter().filter(|t| t.trade_date == day) {
+            let amount = t.quantity * t.price;
+            cash_flow += if t.side == TradeSide::Buy.code() { amount } else { -amount };
+        }
+        let quantity = compute_cost_basis(h.id, &trades, CostBasisMethod::Fifo)?.quantity;
+        if quantity.is_zero() {
+            continue;
+        }
+        let close = stock_daily::Entity::find()
+            .filter(ColumnTrait::eq(&stock_daily::Column::TsCode, &h.symbol))
+            .filter(stock_daily::Column::TradeDate.lte(&day))
+            .order_by_desc(stock_daily::Column::TradeDate)
+            .one(conn)
+            .await?
+            .ok_or_else(|| anyhow!("No daily price for {} on or before {}", h.symbol, day))?
+            .close;
+        market_value += quantity * close;
+    }
+    record_valuation(conn, portfolio_id, date, market_value, cash_flow).await?;
+    Ok(market_value)
+}
+
+/// 新增 `date` 的成交后重新估值: `date` 当天以及之后已有估值的日期, 补录的历史成交会改变之后的市值
+async fn revalue_from(conn: &DatabaseConnection, portfolio_id: i32, date: &NaiveDate) -> Result<()> {
+    let later: Vec<String> = portfolio_valuation::Entity::find()
+        .select_only()
+        .column(portfolio_valuation::Column::TradeDate)
+        .filter(ColumnTrait::eq(&portfolio_valuation::Column::PortfolioId, portfolio_id))
+        .filter(portfolio_valuation::Column::TradeDate.gt(date.format(common::date::FORMAT).to_string()))
+        .into_tuple()
+        .all(conn)
+        .await
+        .context("Failed to fetch portfolio valuations")?;
+    snapshot_valuation(conn, portfolio_id, date).await?;
+    for day in later {
+        let day = NaiveDate::parse_from_str(&day, common::date::FORMAT)?;
+        snapshot_valuation(conn, portfolio_id, &day).await?;
+    }
+    Ok(())
+}
+
+/// 按当前持仓重新计算组合全部已有日期的估值
+async fn revalue_all(conn: &DatabaseConnection, portfolio_id: i32) -> Result<()> {
+    let days: Vec<String> = portfolio_valuation::Entity::find()
+        .select_only()
+        .column(portfolio_valuation::Column::TradeDate)
+        .filter(ColumnTrait::eq(&portfolio_valuation::Column::PortfolioId, portfolio_id))
+        .into_tuple()
+        .all(conn)
+        .await
+        .context("Failed to fetch portfolio valuations")?;
+    for day in days {
+        let day = NaiveDate::parse_from_str(&day, common::date::FORMAT)?;
+        snapshot_valuation(conn, portfolio_id, &day).await?;
+    }
+    Ok(())
+}
+
+/// 对全部组合记录 `date` 的估值, 单个组合失败只记录日志, 返回成功估值的组合数
+pub async fn snapshot_all_valuations(conn: &DatabaseConnection, date: &NaiveDate) -> Result<usize> {
+    let portfolios = portfolio::Entity::find().all(conn).await.context("Failed to fetch portfolios")?;
+    let mut count = 0;
+    for p in &portfolios {
+        match snapshot_valuation(conn, p.id, date).await {
+            Ok(_) => count += 1,
+            Err(e) => error!("Failed to snapshot valuation of portfolio {} on {}: {:?}", p.id, date, e),
+        }
+    }
+    Ok(count)
+}
+
+/// [start, end] 内组合的时间加权收益率 x%100
+///
+/// 以每次现金流为界切分子区间, 各子区间收益率连乘, 不受入金/出金的时点和金额影响。
+/// 区间内没有现金流时即为简单收益率
+pub async fn time_weighted_return(
+    conn: &DatabaseConnection,
+    portfolio_id: i32,
+    start: &NaiveDate,
+    end: &NaiveDate,
+) -> Result<f64> {
+    let valuations = portfolio_valuation::Entity::find()
+        .filter(ColumnTrait::eq(&portfolio_valuation::Column::PortfolioId, portfolio_id))
+        .filter(portfolio_valuation::Column::TradeDate.gte(start.format(common::date::FORMAT).to_string()))
+        .filter(portfolio_valuation::Column::TradeDate.lte(end.format(common::date::FORMAT).to_string()))
+        .order_by_asc(portfolio_valuation::Column::TradeDate)
+        .all(conn)
+        .await
+        .context("Failed to fetch portfolio valuations")?;
+    if valuations.len() < 2 {
+        bail!("Not enough valuations for portfolio {} between {} and {}", portfolio_id, start, end);
+    }
+    let valuations = valuations
+        .iter()
+        .map(|v| Some((v.market_value.to_f64()?, v.cash_flow.to_f64()?)))
+        .collect::<Option<Vec<(f64, f64)>>>()
+        .ok_or_else(|| anyhow!("Invalid valuation of portfolio {}", portfolio_id))?;
+    chain_returns(&valuations).ok_or_else(|| anyhow!("Portfolio {} has non-positive market value", portfolio_id))
+}
+
+/// `valuations` 为按日期正序的 (市值, 当日现金流), 第一条记录的现金流视为期初已投入
+fn chain_returns(valuations: &[(f64, f64)]) -> Option<f64> {
+    let (begin, _) = *valuations.first()?;
+    let (end, _) = *valuations.last()?;
+    if valuations[1..].iter().all(|(_, cash_flow)| *cash_flow == 0f64) {
+        return (begin > 0f64).then(|| common::finance::pct_chg(begin, end));
+    }
+    let mut growth = 1f64;
+    for pair in valuations.windows(2) {
+        let (prev, _) = pair[0];
+        let (value, cash_flow) = pair[1];
+        if prev <= 0f64 {
+            return None;
+        }
+        growth *= (value - cash_flow) / prev;
+    }
+    Some(100f64 * (growth - 1f64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    /// 资金加权收益率(Modified Dietz) x%100, 每笔现金流按投入天数占区间天数的比例加权
+    ///
+    /// `valuations` 为按日期正序的 (日期, 市值, 当日现金流), 第一条记录的现金流视为期初已投入
+    fn modified_dietz(valuations: &[(NaiveDate, f64, f64)]) -> f64 {
+        let (start, begin, _) = valuations[0];
+        let (end, value, _) = valuations[valuations.len() - 1];
+        let days = (end - start).num_days() as f64;
+        let flows = &valuations[1..];
+        let net_flow = flows.iter().map(|(_, _, cash_flow)| cash_flow).sum::<f64>();
+        let weighted_flow = flows.iter().map(|(day, _, cash_flow)| cash_flow * (end - *day).num_days() as f64 / days).sum::<f64>();
+        100f64 * (value - begin - net_flow) / (begin + weighted_flow)
+    }
+
+    #[tokio::test]
+    async fn test_time_weighted_return() {
+        let conn = test_util::memory_db().await;
+        test_util::create_table(&conn, portfolio_valuation::Entity).await;
+        // 第一段涨 10%, 收盘后入金 100, 第二段跌 10%
+        let valuations = [(date(2), 100f64, 0f64), (date(3), 210f64, 100f64), (date(4), 189f64, 0f64)];
+        for (day, market_value, cash_flow) in valuations {
+            record_valuation(&conn, 1, &day, Decimal::from_f64_retain(market_value).unwrap(), Decimal::from_f64_retain(cash_flow).unwrap())
+                .await
+                .unwrap();
+        }
+
+        let twr = time_weighted_return(&conn, 1, &date(1), &date(31)).await.unwrap();
+        let expected = 100f64 * ((210f64 - 100f64) / 100f64 * (189f64 / 210f64) - 1f64);
+        assert!((twr - expected).abs() < 1e-9);
+        // 入金发生在下跌之前, 资金加权收益率受入金时点影响, 与时间加权收益率不同
+        assert!((twr - modified_dietz(&valuations)).abs() > 1f64);
+
+        // 期初记录的现金流不算区间内现金流, 没有现金流时退化为简单收益率, 与资金加权收益率一致
+        let twr = time_weighted_return(&conn, 1, &date(3), &date(4)).await.unwrap();
+        assert!((twr - 100f64 * (189f64 - 210f64) / 210f64).abs() < 1e-9);
+        assert!((twr - modified_dietz(&valuations[1..])).abs() < 1e-9);
+        assert!(time_weighted_return(&conn, 1, &date(5), &date(31)).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_add_trade_records_valuation() {
+        let conn = test_util::memory_db().await;
+        test_util::create_table(&conn, holding_trade::Entity).await;
+        test_util::create_table(&conn, portfolio_valuation::Entity).await;
+        let holding = holding::Model {
+            id: 1,
+            exchange_id: "cn".to_string(),
+            symbol: "600000.SH".to_string(),
+            portfolio_id: 1,
+            name: None,
+            desc: None,
+            order: 0,
+        };
+        // 美股持仓以美元计价, 估值时跳过
+        let us_holding = holding::Model { id: 3, exchange_id: "NASDAQ".to_string(), symbol: "AAPL".to_string(), ..holding.clone() };
+        test_util::seed(&conn, holding::Entity, vec![holding, us_holding]).await;
+        let prices = vec![
+            test_util::daily("600000.SH", "20240102", 10),
+            test_util::daily("600000.SH", "20240103", 11),
+            test_util::daily("600000.SH", "20240104", 9),
+        ];
+        test_util::seed(&conn, stock_daily::Entity, prices).await;
+
+        let buy = |trade_date: &str, price: i64| AddTradeRequest {
+            side: TradeSide::Buy,
+            trade_date: trade_date.to_string(),
+            quantity: Decimal::from(100),
+            price: Decimal::from(price),
+        };
+        add_trade(&conn, 1, 1, buy("20240102", 10)).await.unwrap();
+        add_trade(&conn, 1, 1, buy("20240103", 11)).await.unwrap();
+        add_trade(&conn, 1, 3, buy("20240103", 150)).await.unwrap();
+        assert_eq!(snapshot_valuation(&conn, 1, &date(4)).await.unwrap(), Decimal::from(1800));
+
+        // 1000 -> 1100 涨 10%, 收盘后买入 1100; 2200 -> 1800 跌 18.18%
+        let twr = time_weighted_return(&conn, 1, &date(1), &date(31)).await.unwrap();
+        assert!((twr - -10f64).abs() < 1e-9);
+
+        // 补录更早的成交后, 之后已有的估值被重新计算
+        add_trade(&conn, 1, 1, buy("20240102", 10)).await.unwrap();
+        let valuations = portfolio_valuation::Entity::find().order_by_asc(portfolio_valuation::Column::TradeDate).all(&conn).await.unwrap();
+        let market_values: Vec<Decimal> = valuations.iter().map(|v| v.market_value).collect();
+        assert_eq!(market_values, vec![Decimal::from(2000), Decimal::from(3300), Decimal::from(2700)]);
+
+        let basis = cost_basis(&conn, 1, 1, CostBasisMethod::Fifo).await.unwrap();
+        assert_eq!(basis.quantity, Decimal::from(300));
+        assert!(cost_basis(&conn, 2, 1, CostBasisMethod::Fifo).await.is_err());
+        assert!(cost_basis(&conn, 1, 2, CostBasisMethod::Fifo).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_remove_holding_and_delete_portfolio_delete_dependents() {
+        let conn = test_util::memory_db().await;
+        test_util::create_table(&conn, holding_trade::Entity).await;
+        test_util::create_table(&conn, portfolio_valuation::Entity).await;
+        test_util::seed(&conn, portfolio::Entity, vec![portfolio::Model { id: 1, name: "p".to_string() }]).await;
+        let holding = |id: i32, symbol: &str| holding::Model {
+            id,
+            exchange_id: "cn".to_string(),
+            symbol: symbol.to_string(),
+            portfolio_id: 1,
+            name: None,
+            desc: None,
+            order: 0,
+        };
+        test_util::seed(&conn, holding::Entity, vec![holding(1, "600000.SH"), holding(2, "000001.SZ")]).await;
+        let prices = vec![test_util::daily("600000.SH", "20240102", 10), test_util::daily("000001.SZ", "20240102", 20)];
+        test_util::seed(&conn, stock_daily::Entity, prices).await;
+        let buy = AddTradeRequest { side: TradeSide::Buy, trade_date: "20240102".to_string(), quantity: Decimal::from(100), price: Decimal::from(10) };
+        add_trade(&conn, 1, 1, buy.clone()).await.unwrap();
+        add_trade(&conn, 1, 2, AddTradeRequest { price: Decimal::from(20), ..buy }).await.unwrap();
+        assert_eq!(snapshot_valuation(&conn, 1, &date(2)).await.unwrap(), Decimal::from(3000));
+
+        // 删除持仓同时删除其成交, 已有估值按剩余持仓重新计算
+        remove_holding(&conn, 1, 2).await.unwrap();
+        let trades = holding_trade::Entity::find().all(&conn).await.unwrap();
+        assert_eq!(trades.iter().map(|t| t.holding_id).collect::<Vec<_>>(), vec![1]);
+        let valuation = portfolio_valuation::Entity::find().one(&conn).await.unwrap().unwrap();
+        assert_eq!(valuation.market_value, Decimal::from(1000));
+
+        delete_portfolio(&conn, 1).await.unwrap();
+        assert!(holding::Entity::find().all(&conn).await.unwrap().is_empty());
+        assert!(holding_trade::Entity::find().all(&conn).await.unwrap().is_empty());
+        assert!(portfolio_valuation::Entity::find().all(&conn).await.unwrap().is_empty());
+    }
+
+    fn trade(id: i32, side: &str, trade_date: &str, quantity: i64, price: i64) -> holding_trade::Model {
+        holding_trade::Model {
+            id,
+            holding_id: 1,
+            side: side.to_string(),
+            trade_date: trade_date.to_string(),
+            quantity: Decimal::from(quantity),
+            price: Decimal::from(price),
+        }
+    }
+
+    #[test]
+    fn test_cost_basis() {
+        // 100 股 @10, 100 股 @12, 卖出 150 股 @15
+        let trades = vec![
+            trade(1, "B", "20240102", 100, 10),
+            trade(2, "B", "20240103", 100, 12),
+            trade(3, "S", "20240104", 150, 15),
+        ];
+
+        let fifo = compute_cost_basis(1, &trades, CostBasisMethod::Fifo).unwrap();
+        assert_eq!(fifo.quantity, Decimal::from(50));
+        assert_eq!(fifo.total_cost, Decimal::from(600));
+        assert_eq!(fifo.avg_cost, Some(Decimal::from(12)));
+        assert_eq!(fifo.realized_pnl, Decimal::from(650));
+        assert_eq!(fifo.lots, vec![Lot { trade_date: "20240103".to_string(), quantity: Decimal::from(50), price: Decimal::from(12) }]);
+
+        let average = compute_cost_basis(1, &trades, CostBasisMethod::AverageCost).unwrap();
+        assert_eq!(average.quantity, Decimal::from(50));
+        assert_eq!(average.total_cost, Decimal::from(550));
+        assert_eq!(average.avg_cost, Some(Decimal::from(11)));
+        assert_eq!(average.realized_pnl, Decimal::from(600));
+        assert!(average.lots.is_empty());
+
+        let oversell = vec![trade(1, "B", "20240102", 100, 10), trade(2, "S", "20240103", 101, 10)];
+        assert!(compute_cost_basis(1, &oversell, CostBasisMethod::Fifo).is_err());
+    }
+
+    #[test]
+    fn test_plan_rebalance() {
+        // A 超配 75%, B 低配 25%, 目标各 50%
+        let positions = vec![
+            ("600000.SH".to_string(), 1500f64, 10f64),
+            ("000001.SZ".to_string(), 250f64, 20f64),
+            ("300750.SZ".to_string(), 0f64, 200f64),
+        ];
+        let targets = HashMap::from([("600000.SH".to_string(), 0.5), ("000001.SZ".to_string(), 0.5)]);
+        validate_targets(&targets).unwrap();
+        let actions = plan_rebalance(&positions, &targets).unwrap();
+        assert_eq!(actions.len(), 2);
+        assert_eq!((actions[0].ts_code.as_str(), actions[0].side, actions[0].quantity), ("600000.SH", TradeSide::Sell, 500));
+        assert!((actions[0].current_weight - 0.75).abs() < 1e-9);
+        // 需买入 250 股, 按整手取 200 股
+        assert_eq!((actions[1].ts_code.as_str(), actions[1].side, actions[1].quantity), ("000001.SZ", TradeSide::Buy, 200));
+
+        let targets = HashMap::from([("600000.SH".to_string(), 0.5), ("000001.SZ".to_string(), 0.4)]);
+        assert!(validate_targets(&targets).is_err());
+    }
+
+    #[test]
+    fn test_chain_returns_without_cash_flow() {
+        let valuations = [(100f64, 100f64), (120f64, 0f64), (90f64, 0f64)];
+        assert!((chain_returns(&valuations).unwrap() - -10f64).abs() < 1e-9);
+        assert_eq!(chain_returns(&[]), None);
+    }
+}
@@ -0,0 +1,176 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use anyhow::anyhow;
+use futures::future;
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+/// 全市场扫描: 对每只股票并发执行 `f`, 同时最多运行 `max_concurrency` 个
+///
+/// 单只股票失败不会中断扫描, 结果按完成顺序返回, 每项为 (ts_code, 该股票的结果)
+pub async fn market_scan<F, Fut, T>(ts_codes: Vec<String>, max_concurrency: usize, f: F) -> Vec<(String, anyhow::Result<T>)>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = anyhow::Result<T>>,
+{
+    scan(ts_codes, max_concurrency, &CancelToken::new(), |_| {}, f).await
+}
+
+/// 扫描进度
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize)]
+pub struct ScanProgress {
+    pub processed: usize,
+    pub total: usize,
+    pub cancelled: bool,
+}
+
+/// 取消扫描的令牌, 可以克隆后交给其他任务
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// 后台运行的全市场扫描
+pub struct ScanHandle<T> {
+    progress: watch::Receiver<ScanProgress>,
+    cancel: CancelToken,
+    task: JoinHandle<Vec<(String, anyhow::Result<T>)>>,
+}
+
+impl<T> ScanHandle<T> {
+    /// 进度订阅, 每处理完一只股票更新一次
+    pub fn progress(&self) -> watch::Receiver<ScanProgress> {
+        self.progress.clone()
+    }
+
+    pub fn cancel_token(&self) -> CancelToken {
+        self.cancel.clone()
+    }
+
+    /// 取消扫描: 不再开始新的股票, 已经在运行的会执行完
+    pub fn cancel(&self) {
+        self.cancel.cancel();
+    }
+
+    /// 等待扫描结束, 取消时返回已处理完的部分结果
+    pub async fn join(self) -> anyhow::Result<Vec<(String, anyhow::Result<T>)>> {
+        self.task.await.map_err(|e| anyhow!("market scan task failed: {}", e))
+    }
+}
+
+/// 在后台运行 `market_scan`, 返回可以查看进度和取消的句柄
+pub fn spawn_market_scan<F, Fut, T>(ts_codes: Vec<String>, max_concurrency: usize, f: F) -> ScanHandle<T>
+where
+    F: Fn(String) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = anyhow::Result<T>> + Send + 'static,
+    T: Send + 'static,
+{
+    let total = ts_codes.len();
+    let (tx, rx) = watch::channel(ScanProgress { processed: 0, total, cancelled: false });
+    let cancel = CancelToken::new();
+    let token = cancel.clone();
+    let task = tokio::spawn(async move {
+        let on_done = |processed| {
+            tx.send_replace(ScanProgress { processed, total, cancelled: token.is_cancelled() });
+        };
+        let results = scan(ts_codes, max_concurrency, &token, on_done, f).await;
+        tx.send_replace(ScanProgress { processed: results.len(), total, cancelled: token.is_cancelled() });
+        results
+    });
+    ScanHandle { progress: rx, cancel, task }
+}
+
+/// 取消后不再开始新的股票; 每处理完一只股票以已处理数调用 `on_done`
+async fn scan<F, Fut, T, P>(ts_codes: Vec<String>, max_concurrency: usize, cancel: &CancelToken, mut on_done: P, f: F) -> Vec<(String, anyhow::Result<T>)>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = anyhow::Result<T>>,
+    P: FnMut(usize),
+{
+    let mut processed = 0;
+    stream::iter(ts_codes)
+        .take_while(|_| future::ready(!cancel.is_cancelled()))
+        .map(|ts_code| {
+            let fut = f(ts_code.clone());
+            async move { (ts_code, fut.await) }
+        })
+        .buffer_unordered(max_concurrency.max(1))
+        .inspect(|_| {
+            processed += 1;
+            on_done(processed);
+        })
+        .collect()
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use anyhow::bail;
+
+    #[tokio::test]
+    async fn test_market_scan_limits_concurrency() {
+        let running = AtomicUsize::new(0);
+        let max_running = AtomicUsize::new(0);
+        let ts_codes = (0..20).map(|i| format!("{:06}.SZ", i)).collect::<Vec<_>>();
+
+        let results = market_scan(ts_codes, 3, |ts_code| {
+            let running = &running;
+            let max_running = &max_running;
+            async move {
+                let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                max_running.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                if ts_code == "000007.SZ" {
+                    bail!("no data for {}", ts_code);
+                }
+                Ok(ts_code.len())
+            }
+        })
+        .await;
+
+        assert_eq!(results.len(), 20);
+        assert_eq!(max_running.load(Ordering::SeqCst), 3);
+        let failed = results.iter().filter(|(_, r)| r.is_err()).map(|(code, _)| code.as_str()).collect::<Vec<_>>();
+        assert_eq!(failed, vec!["000007.SZ"]);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_spawned_scan() {
+        let ts_codes = (0..20).map(|i| format!("{:06}.SZ", i)).collect::<Vec<_>>();
+        let handle = spawn_market_scan(ts_codes, 2, |ts_code| async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            Ok(ts_code)
+        });
+
+        let mut progress = handle.progress();
+        progress.wait_for(|p| p.processed >= 4).await.unwrap();
+        handle.cancel();
+        let results = handle.join().await.unwrap();
+
+        // 取消时正在运行的会执行完, 之后不再开始新的
+        assert!(results.len() >= 4 && results.len() < 20);
+        let last = *progress.borrow();
+        assert_eq!(last, ScanProgress { processed: results.len(), total: 20, cancelled: true });
+    }
+}
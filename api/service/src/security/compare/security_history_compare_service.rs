@@ -1,11 +1,13 @@
 use std::collections::HashMap;
-use anyhow::anyhow;
+use anyhow::{anyhow, bail};
 use chrono::NaiveDate;
+use serde::Serialize;
 use common::data_type::period::Period;
-use entity::sea_orm::{QueryFilter, QueryOrder, EntityTrait};
+use common::db::find_between;
+use entity::sea_orm::{ColumnTrait, EntityTrait, Order, QueryFilter, QueryOrder};
 
-use entity::{fund_daily, index_daily, index_monthly, index_weekly, stock_daily, stock_monthly, stock_weekly};
-use entity::sea_orm::{ColumnTrait, DatabaseConnection};
+use entity::{index_daily, index_monthly, index_weekly, stock_daily, stock_monthly, stock_weekly, ths_daily, trade_calendar, us_daily};
+use entity::sea_orm::DatabaseConnection;
 use crate::fund as fund_service;
 use crate::security::{SecurityPrice, SecurityType, Year};
 
@@ -18,38 +20,96 @@ pub async fn get_security_by_years(r#type: SecurityType, ts_code: &str, period:
         let datas = match r#type {
             SecurityType::Index => get_index_history(ts_code, period, &start, &end, conn).await?,
             SecurityType::Stock => get_stock_history(ts_code, period, &start, &end, conn).await?,
-            SecurityType::Fund => get_fund_history(ts_code, period, &start_date, &end_date, conn).await?
+            SecurityType::Fund => get_fund_history(ts_code, period, &start_date, &end_date, conn).await?,
+            SecurityType::UsStock => get_us_stock_history(ts_code, period, &start, &end, conn).await?,
+            SecurityType::ThsIndex => get_ths_index_history(ts_code, period, &start, &end, conn).await?,
         };
         all.insert(*year, datas);
     }
     Ok(all)
 }
 
+/// 归一化后的单个点, 起始日为 100
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct NormalizedPoint {
+    pub trade_date: String,
+    pub value: f64,
+}
+
+/// 以起始日收盘价为 100 归一化的走势, 便于比较不同价格水平证券的相对表现
+#[derive(Debug, Clone, Serialize)]
+pub struct NormalizedSeries {
+    pub ts_code: String,
+    pub r#type: SecurityType,
+    pub base_date: String, // 作为基准(100)的交易日
+    pub points: Vec<NormalizedPoint>,
+}
+
+/// 比较多只证券在 `[start, end]` 内的相对表现, 每条走势在起始日归一化为 100
+///
+/// 起始日按交易日历对齐到 `start` 当天或之后的第一个交易日; 某只证券当天没有数据(如停牌)时,
+/// 以它在对齐日之后的第一条数据为基准; 区间内没有数据的证券不返回
+pub async fn compare_normalized(securities: &[(SecurityType, String)], period: Period, start: &NaiveDate, end: &NaiveDate, conn: &DatabaseConnection) -> anyhow::Result<Vec<NormalizedSeries>> {
+    let start_str = start.format("%Y%m%d").to_string();
+    let end_str = end.format("%Y%m%d").to_string();
+    let aligned_start = trade_calendar::Entity::find()
+        .filter(ColumnTrait::eq(&trade_calendar::Column::Exchange, "SSE"))
+        .filter(ColumnTrait::eq(&trade_calendar::Column::IsOpen, 1))
+        .filter(trade_calendar::Column::CalDate.gte(&start_str))
+        .filter(trade_calendar::Column::CalDate.lte(&end_str))
+        .order_by_asc(trade_calendar::Column::CalDate)
+        .one(conn)
+        .await?
+        .map(|d| d.cal_date);
+    let Some(aligned_start) = aligned_start else {
+        bail!("no trade date between {} and {}", start_str, end_str);
+    };
+
+    let mut all = vec![];
+    for (r#type, ts_code) in securities {
+        let datas = match r#type {
+            SecurityType::Index => get_index_history(ts_code, period, &aligned_start, &end_str, conn).await?,
+            SecurityType::Stock => get_stock_history(ts_code, period, &aligned_start, &end_str, conn).await?,
+            SecurityType::Fund => get_fund_history(ts_code, period, start, end, conn).await?,
+            SecurityType::UsStock => get_us_stock_history(ts_code, period, &aligned_start, &end_str, conn).await?,
+            SecurityType::ThsIndex => get_ths_index_history(ts_code, period, &aligned_start, &end_str, conn).await?,
+        };
+        if let Some((base_date, points)) = normalize(datas, &aligned_start) {
+            all.push(NormalizedSeries { ts_code: ts_code.clone(), r#type: *r#type, base_date, points });
+        }
+    }
+    Ok(all)
+}
+
+/// 以 `start` 当天或之后第一条有收盘价的数据为 100 归一化, 返回 (基准日, 归一化序列)
+fn normalize(mut prices: Vec<SecurityPrice>, start: &str) -> Option<(String, Vec<NormalizedPoint>)> {
+    prices.sort_by(|a, b| a.trade_date.cmp(&b.trade_date));
+    let prices: Vec<(String, f64)> = prices
+        .into_iter()
+        .filter(|p| p.trade_date.as_str() >= start)
+        .filter_map(|p| p.close.map(|close| (p.trade_date, close)))
+        .collect();
+    let (base_date, base) = prices.first().filter(|(_, close)| *close > 0f64).cloned()?;
+    let points = prices
+        .into_iter()
+        .map(|(trade_date, close)| NormalizedPoint { trade_date, value: close / base * 100f64 })
+        .collect();
+    Some((base_date, points))
+}
+
 async fn get_stock_history(ts_code: &str, period: Period, start: &str, end: &str, conn: &DatabaseConnection) -> anyhow::Result<Vec<SecurityPrice>> {
     let data = match period {
         Period::Day => {
-            stock_daily::Entity::find()
-                .filter(ColumnTrait::eq(&stock_daily::Column::TsCode, ts_code))
-                .filter(stock_daily::Column::TradeDate.gte(start))
-                .filter(stock_daily::Column::TradeDate.lte(end))
-                .order_by_desc(stock_daily::Column::TradeDate)
-                .all(conn).await?.into_iter().map(|d| SecurityPrice::from_stock_daily(d)).collect()
+            find_between::<stock_daily::Entity>(conn, stock_daily::Column::TsCode, stock_daily::Column::TradeDate, ts_code, start, end, Order::Desc)
+                .await?.into_iter().map(SecurityPrice::from_stock_daily).collect()
         }
         Period::Week => {
-            stock_weekly::Entity::find()
-                .filter(stock_weekly::Column::TsCode.eq(ts_code))
-                .filter(stock_weekly::Column::TradeDate.gte(start))
-                .filter(stock_weekly::Column::TradeDate.lte(end))
-                .order_by_desc(stock_weekly::Column::TradeDate)
-                .all(conn).await?.into_iter().map(|d| SecurityPrice::from_stock_weekly(d)).collect()
+            find_between::<stock_weekly::Entity>(conn, stock_weekly::Column::TsCode, stock_weekly::Column::TradeDate, ts_code, start, end, Order::Desc)
+                .await?.into_iter().map(SecurityPrice::from_stock_weekly).collect()
         }
         Period::Month => {
-            stock_monthly::Entity::find()
-                .filter(stock_monthly::Column::TsCode.eq(ts_code))
-                .filter(stock_monthly::Column::TradeDate.gte(start))
-                .filter(stock_monthly::Column::TradeDate.lte(end))
-                .order_by_desc(stock_monthly::Column::TradeDate)
-                .all(conn).await?.into_iter().map(|d| SecurityPrice::from_stock_monthly(d)).collect()
+            find_between::<stock_monthly::Entity>(conn, stock_monthly::Column::TsCode, stock_monthly::Column::TradeDate, ts_code, start, end, Order::Desc)
+                .await?.into_iter().map(SecurityPrice::from_stock_monthly).collect()
         }
     };
     Ok(data)
@@ -59,28 +119,16 @@ async fn get_stock_history(ts_code: &str, period: Period, start: &str, end: &str
 async fn get_index_history(ts_code: &str, period: Period, start: &str, end: &str, conn: &DatabaseConnection) -> anyhow::Result<Vec<SecurityPrice>> {
     let data = match period {
         Period::Day => {
-            index_daily::Entity::find()
-                .filter(index_daily::Column::TsCode.eq(ts_code))
-                .filter(index_daily::Column::TradeDate.gte(start))
-                .filter(index_daily::Column::TradeDate.lte(end))
-                .order_by_desc(index_daily::Column::TradeDate)
-                .all(conn).await?.into_iter().map(|d| SecurityPrice::from_index_daily(d)).collect()
+            find_between::<index_daily::Entity>(conn, index_daily::Column::TsCode, index_daily::Column::TradeDate, ts_code, start, end, Order::Desc)
+                .await?.into_iter().map(SecurityPrice::from_index_daily).collect()
         }
         Period::Week => {
-            index_weekly::Entity::find()
-                .filter(index_weekly::Column::TsCode.eq(ts_code))
-                .filter(index_weekly::Column::TradeDate.gte(start))
-                .filter(index_weekly::Column::TradeDate.lte(end))
-                .order_by_desc(index_weekly::Column::TradeDate)
-                .all(conn).await?.into_iter().map(|d| SecurityPrice::from_index_weekly(d)).collect()
+            find_between::<index_weekly::Entity>(conn, index_weekly::Column::TsCode, index_weekly::Column::TradeDate, ts_code, start, end, Order::Desc)
+                .await?.into_iter().map(SecurityPrice::from_index_weekly).collect()
         }
         Period::Month => {
-            index_monthly::Entity::find()
-                .filter(index_monthly::Column::TsCode.eq(ts_code))
-                .filter(index_monthly::Column::TradeDate.gte(start))
-                .filter(index_monthly::Column::TradeDate.lte(end))
-                .order_by_desc(index_monthly::Column::TradeDate)
-                .all(conn).await?.into_iter().map(|d| SecurityPrice::from_index_monthly(d)).collect()
+            find_between::<index_monthly::Entity>(conn, index_monthly::Column::TsCode, index_monthly::Column::TradeDate, ts_code, start, end, Order::Desc)
+                .await?.into_iter().map(SecurityPrice::from_index_monthly).collect()
         }
     };
     Ok(data)
@@ -89,21 +137,102 @@ async fn get_index_history(ts_code: &str, period: Period, start: &str, end: &str
 async fn get_fund_history(ts_code: &str, period: Period, start: &NaiveDate, end: &NaiveDate, conn: &DatabaseConnection) -> anyhow::Result<Vec<SecurityPrice>> {
     let data = match period {
         Period::Day => {
-           fund_service::get_fund_daily(ts_code, start, end, conn).await?.into_iter().map(|d| SecurityPrice::from_fund_daily(d)).collect()
+           fund_service::get_fund_daily(ts_code, start, end, conn).await?.into_iter().map(SecurityPrice::from_fund_daily).collect()
         }
         Period::Week => {
-            fund_service::get_fund_weekly(ts_code, start, end, conn).await?.into_iter().map(|d| SecurityPrice::from_fund_daily(d)).collect()
+            fund_service::get_fund_weekly(ts_code, start, end, conn).await?.into_iter().map(SecurityPrice::from_fund_daily).collect()
         }
         Period::Month => {
-            fund_service::get_fund_monthly(ts_code, start, end, conn).await?.into_iter().map(|d| SecurityPrice::from_fund_daily(d)).collect()
+            fund_service::get_fund_monthly(ts_code, start, end, conn).await?.into_iter().map(SecurityPrice::from_fund_daily).collect()
         }
     };
     Ok(data)
 }
 
+/// 美股只有日线
+async fn get_us_stock_history(ts_code: &str, period: Period, start: &str, end: &str, conn: &DatabaseConnection) -> anyhow::Result<Vec<SecurityPrice>> {
+    if !matches!(period, Period::Day) {
+        bail!("only daily history is available for us stock {}", ts_code);
+    }
+    let data = find_between::<us_daily::Entity>(conn, us_daily::Column::TsCode, us_daily::Column::TradeDate, ts_code, start, end, Order::Desc)
+        .await?.into_iter().map(SecurityPrice::from_us_daily).collect();
+    Ok(data)
+}
+
+/// 同花顺指数只有日线
+async fn get_ths_index_history(ts_code: &str, period: Period, start: &str, end: &str, conn: &DatabaseConnection) -> anyhow::Result<Vec<SecurityPrice>> {
+    if !matches!(period, Period::Day) {
+        bail!("only daily history is available for ths index {}", ts_code);
+    }
+    let data = find_between::<ths_daily::Entity>(conn, ths_daily::Column::TsCode, ths_daily::Column::TradeDate, ts_code, start, end, Order::Desc)
+        .await?.into_iter().map(SecurityPrice::from_ths_daily).collect();
+    Ok(data)
+}
 
 fn get_year_begin_end(year: u32) -> anyhow::Result<(NaiveDate, NaiveDate)> {
     let start = NaiveDate::from_ymd_opt(year as i32, 1, 1).ok_or(anyhow!("invalid year"))?;
     let end = NaiveDate::from_ymd_opt(year as i32, 12, 31).ok_or(anyhow!("invalid year"))?;
     Ok((start, end))
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    fn daily(ts_code: &str, trade_date: &str, close: i64) -> stock_daily::Model {
+        test_util::daily(ts_code, trade_date, close)
+    }
+
+    fn calendar(cal_date: &str, is_open: i16) -> trade_calendar::Model {
+        trade_calendar::Model {
+            exchange: "SSE".to_string(),
+            cal_date: cal_date.to_string(),
+            is_open,
+            pretrade_date: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_compare_normalized() {
+        let conn = test_util::memory_db().await;
+        let calendar = vec![
+            calendar("20240105", 1),
+            calendar("20240106", 0),
+            calendar("20240107", 0),
+            calendar("20240108", 1),
+            calendar("20240109", 1),
+            calendar("20240110", 1),
+        ];
+        test_util::seed(&conn, trade_calendar::Entity, calendar).await;
+        let prices = vec![
+            // 起始日之前的数据不参与
+            daily("000001.SZ", "20240105", 8),
+            daily("000001.SZ", "20240108", 10),
+            daily("000001.SZ", "20240109", 11),
+            daily("000001.SZ", "20240110", 12),
+            daily("600519.SH", "20240105", 1800),
+            daily("600519.SH", "20240108", 1600),
+            daily("600519.SH", "20240109", 1640),
+            daily("600519.SH", "20240110", 1680),
+        ];
+        test_util::seed(&conn, stock_daily::Entity, prices).await;
+
+        let securities = vec![(SecurityType::Stock, "000001.SZ".to_string()), (SecurityType::Stock, "600519.SH".to_string())];
+        // 起始日为周六, 对齐到下一个交易日 20240108
+        let start = NaiveDate::from_ymd_opt(2024, 1, 6).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 1, 10).unwrap();
+        let series = compare_normalized(&securities, Period::Day, &start, &end, &conn).await.unwrap();
+
+        assert_eq!(series.len(), 2);
+        for s in &series {
+            assert_eq!(s.base_date, "20240108");
+            assert_eq!(s.points[0], NormalizedPoint { trade_date: "20240108".to_string(), value: 100f64 });
+        }
+        let (bank, moutai) = (series[0].points.last().unwrap().value, series[1].points.last().unwrap().value);
+        // 000001.SZ 上涨 20%, 600519.SH 上涨 5%
+        assert!((bank - 120f64).abs() < 1e-9);
+        assert!((moutai - 105f64).abs() < 1e-9);
+        assert!(bank > moutai);
+    }
+}
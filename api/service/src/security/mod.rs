@@ -5,20 +5,28 @@ use futures::FutureExt;
 use num_traits::ToPrimitive;
 use serde::{Deserialize, Serialize};
 use entity::sea_orm::prelude::Decimal;
-use entity::{fund_daily, index_daily, index_monthly, index_weekly, stock_daily, stock_monthly, stock_weekly};
+use entity::{fund_daily, index_daily, index_monthly, index_weekly, stock_daily, stock_monthly, stock_weekly, ths_daily, us_daily};
+use common::data_type::Ohlcv;
 use crate::security::SecurityType::Stock;
 pub use compare::security_history_compare_service;
+pub use security_resolve_service::resolve;
 
 pub mod security_search_service;
 pub mod security_daily_service;
 mod compare;
 pub mod stock_asset_service;
+pub mod security_change_service;
+pub mod security_status_service;
+pub mod security_resolve_service;
 
-#[derive(Debug, Copy, Clone, Deserialize, Serialize, Display)]
+/// 证券类型, 序列化为变体名, 与各自的行情表对应
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Deserialize, Serialize, Display)]
 pub enum SecurityType {
-    Index,
-    Stock,
-    Fund,
+    Index, // index_daily/weekly/monthly
+    Stock, // stock_daily/weekly/monthly
+    Fund, // fund_daily
+    UsStock, // us_daily, 只有日线
+    ThsIndex, // 同花顺概念/行业指数 ths_daily, 只有日线
 }
 
 #[derive(Serialize, Debug, Clone)]
@@ -55,9 +63,9 @@ impl SecurityPrice {
             high: data.high.to_f64(),
             low: data.low.to_f64(),
             close: data.close.to_f64(),
-            pre_close: data.pre_close.map(|v| v.to_f64()).flatten(),
-            change: data.change.map(|v| v.to_f64()).flatten(),
-            pct_chg: data.pct_chg.map(|v| v.to_f64()).flatten(),
+            pre_close: data.pre_close.and_then(|v| v.to_f64()),
+            change: data.change.and_then(|v| v.to_f64()),
+            pct_chg: data.pct_chg.and_then(|v| v.to_f64()),
             vol:data.vol.to_f64(),
             amount: data.amount.to_f64(),
         }
@@ -71,9 +79,9 @@ impl SecurityPrice {
             high: data.high.to_f64(),
             low: data.low.to_f64(),
             close: data.close.to_f64(),
-            pre_close: data.pre_close.map(|v| v.to_f64()).flatten(),
-            change: data.change.map(|v| v.to_f64()).flatten(),
-            pct_chg: data.pct_chg.map(|v| v.to_f64()).flatten(),
+            pre_close: data.pre_close.and_then(|v| v.to_f64()),
+            change: data.change.and_then(|v| v.to_f64()),
+            pct_chg: data.pct_chg.and_then(|v| v.to_f64()),
             vol:data.vol.to_f64(),
             amount: data.amount.to_f64(),
         }
@@ -87,9 +95,9 @@ impl SecurityPrice {
             high: data.high.to_f64(),
             low: data.low.to_f64(),
             close: data.close.to_f64(),
-            pre_close: data.pre_close.map(|v| v.to_f64()).flatten(),
-            change: data.change.map(|v| v.to_f64()).flatten(),
-            pct_chg: data.pct_chg.map(|v| v.to_f64()).flatten(),
+            pre_close: data.pre_close.and_then(|v| v.to_f64()),
+            change: data.change.and_then(|v| v.to_f64()),
+            pct_chg: data.pct_chg.and_then(|v| v.to_f64()),
             vol: data.vol.to_f64(),
             amount: data.amount.to_f64(),
         }
@@ -103,27 +111,60 @@ impl SecurityPrice {
             high: data.high.to_f64(),
             low: data.low.to_f64(),
             close: data.close.to_f64(),
-            pre_close: data.pre_close.map(|v| v.to_f64()).flatten(),
-            change: data.change.map(|v| v.to_f64()).flatten(),
-            pct_chg: data.pct_chg.map(|v| v.to_f64()).flatten(),
+            pre_close: data.pre_close.and_then(|v| v.to_f64()),
+            change: data.change.and_then(|v| v.to_f64()),
+            pct_chg: data.pct_chg.and_then(|v| v.to_f64()),
             vol: data.vol.to_f64(),
             amount: data.amount.to_f64()
         }
     }
 
+    pub fn from_us_daily(data: us_daily::Model) -> SecurityPrice {
+        SecurityPrice {
+            ts_code: data.ts_code,
+            trade_date: data.trade_date,
+            open: data.open.and_then(|v| v.to_f64()),
+            high: data.high.and_then(|v| v.to_f64()),
+            low: data.low.and_then(|v| v.to_f64()),
+            close: data.close.and_then(|v| v.to_f64()),
+            pre_close: data.pre_close.and_then(|v| v.to_f64()),
+            change: data.change.and_then(|v| v.to_f64()),
+            pct_chg: data.pct_change.and_then(|v| v.to_f64()),
+            vol: data.vol.and_then(|v| v.to_f64()),
+            amount: data.amount.and_then(|v| v.to_f64()),
+        }
+    }
+
+    /// ths_daily 没有成交额
+    pub fn from_ths_daily(data: ths_daily::Model) -> SecurityPrice {
+        SecurityPrice {
+            ts_code: data.ts_code,
+            trade_date: data.trade_date,
+            open: data.open.and_then(|v| v.to_f64()),
+            high: data.high.and_then(|v| v.to_f64()),
+            low: data.low.and_then(|v| v.to_f64()),
+            close: data.close.and_then(|v| v.to_f64()),
+            pre_close: data.pre_close.and_then(|v| v.to_f64()),
+            change: data.change.and_then(|v| v.to_f64()),
+            pct_chg: data.pct_change.and_then(|v| v.to_f64()),
+            vol: data.vol.and_then(|v| v.to_f64()),
+            amount: None,
+        }
+    }
+
     pub fn from_index_daily(data: index_daily::Model) -> SecurityPrice {
         SecurityPrice {
             ts_code: data.ts_code,
             trade_date: data.trade_date,
-            open: data.open.map(|v| v.to_f64()).flatten(),
-            high: data.high.map(|v| v.to_f64()).flatten(),
-            low: data.low.map(|v| v.to_f64()).flatten(),
-            close: data.close.map(|v| v.to_f64()).flatten(),
-            pre_close: data.pre_close.map(|v| v.to_f64()).flatten(),
-            change: data.change.map(|v| v.to_f64()).flatten(),
-            pct_chg: data.pct_chg.map(|v| v.to_f64()).flatten(),
-            vol: data.vol.map(|v| v.to_f64()).flatten(),
-            amount: data.amount.map(|v| v.to_f64()).flatten(),
+            open: data.open.and_then(|v| v.to_f64()),
+            high: data.high.and_then(|v| v.to_f64()),
+            low: data.low.and_then(|v| v.to_f64()),
+            close: data.close.and_then(|v| v.to_f64()),
+            pre_close: data.pre_close.and_then(|v| v.to_f64()),
+            change: data.change.and_then(|v| v.to_f64()),
+            pct_chg: data.pct_chg.and_then(|v| v.to_f64()),
+            vol: data.vol.and_then(|v| v.to_f64()),
+            amount: data.amount.and_then(|v| v.to_f64()),
         }
     }
 
@@ -131,15 +172,15 @@ impl SecurityPrice {
         SecurityPrice {
             ts_code: data.ts_code,
             trade_date: data.trade_date,
-            open: data.open.map(|v| v.to_f64()).flatten(),
-            high: data.high.map(|v| v.to_f64()).flatten(),
-            low: data.low.map(|v| v.to_f64()).flatten(),
-            close: data.close.map(|v| v.to_f64()).flatten(),
-            pre_close: data.pre_close.map(|v| v.to_f64()).flatten(),
-            change: data.change.map(|v| v.to_f64()).flatten(),
-            pct_chg: data.pct_chg.map(|v| v.to_f64()).flatten(),
-            vol: data.vol.map(|v| v.to_f64()).flatten(),
-            amount: data.amount.map(|v| v.to_f64()).flatten(),
+            open: data.open.and_then(|v| v.to_f64()),
+            high: data.high.and_then(|v| v.to_f64()),
+            low: data.low.and_then(|v| v.to_f64()),
+            close: data.close.and_then(|v| v.to_f64()),
+            pre_close: data.pre_close.and_then(|v| v.to_f64()),
+            change: data.change.and_then(|v| v.to_f64()),
+            pct_chg: data.pct_chg.and_then(|v| v.to_f64()),
+            vol: data.vol.and_then(|v| v.to_f64()),
+            amount: data.amount.and_then(|v| v.to_f64()),
         }
     }
 
@@ -147,15 +188,118 @@ impl SecurityPrice {
         SecurityPrice {
             ts_code: data.ts_code,
             trade_date: data.trade_date,
-            open: data.open.map(|v| v.to_f64()).flatten(),
-            high: data.high.map(|v| v.to_f64()).flatten(),
-            low: data.low.map(|v| v.to_f64()).flatten(),
-            close: data.close.map(|v| v.to_f64()).flatten(),
-            pre_close: data.pre_close.map(|v| v.to_f64()).flatten(),
-            change: data.change.map(|v| v.to_f64()).flatten(),
-            pct_chg: data.pct_chg.map(|v| v.to_f64()).flatten(),
-            vol: data.vol.map(|v| v.to_f64()).flatten(),
-            amount: data.amount.map(|v| v.to_f64()).flatten(),
+            open: data.open.and_then(|v| v.to_f64()),
+            high: data.high.and_then(|v| v.to_f64()),
+            low: data.low.and_then(|v| v.to_f64()),
+            close: data.close.and_then(|v| v.to_f64()),
+            pre_close: data.pre_close.and_then(|v| v.to_f64()),
+            change: data.change.and_then(|v| v.to_f64()),
+            pct_chg: data.pct_chg.and_then(|v| v.to_f64()),
+            vol: data.vol.and_then(|v| v.to_f64()),
+            amount: data.amount.and_then(|v| v.to_f64()),
+        }
+    }
+}
+
+/// 保留 `Decimal` 精度的行情, 对成交额、成交量求和(如 VWAP)时使用, 避免 f64 累加误差; 展示时转换为 `SecurityPrice`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SecurityPriceDecimal {
+    pub ts_code: String,
+    pub trade_date: String,
+    pub open: Option<Decimal>,
+    pub high: Option<Decimal>,
+    pub low: Option<Decimal>,
+    pub close: Option<Decimal>,
+    pub pre_close: Option<Decimal>,
+    pub change: Option<Decimal>,
+    pub pct_chg: Option<Decimal>,
+    pub vol: Option<Decimal>,   // 单位: 手
+    pub amount: Option<Decimal>, // 单位: 千元
+}
+
+impl SecurityPriceDecimal {
+    pub fn from_stock_daily(data: stock_daily::Model) -> SecurityPriceDecimal {
+        SecurityPriceDecimal {
+            ts_code: data.ts_code,
+            trade_date: data.trade_date,
+            open: Some(data.open),
+            high: Some(data.high),
+            low: Some(data.low),
+            close: Some(data.close),
+            pre_close: data.pre_close,
+            change: data.change,
+            pct_chg: data.pct_chg,
+            vol: Some(data.vol),
+            amount: Some(data.amount),
+        }
+    }
+
+    pub fn from_fund_daily(data: fund_daily::Model) -> SecurityPriceDecimal {
+        SecurityPriceDecimal {
+            ts_code: data.ts_code,
+            trade_date: data.trade_date,
+            open: Some(data.open),
+            high: Some(data.high),
+            low: Some(data.low),
+            close: Some(data.close),
+            pre_close: data.pre_close,
+            change: data.change,
+            pct_chg: data.pct_chg,
+            vol: Some(data.vol),
+            amount: Some(data.amount),
+        }
+    }
+
+    pub fn from_index_daily(data: index_daily::Model) -> SecurityPriceDecimal {
+        SecurityPriceDecimal {
+            ts_code: data.ts_code,
+            trade_date: data.trade_date,
+            open: data.open,
+            high: data.high,
+            low: data.low,
+            close: data.close,
+            pre_close: data.pre_close,
+            change: data.change,
+            pct_chg: data.pct_chg,
+            vol: data.vol,
+            amount: data.amount,
+        }
+    }
+}
+
+/// 缺失的字段求和时跳过
+pub fn sum_decimal<'a>(prices: impl IntoIterator<Item = &'a SecurityPriceDecimal>, field: fn(&SecurityPriceDecimal) -> Option<Decimal>) -> Decimal {
+    prices.into_iter().filter_map(field).sum()
+}
+
+impl From<SecurityPriceDecimal> for SecurityPrice {
+    fn from(data: SecurityPriceDecimal) -> Self {
+        let value = |v: Option<Decimal>| v.and_then(|v| v.to_f64());
+        SecurityPrice {
+            ts_code: data.ts_code,
+            trade_date: data.trade_date,
+            open: value(data.open),
+            high: value(data.high),
+            low: value(data.low),
+            close: value(data.close),
+            pre_close: value(data.pre_close),
+            change: value(data.change),
+            pct_chg: value(data.pct_chg),
+            vol: value(data.vol),
+            amount: value(data.amount),
+        }
+    }
+}
+
+/// 缺失的价格、成交量按 0 处理
+impl From<&SecurityPrice> for Ohlcv {
+    fn from(p: &SecurityPrice) -> Self {
+        Ohlcv {
+            open: p.open.unwrap_or_default(),
+            high: p.high.unwrap_or_default(),
+            low: p.low.unwrap_or_default(),
+            close: p.close.unwrap_or_default(),
+            volume: p.vol.unwrap_or_default(),
         }
     }
 }
@@ -168,7 +312,48 @@ impl FromStr for SecurityType{
             "Stock" => Ok(Stock),
             "Index" => Ok(SecurityType::Index),
             "Fund" => Ok(SecurityType::Fund),
+            "UsStock" => Ok(SecurityType::UsStock),
+            "ThsIndex" => Ok(SecurityType::ThsIndex),
             _ => Err(anyhow!("Unknown security type: {}", s)),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    #[test]
+    fn test_security_type_round_trip() {
+        let types = [SecurityType::Index, SecurityType::Stock, SecurityType::Fund, SecurityType::UsStock, SecurityType::ThsIndex];
+        for t in types {
+            assert_eq!(SecurityType::from_str(&t.to_string()).unwrap(), t);
+            let json = serde_json::to_string(&t).unwrap();
+            assert_eq!(json, format!("\"{}\"", t));
+            assert_eq!(serde_json::from_str::<SecurityType>(&json).unwrap(), t);
+        }
+        // 已有客户端使用的名称保持不变
+        assert_eq!(serde_json::to_string(&SecurityType::Stock).unwrap(), "\"Stock\"");
+        assert!(SecurityType::from_str("Bond").is_err());
+    }
+
+    #[test]
+    fn test_decimal_amount_sum_is_exact() {
+        let daily = |i: usize| stock_daily::Model {
+            vol: Decimal::ONE,
+            amount: Decimal::from_str("0.1").unwrap(),
+            ..test_util::daily("000001.SZ", &i.to_string(), 10)
+        };
+        let prices: Vec<SecurityPriceDecimal> = (0..1000).map(|i| SecurityPriceDecimal::from_stock_daily(daily(i))).collect();
+
+        let exact = sum_decimal(&prices, |p| p.amount);
+        assert_eq!(exact, Decimal::from(100));
+
+        let float_prices: Vec<SecurityPrice> = prices.into_iter().map(SecurityPrice::from).collect();
+        let drifted: f64 = float_prices.iter().filter_map(|p| p.amount).sum();
+        assert_eq!(float_prices[0].amount, Some(0.1));
+        assert_ne!(drifted, 100.0);
+        assert!((drifted - 100.0).abs() < 1e-9);
+    }
+}
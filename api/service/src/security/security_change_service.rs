@@ -0,0 +1,212 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{Days, Local};
+use derive_more::Display;
+use serde::{Deserialize, Serialize};
+
+use entity::sea_orm::sea_query::Expr;
+use entity::sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Set};
+use entity::{security_change, stock};
+
+use crate::stock::ListStatus;
+
+const DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize, Display)]
+pub enum SecurityChangeKind {
+    Listed,   // 新上市, 或暂停上市、退市后恢复上市
+    Paused,   // 暂停上市
+    Delisted, // 退市
+    Renamed,  // 更名
+}
+
+impl From<ListStatus> for SecurityChangeKind {
+    /// 上市状态变为 `status` 对应的变动类型
+    fn from(status: ListStatus) -> Self {
+        match status {
+            ListStatus::Listed => SecurityChangeKind::Listed,
+            ListStatus::Paused => SecurityChangeKind::Paused,
+            ListStatus::Delisted => SecurityChangeKind::Delisted,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SecurityChange {
+    pub ts_code: String,
+    pub kind: SecurityChangeKind,
+    pub detail: Option<String>,
+}
+
+/// 对比已保存的股票列表和最新拉取的股票列表(包含上市、暂停上市和退市), 结果按 ts_code 排序
+///
+/// 上市状态按拉取结果的 `list_status` 判断: 状态变化时按新状态记为上市、暂停上市或退市,
+/// 未保存过的股票只在上市时记为新上市. 不在拉取结果中的股票状态未知, 不做判断
+pub fn diff_stock_list(stored: &[stock::Model], incoming: &[stock::Model]) -> Vec<SecurityChange> {
+    let stored = stored.iter().map(|stock| (stock.ts_code.as_str(), stock)).collect::<HashMap<&str, &stock::Model>>();
+
+    let mut changes = vec![];
+    for stock in incoming {
+        let status = list_status(stock);
+        let old = stored.get(stock.ts_code.as_str());
+        let status_changed = match old {
+            None => status == Some(ListStatus::Listed),
+            Some(old) => list_status(old) != status,
+        };
+        if let Some(status) = status.filter(|_| status_changed) {
+            changes.push(SecurityChange { ts_code: stock.ts_code.clone(), kind: status.into(), detail: stock.name.clone() });
+        }
+        if let Some(old) = old.filter(|old| old.name != stock.name) {
+            changes.push(SecurityChange {
+                ts_code: stock.ts_code.clone(),
+                kind: SecurityChangeKind::Renamed,
+                detail: Some(format!(
+                    "{} -> {}",
+                    old.name.as_deref().unwrap_or_default(),
+                    stock.name.as_deref().unwrap_or_default()
+                )),
+            });
+        }
+    }
+    changes.sort_by(|a, b| a.ts_code.cmp(&b.ts_code));
+    changes
+}
+
+fn list_status(stock: &stock::Model) -> Option<ListStatus> {
+    stock.list_status.as_deref().and_then(|status| status.parse().ok())
+}
+
+/// 对比最新股票列表与 stock 表, 保存变动记录并更新上市状态有变化的股票
+///
+/// stock 表为空(首次拉取)时不记录
+pub async fn record_stock_list_changes(incoming: &[stock::Model], conn: &DatabaseConnection) -> anyhow::Result<Vec<SecurityChange>> {
+    let stored = stock::Entity::find().all(conn).await?;
+    if stored.is_empty() {
+        return Ok(vec![]);
+    }
+    let changes = diff_stock_list(&stored, incoming);
+    save_changes(&changes, conn).await?;
+
+    let changed = changes
+        .iter()
+        .filter(|change| change.kind != SecurityChangeKind::Renamed)
+        .map(|change| change.ts_code.as_str())
+        .collect::<HashSet<&str>>();
+    let mut by_status: HashMap<&'static str, Vec<String>> = HashMap::new();
+    for stock in incoming.iter().filter(|stock| changed.contains(stock.ts_code.as_str())) {
+        if let Some(status) = list_status(stock) {
+            by_status.entry(status.code()).or_default().push(stock.ts_code.clone());
+        }
+    }
+    for (status, ts_codes) in by_status {
+        stock::Entity::update_many()
+            .col_expr(stock::Column::ListStatus, Expr::value(status))
+            .filter(stock::Column::TsCode.is_in(ts_codes))
+            .exec(conn)
+            .await?;
+    }
+    Ok(changes)
+}
+
+async fn save_changes(changes: &[SecurityChange], conn: &DatabaseConnection) -> anyhow::Result<()> {
+    if changes.is_empty() {
+        return Ok(());
+    }
+    let detected_at = Local::now().format(DATETIME_FORMAT).to_string();
+    let models = changes.iter().map(|change| security_change::ActiveModel {
+        ts_code: Set(change.ts_code.clone()),
+        kind: Set(change.kind.to_string()),
+        detail: Set(change.detail.clone()),
+        detected_at: Set(detected_at.clone()),
+        ..Default::default()
+    });
+    security_change::Entity::insert_many(models).exec(conn).await?;
+    Ok(())
+}
+
+/// 最近 `days` 天内发现的证券变动, 按发现时间倒序
+pub async fn recent_changes(days: u64, conn: &DatabaseConnection) -> anyhow::Result<Vec<security_change::Model>> {
+    let since = Local::now()
+        .checked_sub_days(Days::new(days))
+        .ok_or(anyhow::anyhow!("invalid days: {}", days))?
+        .format(DATETIME_FORMAT)
+        .to_string();
+    let changes = security_change::Entity::find()
+        .filter(security_change::Column::DetectedAt.gte(since))
+        .order_by_desc(security_change::Column::DetectedAt)
+        .order_by_asc(security_change::Column::TsCode)
+        .all(conn)
+        .await?;
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    fn stock(ts_code: &str, name: &str, list_status: &str) -> stock::Model {
+        stock::Model {
+            name: Some(name.to_string()),
+            list_status: Some(list_status.to_string()),
+            ..test_util::stock(ts_code)
+        }
+    }
+
+    #[test]
+    fn test_diff_stock_list() {
+        let stored = vec![
+            stock("000001.SZ", "平安银行", "L"),
+            stock("000002.SZ", "万科A", "L"),
+            stock("000003.SZ", "PT金田A", "D"),
+            stock("600001.SH", "邯郸钢铁", "L"),
+            stock("600002.SH", "齐鲁石化", "L"),
+            stock("600003.SH", "ST东北高", "P"),
+        ];
+        let incoming = vec![
+            stock("000001.SZ", "平安银行", "L"),
+            stock("000002.SZ", "万  科Ａ", "L"),
+            stock("000003.SZ", "PT金田A", "D"),
+            stock("301999.SZ", "新股", "L"),
+            // 首次拉取到的历史退市股票不记录
+            stock("000004.SZ", "老股", "D"),
+            stock("600001.SH", "邯郸钢铁", "D"),
+            stock("600002.SH", "齐鲁石化", "P"),
+            stock("600003.SH", "ST东北高", "L"),
+        ];
+        let changes = diff_stock_list(&stored, &incoming);
+        let expected = vec![
+            SecurityChange { ts_code: "000002.SZ".into(), kind: SecurityChangeKind::Renamed, detail: Some("万科A -> 万  科Ａ".into()) },
+            SecurityChange { ts_code: "301999.SZ".into(), kind: SecurityChangeKind::Listed, detail: Some("新股".into()) },
+            SecurityChange { ts_code: "600001.SH".into(), kind: SecurityChangeKind::Delisted, detail: Some("邯郸钢铁".into()) },
+            SecurityChange { ts_code: "600002.SH".into(), kind: SecurityChangeKind::Paused, detail: Some("齐鲁石化".into()) },
+            SecurityChange { ts_code: "600003.SH".into(), kind: SecurityChangeKind::Listed, detail: Some("ST东北高".into()) },
+        ];
+        assert_eq!(changes, expected);
+
+        // 不在拉取结果中的股票不视为退市
+        assert!(diff_stock_list(&stored, &incoming[..1]).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_record_stock_list_changes() {
+        let conn = test_util::memory_db().await;
+        let stored = vec![stock("000001.SZ", "平安银行", "L"), stock("600001.SH", "邯郸钢铁", "L"), stock("600002.SH", "齐鲁石化", "L")];
+        test_util::seed(&conn, stock::Entity, stored).await;
+        test_util::create_table(&conn, security_change::Entity).await;
+
+        let incoming = vec![stock("000001.SZ", "平安银行", "L"), stock("600001.SH", "邯郸钢铁", "D"), stock("600002.SH", "齐鲁石化", "P")];
+        let changes = record_stock_list_changes(&incoming, &conn).await.unwrap();
+        assert_eq!(changes.len(), 2);
+        // 上市状态已更新, 再次运行不会重复记录
+        let changes = record_stock_list_changes(&incoming, &conn).await.unwrap();
+        assert!(changes.is_empty());
+        let paused = stock::Entity::find_by_id("600002.SH").one(&conn).await.unwrap().unwrap();
+        assert_eq!(paused.list_status.as_deref(), Some("P"));
+
+        let recent = recent_changes(1, &conn).await.unwrap();
+        assert_eq!(recent.len(), 2);
+        assert_eq!((recent[0].ts_code.as_str(), recent[0].kind.as_str()), ("600001.SH", "Delisted"));
+        assert_eq!((recent[1].ts_code.as_str(), recent[1].kind.as_str()), ("600002.SH", "Paused"));
+    }
+}
@@ -1,6 +1,6 @@
 use chrono::NaiveDate;
 
-use entity::{fund_daily, index_daily, stock_daily};
+use entity::{fund_daily, index_daily, stock_daily, ths_daily, us_daily};
 use entity::sea_orm::{ColumnTrait, DatabaseConnection};
 use entity::sea_orm::ActiveModelTrait;
 use entity::sea_orm::EntityTrait;
@@ -37,6 +37,22 @@ pub async fn get_security_daily(r#type: SecurityType, ts_code: &str, start: &Nai
                 .order_by_desc(fund_daily::Column::TradeDate)
                 .all(conn).await?.into_iter().map(|d| SecurityPrice::from_fund_daily(d)).collect()
         }
+        SecurityType::UsStock => {
+            us_daily::Entity::find()
+                .filter(ColumnTrait::eq(&us_daily::Column::TsCode, ts_code))
+                .filter(us_daily::Column::TradeDate.gte(&start))
+                .filter(us_daily::Column::TradeDate.lte(&end))
+                .order_by_desc(us_daily::Column::TradeDate)
+                .all(conn).await?.into_iter().map(|d| SecurityPrice::from_us_daily(d)).collect()
+        }
+        SecurityType::ThsIndex => {
+            ths_daily::Entity::find()
+                .filter(ColumnTrait::eq(&ths_daily::Column::TsCode, ts_code))
+                .filter(ths_daily::Column::TradeDate.gte(&start))
+                .filter(ths_daily::Column::TradeDate.lte(&end))
+                .order_by_desc(ths_daily::Column::TradeDate)
+                .all(conn).await?.into_iter().map(|d| SecurityPrice::from_ths_daily(d)).collect()
+        }
     };
     Ok(datas)
 }
\ No newline at end of file
@@ -0,0 +1,102 @@
+use entity::sea_orm::DatabaseConnection;
+
+use common::get_security_pinyin;
+
+use crate::security::security_search_service::match_securities;
+use crate::security::Security;
+
+/// 最多返回的候选数
+const MAX_CANDIDATES: usize = 100;
+
+/// 把用户输入的名称、部分名称或拼音首字母解析为候选证券(股票、基金、指数), 由调用方选择
+///
+/// 匹配规则与 `search_securities` 相同, 结果按匹配程度排序: 代码/名称/拼音完全相同 > 前缀匹配 > 包含,
+/// 在全部匹配结果上排序后再截取前 `MAX_CANDIDATES` 个, 避免完全匹配的证券被截断
+pub async fn resolve(query: &str, conn: &DatabaseConnection) -> anyhow::Result<Vec<Security>> {
+    let query = query.trim();
+    if query.is_empty() {
+        return Ok(vec![]);
+    }
+    let candidates = match_securities(query, conn).await?;
+    let mut ranked = rank(query, candidates);
+    ranked.truncate(MAX_CANDIDATES);
+    Ok(ranked)
+}
+
+fn rank(query: &str, mut candidates: Vec<Security>) -> Vec<Security> {
+    let query = query.to_lowercase();
+    // sort_by_key 是稳定排序, 同一档位内保持 股票 > 指数 > 基金 的顺序
+    candidates.sort_by_key(|security| match_level(&query, security));
+    candidates
+}
+
+fn match_level(query: &str, security: &Security) -> u8 {
+    let code = security.ts_code.to_lowercase();
+    let name = security.name.as_deref().unwrap_or_default().to_lowercase();
+    let pinyin = get_security_pinyin(&name).to_lowercase();
+    let keys = [code.as_str(), name.as_str(), pinyin.as_str()];
+    if keys.iter().any(|key| *key == query) {
+        0
+    } else if keys.iter().any(|key| key.starts_with(query)) {
+        1
+    } else if keys.iter().any(|key| key.contains(query)) {
+        2
+    } else {
+        3
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use entity::{fund, index, stock};
+
+    fn stock(ts_code: &str, name: &str, name_py: &str) -> stock::Model {
+        stock::Model {
+            name: Some(name.to_string()),
+            list_status: Some("L".to_string()),
+            name_py: Some(name_py.to_string()),
+            ..test_util::stock(ts_code)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolve_name_and_pinyin() {
+        let conn = test_util::memory_db().await;
+        let stocks = vec![
+            stock("601318.SH", "中国平安", "ZGPA"),
+            stock("000001.SZ", "平安银行", "PAYH"),
+            stock("600036.SH", "招商银行", "ZSYH"),
+        ];
+        test_util::seed(&conn, stock::Entity, stocks).await;
+        test_util::create_table(&conn, index::Entity).await;
+        test_util::create_table(&conn, fund::Entity).await;
+
+        let by_name = resolve("平安银行", &conn).await.unwrap();
+        let by_pinyin = resolve("payh", &conn).await.unwrap();
+        assert_eq!(by_name[0].ts_code, "000001.SZ");
+        assert_eq!(by_pinyin[0].ts_code, "000001.SZ");
+
+        // 部分名称: 前缀匹配的排在包含匹配之前
+        let partial: Vec<String> = resolve("平安", &conn).await.unwrap().into_iter().map(|s| s.ts_code).collect();
+        assert_eq!(partial, vec!["000001.SZ", "601318.SH"]);
+        assert!(resolve("  ", &conn).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_ranks_before_truncating() {
+        let conn = test_util::memory_db().await;
+        // 代码更小的包含匹配超过候选上限, 完全匹配的股票排在最后
+        let mut stocks: Vec<stock::Model> =
+            (1..=MAX_CANDIDATES + 1).map(|i| stock(&format!("{:06}.SZ", i), &format!("中国平安{}", i), "ZGPA")).collect();
+        stocks.push(stock("601318.SH", "平安", "PA"));
+        test_util::seed(&conn, stock::Entity, stocks).await;
+        test_util::create_table(&conn, index::Entity).await;
+        test_util::create_table(&conn, fund::Entity).await;
+
+        let resolved = resolve("平安", &conn).await.unwrap();
+        assert_eq!(resolved.len(), MAX_CANDIDATES);
+        assert_eq!(resolved[0].ts_code, "601318.SH");
+    }
+}
@@ -2,16 +2,29 @@ use anyhow::anyhow;
 use serde::Serialize;
 use entity::sea_orm::DatabaseConnection;
 use entity::{fund, index, stock};
-use crate::stock::get_stock_list;
+use crate::stock::{get_stock_list, StockListFilter};
 
 use entity::sea_orm::EntityTrait;
 use crate::security::Security;
 use crate::security::SecurityType;
 
+/// 每种证券类型最多返回的条数
+const MAX_PER_TYPE: usize = 100;
+
 pub async fn search_securities(keyword: &str, conn: &DatabaseConnection) -> anyhow::Result<Vec<Security>> {
+    let matched = match_securities(keyword, conn).await?;
+    let mut all = vec![];
+    for r#type in [SecurityType::Stock, SecurityType::Index, SecurityType::Fund] {
+        all.extend(matched.iter().filter(|s| s.r#type == r#type).take(MAX_PER_TYPE).cloned());
+    }
+    Ok(all)
+}
+
+/// 代码、名称或拼音包含 `keyword` 的全部股票、指数、基金, 按 股票 > 指数 > 基金 的顺序, 不截断
+pub(crate) async fn match_securities(keyword: &str, conn: &DatabaseConnection) -> anyhow::Result<Vec<Security>> {
     let keyword_own = keyword.to_lowercase();
     let keyword = keyword_own.as_str();
-    let stocks: Vec<stock::Model> = get_stock_list(conn).await?;
+    let stocks: Vec<stock::Model> = get_stock_list(&StockListFilter::default(), conn).await?;
     let stocks: Vec<stock::Model> = stocks
         .into_iter()
         .filter(|s| s.name_py.as_ref().map(|v| v.to_lowercase().contains(keyword)).unwrap_or(false) || s.ts_code.contains(keyword) || s.name.as_ref().map(|name| name.to_lowercase().contains(keyword)).unwrap_or(false))
@@ -32,13 +45,8 @@ pub async fn search_securities(keyword: &str, conn: &DatabaseConnection) -> anyh
         .collect();
     let funds: Vec<Security> = funds.into_iter().map(|s| Security { ts_code: s.ts_code.clone(), name: s.name.clone(), r#type: SecurityType::Fund }).collect();
 
-    let mut all = vec![];
-    all.extend(take(stocks, 100));
-    all.extend(take(indexes, 100));
-    all.extend(take(funds, 100));
+    let mut all = stocks;
+    all.extend(indexes);
+    all.extend(funds);
     Ok(all)
-}
-
-fn take(datas: Vec<Security>, n: usize) -> Vec<Security> {
-    datas[0..n.min(datas.len())].into_iter().map(|v| v.clone()).collect::<Vec<Security>>()
 }
\ No newline at end of file
@@ -0,0 +1,91 @@
+use anyhow::anyhow;
+use chrono::NaiveDate;
+use serde::Serialize;
+
+use common::finance::stock::{is_st_name, Board};
+use entity::sea_orm::{DatabaseConnection, EntityTrait};
+use entity::{stock, stock_daily};
+
+use crate::trade_calendar_service;
+
+/// 交易日历使用的交易所, A股各交易所交易日相同
+const CALENDAR_EXCHANGE: &str = "SSE";
+
+/// 股票在某个交易日的状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct SecurityStatus {
+    pub is_st: bool,        // 按当前股票名称判断, 不是当天的历史名称
+    pub is_suspended: bool, // 交易日没有日线数据视为停牌, 非交易日为 false
+    pub board: Board,
+}
+
+pub async fn security_status(ts_code: &str, date: &NaiveDate, conn: &DatabaseConnection) -> anyhow::Result<SecurityStatus> {
+    let stock = stock::Entity::find_by_id(ts_code)
+        .one(conn)
+        .await?
+        .ok_or(anyhow!("stock not found: {}", ts_code))?;
+    let is_suspended = if trade_calendar_service::is_trading_day(*date, CALENDAR_EXCHANGE, conn).await? {
+        let trade_date = date.format(common::date::FORMAT).to_string();
+        stock_daily::Entity::find_by_id((ts_code.to_string(), trade_date)).one(conn).await?.is_none()
+    } else {
+        false
+    };
+    Ok(SecurityStatus {
+        is_st: stock.name.as_deref().is_some_and(is_st_name),
+        is_suspended,
+        board: Board::from_ts_code(ts_code),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use entity::trade_calendar;
+
+    fn stock(ts_code: &str, name: &str) -> stock::Model {
+        stock::Model {
+            name: Some(name.to_string()),
+            list_status: Some("L".to_string()),
+            ..test_util::stock(ts_code)
+        }
+    }
+
+    fn daily(ts_code: &str, trade_date: &str) -> stock_daily::Model {
+        test_util::daily(ts_code, trade_date, 1)
+    }
+
+    fn calendar(cal_date: &str, is_open: i16) -> trade_calendar::Model {
+        trade_calendar::Model {
+            exchange: CALENDAR_EXCHANGE.to_string(),
+            cal_date: cal_date.to_string(),
+            is_open,
+            pretrade_date: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_security_status() {
+        let conn = test_util::memory_db().await;
+        test_util::seed(&conn, stock::Entity, vec![stock("600000.SH", "浦发银行"), stock("300001.SZ", "*ST特锐")]).await;
+        test_util::seed(&conn, stock_daily::Entity, vec![daily("600000.SH", "20150105"), daily("600000.SH", "20150106")]).await;
+        test_util::seed(
+            &conn,
+            trade_calendar::Entity,
+            vec![calendar("20150103", 0), calendar("20150105", 1), calendar("20150106", 1)],
+        )
+        .await;
+
+        let date = NaiveDate::from_ymd_opt(2015, 1, 6).unwrap();
+        let status = security_status("600000.SH", &date, &conn).await.unwrap();
+        assert_eq!(status, SecurityStatus { is_st: false, is_suspended: false, board: Board::Main });
+
+        // 交易日没有日线数据
+        let status = security_status("300001.SZ", &date, &conn).await.unwrap();
+        assert_eq!(status, SecurityStatus { is_st: true, is_suspended: true, board: Board::ChiNext });
+
+        // 非交易日不算停牌
+        let weekend = NaiveDate::from_ymd_opt(2015, 1, 3).unwrap();
+        assert!(!security_status("300001.SZ", &weekend, &conn).await.unwrap().is_suspended);
+    }
+}
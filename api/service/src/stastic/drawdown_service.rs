@@ -0,0 +1,103 @@
+use chrono::{Local, NaiveDate};
+use itertools::Itertools;
+use num_traits::ToPrimitive;
+use serde::Serialize;
+
+use common::finance::max_drawdown;
+use entity::sea_orm::DatabaseConnection;
+use entity::stock_daily;
+
+use crate::stock::stock_price_service;
+
+/// 某一自然年内的最大回撤及修复情况
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct YearDrawdown {
+    pub year: i32,
+    pub peak_date: String,
+    pub trough_date: String,
+    pub drawdown_pct: f64,             // 回撤幅度 x%100
+    pub recovery_date: Option<String>, // 收盘价回到高点的日期, 可以在下一年, 未修复为 None
+    pub recovery_days: Option<i64>,    // 从最低点到修复的自然日天数
+}
+
+/// 按年统计最大回撤, 年内没有回撤(一路上涨)的年份不返回
+pub async fn yearly_drawdown(ts_code: &str, conn: &DatabaseConnection) -> anyhow::Result<Vec<YearDrawdown>> {
+    let start = NaiveDate::from_ymd_opt(1990, 1, 1).unwrap();
+    let end = Local::now().date_naive();
+    let mut prices = stock_price_service::get_stock_prices(ts_code, &start, &end, conn).await?;
+    prices.reverse();
+    Ok(drawdown_by_year(&prices))
+}
+
+fn drawdown_by_year(prices: &[stock_daily::Model]) -> Vec<YearDrawdown> {
+    let closes = prices.iter().map(|p| p.close.to_f64().unwrap_or_default()).collect::<Vec<f64>>();
+    let mut result = vec![];
+    let mut offset = 0;
+    for (year, group) in &prices.iter().group_by(|p| p.trade_date[..4].to_string()) {
+        let len = group.count();
+        let year_closes = &closes[offset..offset + len];
+        if let Some(drawdown) = max_drawdown(year_closes) {
+            let peak = offset + drawdown.peak;
+            let trough = offset + drawdown.trough;
+            let recovery = (trough + 1..closes.len()).find(|i| closes[*i] >= closes[peak]);
+            result.push(YearDrawdown {
+                year: year.parse().unwrap_or_default(),
+                peak_date: prices[peak].trade_date.clone(),
+                trough_date: prices[trough].trade_date.clone(),
+                drawdown_pct: drawdown.drawdown_pct,
+                recovery_date: recovery.map(|i| prices[i].trade_date.clone()),
+                recovery_days: recovery.and_then(|i| days_between(&prices[trough].trade_date, &prices[i].trade_date)),
+            });
+        }
+        offset += len;
+    }
+    result
+}
+
+fn days_between(start: &str, end: &str) -> Option<i64> {
+    let start = NaiveDate::parse_from_str(start, "%Y%m%d").ok()?;
+    let end = NaiveDate::parse_from_str(end, "%Y%m%d").ok()?;
+    Some((end - start).num_days())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    fn price(date: &str, close: i64) -> stock_daily::Model {
+        test_util::daily("000001.SZ", date, close)
+    }
+
+    #[test]
+    fn test_drawdown_by_year() {
+        let prices = vec![
+            // 2023 年内回撤 20% 后修复
+            price("20230103", 100),
+            price("20230301", 120),
+            price("20230601", 96),
+            price("20230901", 110),
+            price("20231101", 125),
+            // 2024 年回撤到年底仍未修复
+            price("20240102", 130),
+            price("20240601", 117),
+            price("20241231", 120),
+        ];
+        let result = drawdown_by_year(&prices);
+        assert_eq!(result.len(), 2);
+
+        let y2023 = &result[0];
+        assert_eq!(y2023.year, 2023);
+        assert_eq!(y2023.peak_date, "20230301");
+        assert_eq!(y2023.trough_date, "20230601");
+        assert!((y2023.drawdown_pct - 20f64).abs() < 1e-9);
+        assert_eq!(y2023.recovery_date.as_deref(), Some("20231101"));
+        assert_eq!(y2023.recovery_days, Some(153));
+
+        let y2024 = &result[1];
+        assert_eq!(y2024.peak_date, "20240102");
+        assert_eq!(y2024.trough_date, "20240601");
+        assert_eq!(y2024.recovery_date, None);
+        assert_eq!(y2024.recovery_days, None);
+    }
+}
@@ -1,8 +1,10 @@
+use anyhow::bail;
 use num_traits::ToPrimitive;
 use serde::Serialize;
 use entity::sea_orm::{ColumnTrait, DatabaseConnection};
 use entity::stock_daily;
-use crate::stock::get_stock_list;
+use crate::scan::market_scan;
+use crate::stock::{get_stock_list, StockListFilter};
 use crate::trade_calendar_service;
 
 use entity::sea_orm::ActiveModelTrait;
@@ -11,6 +13,10 @@ use entity::sea_orm::QueryOrder;
 use entity::sea_orm::QueryFilter;
 
 use common::finance::ma;
+use tracing::warn;
+
+/// 扫描全市场时的最大并发查询数
+const SCAN_CONCURRENCY: usize = 8;
 
 #[derive(Debug, Serialize)]
 pub struct MacdStastics {
@@ -29,24 +35,36 @@ pub struct MacdStastic {
 }
 
 pub async fn macd_stastic(conn: &DatabaseConnection) -> anyhow::Result<MacdStastics> {
-    let stock_list = get_stock_list(conn).await?;
+    let stock_list = get_stock_list(&StockListFilter::default(), conn).await?;
     let dates = trade_calendar_service::get_trade_calendar(300, conn).await?;
     let dates = &dates[0..251];
     let start_date = dates[dates.len() - 1].cal_date.as_str();
     let end_date = dates[0].cal_date.as_str();
     let (mut ma5up_num, mut ma10up_num, mut ma20up_num, mut ma60up_num, mut ma250up_num) = (0, 0, 0, 0, 0);
     let (mut ma5down_num, mut ma10down_num, mut ma20down_num, mut ma60down_num, mut ma250down_num) = (0, 0, 0, 0, 0);
-    let mut curr = 0;
     let total = stock_list.len();
-    for stock in &stock_list {
+    let ts_codes = stock_list.into_iter().map(|stock| stock.ts_code).collect::<Vec<String>>();
+    let results = market_scan(ts_codes, SCAN_CONCURRENCY, |ts_code| async move {
         let dailies: Vec<stock_daily::Model> = stock_daily::Entity::find()
-            .filter(ColumnTrait::eq(&stock_daily::Column::TsCode, &stock.ts_code))
+            .filter(ColumnTrait::eq(&stock_daily::Column::TsCode, &ts_code))
             .filter(stock_daily::Column::TradeDate.gte(start_date))
             .filter(stock_daily::Column::TradeDate.lte(end_date))
             .order_by_desc(stock_daily::Column::TradeDate)
             .all(conn).await?;
         let prices = dailies.iter().map(|d| d.close.to_f64()).collect::<Option<Vec<f64>>>().unwrap_or(vec![]);
-        let (ma5_up, ma10_up, ma20_up, ma60_up, ma250_up)  = calc_macd(&prices, |curr, ma| curr > ma);
+        if prices.is_empty() {
+            bail!("no daily price");
+        }
+        Ok(calc_macd(&prices, |curr, ma| curr > ma))
+    }).await;
+    for (ts_code, result) in results {
+        let (ma5_up, ma10_up, ma20_up, ma60_up, ma250_up) = match result {
+            Ok(result) => result,
+            Err(e) => {
+                warn!("calc macd stastic failed, ts_code: {}, error: {:?}", ts_code, e);
+                continue;
+            }
+        };
         if ma5_up {
             ma5up_num += 1;
         } else {
@@ -72,10 +90,6 @@ pub async fn macd_stastic(conn: &DatabaseConnection) -> anyhow::Result<MacdStast
         } else {
             ma250down_num += 1;
         }
-        curr += 1;
-        if curr % 30 == 0 {
-            println!("calc macd stastic progress: {}/{}", curr, total);
-        }
     }
     let maup = MacdStastic {
         ma5_num: ma5up_num,
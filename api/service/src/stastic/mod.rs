@@ -1 +1,8 @@
-pub mod macd_stastic_service;
\ No newline at end of file
+pub mod macd_stastic_service;
+pub mod seasonality_service;
+pub mod drawdown_service;
+pub mod streak_service;
+
+pub use seasonality_service::{seasonality, MonthlyStat};
+pub use drawdown_service::{yearly_drawdown, YearDrawdown};
+pub use streak_service::streaks;
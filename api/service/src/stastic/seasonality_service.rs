@@ -0,0 +1,110 @@
+use anyhow::bail;
+use chrono::{Datelike, Local, Months, NaiveDate};
+use num_traits::ToPrimitive;
+use serde::Serialize;
+
+use common::finance::pct_chg;
+use common::util::date_util;
+use entity::sea_orm::DatabaseConnection;
+use entity::stock_daily;
+
+use crate::stock::stock_price_service;
+
+/// 某个自然月在历年的表现, 没有数据的月份 `avg_return` / `win_rate` 为 None
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct MonthlyStat {
+    pub month: u32,
+    pub samples: usize,
+    pub avg_return: Option<f64>, // 平均月涨跌幅 x%100
+    pub win_rate: Option<f64>,   // 上涨月份占比 0~1
+}
+
+/// 季节性分析: 统计最近 `years` 年(不含当月)每个自然月的平均涨跌幅和胜率
+pub async fn seasonality(ts_code: &str, years: u32, conn: &DatabaseConnection) -> anyhow::Result<[MonthlyStat; 12]> {
+    if years == 0 {
+        bail!("years must be greater than 0");
+    }
+    let this_month = Local::now().date_naive().with_day(1).unwrap();
+    let end = this_month.pred_opt().unwrap();
+    // 多取一个月, 用于计算第一个月的涨跌幅
+    let start = this_month - Months::new(12 * years + 1);
+    let prices = stock_price_service::get_stock_prices(ts_code, &start, &end, conn).await?;
+    Ok(monthly_stats(prices))
+}
+
+fn monthly_stats(prices: Vec<stock_daily::Model>) -> [MonthlyStat; 12] {
+    let mut returns: [Vec<f64>; 12] = Default::default();
+    let month_ends = date_util::month_end(prices, |price| &price.trade_date);
+    for pair in month_ends.windows(2) {
+        let (Some(prev), Some(curr)) = (month_of(&pair[0]), month_of(&pair[1])) else {
+            continue;
+        };
+        // 中间缺月(停牌等)时无法得到单月涨跌幅
+        if prev.checked_add_months(Months::new(1)) != Some(curr) {
+            continue;
+        }
+        let (Some(begin), Some(end)) = (pair[0].close.to_f64(), pair[1].close.to_f64()) else {
+            continue;
+        };
+        returns[curr.month0() as usize].push(pct_chg(begin, end));
+    }
+
+    std::array::from_fn(|i| {
+        let rets = &returns[i];
+        let samples = rets.len();
+        let (avg_return, win_rate) = if samples == 0 {
+            (None, None)
+        } else {
+            let wins = rets.iter().filter(|r| **r > 0f64).count();
+            (Some(rets.iter().sum::<f64>() / samples as f64), Some(wins as f64 / samples as f64))
+        };
+        MonthlyStat { month: i as u32 + 1, samples, avg_return, win_rate }
+    })
+}
+
+/// 交易日所在月的第一天
+fn month_of(price: &stock_daily::Model) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(&price.trade_date, "%Y%m%d").ok()?.with_day(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    fn price(date: &str, close: i64) -> stock_daily::Model {
+        test_util::daily("000001.SZ", date, close)
+    }
+
+    #[test]
+    fn test_monthly_stats() {
+        let mut prices = vec![];
+        for year in 2021..=2023 {
+            // 每年 1 月都上涨 10%, 2 月涨跌互现, 3 月之后没有数据
+            prices.push(price(&format!("{}1230", year - 1), 100));
+            prices.push(price(&format!("{}1231", year - 1), 100));
+            prices.push(price(&format!("{}0115", year), 95));
+            prices.push(price(&format!("{}0131", year), 110));
+            let feb = if year == 2022 { 99 } else { 121 };
+            prices.push(price(&format!("{}0228", year), feb));
+        }
+        let stats = monthly_stats(prices);
+
+        let jan = stats[0];
+        assert_eq!(jan.month, 1);
+        assert_eq!(jan.samples, 3);
+        assert!((jan.avg_return.unwrap() - 10f64).abs() < 1e-9);
+        assert_eq!(jan.win_rate, Some(1f64));
+
+        let feb = stats[1];
+        assert_eq!(feb.samples, 3);
+        assert!((feb.win_rate.unwrap() - 2f64 / 3f64).abs() < 1e-9);
+
+        // 12 月只有 12-31 一个月末且上一个月缺失
+        for stat in &stats[2..] {
+            assert_eq!(stat.samples, 0);
+            assert_eq!(stat.avg_return, None);
+            assert_eq!(stat.win_rate, None);
+        }
+    }
+}
@@ -0,0 +1,53 @@
+use chrono::NaiveDate;
+use num_traits::ToPrimitive;
+
+use common::stastics::IncDecInfo;
+use entity::sea_orm::DatabaseConnection;
+use entity::stock_daily;
+
+use crate::stock::stock_price_service;
+
+/// 区间内按收盘价统计的涨跌天数, 以及截止区间最后一个交易日的连涨/连跌天数
+pub async fn streaks(ts_code: &str, start: &NaiveDate, end: &NaiveDate, conn: &DatabaseConnection) -> anyhow::Result<IncDecInfo> {
+    let prices = stock_price_service::get_stock_prices(ts_code, start, end, conn).await?;
+    Ok(inc_dec_info(prices))
+}
+
+fn inc_dec_info(mut prices: Vec<stock_daily::Model>) -> IncDecInfo {
+    prices.sort_by(|a, b| a.trade_date.cmp(&b.trade_date));
+    let closes: Vec<f64> = prices.iter().filter_map(|p| p.close.to_f64()).collect();
+    IncDecInfo::from(&closes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    fn price(date: &str, close: i64) -> stock_daily::Model {
+        test_util::daily("000001.SZ", date, close)
+    }
+
+    #[tokio::test]
+    async fn test_streaks_trailing_up() {
+        let conn = test_util::memory_db().await;
+        let prices = vec![
+            price("20240102", 10),
+            price("20240103", 9),
+            price("20240104", 8),
+            price("20240105", 8),
+            price("20240108", 9),
+            price("20240109", 10),
+            price("20240110", 11),
+            // 区间外
+            price("20240111", 5),
+        ];
+        test_util::seed(&conn, stock_daily::Entity, prices).await;
+
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let end = NaiveDate::from_ymd_opt(2024, 1, 10).unwrap();
+        let info = streaks("000001.SZ", &start, &end, &conn).await.unwrap();
+        assert_eq!((info.consecutive_inc, info.consecutive_dec), (3, 0));
+        assert_eq!((info.inc, info.dec), (3, 2));
+    }
+}
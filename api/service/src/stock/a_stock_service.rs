@@ -4,12 +4,13 @@ use anyhow::anyhow;
 use entity::sea_orm::{
     ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect,
 };
-use entity::{stock, stock_daily, stock_daily_basic};
+use entity::{stock_daily, stock_daily_basic};
 use entity::cn_security_info;
 use entity::sea_orm::prelude::Decimal;
 use serde::Serialize;
 
 use crate::pct_chg::PeriodPctChg;
+use crate::stock::{get_stock_list, StockListFilter};
 
 #[derive(Debug, Clone, Serialize)]
 pub struct AStockOverview {
@@ -29,9 +30,9 @@ pub struct AStockOverview {
     pub total_mv: Option<Decimal>,
 }
 
-pub async fn get_all_a_stocks(conn: &DatabaseConnection) -> anyhow::Result<Vec<AStockOverview>> {
+pub async fn get_all_a_stocks(filter: &StockListFilter, conn: &DatabaseConnection) -> anyhow::Result<Vec<AStockOverview>> {
     // 1) 股票基础信息
-    let stocks = stock::Entity::find().all(conn).await?;
+    let stocks = get_stock_list(filter, conn).await?;
     if stocks.is_empty() {
         return Ok(vec![]);
     }
@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::time::Instant;
 use anyhow::anyhow;
 use chrono::NaiveDate;
@@ -5,7 +6,8 @@ use derive_more::Display;
 use num_traits::ToPrimitive;
 use serde::{Deserialize, Serialize};
 use tracing::info;
-use common::calc::{DailyTradeRecord, calculate_volatility};
+use common::calc::{DailyTradeRecord, calculate_volatility, rolling_volatility};
+use common::db::find_latest_n;
 use entity::stock_daily::Model as StockDaily;
 use entity::sea_orm::DatabaseConnection;
 use entity::sea_orm::EntityTrait;
@@ -15,6 +17,7 @@ use entity::stock;
 
 use super::super::stock_price_service;
 use crate::trade_calendar_service;
+use crate::universe::Universe;
 
 #[derive(Debug, Deserialize, Copy, Clone, Display, PartialEq)]
 #[serde(rename_all = "lowercase")]
@@ -40,7 +43,7 @@ pub struct VolatilityFilter {
 pub struct SecurityVolatility {
     ts_code: String,
     name: String,
-    volatility: f64,
+    volatility: f64, // 最近窗口内对数收益率的标准差, 按 `VolatilityOptions::annualize` 年化
     max_price_swing: f64,
     avg_price: f64,
     max_price: f64,
@@ -54,6 +57,57 @@ pub struct VolatilityResponse {
     securities: Vec<SecurityVolatility>,
     start_date: String,
     end_date: String,
+    window: Option<usize>,
+    annualized: bool,
+}
+
+/// 波动率的计算方式
+#[derive(Debug, Clone, Copy)]
+pub struct VolatilityOptions {
+    pub window: Option<usize>, // 收益率个数, None 表示使用整个区间
+    pub annualize: bool,       // 是否乘以 sqrt(252) 年化
+}
+
+impl Default for VolatilityOptions {
+    fn default() -> Self {
+        VolatilityOptions { window: None, annualize: true }
+    }
+}
+
+/// 个股滚动年化历史波动率序列, 与 `trade_dates` 一一对应
+#[derive(Debug, Serialize, Clone)]
+pub struct VolatilitySeries {
+    pub ts_code: String,
+    pub window: usize,
+    pub trade_dates: Vec<String>, // 窗口结束的交易日, 按日期正序
+    pub values: Vec<f64>,
+}
+
+/// 波动率序列最多返回的交易日数, 约一年
+const VOLATILITY_SERIES_DAYS: usize = 250;
+
+/// 最近一年每个交易日的年化历史波动率, 每个值为截至当日 `window` 个对数收益率的样本标准差 * sqrt(252)
+pub async fn stock_volatility_series(ts_code: &str, window: usize, conn: &DatabaseConnection) -> anyhow::Result<VolatilitySeries> {
+    // 第一个窗口需要额外的 window 根K线
+    let prices = find_latest_n::<stock_daily::Entity>(
+        conn,
+        stock_daily::Column::TsCode,
+        stock_daily::Column::TradeDate,
+        ts_code,
+        VOLATILITY_SERIES_DAYS + window,
+    )
+    .await?;
+    if prices.is_empty() {
+        return Err(anyhow!("stock daily of {} not found", ts_code));
+    }
+    let closes = prices.iter().map(|p| p.close.to_f64()).collect::<Option<Vec<f64>>>().ok_or(anyhow!("close is none"))?;
+    let values = rolling_volatility(&closes, window, true)?;
+    Ok(VolatilitySeries {
+        ts_code: ts_code.to_string(),
+        window,
+        trade_dates: prices[window..].iter().map(|p| p.trade_date.clone()).collect(),
+        values,
+    })
 }
 
 fn from_stock_daily(stock_daily: &StockDaily) -> anyhow::Result<DailyTradeRecord> {
@@ -66,8 +120,10 @@ fn from_stock_daily(stock_daily: &StockDaily) -> anyhow::Result<DailyTradeRecord
 }
 
 
-pub async fn filter(filter: &VolatilityFilter, conn: &DatabaseConnection) -> anyhow::Result<VolatilityResponse> {
-    let stocks = stock::Entity::find().all(conn).await?;
+/// 只筛选 `universe` 范围内的股票
+pub async fn filter(filter: &VolatilityFilter, options: &VolatilityOptions, universe: &Universe, conn: &DatabaseConnection) -> anyhow::Result<VolatilityResponse> {
+    let universe = universe.resolve_set(conn).await?;
+    let stocks: Vec<stock::Model> = stock::Entity::find().all(conn).await?.into_iter().filter(|s| universe.contains(&s.ts_code)).collect();
 
 
     let dates = trade_calendar_service::get_trade_calendar(filter.days, conn).await?.into_iter().map(|c| c.cal_date).collect::<Vec<String>>();
@@ -82,40 +138,11 @@ pub async fn filter(filter: &VolatilityFilter, conn: &DatabaseConnection) -> any
     let grouped_prices = stock_price_service::get_stock_prices_batch(&ts_codes, &start, &end, conn).await?;
     info!("get stock prices batch cost: {:?}, start: {:?}, end: {:?}", instant.elapsed(), start, end);
     
-    // 处理每个股票的价格数据
-    let mut volatilities = Vec::new();
-
     let instant = Instant::now();
-    for (ts_code, prices) in grouped_prices {
-        let records = prices
-            .iter()
-            .map(|p| from_stock_daily(p))
-            .collect::<anyhow::Result<Vec<DailyTradeRecord>>>()?;
-        
-        if records.len() > 1 {
-            let name: String = "".into();
-            let metrics = calculate_volatility(&records);
-            if metrics.max_price_swing <= filter.max_price_swing {
-                volatilities.push(SecurityVolatility {
-                    ts_code,
-                    name,
-                    volatility: metrics.volatility(),   
-                    max_price_swing: metrics.max_price_swing,
-                    avg_price: metrics.avg_price,
-                    max_price: metrics.max_price,
-                    min_price: metrics.min_price,
-                });
-            }
-        }
-    }
+    let volatilities = rank_volatilities(grouped_prices, filter, options)?;
     info!("calculate volatility cost: {:?}", instant.elapsed());
 
-    if filter.sort == Sort::Asc {
-        volatilities.sort_by(|a, b| a.volatility.partial_cmp(&b.volatility).unwrap());
-    } else {
-        volatilities.sort_by(|a, b| b.volatility.partial_cmp(&a.volatility).unwrap());
-    }
-    let mut volatilities =  volatilities.into_iter().take(filter.num as usize).collect::<Vec<SecurityVolatility>>();
+    let mut volatilities = volatilities;
     for v in volatilities.iter_mut() {
         let stock = stock::Entity::find_by_id(&v.ts_code)
             .one(conn)
@@ -128,6 +155,123 @@ pub async fn filter(filter: &VolatilityFilter, conn: &DatabaseConnection) -> any
         securities: volatilities,
         start_date: start.format("%Y%m%d").to_string(),
         end_date: end.format("%Y%m%d").to_string(),
+        window: options.window,
+        annualized: options.annualize,
     };
     Ok(resp)
-}
\ No newline at end of file
+}
+
+/// 计算每只证券最近窗口内的波动率, 按 `max_price_swing` 过滤后排序取前 `num` 个, 名称由调用方填充
+///
+/// `grouped_prices` 每只证券的K线按日期倒序
+fn rank_volatilities(
+    grouped_prices: HashMap<String, Vec<StockDaily>>,
+    filter: &VolatilityFilter,
+    options: &VolatilityOptions,
+) -> anyhow::Result<Vec<SecurityVolatility>> {
+    let mut volatilities = Vec::new();
+    for (ts_code, prices) in grouped_prices {
+        let mut records = prices
+            .iter()
+            .map(from_stock_daily)
+            .collect::<anyhow::Result<Vec<DailyTradeRecord>>>()?;
+        records.reverse();
+        // 最后一个窗口需要 window + 1 个收盘价, 不传时整个区间作为一个窗口; 至少需要 2 个收益率
+        let window = options.window.unwrap_or(records.len().saturating_sub(1));
+        if window < 2 || records.len() < window + 1 {
+            continue;
+        }
+        let records = &records[records.len() - window - 1..];
+        let closes: Vec<f64> = records.iter().map(|r| r.price).collect();
+        // 收盘价异常(不大于 0)的证券跳过
+        let Some(volatility) = rolling_volatility(&closes, window, options.annualize).ok().and_then(|mut v| v.pop()) else {
+            continue;
+        };
+        let metrics = calculate_volatility(records);
+        if metrics.max_price_swing <= filter.max_price_swing {
+            volatilities.push(SecurityVolatility {
+                ts_code,
+                name: "".into(),
+                volatility,
+                max_price_swing: metrics.max_price_swing,
+                avg_price: metrics.avg_price,
+                max_price: metrics.max_price,
+                min_price: metrics.min_price,
+            });
+        }
+    }
+
+    if filter.sort == Sort::Asc {
+        volatilities.sort_by(|a, b| a.volatility.total_cmp(&b.volatility));
+    } else {
+        volatilities.sort_by(|a, b| b.volatility.total_cmp(&a.volatility));
+    }
+    volatilities.truncate(filter.num as usize);
+    Ok(volatilities)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    fn price(date: &str, close: i64) -> stock_daily::Model {
+        bar("000001.SZ", date, close)
+    }
+
+    fn bar(ts_code: &str, date: &str, close: i64) -> stock_daily::Model {
+        test_util::daily(ts_code, date, close)
+    }
+
+    #[tokio::test]
+    async fn test_stock_volatility_series() {
+        let conn = test_util::memory_db().await;
+        let prices = vec![
+            price("20240102", 100),
+            price("20240103", 100),
+            price("20240104", 100),
+            price("20240105", 110),
+            price("20240108", 100),
+        ];
+        test_util::seed(&conn, stock_daily::Entity, prices).await;
+
+        let series = stock_volatility_series("000001.SZ", 2, &conn).await.unwrap();
+        assert_eq!(series.trade_dates, vec!["20240104", "20240105", "20240108"]);
+        assert_eq!(series.values.len(), 3);
+        // 价格不变的窗口波动率为 0
+        assert_eq!(series.values[0], 0f64);
+        assert!(series.values[1] > 0f64 && series.values[2] > series.values[1]);
+
+        assert!(stock_volatility_series("000001.SZ", 5, &conn).await.is_err());
+        assert!(stock_volatility_series("600000.SH", 2, &conn).await.is_err());
+    }
+
+    #[test]
+    fn test_rank_volatilities_by_window() {
+        // 000001.SZ 前期剧烈波动、最近走平, 000002.SZ 始终小幅波动
+        let dates = ["20240102", "20240103", "20240104", "20240105", "20240108", "20240109", "20240110"];
+        let series = |ts_code: &str, closes: [i64; 7]| {
+            let mut bars: Vec<StockDaily> = dates.iter().zip(closes).map(|(date, close)| bar(ts_code, date, close)).collect();
+            bars.reverse();
+            (ts_code.to_string(), bars)
+        };
+        let grouped_prices = || HashMap::from([
+            series("000001.SZ", [100, 130, 90, 120, 100, 100, 100]),
+            series("000002.SZ", [100, 101, 100, 101, 100, 101, 100]),
+        ]);
+        let filter = VolatilityFilter { num: 1, days: 7, sort: Sort::Desc, r#type: Type::Stock, max_price_swing: f64::MAX };
+
+        let whole = rank_volatilities(grouped_prices(), &filter, &VolatilityOptions::default()).unwrap();
+        assert_eq!(whole.len(), 1);
+        assert_eq!(whole[0].ts_code, "000001.SZ");
+
+        let recent = rank_volatilities(grouped_prices(), &filter, &VolatilityOptions { window: Some(2), annualize: true }).unwrap();
+        assert_eq!(recent.len(), 1);
+        assert_eq!(recent[0].ts_code, "000002.SZ");
+        let daily = rank_volatilities(grouped_prices(), &filter, &VolatilityOptions { window: Some(2), annualize: false }).unwrap();
+        assert!((recent[0].volatility - daily[0].volatility * 252f64.sqrt()).abs() < 1e-9);
+
+        // 数据不足一个窗口的证券被跳过
+        assert!(rank_volatilities(grouped_prices(), &filter, &VolatilityOptions { window: Some(7), annualize: true }).unwrap().is_empty());
+    }
+}
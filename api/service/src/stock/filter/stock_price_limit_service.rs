@@ -7,6 +7,7 @@ use entity::sea_orm::prelude::Decimal;
 use entity::stock_daily;
 use entity::trade_calendar;
 use crate::trade_calendar_service;
+use crate::universe::Universe;
 
 use entity::sea_orm::ActiveModelTrait;
 use entity::sea_orm::EntityTrait;
@@ -49,16 +50,23 @@ struct StasticInfo {
 }
 
 
-pub async fn filter_continue_price_limit(past_ndays: u64, conn: &DatabaseConnection) -> anyhow::Result<LimitupStocks> {
+/// 只筛选 `universe` 范围内的股票
+pub async fn filter_continue_price_limit(past_ndays: u64, universe: &Universe, conn: &DatabaseConnection) -> anyhow::Result<LimitupStocks> {
     let mut cal_dates = trade_calendar_service::get_trade_calendar(past_ndays, conn).await?;
+    let universe = universe.resolve_set(conn).await?;
 
     let start_date = &cal_dates[cal_dates.len() - 1].cal_date;
-    let end_date = &cal_dates[0].cal_date;
+    // 最近一个交易日的行情可能还未入库, 取之前最近一个有行情的交易日
+    let end_date = NaiveDate::parse_from_str(&cal_dates[0].cal_date, common::date::FORMAT)?;
+    let end_date = &crate::stock::stock_price_service::trade_date_on_or_before(&end_date, conn)
+        .await?
+        .ok_or(anyhow::anyhow!("no stock daily on or before {}", end_date))?;
 
     let stock_dailies: Vec<stock_daily::Model> = stock_daily::Entity::find()
         .filter(ColumnTrait::eq(&stock_daily::Column::TradeDate, end_date))
         .all(conn)
         .await?;
+    let stock_dailies = stock_dailies.into_iter().filter(|d| universe.contains(&d.ts_code)).collect();
     let limitup_stocks = filter_price_limit_stocks(stock_dailies);
     info!("past_ndays = {}, start_date = {}, end_date = {}", past_ndays, start_date, end_date);
 
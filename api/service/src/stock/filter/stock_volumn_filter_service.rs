@@ -1,10 +1,12 @@
 use anyhow::anyhow;
+use common::stastics::calc_stastics;
 use num_traits::ToPrimitive;
 use serde::{Deserialize, Serialize};
 use entity::sea_orm::{ColumnTrait, DatabaseConnection};
 use entity::stock_daily;
 use entity::stock;
 use crate::trade_calendar_service;
+use crate::universe::Universe;
 
 use entity::sea_orm::EntityTrait;
 use entity::sea_orm::QueryFilter;
@@ -12,8 +14,10 @@ use entity::sea_orm::QueryOrder;
 
 #[derive(Debug, Deserialize, Copy, Clone)]
 pub struct VolumnFilter {
-    pub rate: f64,
-    pub days: u64
+    pub rate: f64, // 放量倍数, 成交量不低于均量(不含峰值当天)的 rate 倍算一次放量
+    pub days: u64, // 回看的交易日数
+    #[serde(default)]
+    pub min_avg_vol: f64, // 区间均量下限, 单位: 手
 }
 
 #[derive(Debug, Serialize)]
@@ -28,14 +32,18 @@ pub struct VolumnFilterResultItem {
     pub ts_code: String,
     pub vol: f64,
     pub date: String,
-    pub avg_vol: f64,
-    pub rate: f64,
+    pub avg_vol: f64, // 区间内除峰值当天外的均量
+    pub rate: f64,    // 峰值成交量 / avg_vol
+    pub spike_count: usize, // 区间内放量的交易日数
 }
 
-pub async fn filter(filter: &VolumnFilter, conn: &DatabaseConnection) -> anyhow::Result<VolumnFilterResult> {
+/// 只筛选 `universe` 范围内的股票
+pub async fn filter(filter: &VolumnFilter, universe: &Universe, conn: &DatabaseConnection) -> anyhow::Result<VolumnFilterResult> {
     let calendars = trade_calendar_service::get_trade_calendar(filter.days, conn).await?;
     let start_date = calendars.last().ok_or(anyhow!("no start date"))?.cal_date.clone();
+    let universe = universe.resolve_set(conn).await?;
     let stocks = stock::Entity::find().all(conn).await.map_err(|err| anyhow!("get stock list failed, error: {:?}", err))?;
+    let stocks = stocks.into_iter().filter(|stock| universe.contains(&stock.ts_code));
     let mut items = vec![];
     for stock in stocks {
         let stock_dailies: Vec<stock_daily::Model> = stock_daily::Entity::find()
@@ -44,16 +52,8 @@ pub async fn filter(filter: &VolumnFilter, conn: &DatabaseConnection) -> anyhow:
             .order_by_desc(stock_daily::Column::TradeDate)
             .all(conn)
             .await?;
-        let meet = meet_filter(filter.rate, stock_dailies);
-        if meet.0 {
-            items.push(VolumnFilterResultItem {
-                name: stock.name.clone().unwrap_or("".into()),
-                ts_code: stock.ts_code,
-                vol: meet.3,
-                date: meet.2.clone(),
-                avg_vol: meet.1,
-                rate: meet.3 / meet.1
-            })
+        if let Some(item) = volume_spikes(&stock, &stock_dailies, filter) {
+            items.push(item);
         }
     }
     items.sort_by(|a, b| b.rate.partial_cmp(&a.rate).unwrap());
@@ -61,19 +61,105 @@ pub async fn filter(filter: &VolumnFilter, conn: &DatabaseConnection) -> anyhow:
     Ok(VolumnFilterResult { items, total })
 }
 
-fn meet_filter(rate: f64, mut stock_dailies: Vec<stock_daily::Model>) -> (bool, f64, String, f64) {
-    if stock_dailies.is_empty() {
-        return (false, 0f64, "".into(), 0f64);
+/// 峰值成交量不低于其余交易日均量 `rate` 倍且该均量不低于 `min_avg_vol` 时返回放量信息, `vol`/`date` 为区间内最大成交量及其日期
+///
+/// 均量不含峰值当天, 否则峰值会抬高均量、低估放量倍数; 除峰值外不足 2 个交易日时无法统计, 返回 None
+fn volume_spikes(stock: &stock::Model, stock_dailies: &[stock_daily::Model], filter: &VolumnFilter) -> Option<VolumnFilterResultItem> {
+    let (peak_index, max_data) = stock_dailies.iter().enumerate().max_by(|a, b| a.1.vol.cmp(&b.1.vol))?;
+    let vols: Vec<f64> = stock_dailies.iter().map(|v| v.vol.to_f64().unwrap_or(0f64)).collect();
+    let max_vol = vols[peak_index];
+    let mut rest: Vec<f64> = vols.iter().enumerate().filter(|(i, _)| *i != peak_index).map(|(_, v)| *v).collect();
+    let avg_vol = calc_stastics(&mut rest)?.avg;
+    if avg_vol <= 0f64 || avg_vol < filter.min_avg_vol {
+        return None;
     }
-  //  stock_dailies.sort_by_key(|v| std::cmp::Reverse(v.vol));
-    stock_dailies.sort_by(|a, b| b.vol.partial_cmp(&a.vol).unwrap());
-
-    let max_data = stock_dailies.remove(0);
-    let max = max_data.vol.clone().to_f64().unwrap_or(0f64);
-    let date = max_data.trade_date.clone();
-    let avg = stock_dailies.iter().map(|v| v.vol.to_f64().unwrap_or(0f64)).sum::<f64>() / stock_dailies.len() as f64;
-    (max / avg >= rate, avg, date, max)
+    let rate = max_vol / avg_vol;
+    if rate < filter.rate {
+        return None;
+    }
+    Some(VolumnFilterResultItem {
+        name: stock.name.clone().unwrap_or("".into()),
+        ts_code: stock.ts_code.clone(),
+        vol: max_vol,
+        date: max_data.trade_date.clone(),
+        avg_vol,
+        rate,
+        spike_count: vols.iter().filter(|v| **v >= avg_vol * filter.rate).count(),
+    })
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use entity::trade_calendar;
+    use rust_decimal::Decimal;
+
+    fn stock(ts_code: &str) -> stock::Model {
+        stock::Model {
+            name: Some(ts_code.to_string()),
+            list_status: Some("L".to_string()),
+            ..test_util::stock(ts_code)
+        }
+    }
+
+    fn daily(ts_code: &str, trade_date: &str, vol: i64) -> stock_daily::Model {
+        stock_daily::Model {
+            vol: Decimal::from(vol),
+            ..test_util::daily(ts_code, trade_date, 10)
+        }
+    }
 
+    #[test]
+    fn test_volume_spikes_shrink_with_multiplier() {
+        let dates = ["20240102", "20240103", "20240104", "20240105"];
+        let dailies = |ts_code: &str, vols: [i64; 4]| dates.iter().zip(vols).map(|(date, vol)| daily(ts_code, date, vol)).collect::<Vec<_>>();
+        // 不含峰值的均量分别为 1000 (峰值 7 倍) 和 2000 (峰值 1.5 倍)
+        let stocks = [
+            (stock("000001.SZ"), dailies("000001.SZ", [1000, 1000, 1000, 7000])),
+            (stock("000002.SZ"), dailies("000002.SZ", [2000, 2000, 2000, 3000])),
+        ];
+        let matched = |filter: VolumnFilter| {
+            stocks.iter().filter_map(|(stock, dailies)| volume_spikes(stock, dailies, &filter)).map(|item| item.ts_code).collect::<Vec<_>>()
+        };
 
+        assert_eq!(matched(VolumnFilter { rate: 1.5, days: 4, min_avg_vol: 0.0 }), vec!["000001.SZ", "000002.SZ"]);
+        assert_eq!(matched(VolumnFilter { rate: 2.0, days: 4, min_avg_vol: 0.0 }), vec!["000001.SZ"]);
+        assert!(matched(VolumnFilter { rate: 8.0, days: 4, min_avg_vol: 0.0 }).is_empty());
+        assert_eq!(matched(VolumnFilter { rate: 1.5, days: 4, min_avg_vol: 1500.0 }), vec!["000002.SZ"]);
+
+        let item = volume_spikes(&stocks[0].0, &stocks[0].1, &VolumnFilter { rate: 1.5, days: 4, min_avg_vol: 0.0 }).unwrap();
+        assert_eq!(item.avg_vol, 1000.0);
+        assert_eq!(item.date, "20240105");
+        assert_eq!(item.spike_count, 1);
+        assert_eq!(item.rate, 7.0);
+
+        // 除峰值外只有一个交易日, 不足以统计均量
+        let short = dailies("000001.SZ", [1000, 1000, 1000, 7000]).split_off(2);
+        assert!(volume_spikes(&stocks[0].0, &short, &VolumnFilter { rate: 1.5, days: 2, min_avg_vol: 0.0 }).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_filter_within_universe() {
+        let conn = test_util::memory_db().await;
+        let dates = ["20240102", "20240103", "20240104", "20240105", "20240108"];
+        let calendar = dates
+            .iter()
+            .map(|d| trade_calendar::Model { exchange: "SSE".to_string(), cal_date: d.to_string(), is_open: 1, pretrade_date: None })
+            .collect();
+        test_util::seed(&conn, trade_calendar::Entity, calendar).await;
+        test_util::seed(&conn, stock::Entity, vec![stock("000001.SZ"), stock("000002.SZ")]).await;
+        let vols = [1000, 1000, 1000, 1000, 7000];
+        let dailies = ["000001.SZ", "000002.SZ"]
+            .iter()
+            .flat_map(|ts_code| dates.iter().zip(vols).map(|(date, vol)| daily(ts_code, date, vol)))
+            .collect();
+        test_util::seed(&conn, stock_daily::Entity, dailies).await;
+
+        let filter = VolumnFilter { rate: 1.5, days: 5, min_avg_vol: 0.0 };
+        let all = super::filter(&filter, &Universe::All, &conn).await.unwrap();
+        assert_eq!(all.total, 2);
+        let listed = super::filter(&filter, &Universe::List(vec!["000002.SZ".to_string()]), &conn).await.unwrap();
+        assert_eq!(listed.items.iter().map(|item| item.ts_code.as_str()).collect::<Vec<_>>(), vec!["000002.SZ"]);
+    }
+}
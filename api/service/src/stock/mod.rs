@@ -1,8 +1,12 @@
 use std::collections::HashSet;
+use std::str::FromStr;
 use anyhow::anyhow;
+use serde::{Deserialize, Serialize};
 use tracing::info;
 
-use entity::sea_orm::DatabaseConnection;
+use common::db::BoundedSelectExt;
+use common::finance::stock::Board;
+use entity::sea_orm::{ColumnTrait, DatabaseConnection, QueryFilter};
 use entity::sea_orm::EntityTrait;
 use entity::stock;
 use entity::sea_orm::EntityOrSelect;
@@ -19,6 +23,9 @@ pub mod volume_distribution_service;
 pub mod stock_history_service;
 pub mod stock_similarity_service;
 pub mod holder_per_capita_service;
+pub mod stock_peer_service;
+pub mod stock_compare_service;
+pub mod quote_cache;
 
 pub async fn get_stock(ts_code: &str, conn: &DatabaseConnection) -> anyhow::Result<stock::Model> {
     let data = stock::Entity::find_by_id(ts_code).one(conn).await;
@@ -28,20 +35,103 @@ pub async fn get_stock(ts_code: &str, conn: &DatabaseConnection) -> anyhow::Resu
     }
 }
 
-pub async fn get_stock_list(conn: &DatabaseConnection) -> anyhow::Result<Vec<stock::Model>> {
-    stock::Entity::find().all(conn).await.map_err(|err| anyhow!("get stock list failed, error: {:?}", err))
+/// 上市状态, 对应 `stock.list_status`
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ListStatus {
+    Listed,   // L
+    Delisted, // D
+    Paused,   // P 暂停上市
+}
+
+impl ListStatus {
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::Listed => "L",
+            Self::Delisted => "D",
+            Self::Paused => "P",
+        }
+    }
+}
+
+impl FromStr for ListStatus {
+    type Err = anyhow::Error;
+
+    /// 接受 `L`/`D`/`P` 或 `listed`/`delisted`/`paused`, 不区分大小写
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "l" | "listed" => Ok(Self::Listed),
+            "d" | "delisted" => Ok(Self::Delisted),
+            "p" | "paused" => Ok(Self::Paused),
+            _ => Err(anyhow!("Unknown list status: {}", s)),
+        }
+    }
+}
+
+/// 股票列表过滤条件, 字段为 None 时不按该条件过滤
+#[derive(Debug, Copy, Clone, Default)]
+pub struct StockListFilter {
+    pub board: Option<Board>, // 按 ts_code 前缀判断
+    pub list_status: Option<ListStatus>,
+}
+
+pub async fn get_stock_list(filter: &StockListFilter, conn: &DatabaseConnection) -> anyhow::Result<Vec<stock::Model>> {
+    let mut query = stock::Entity::find();
+    if let Some(status) = filter.list_status {
+        query = query.filter(ColumnTrait::eq(&stock::Column::ListStatus, status.code()));
+    }
+    let stocks = query.bounded().all(conn).await.map_err(|err| anyhow!("get stock list failed, error: {:?}", err))?;
+    Ok(match filter.board {
+        Some(board) => stocks.into_iter().filter(|s| Board::from_ts_code(&s.ts_code) == board).collect(),
+        None => stocks,
+    })
 }
 
 pub async fn get_stock_area_list(conn: &DatabaseConnection) -> anyhow::Result<HashSet<String>> {
-    let areas: Vec<stock::Model> = stock::Entity::find().all(conn).await.map_err(|err| anyhow!("get stock area list failed, error: {:?}", err))?;
+    let areas: Vec<stock::Model> = stock::Entity::find().bounded().all(conn).await.map_err(|err| anyhow!("get stock area list failed, error: {:?}", err))?;
     println!("areas num: {}", areas.len());
     let areas = areas.into_iter().filter(|v| v.area.is_some()).map(|v| v.area.or(Some("null".into()))).collect::<Option<HashSet<String>>>();
     areas.ok_or(anyhow!("get stock area list failed"))
 }
 
 pub async fn get_stock_industry_list(conn: &DatabaseConnection) -> anyhow::Result<HashSet<String>> {
-    let industries: Vec<stock::Model> = stock::Entity::find().all(conn).await.map_err(|err| anyhow!("get stock industry list failed, error: {:?}", err))?;
+    let industries: Vec<stock::Model> = stock::Entity::find().bounded().all(conn).await.map_err(|err| anyhow!("get stock industry list failed, error: {:?}", err))?;
     println!("industries num: {}", industries.len());
     let industries = industries.into_iter().map(|v| v.industry.or(Some("null".into()))).collect::<Option<HashSet<String>>>();
     industries.ok_or(anyhow!("get stock industry list failed"))
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    fn stock(ts_code: &str, list_status: &str) -> stock::Model {
+        stock::Model {
+            list_status: Some(list_status.to_string()),
+            ..test_util::stock(ts_code)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_get_stock_list_by_board() {
+        let conn = test_util::memory_db().await;
+        let stocks = vec![
+            stock("600000.SH", "L"),
+            stock("688981.SH", "L"),
+            stock("689009.SH", "L"),
+            stock("688086.SH", "D"),
+            stock("300750.SZ", "L"),
+            stock("430047.BJ", "L"),
+        ];
+        test_util::seed(&conn, stock::Entity, stocks).await;
+
+        let filter = StockListFilter { board: Some(Board::Star), list_status: None };
+        let mut star: Vec<String> = get_stock_list(&filter, &conn).await.unwrap().into_iter().map(|s| s.ts_code).collect();
+        star.sort();
+        assert_eq!(star, vec!["688086.SH", "688981.SH", "689009.SH"]);
+
+        let filter = StockListFilter { board: Some(Board::Star), list_status: Some(ListStatus::Listed) };
+        assert_eq!(get_stock_list(&filter, &conn).await.unwrap().len(), 2);
+        assert_eq!(get_stock_list(&StockListFilter::default(), &conn).await.unwrap().len(), 6);
+    }
+}
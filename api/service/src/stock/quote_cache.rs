@@ -0,0 +1,237 @@
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+use anyhow::anyhow;
+use chrono::{Local, NaiveDate};
+use num_traits::ToPrimitive;
+use serde::Serialize;
+
+use entity::sea_orm::sea_query::Expr;
+use entity::sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use entity::stock_daily;
+
+/// 缓存时间, 收盘数据更新后最多延迟这么久可见
+const QUOTE_TTL: Duration = Duration::from_secs(60);
+/// 最多缓存的股票数, 超过后先清理过期的, 仍超过时淘汰最早加载的
+const QUOTE_CAPACITY: usize = 2000;
+
+/// 最近一个交易日的收盘价
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct LatestClose {
+    pub ts_code: String,
+    pub trade_date: String,
+    pub close: f64,
+    pub pct_chg: Option<f64>,
+}
+
+/// 按 ts_code 缓存的最新收盘价, 首次查询时加载, 超过 TTL 或跨天后重新加载
+///
+/// 缓存不区分数据库, 一个实例只用于一个数据库连接(web_api 中由 rocket 托管)
+pub struct QuoteCache {
+    ttl: Duration,
+    capacity: usize,
+    quotes: RwLock<HashMap<String, CachedQuote>>,
+}
+
+struct CachedQuote {
+    loaded_at: Instant,
+    loaded_on: NaiveDate,
+    quote: LatestClose,
+}
+
+impl Default for QuoteCache {
+    fn default() -> Self {
+        Self::new(QUOTE_TTL, QUOTE_CAPACITY)
+    }
+}
+
+impl QuoteCache {
+    pub fn new(ttl: Duration, capacity: usize) -> Self {
+        Self { ttl, capacity, quotes: RwLock::new(HashMap::new()) }
+    }
+
+    /// 股票最新收盘价, 热门股票在 TTL 内不重复查询数据库
+    pub async fn latest_close(&self, ts_code: &str, conn: &DatabaseConnection) -> anyhow::Result<LatestClose> {
+        self.latest_close_with(ts_code, || load_latest_close(ts_code, conn)).await
+    }
+
+    /// 批量查询最新收盘价, 未缓存的股票合并为一次查询; 没有行情的股票不在结果中
+    pub async fn latest_closes(&self, ts_codes: &[String], conn: &DatabaseConnection) -> anyhow::Result<HashMap<String, LatestClose>> {
+        self.latest_closes_with(ts_codes, |missing| load_latest_closes(missing, conn)).await
+    }
+
+    async fn latest_close_with<F, Fut>(&self, ts_code: &str, load: F) -> anyhow::Result<LatestClose>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = anyhow::Result<LatestClose>>,
+    {
+        let today = Local::now().date_naive();
+        if let Some(quote) = self.lookup(ts_code, today) {
+            return Ok(quote);
+        }
+
+        let quote = load().await?;
+        self.insert(vec![quote.clone()], today)?;
+        Ok(quote)
+    }
+
+    async fn latest_closes_with<F, Fut>(&self, ts_codes: &[String], load: F) -> anyhow::Result<HashMap<String, LatestClose>>
+    where
+        F: FnOnce(Vec<String>) -> Fut,
+        Fut: Future<Output = anyhow::Result<Vec<LatestClose>>>,
+    {
+        let today = Local::now().date_naive();
+        let mut result = HashMap::with_capacity(ts_codes.len());
+        let mut missing = vec![];
+        for ts_code in ts_codes {
+            match self.lookup(ts_code, today) {
+                Some(quote) => {
+                    result.insert(ts_code.clone(), quote);
+                }
+                None => missing.push(ts_code.clone()),
+            }
+        }
+        if missing.is_empty() {
+            return Ok(result);
+        }
+
+        let loaded = load(missing).await?;
+        self.insert(loaded.clone(), today)?;
+        result.extend(loaded.into_iter().map(|quote| (quote.ts_code.clone(), quote)));
+        Ok(result)
+    }
+
+    fn lookup(&self, ts_code: &str, today: NaiveDate) -> Option<LatestClose> {
+        let quotes = self.quotes.read().ok()?;
+        quotes.get(ts_code).filter(|cached| self.is_fresh(cached, today)).map(|cached| cached.quote.clone())
+    }
+
+    fn insert(&self, loaded: Vec<LatestClose>, today: NaiveDate) -> anyhow::Result<()> {
+        let mut quotes = self.quotes.write().map_err(|_| anyhow!("quote cache poisoned"))?;
+        let now = Instant::now();
+        for quote in loaded {
+            quotes.insert(quote.ts_code.clone(), CachedQuote { loaded_at: now, loaded_on: today, quote });
+        }
+        if quotes.len() > self.capacity {
+            quotes.retain(|_, cached| self.is_fresh(cached, today));
+        }
+        if quotes.len() > self.capacity {
+            let overflow = quotes.len() - self.capacity;
+            let mut by_age = quotes.iter().map(|(ts_code, cached)| (cached.loaded_at, ts_code.clone())).collect::<Vec<_>>();
+            by_age.sort_unstable();
+            for (_, ts_code) in by_age.into_iter().take(overflow) {
+                quotes.remove(&ts_code);
+            }
+        }
+        Ok(())
+    }
+
+    fn is_fresh(&self, cached: &CachedQuote, today: NaiveDate) -> bool {
+        cached.loaded_on == today && cached.loaded_at.elapsed() < self.ttl
+    }
+}
+
+async fn load_latest_close(ts_code: &str, conn: &DatabaseConnection) -> anyhow::Result<LatestClose> {
+    let daily = stock_daily::Entity::find()
+        .filter(ColumnTrait::eq(&stock_daily::Column::TsCode, ts_code))
+        .order_by_desc(stock_daily::Column::TradeDate)
+        .one(conn)
+        .await?
+        .ok_or_else(|| anyhow!("stock daily of {} not found", ts_code))?;
+    to_latest_close(daily)
+}
+
+/// 先查出每只股票的最新交易日, 再一次取出这些交易日的行情
+async fn load_latest_closes(ts_codes: Vec<String>, conn: &DatabaseConnection) -> anyhow::Result<Vec<LatestClose>> {
+    let latest_dates: HashMap<String, String> = stock_daily::Entity::find()
+        .select_only()
+        .column(stock_daily::Column::TsCode)
+        .expr(Expr::col(stock_daily::Column::TradeDate).max())
+        .filter(stock_daily::Column::TsCode.is_in(ts_codes.clone()))
+        .group_by(stock_daily::Column::TsCode)
+        .into_tuple::<(String, String)>()
+        .all(conn)
+        .await?
+        .into_iter()
+        .collect();
+    if latest_dates.is_empty() {
+        return Ok(vec![]);
+    }
+    let trade_dates = latest_dates.values().cloned().collect::<HashSet<String>>();
+    stock_daily::Entity::find()
+        .filter(stock_daily::Column::TsCode.is_in(ts_codes))
+        .filter(stock_daily::Column::TradeDate.is_in(trade_dates))
+        .all(conn)
+        .await?
+        .into_iter()
+        .filter(|daily| latest_dates.get(&daily.ts_code) == Some(&daily.trade_date))
+        .map(to_latest_close)
+        .collect()
+}
+
+fn to_latest_close(daily: stock_daily::Model) -> anyhow::Result<LatestClose> {
+    let close = daily.close.to_f64().ok_or_else(|| anyhow!("invalid close price of {}", daily.ts_code))?;
+    Ok(LatestClose {
+        close,
+        pct_chg: daily.pct_chg.and_then(|v| v.to_f64()),
+        ts_code: daily.ts_code,
+        trade_date: daily.trade_date,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_second_lookup_within_ttl_is_cached() {
+        let cache = QuoteCache::new(Duration::from_secs(60), 10);
+        let loads = &AtomicUsize::new(0);
+        let load = move || async move {
+            loads.fetch_add(1, Ordering::SeqCst);
+            anyhow::Ok(LatestClose { ts_code: "600000.SH".to_string(), trade_date: "20240102".to_string(), close: 10f64, pct_chg: None })
+        };
+
+        let first = cache.latest_close_with("600000.SH", load).await.unwrap();
+        let second = cache.latest_close_with("600000.SH", load).await.unwrap();
+        assert_eq!(first, second);
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+
+        // 过期后重新加载
+        let expired = QuoteCache::new(Duration::ZERO, 10);
+        expired.latest_close_with("600000.SH", load).await.unwrap();
+        expired.latest_close_with("600000.SH", load).await.unwrap();
+        assert_eq!(loads.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_latest_closes_batched_and_bounded() {
+        let conn = test_util::memory_db().await;
+        let dailies = vec![
+            test_util::daily("600000.SH", "20240102", 10),
+            test_util::daily("600000.SH", "20240103", 11),
+            // 停牌, 最新收盘在更早的交易日
+            test_util::daily("000001.SZ", "20240102", 20),
+            test_util::daily("601166.SH", "20240103", 30),
+        ];
+        test_util::seed(&conn, stock_daily::Entity, dailies).await;
+
+        let cache = QuoteCache::new(Duration::from_secs(60), 2);
+        let ts_codes = ["600000.SH", "000001.SZ", "601166.SH", "999999.SH"].map(String::from);
+        let closes = cache.latest_closes(&ts_codes, &conn).await.unwrap();
+        assert_eq!(closes.len(), 3);
+        assert_eq!((closes["600000.SH"].trade_date.as_str(), closes["600000.SH"].close), ("20240103", 11f64));
+        assert_eq!((closes["000001.SZ"].trade_date.as_str(), closes["000001.SZ"].close), ("20240102", 20f64));
+        assert!(cache.quotes.read().unwrap().len() <= 2);
+
+        // 另一个库使用各自的缓存实例, 不会读到上面的结果
+        let other = test_util::memory_db().await;
+        test_util::seed(&other, stock_daily::Entity, vec![test_util::daily("600000.SH", "20240103", 12)]).await;
+        let latest = QuoteCache::default().latest_close("600000.SH", &other).await.unwrap();
+        assert_eq!(latest.close, 12f64);
+    }
+}
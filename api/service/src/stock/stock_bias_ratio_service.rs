@@ -1,18 +1,14 @@
 use anyhow::anyhow;
-use chrono::NaiveDate;
 use num_traits::ToPrimitive;
 use serde::Serialize;
 use tracing::info;
 use entity::sea_orm::DatabaseConnection;
-use crate::stock_daily_service;
-use crate::trade_calendar_service;
+use entity::stock_daily;
+use common::db::find_latest_n;
 use common::finance::ma_n;
 
-use entity::sea_orm::EntityTrait;
-use entity::sea_orm::ActiveModelTrait;
-use entity::sea_orm::ColumnTrait;
-use entity::sea_orm::QueryFilter;
-use entity::sea_orm::QueryOrder;
+/// 计算乖离率加载的K线数
+const BIAS_BARS: usize = 61;
 
 #[derive(Debug, Clone, Serialize)]
 pub struct StockBiasRatio {
@@ -30,16 +26,17 @@ pub struct BiasRatio {
 }
 
 pub async fn get_bias_ratio(ts_code: &str, price: Option<f64>, conn: &DatabaseConnection) -> anyhow::Result<StockBiasRatio> {
-    let trade_calendar = trade_calendar_service::get_trade_calendar(70, conn).await?;
-    let dates = trade_calendar.into_iter().map(|x| x.cal_date).collect::<Vec<String>>();
-    let end_date = NaiveDate::parse_from_str(dates[0].as_str(), "%Y%m%d").map_err(|e| anyhow!(e))?;
-    let start_date = NaiveDate::parse_from_str(dates[61].as_str(), "%Y%m%d").map_err(|e| anyhow!(e))?;
-    let prices = stock_daily_service::get_stock_daily(ts_code, &start_date, &end_date, conn).await?;
-    let prices = prices.into_iter().map(|x| x.close.to_f64()).collect::<Option<Vec<f64>>>().ok_or(anyhow!("prices is none"))?;
+    // 最多需要当前价和之前 60 个收盘价, 按日期倒序
+    let prices = find_latest_n::<stock_daily::Entity>(conn, stock_daily::Column::TsCode, stock_daily::Column::TradeDate, ts_code, BIAS_BARS).await?;
+    let mut prices = prices.into_iter().map(|x| x.close.to_f64()).collect::<Option<Vec<f64>>>().ok_or(anyhow!("prices is none"))?;
+    prices.reverse();
+    if prices.is_empty() {
+        return Err(anyhow!("stock daily of {} not found", ts_code));
+    }
     let (price, prev_prices) = if let Some(price) = price {
-        (price, &prices[0..60])
+        (price, &prices[..prices.len().min(BIAS_BARS - 1)])
     } else {
-        (prices[0], &prices[1..61]) // &prices[1..61])
+        (prices[0], &prices[1..])
     };
 
     info!("price: {}, prev_prices len: {:?}, prices len: {}", price, prev_prices.len(), prices.len());
@@ -0,0 +1,284 @@
+use std::collections::HashSet;
+
+use num_traits::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::Serialize;
+
+use entity::sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use entity::{finance_indicator, stock, stock_daily_basic};
+
+/// 两只股票的基本面对比, 每行一个指标
+#[derive(Debug, Clone, Serialize)]
+pub struct FundamentalComparison {
+    pub a: FundamentalSide,
+    pub b: FundamentalSide,
+    pub rows: Vec<MetricComparison>,
+}
+
+/// 对比的一方, 日期为取数所用的最新交易日、最新报告期, 没有数据时为 None
+#[derive(Debug, Clone, Serialize)]
+pub struct FundamentalSide {
+    pub ts_code: String,
+    pub name: Option<String>,
+    pub trade_date: Option<String>,
+    pub end_date: Option<String>,
+}
+
+/// 单个指标的对比, 任一方缺少该指标时对应值为 None, `better` 也为 None
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MetricComparison {
+    pub metric: &'static str,
+    pub a: Option<f64>,
+    pub b: Option<f64>,
+    pub better: Option<Better>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Better {
+    A,
+    B,
+    Equal,
+}
+
+/// 指标的比较方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Direction {
+    Higher,
+    /// 越低越好, 但不大于 0 的值(如亏损时的市盈率)不参与比较, 总是劣于正值
+    LowerPositive,
+    /// 不分好坏, 如市值
+    Neutral,
+}
+
+/// 一个参与对比的指标: 名称、取值字段和比较方向
+struct Metric<M> {
+    name: &'static str,
+    field: fn(&M) -> &Option<Decimal>,
+    direction: Direction,
+}
+
+impl<M> Metric<M> {
+    fn compare(&self, a: Option<&M>, b: Option<&M>) -> MetricComparison {
+        let value = |m: &M| (self.field)(m).as_ref().and_then(|v| v.to_f64());
+        let (a, b) = (a.and_then(value), b.and_then(value));
+        MetricComparison { metric: self.name, a, b, better: better(a, b, self.direction) }
+    }
+}
+
+/// 对比两只股票最新的市盈率(TTM)、市净率、ROE、毛利率、净利率、营收同比增长率和总市值
+///
+/// 估值取 `stock_daily_basic` 最新交易日; 财务指标取两只股票都已披露的最新报告期,
+/// 没有共同报告期时各取各自最新报告期. 百分比均为 x%100, 总市值单位: 万元
+pub async fn compare_fundamentals(code_a: &str, code_b: &str, conn: &DatabaseConnection) -> anyhow::Result<FundamentalComparison> {
+    let stock_a = super::get_stock(code_a, conn).await?;
+    let stock_b = super::get_stock(code_b, conn).await?;
+    let basic_a = latest_basic(code_a, conn).await?;
+    let basic_b = latest_basic(code_b, conn).await?;
+    let end_date = common_end_date(code_a, code_b, conn).await?;
+    let indicator_a = latest_indicator(code_a, end_date.as_deref(), conn).await?;
+    let indicator_b = latest_indicator(code_b, end_date.as_deref(), conn).await?;
+
+    let rows = compare_rows(basic_a.as_ref(), indicator_a.as_ref(), basic_b.as_ref(), indicator_b.as_ref());
+    Ok(FundamentalComparison {
+        a: side(stock_a, basic_a.as_ref(), indicator_a.as_ref()),
+        b: side(stock_b, basic_b.as_ref(), indicator_b.as_ref()),
+        rows,
+    })
+}
+
+fn side(stock: stock::Model, basic: Option<&stock_daily_basic::Model>, indicator: Option<&finance_indicator::Model>) -> FundamentalSide {
+    FundamentalSide {
+        ts_code: stock.ts_code,
+        name: stock.name,
+        trade_date: basic.map(|b| b.trade_date.clone()),
+        end_date: indicator.map(|i| i.end_date.clone()),
+    }
+}
+
+async fn latest_basic(ts_code: &str, conn: &DatabaseConnection) -> anyhow::Result<Option<stock_daily_basic::Model>> {
+    let basic = stock_daily_basic::Entity::find()
+        .filter(ColumnTrait::eq(&stock_daily_basic::Column::TsCode, ts_code))
+        .order_by_desc(stock_daily_basic::Column::TradeDate)
+        .one(conn)
+        .await?;
+    Ok(basic)
+}
+
+/// 两只股票都有财务指标的最新报告期
+async fn common_end_date(code_a: &str, code_b: &str, conn: &DatabaseConnection) -> anyhow::Result<Option<String>> {
+    let end_dates = |ts_code: &str| {
+        finance_indicator::Entity::find()
+            .filter(ColumnTrait::eq(&finance_indicator::Column::TsCode, ts_code))
+            .select_only()
+            .column(finance_indicator::Column::EndDate)
+            .distinct()
+            .order_by_desc(finance_indicator::Column::EndDate)
+            .into_tuple::<String>()
+            .all(conn)
+    };
+    let end_dates_a = end_dates(code_a).await?;
+    let end_dates_b: HashSet<String> = end_dates(code_b).await?.into_iter().collect();
+    Ok(end_dates_a.into_iter().find(|end_date| end_dates_b.contains(end_date)))
+}
+
+/// 指定报告期(为 None 时为最新报告期)的财务指标, 同一报告期有多次披露时取最新公告
+async fn latest_indicator(
+    ts_code: &str,
+    end_date: Option<&str>,
+    conn: &DatabaseConnection,
+) -> anyhow::Result<Option<finance_indicator::Model>> {
+    let mut query = finance_indicator::Entity::find().filter(ColumnTrait::eq(&finance_indicator::Column::TsCode, ts_code));
+    if let Some(end_date) = end_date {
+        query = query.filter(ColumnTrait::eq(&finance_indicator::Column::EndDate, end_date));
+    }
+    let indicator = query
+        .order_by_desc(finance_indicator::Column::EndDate)
+        .order_by_desc(finance_indicator::Column::AnnDate)
+        .one(conn)
+        .await?;
+    Ok(indicator)
+}
+
+fn compare_rows(
+    basic_a: Option<&stock_daily_basic::Model>,
+    indicator_a: Option<&finance_indicator::Model>,
+    basic_b: Option<&stock_daily_basic::Model>,
+    indicator_b: Option<&finance_indicator::Model>,
+) -> Vec<MetricComparison> {
+    let basic_metrics: [Metric<stock_daily_basic::Model>; 3] = [
+        Metric { name: "pe_ttm", field: |b| &b.pe_ttm, direction: Direction::LowerPositive },
+        Metric { name: "pb", field: |b| &b.pb, direction: Direction::LowerPositive },
+        Metric { name: "total_mv", field: |b| &b.total_mv, direction: Direction::Neutral },
+    ];
+    let indicator_metrics: [Metric<finance_indicator::Model>; 4] = [
+        Metric { name: "roe", field: |i| &i.roe, direction: Direction::Higher },
+        Metric { name: "grossprofit_margin", field: |i| &i.grossprofit_margin, direction: Direction::Higher },
+        Metric { name: "netprofit_margin", field: |i| &i.netprofit_margin, direction: Direction::Higher },
+        Metric { name: "revenue_yoy", field: |i| &i.or_yoy, direction: Direction::Higher },
+    ];
+
+    let basic_rows = basic_metrics.iter().map(|metric| metric.compare(basic_a, basic_b));
+    let indicator_rows = indicator_metrics.iter().map(|metric| metric.compare(indicator_a, indicator_b));
+    basic_rows.chain(indicator_rows).collect()
+}
+
+fn better(a: Option<f64>, b: Option<f64>, direction: Direction) -> Option<Better> {
+    let (a, b) = (a?, b?);
+    let higher = |a: f64, b: f64| {
+        if a > b {
+            Better::A
+        } else if a < b {
+            Better::B
+        } else {
+            Better::Equal
+        }
+    };
+    match direction {
+        Direction::Neutral => None,
+        Direction::Higher => Some(higher(a, b)),
+        Direction::LowerPositive => match (a > 0f64, b > 0f64) {
+            (true, true) => Some(higher(b, a)),
+            (true, false) => Some(Better::A),
+            (false, true) => Some(Better::B),
+            (false, false) => None,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use serde_json::json;
+
+    fn stock(ts_code: &str) -> stock::Model {
+        stock::Model {
+            name: Some(ts_code.to_string()),
+            industry: Some("银行".to_string()),
+            list_status: Some("L".to_string()),
+            ..test_util::stock(ts_code)
+        }
+    }
+
+    fn basic(ts_code: &str, trade_date: &str, pe_ttm: Option<i64>, pb: i64, total_mv: i64) -> stock_daily_basic::Model {
+        stock_daily_basic::Model {
+            ts_code: ts_code.to_string(),
+            trade_date: trade_date.to_string(),
+            close: None,
+            turnover_rate: None,
+            turnover_rate_f: None,
+            volume_ratio: None,
+            pe: None,
+            pe_ttm: pe_ttm.map(Decimal::from),
+            pb: Some(Decimal::from(pb)),
+            ps: None,
+            ps_ttm: None,
+            dv_ratio: None,
+            dv_ttm: None,
+            total_share: None,
+            float_share: None,
+            free_share: None,
+            total_mv: Some(Decimal::from(total_mv)),
+            circ_mv: None,
+        }
+    }
+
+    // finance_indicator 字段很多, 只填需要的字段, 其余为 None
+    fn indicator(ts_code: &str, end_date: &str, roe: &str, gross: Option<&str>, or_yoy: &str) -> finance_indicator::Model {
+        serde_json::from_value(json!({
+            "ts_code": ts_code,
+            "ann_date": end_date,
+            "end_date": end_date,
+            "roe": roe,
+            "grossprofit_margin": gross,
+            "or_yoy": or_yoy,
+            "update_flag": "1",
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_compare_fundamentals() {
+        let conn = test_util::memory_db().await;
+        test_util::seed(&conn, stock::Entity, vec![stock("600000.SH"), stock("601166.SH")]).await;
+        let basics = vec![
+            basic("600000.SH", "20240102", Some(8), 2, 3_000_000),
+            basic("600000.SH", "20240103", Some(6), 1, 3_100_000),
+            // 亏损, 市盈率缺失
+            basic("601166.SH", "20240103", None, 2, 4_000_000),
+        ];
+        test_util::seed(&conn, stock_daily_basic::Entity, basics).await;
+        let indicators = vec![
+            indicator("600000.SH", "20230930", "8.5", Some("30.0"), "-2.0"),
+            indicator("600000.SH", "20231231", "10.5", None, "3.5"),
+            indicator("601166.SH", "20230930", "11.0", Some("34.0"), "1.0"),
+            indicator("601166.SH", "20231231", "12.0", Some("35.0"), "3.5"),
+            // 601166.SH 尚未披露, 不参与对比
+            indicator("600000.SH", "20240331", "2.5", Some("31.0"), "9.0"),
+        ];
+        test_util::seed(&conn, finance_indicator::Entity, indicators).await;
+
+        let cmp = compare_fundamentals("600000.SH", "601166.SH", &conn).await.unwrap();
+        assert_eq!(cmp.a.trade_date.as_deref(), Some("20240103"));
+        assert_eq!(cmp.a.end_date.as_deref(), Some("20231231"));
+        assert_eq!(cmp.b.end_date.as_deref(), Some("20231231"));
+
+        let row = |metric: &str| cmp.rows.iter().find(|r| r.metric == metric).unwrap().clone();
+        assert_eq!(row("pe_ttm"), MetricComparison { metric: "pe_ttm", a: Some(6.0), b: None, better: None });
+        assert_eq!(row("pb").better, Some(Better::A));
+        assert_eq!(row("total_mv").better, None);
+        assert_eq!(row("roe").better, Some(Better::B));
+        assert_eq!(row("grossprofit_margin"), MetricComparison { metric: "grossprofit_margin", a: None, b: Some(35.0), better: None });
+        assert_eq!(row("revenue_yoy").better, Some(Better::Equal));
+
+        assert!(compare_fundamentals("600000.SH", "000000.SZ", &conn).await.is_err());
+    }
+
+    #[test]
+    fn test_better_lower_positive() {
+        assert_eq!(better(Some(-5.0), Some(20.0), Direction::LowerPositive), Some(Better::B));
+        assert_eq!(better(Some(10.0), Some(20.0), Direction::LowerPositive), Some(Better::A));
+        assert_eq!(better(Some(-5.0), Some(-1.0), Direction::LowerPositive), None);
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+
+use anyhow::{anyhow, bail};
+use num_traits::ToPrimitive;
+use serde::{Deserialize, Serialize};
+
+use common::finance::pct_chg;
+use entity::sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use entity::stock_daily;
+
+use crate::analysis::{cached_analysis, AnalysisKind};
+use crate::scan::market_scan;
+use crate::stock::quote_cache::QuoteCache;
+
+/// 一年的交易日数
+const TRADE_DAYS_OF_YEAR: usize = 250;
+/// 批量概览单次最多股票数
+const MAX_BATCH_SIZE: usize = 200;
+/// 批量概览的最大并发查询数
+const BATCH_CONCURRENCY: usize = 8;
+
+/// 股票概览
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StockOverview {
+    pub ts_code: String,
+    pub name: Option<String>,
+    pub industry: Option<String>,
+    pub trade_date: String,
+    pub close: f64,
+    pub pct_chg: Option<f64>,
+    pub week52: Week52Range,
+}
+
+/// 最近 52 周(约 250 个交易日)收盘价区间
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Week52Range {
+    pub high: f64,
+    pub low: f64,
+    pub pct_from_high: f64, // 当前价距 52 周最高价 x%100, <= 0
+    pub pct_from_low: f64,  // 当前价距 52 周最低价 x%100, >= 0
+    pub days: usize,        // 实际参与计算的交易日数
+    pub full_year: bool,    // 上市不满一年时为 false, 区间只基于已有数据
+}
+
+/// 股票概览, 同一交易日内的结果会被缓存
+pub async fn stock_overview(ts_code: &str, quotes: &QuoteCache, conn: &DatabaseConnection) -> anyhow::Result<StockOverview> {
+    cached_analysis(ts_code, AnalysisKind::Overview, conn, || compute_stock_overview(ts_code, quotes, conn)).await
+}
+
+async fn compute_stock_overview(ts_code: &str, quotes: &QuoteCache, conn: &DatabaseConnection) -> anyhow::Result<StockOverview> {
+    let stock = super::get_stock(ts_code, conn).await?;
+    let latest = quotes.latest_close(ts_code, conn).await?;
+    // 52 周区间截止到缓存的最新收盘日, 与当前价保持一致
+    let dailies = stock_daily::Entity::find()
+        .filter(ColumnTrait::eq(&stock_daily::Column::TsCode, ts_code))
+        .filter(stock_daily::Column::TradeDate.lte(&latest.trade_date))
+        .order_by_desc(stock_daily::Column::TradeDate)
+        .limit(TRADE_DAYS_OF_YEAR as u64)
+        .all(conn)
+        .await?;
+    let closes = dailies.iter().map(|d| d.close.to_f64()).collect::<Option<Vec<f64>>>().ok_or(anyhow!("invalid close price"))?;
+    let week52 = week52_range(&closes).ok_or(anyhow!("no daily price for {}", ts_code))?;
+    Ok(StockOverview {
+        ts_code: stock.ts_code,
+        name: stock.name,
+        industry: stock.industry,
+        trade_date: latest.trade_date,
+        close: latest.close,
+        pct_chg: latest.pct_chg,
+        week52,
+    })
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchOverviewRequest {
+    pub ts_codes: Vec<String>,
+}
+
+/// 批量概览中单只股票的结果, 查询失败时 `overview` 为 None, `error` 为失败原因
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchOverviewItem {
+    pub ts_code: String,
+    pub overview: Option<StockOverview>,
+    pub error: Option<String>,
+}
+
+/// 批量查询股票概览, 单只股票失败不影响其它股票, 结果顺序与 `ts_codes` 一致
+pub async fn stock_overviews(ts_codes: Vec<String>, quotes: &QuoteCache, conn: &DatabaseConnection) -> anyhow::Result<Vec<BatchOverviewItem>> {
+    if ts_codes.len() > MAX_BATCH_SIZE {
+        bail!("too many ts_codes: {}, max: {}", ts_codes.len(), MAX_BATCH_SIZE);
+    }
+    let order = ts_codes.iter().enumerate().map(|(i, ts_code)| (ts_code.clone(), i)).collect::<HashMap<String, usize>>();
+    let mut items = market_scan(ts_codes, BATCH_CONCURRENCY, |ts_code| async move { stock_overview(&ts_code, quotes, conn).await })
+        .await
+        .into_iter()
+        .map(|(ts_code, result)| match result {
+            Ok(overview) => BatchOverviewItem { ts_code, overview: Some(overview), error: None },
+            Err(e) => BatchOverviewItem { ts_code, overview: None, error: Some(e.to_string()) },
+        })
+        .collect::<Vec<_>>();
+    items.sort_by_key(|item| order.get(&item.ts_code).copied());
+    Ok(items)
+}
+
+/// # Arguments
+/// - `closes` 收盘价, 日期按逆序排序, 第一个为当前价
+fn week52_range(closes: &[f64]) -> Option<Week52Range> {
+    let closes = &closes[..closes.len().min(TRADE_DAYS_OF_YEAR)];
+    let close = *closes.first()?;
+    let high = closes.iter().copied().fold(f64::MIN, f64::max);
+    let low = closes.iter().copied().fold(f64::MAX, f64::min);
+    Some(Week52Range {
+        high,
+        low,
+        pct_from_high: pct_chg(high, close),
+        pct_from_low: pct_chg(low, close),
+        days: closes.len(),
+        full_year: closes.len() >= TRADE_DAYS_OF_YEAR,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use entity::stock;
+
+    #[test]
+    fn test_week52_range_at_high() {
+        let mut closes = vec![12f64, 11f64, 8f64];
+        closes.extend(std::iter::repeat_n(10f64, 300));
+        let range = week52_range(&closes).unwrap();
+        assert_eq!(range.high, 12f64);
+        assert_eq!(range.low, 8f64);
+        assert_eq!(range.pct_from_high, 0f64);
+        assert!((range.pct_from_low - 50f64).abs() < 1e-9);
+        assert_eq!(range.days, TRADE_DAYS_OF_YEAR);
+        assert!(range.full_year);
+
+        // 上市不满一年
+        let range = week52_range(&[10f64, 12f64, 9f64]).unwrap();
+        assert_eq!((range.days, range.full_year), (3, false));
+        assert!(week52_range(&[]).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_stock_overviews_isolates_errors() {
+        let conn = test_util::memory_db().await;
+        let stock = stock::Model {
+            name: Some("浦发银行".to_string()),
+            industry: Some("银行".to_string()),
+            ..test_util::stock("600000.SH")
+        };
+        test_util::seed(&conn, stock::Entity, vec![stock]).await;
+        let daily = test_util::daily("600000.SH", "20240102", 10);
+        test_util::seed(&conn, stock_daily::Entity, vec![daily]).await;
+
+        let ts_codes = vec!["999999.SH".to_string(), "600000.SH".to_string()];
+        let items = stock_overviews(ts_codes, &QuoteCache::default(), &conn).await.unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].ts_code, "999999.SH");
+        assert!(items[0].overview.is_none() && items[0].error.is_some());
+        assert_eq!(items[1].ts_code, "600000.SH");
+        assert_eq!(items[1].overview.as_ref().map(|o| o.close), Some(10f64));
+    }
+}
@@ -0,0 +1,109 @@
+use anyhow::anyhow;
+use chrono::Local;
+use entity::sea_orm::sea_query::OnConflict;
+use entity::sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect, Set};
+use entity::stock_peer_map;
+
+// A股 / 美股 关联映射
+//
+// `common::llm::calculate_stock_similarity` 返回的是结构化文本, 这里解析出各维度评分后落库,
+// 之后可以按 A 股代码查询综合关联度最高的美股
+
+/// 一对 A股 / 美股 的相似度评分(0~100)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PeerScores {
+    pub composite: i32,
+    pub main_business: Option<i32>,
+    pub industry: Option<i32>,
+    pub concept: Option<i32>,
+}
+
+impl PeerScores {
+    /// 从 LLM 输出中解析评分, 格式如 `- 综合关联度：85 / 100`, 缺少综合关联度时返回错误
+    pub fn parse(text: &str) -> anyhow::Result<Self> {
+        let composite = find_score(text, "综合关联度").ok_or(anyhow!("composite score not found in llm output"))?;
+        Ok(Self {
+            composite,
+            main_business: find_score(text, "主营业务相似度"),
+            industry: find_score(text, "行业板块相似度"),
+            concept: find_score(text, "概念板块相似度"),
+        })
+    }
+}
+
+fn find_score(text: &str, label: &str) -> Option<i32> {
+    text.lines().find_map(|line| {
+        let rest = line.split_once(label)?.1;
+        let rest = rest.trim_start_matches(|c: char| c == '：' || c == ':' || c.is_whitespace());
+        let score = rest.split('/').next()?.trim();
+        score.parse::<i32>().ok().filter(|v| (0..=100).contains(v))
+    })
+}
+
+/// 写入或更新一对 A股 / 美股 的评分
+pub async fn upsert_peer(cn_code: &str, us_code: &str, scores: PeerScores, conn: &DatabaseConnection) -> anyhow::Result<()> {
+    let model = stock_peer_map::ActiveModel {
+        cn_code: Set(cn_code.to_string()),
+        us_code: Set(us_code.to_string()),
+        composite_score: Set(scores.composite),
+        main_business_score: Set(scores.main_business),
+        industry_score: Set(scores.industry),
+        concept_score: Set(scores.concept),
+        computed_at: Set(Local::now().format("%Y-%m-%d %H:%M:%S").to_string()),
+    };
+    let on_conflict = OnConflict::columns([stock_peer_map::Column::CnCode, stock_peer_map::Column::UsCode])
+        .update_columns([
+            stock_peer_map::Column::CompositeScore,
+            stock_peer_map::Column::MainBusinessScore,
+            stock_peer_map::Column::IndustryScore,
+            stock_peer_map::Column::ConceptScore,
+            stock_peer_map::Column::ComputedAt,
+        ])
+        .to_owned();
+    stock_peer_map::Entity::insert(model).on_conflict(on_conflict).exec(conn).await?;
+    Ok(())
+}
+
+/// 与 A 股 `cn_code` 综合关联度最高的 `n` 只美股, 按评分从高到低
+pub async fn top_peers(cn_code: &str, n: u64, conn: &DatabaseConnection) -> anyhow::Result<Vec<stock_peer_map::Model>> {
+    let peers = stock_peer_map::Entity::find()
+        .filter(stock_peer_map::Column::CnCode.eq(cn_code))
+        .order_by_desc(stock_peer_map::Column::CompositeScore)
+        .order_by_asc(stock_peer_map::Column::UsCode)
+        .limit(n)
+        .all(conn)
+        .await?;
+    Ok(peers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    #[test]
+    fn test_parse_scores() {
+        let text = "#### 1. 主营业务关联性\n- 分析说明：……\n- 主营业务相似度：80 / 100\n\
+            - 行业板块相似度: 60/100\n- 概念板块相似度：X / 100\n### 二、综合结果\n- 综合关联度：72 / 100\n";
+        let scores = PeerScores::parse(text).unwrap();
+        assert_eq!(scores, PeerScores { composite: 72, main_business: Some(80), industry: Some(60), concept: None });
+        assert!(PeerScores::parse("- 主营业务相似度：80 / 100").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_top_peers() {
+        let conn = test_util::memory_db().await;
+        test_util::create_table(&conn, stock_peer_map::Entity).await;
+        let score = |composite| PeerScores { composite, ..Default::default() };
+        upsert_peer("300063.SZ", "IRDM", score(60), &conn).await.unwrap();
+        upsert_peer("300063.SZ", "ASTS", score(85), &conn).await.unwrap();
+        upsert_peer("300063.SZ", "GSAT", score(40), &conn).await.unwrap();
+        upsert_peer("600000.SH", "JPM", score(95), &conn).await.unwrap();
+        // 重复计算覆盖旧评分
+        upsert_peer("300063.SZ", "IRDM", score(90), &conn).await.unwrap();
+
+        let peers = top_peers("300063.SZ", 2, &conn).await.unwrap();
+        let codes: Vec<_> = peers.iter().map(|p| (p.us_code.as_str(), p.composite_score)).collect();
+        assert_eq!(codes, vec![("IRDM", 90), ("ASTS", 85)]);
+    }
+}
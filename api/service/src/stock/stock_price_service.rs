@@ -1,20 +1,44 @@
 use chrono::NaiveDate;
-use entity::sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, Condition};
+use common::db::find_between;
+use entity::sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect, Condition, Order};
 use entity::stock_daily;
 use futures::stream::{StreamExt, TryStreamExt};
 use std::collections::HashMap;
+use crate::security::SecurityPrice;
 
 pub async fn get_stock_prices(ts_code: &str, start_date: &NaiveDate, end_date: &NaiveDate, conn: &DatabaseConnection) -> anyhow::Result<Vec<stock_daily::Model>> {
     let start = start_date.format(common::date::FORMAT).to_string();
     let end = end_date.format(common::date::FORMAT).to_string();
-    let stock_prices: Vec<stock_daily::Model> = stock_daily::Entity::find()
+    find_between::<stock_daily::Entity>(conn, stock_daily::Column::TsCode, stock_daily::Column::TradeDate, ts_code, &start, &end, Order::Desc).await
+}
+
+/// 目标日期当天的行情, 目标日期是周末、节假日或停牌日时取之前最近一个有行情的交易日, 之前都没有行情时返回 None
+pub async fn price_on_or_before(ts_code: &str, date: &NaiveDate, conn: &DatabaseConnection) -> anyhow::Result<Option<SecurityPrice>> {
+    let date = date.format(common::date::FORMAT).to_string();
+    let price = stock_daily::Entity::find()
         .filter(ColumnTrait::eq(&stock_daily::Column::TsCode, ts_code))
-        .filter(stock_daily::Column::TradeDate.gte(&start))
-        .filter(stock_daily::Column::TradeDate.lte(&end))
+        .filter(stock_daily::Column::TradeDate.lte(date))
         .order_by_desc(stock_daily::Column::TradeDate)
-        .all(conn)
+        .one(conn)
         .await?;
-    Ok(stock_prices)
+    Ok(price.map(SecurityPrice::from_stock_daily))
+}
+
+/// 全市场在目标日期当天或之前最近一个有行情的交易日, 之前都没有行情时返回 None
+///
+/// 与 `price_on_or_before` 相同的取日规则, 用于按交易日查询全市场行情的场景
+pub async fn trade_date_on_or_before(date: &NaiveDate, conn: &DatabaseConnection) -> anyhow::Result<Option<String>> {
+    let date = date.format(common::date::FORMAT).to_string();
+    let trade_date = stock_daily::Entity::find()
+        .select_only()
+        .column(stock_daily::Column::TradeDate)
+        .filter(stock_daily::Column::TradeDate.lte(date))
+        .order_by_desc(stock_daily::Column::TradeDate)
+        .limit(1)
+        .into_tuple::<String>()
+        .one(conn)
+        .await?;
+    Ok(trade_date)
 }
 
 /// 批量查询多支股票的价格数据
@@ -76,4 +100,36 @@ pub async fn get_stock_prices_batch(ts_codes: &[String], start_date: &NaiveDate,
     }
 
     Ok(grouped_prices)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    fn price(date: &str, close: i64) -> stock_daily::Model {
+        test_util::daily("000001.SZ", date, close)
+    }
+
+    #[tokio::test]
+    async fn test_price_on_or_before() {
+        let conn = test_util::memory_db().await;
+        test_util::seed(&conn, stock_daily::Entity, vec![price("20240104", 10), price("20240105", 11), price("20240108", 12)]).await;
+        let date = |s: &str| NaiveDate::parse_from_str(s, common::date::FORMAT).unwrap();
+
+        // 20240106 是周六, 取周五的收盘价
+        let saturday = price_on_or_before("000001.SZ", &date("20240106"), &conn).await.unwrap().unwrap();
+        assert_eq!(saturday.trade_date, "20240105");
+        assert_eq!(saturday.close, Some(11.0));
+
+        let monday = price_on_or_before("000001.SZ", &date("20240108"), &conn).await.unwrap().unwrap();
+        assert_eq!(monday.trade_date, "20240108");
+
+        assert!(price_on_or_before("000001.SZ", &date("20240103"), &conn).await.unwrap().is_none());
+        assert!(price_on_or_before("600000.SH", &date("20240106"), &conn).await.unwrap().is_none());
+
+        assert_eq!(trade_date_on_or_before(&date("20240107"), &conn).await.unwrap().as_deref(), Some("20240105"));
+        assert_eq!(trade_date_on_or_before(&date("20240108"), &conn).await.unwrap().as_deref(), Some("20240108"));
+        assert!(trade_date_on_or_before(&date("20240103"), &conn).await.unwrap().is_none());
+    }
+}
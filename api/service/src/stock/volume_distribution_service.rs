@@ -1,5 +1,6 @@
 use entity::sea_orm::{DatabaseConnection, EntityTrait, QueryFilter, ColumnTrait, QueryOrder, Condition};
 use entity::stock_daily;
+use crate::stock::stock_price_service;
 use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 use anyhow::{Result, Context};
@@ -114,9 +115,13 @@ pub async fn get_volume_distribution(
 ) -> Result<VolumeDistributionResponse> {
     info!("分析交易日 {} 的成交量分布", trade_date);
     
-    // 验证日期格式
-    let _date = NaiveDate::parse_from_str(trade_date, "%Y%m%d")
+    // 验证日期格式, 不是交易日时取之前最近一个交易日
+    let date = NaiveDate::parse_from_str(trade_date, "%Y%m%d")
         .context("日期格式错误，应为 YYYYMMDD")?;
+    let trade_date = stock_price_service::trade_date_on_or_before(&date, conn)
+        .await?
+        .with_context(|| format!("交易日 {} 及之前没有数据", trade_date))?;
+    let trade_date = trade_date.as_str();
     
     // 获取当日所有股票数据，按成交量降序排列
     let stocks = stock_daily::Entity::find()
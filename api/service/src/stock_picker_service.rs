@@ -8,10 +8,11 @@ use serde::{Deserialize, Serialize};
 use entity::sea_orm::{DatabaseConnection, EntityTrait, JsonValue, QueryFilter, QueryOrder};
 use entity::{stock, stock_daily, stock_daily_basic, finance_indicator, income, cashflow, balancesheet, cn_security_info};
 use entity::sea_orm::ColumnTrait;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::{Arc, Mutex};
 use tracing::{info, warn};
 use common::task_runner::run_with_limit;
+use rust_decimal::prelude::ToPrimitive;
 use crate::strategy::{
     PriceVolumeCandlestickStrategy, PriceVolumeStrategyConfig,
     BottomVolumeSurgeStrategy, BottomVolumeSurgeConfig,
@@ -39,6 +40,10 @@ use crate::strategy::{
 };
 
 use crate::strategy::traits::{SecurityData, StrategyResult, StrategySignal, TradingStrategy, FinancialData};
+use crate::scan::market_scan;
+use crate::universe::Universe;
+use common::indicators::bullish_rsi_divergences;
+use std::future::Future;
 
 /// 选股结果
 #[derive(Debug, Clone, Serialize)]
@@ -73,6 +78,7 @@ impl StockPickerService {
     ///   - 可以为 `None`，使用默认配置
     ///   - 可以包含 `"preset"` 字段来指定预设配置（如 `{"preset": "aggressive"}`）
     ///   - 可以直接提供完整的配置参数（如 `{"lookback_days": 10, "ma_type": "MA5"}`）
+    /// - `universe`: 候选股票范围
     ///
     /// # 预设配置支持
     /// - **turtle**: system1, system2, conservative, aggressive
@@ -84,18 +90,18 @@ impl StockPickerService {
     /// # 示例
     /// ```rust
     /// // 使用默认配置
-    /// service.pick_stocks(&start, &end, "turtle", None).await?;
+    /// service.pick_stocks(&start, &end, "turtle", None, &Universe::All).await?;
     ///
     /// // 使用预设配置
     /// let preset = serde_json::json!({"preset": "aggressive"});
-    /// service.pick_stocks(&start, &end, "turtle", Some(preset)).await?;
+    /// service.pick_stocks(&start, &end, "turtle", Some(preset), &Universe::All).await?;
     ///
     /// // 使用自定义配置
     /// let custom = serde_json::json!({
     ///     "entry_breakout_period": 30,
     ///     "exit_breakout_period": 15
     /// });
-    /// service.pick_stocks(&start, &end, "turtle", Some(custom)).await?;
+    /// service.pick_stocks(&start, &end, "turtle", Some(custom), &Universe::Industry("银行".to_string())).await?;
     /// ```
     pub async fn pick_stocks(
         &self,
@@ -103,7 +109,21 @@ impl StockPickerService {
         end_date: &NaiveDate,
         strategy_type: &str,
         settings: Option<JsonValue>,
+        universe: &Universe,
     ) -> Result<Vec<StockPickResult>> {
+        Ok(self.pick_stocks_explain(start_date, end_date, strategy_type, settings, universe).await?.items)
+    }
+
+    /// 与 `pick_stocks` 相同, 另外返回候选范围内每只被排除的股票未满足的条件:
+    /// 数据不足(`data_points`)、策略分析失败(`analysis`)、信号低于买入(`signal`, 取值 0 强烈卖出 ~ 4 强烈买入)
+    pub async fn pick_stocks_explain(
+        &self,
+        start_date: &NaiveDate,
+        end_date: &NaiveDate,
+        strategy_type: &str,
+        settings: Option<JsonValue>,
+        universe: &Universe,
+    ) -> Result<ExplainedScreen<StockPickResult>> {
 
         let ts_code: Option<String> = settings
             .as_ref()
@@ -155,7 +175,7 @@ impl StockPickerService {
         macro_rules! execute_strategy {
             ($config:ty, $strategy:ty, $preset_handler:expr) => {{
                 let mut strategy = create_strategy!($config, $strategy, $preset_handler);
-                self.pick_stocks_internal(&mut strategy, strategy_type, target_datas.clone(), start_date, end_date, None).await
+                self.pick_stocks_internal(&mut strategy, strategy_type, target_datas.clone(), start_date, end_date, universe, None).await
             }};
         }
 
@@ -355,7 +375,7 @@ impl StockPickerService {
             ),
             _ => bail!("不支持的策略类型: {}。支持的类型: price_volume_candlestick, bottom_volume_surge, long_term_bottom_reversal, yearly_high, price_strength, distressed_reversal, single_limit_up, fundamental, consecutive_strong, turtle, limit_up_pullback, strong_close, quality_value, turnover_ma_bullish, turnover_rise, daily_rise_turnover, ma_divergence_volume, low_shadow, ma_convergence, consecutive_bullish, low_turnover_dividend_roe_smallcap, rise_range_consolidation, ma_breakout", strategy_type)
         }?;
-        for result in &mut results {
+        for result in &mut results.items {
             let tscode = &result.ts_code;
             let concepts = cn_security_info::Entity::find_by_id(tscode).one(&self.db).await?.and_then(|entity| entity.concepts);
             result.concepts = concepts;
@@ -380,8 +400,9 @@ impl StockPickerService {
         target_datas: Arc<HashMap<String, SecurityData>>,
         start_date: &NaiveDate,
         end_date: &NaiveDate,
+        universe: &Universe,
         min_signal: Option<StrategySignal>,
-    ) -> Result<Vec<StockPickResult>> {
+    ) -> Result<ExplainedScreen<StockPickResult>> {
         let min_signal = min_signal.unwrap_or(StrategySignal::Buy);
 
         info!(
@@ -391,12 +412,19 @@ impl StockPickerService {
             end_date,
             min_signal
         );
-        // 获取所有股票列表
-        let stocks = stock::Entity::find().all(&self.db).await?;
+        // 获取候选范围内的股票列表
+        let universe = universe.resolve_set(&self.db).await?;
+        let stocks: Vec<stock::Model> = stock::Entity::find()
+            .all(&self.db)
+            .await?
+            .into_iter()
+            .filter(|stock| universe.contains(&stock.ts_code))
+            .collect();
         info!("共获取 {} 只股票", stocks.len());
 
         let total = stocks.len();
         let prepared_data = Arc::new(Mutex::new(Vec::new()));
+        let excluded = Arc::new(Mutex::new(Vec::new()));
         let processed_count = Arc::new(std::sync::atomic::AtomicUsize::new(0));
         let db_conn = Arc::new(self.db.clone());
 
@@ -419,7 +447,7 @@ impl StockPickerService {
                     let db_conn = db_conn.clone();
                     let value = value.clone();
                     async move {
-                        // 使用静态方法准备股票分析数据
+                        // 使用静态方法准备股票分析数据, 数据不足或失败时返回未满足的条件
                         match StockPickerService::prepare_stock_data(
                             &*db_conn,
                             &stock_model.ts_code,
@@ -431,11 +459,13 @@ impl StockPickerService {
                         )
                             .await
                         {
-                            Ok(Some(data)) => Some((stock_model, data)),
-                            Ok(None) => None, // 数据不足，跳过
+                            Ok(PreparedData::Ready(data)) => Ok((stock_model, data)),
+                            Ok(PreparedData::Insufficient(points)) => {
+                                Err(check_at_least("data_points", Some(points as f64), required_data_points as f64))
+                            }
                             Err(e) => {
                                 warn!("准备股票 {} 数据失败: {}", stock_model.ts_code, e);
-                                None
+                                Err(check_failed("data_points", format!("failed to load data: {}", e)))
                             }
                         }
                     }
@@ -443,17 +473,19 @@ impl StockPickerService {
             },
             {
                 let prepared_data = prepared_data.clone();
+                let excluded = excluded.clone();
                 let processed_count = processed_count.clone();
-                move |_original_stock, data_result| {
+                move |original_stock: stock::Model, data_result| {
                     let prepared_data = prepared_data.clone();
+                    let excluded = excluded.clone();
                     let processed_count = processed_count.clone();
                     async move {
                         let current = processed_count.fetch_add(1, std::sync::atomic::Ordering::SeqCst) + 1;
 
-                        // 如果数据准备成功，添加到准备好的数据列表
-                        if let Some(data) = data_result {
-                            let mut prepared_guard = prepared_data.lock().unwrap();
-                            prepared_guard.push(data);
+                        // 如果数据准备成功，添加到准备好的数据列表，否则记录未满足的条件
+                        match data_result {
+                            Ok(data) => prepared_data.lock().unwrap().push(data),
+                            Err(check) => excluded.lock().unwrap().extend(explain(&original_stock.ts_code, vec![check])),
                         }
 
                         // 每处理100只股票输出进度
@@ -467,6 +499,7 @@ impl StockPickerService {
 
         // 第二阶段：串行进行策略分析
         let prepared_stocks = Arc::try_unwrap(prepared_data).unwrap().into_inner().unwrap();
+        let mut excluded = Arc::try_unwrap(excluded).unwrap().into_inner().unwrap();
         let prepared_count = prepared_stocks.len();
         info!("数据准备完成，开始策略分析，共 {} 只股票有效数据", prepared_count);
 
@@ -475,7 +508,11 @@ impl StockPickerService {
             match strategy.analyze(&stock_model.ts_code, &security_data) {
                 Ok(result) => {
                     // 筛选符合信号条件的股票
-                    if self.meets_signal_criteria(&result.strategy_signal(), &min_signal) {
+                    let signal = result.strategy_signal();
+                    if !self.meets_signal_criteria(&signal, &min_signal) {
+                        let check = check_at_least("signal", Some(signal as u8 as f64), min_signal.clone() as u8 as f64);
+                        excluded.extend(explain(&stock_model.ts_code, vec![check]));
+                    } else {
                         let pick_result = StockPickResult {
                             ts_code: stock_model.ts_code.clone(),
                             stock_name: stock_model.name.clone(),
@@ -495,6 +532,7 @@ impl StockPickerService {
                 }
                 Err(e) => {
                     warn!("分析股票 {} 失败: {}", stock_model.ts_code, e);
+                    excluded.extend(explain(&stock_model.ts_code, vec![check_failed("analysis", e.to_string())]));
                 }
             }
 
@@ -662,7 +700,8 @@ impl StockPickerService {
             results.len()
         );
 
-        Ok(results)
+        excluded.sort_by(|a, b| a.ts_code.cmp(&b.ts_code));
+        Ok(ExplainedScreen { items: results, excluded })
     }
 
     /// 准备股票分析数据（静态方法）
@@ -678,8 +717,8 @@ impl StockPickerService {
     /// - `required_points`: 策略所需的最少数据点数
     ///
     /// # 返回
-    /// - `Ok(Some(Vec<SecurityData>))`: 数据充足，返回转换后的数据
-    /// - `Ok(None)`: 数据不足，无法进行分析
+    /// - `Ok(PreparedData::Ready)`: 数据充足，返回转换后的数据
+    /// - `Ok(PreparedData::Insufficient)`: 数据不足，无法进行分析, 带实际数据点数
     /// - `Err`: 数据库查询错误
     async fn prepare_stock_data(
         db: &DatabaseConnection,
@@ -689,10 +728,10 @@ impl StockPickerService {
         end_date: &NaiveDate,
         required_points: usize,
         target_datas: Arc<HashMap<String, SecurityData>> //(trade_date, SecurityData)
-    ) -> Result<Option<Vec<SecurityData>>> {
+    ) -> Result<PreparedData> {
         // 获取股票日线数据
         if strategy_type == "" {
-            Self::get_financial_data(db, ts_code).await
+            Ok(Self::get_financial_data(db, ts_code).await?.map_or(PreparedData::Insufficient(0), PreparedData::Ready))
         } else {
             let daily_data = Self::get_stock_daily_data(db, ts_code, start_date, end_date).await?;
             // 检查数据是否足够
@@ -703,7 +742,7 @@ impl StockPickerService {
                 // required_points,
                 // daily_data.len()
                 // );
-                return Ok(None);
+                return Ok(PreparedData::Insufficient(daily_data.len()));
             }
 
             // 转换为 SecurityData
@@ -717,7 +756,7 @@ impl StockPickerService {
                 sec_data.target = target_data;
             }
 
-            Ok(Some(security_data))
+            Ok(PreparedData::Ready(security_data))
         }
     }
 
@@ -985,3 +1024,549 @@ impl StockPickerService {
         format!("{}{}", year, quarter)
     }
 }
+
+/// 选股时准备好的分析数据
+enum PreparedData {
+    Ready(Vec<SecurityData>),
+    /// 数据点不足, 带实际数据点数
+    Insufficient(usize),
+}
+
+/// 筛选条件的评估结果, `value` 为实际值, 数据缺失时为 None; 无法按数值评估时 `reason` 为原因
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CriterionCheck {
+    pub criterion: &'static str,
+    pub value: Option<f64>,
+    pub threshold: f64,
+    pub passed: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// 未通过筛选的股票及其未满足的条件
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ScreenExplanation {
+    pub ts_code: String,
+    pub failed: Vec<CriterionCheck>,
+}
+
+/// 带解释的筛选结果, `excluded` 为候选范围内被排除的股票, 按代码排序
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ExplainedScreen<T> {
+    pub items: Vec<T>,
+    pub excluded: Vec<ScreenExplanation>,
+}
+
+/// 不低于阈值
+fn check_at_least(criterion: &'static str, value: Option<f64>, threshold: f64) -> CriterionCheck {
+    CriterionCheck { criterion, value, threshold, passed: value.is_some_and(|v| v >= threshold), reason: None }
+}
+
+/// 严格高于阈值
+fn check_above(criterion: &'static str, value: Option<f64>, threshold: f64) -> CriterionCheck {
+    CriterionCheck { criterion, value, threshold, passed: value.is_some_and(|v| v > threshold), reason: None }
+}
+
+/// 无法按数值评估的条件(如分析出错、数据无法转换), 总是不通过
+fn check_failed(criterion: &'static str, reason: String) -> CriterionCheck {
+    CriterionCheck { criterion, value: None, threshold: 0f64, passed: false, reason: Some(reason) }
+}
+
+/// 未通过的条件, 全部通过时为 None
+fn explain(ts_code: &str, checks: Vec<CriterionCheck>) -> Option<ScreenExplanation> {
+    let failed: Vec<CriterionCheck> = checks.into_iter().filter(|c| !c.passed).collect();
+    (!failed.is_empty()).then(|| ScreenExplanation { ts_code: ts_code.to_string(), failed })
+}
+
+/// 流动性筛选结果
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct LiquidityItem {
+    pub ts_code: String,
+    /// 平均换手率(%)
+    pub avg_turnover_rate: f64,
+    /// 平均成交额(千元)
+    pub avg_amount: f64,
+}
+
+/// 流动性筛选: 最近 `window` 个交易日的平均换手率和平均成交额都不低于下限的股票, 按平均成交额降序
+///
+/// # 参数
+/// - `min_avg_turnover`: 平均换手率下限(%), 取自 `stock_daily_basic`
+/// - `min_avg_amount`: 平均成交额下限(千元), 取自 `stock_daily`
+/// - `window`: 统计的交易日数, 期间交易日不足 `window` 天的股票(新股/停牌)不参与筛选
+/// - `universe`: 候选股票范围
+pub async fn liquidity_screen(
+    min_avg_turnover: f64,
+    min_avg_amount: f64,
+    window: usize,
+    universe: &Universe,
+    conn: &DatabaseConnection,
+) -> Result<Vec<LiquidityItem>> {
+    Ok(liquidity_screen_explain(min_avg_turnover, min_avg_amount, window, universe, conn).await?.items)
+}
+
+/// 与 `liquidity_screen` 相同, 另外返回候选范围内每只被排除的股票未满足的条件(交易日数、平均换手率、平均成交额)
+pub async fn liquidity_screen_explain(
+    min_avg_turnover: f64,
+    min_avg_amount: f64,
+    window: usize,
+    universe: &Universe,
+    conn: &DatabaseConnection,
+) -> Result<ExplainedScreen<LiquidityItem>> {
+    if window == 0 {
+        bail!("window must be greater than 0");
+    }
+    let mut dates = crate::trade_calendar_service::get_trade_calendar(window as u64 + 1, conn).await?;
+    dates.truncate(window);
+    if dates.len() < window {
+        bail!("not enough trade dates, expected: {}, actual: {}", window, dates.len());
+    }
+    let start = &dates[window - 1].cal_date;
+    let end = &dates[0].cal_date;
+
+    let dailies = stock_daily::Entity::find()
+        .filter(stock_daily::Column::TradeDate.gte(start))
+        .filter(stock_daily::Column::TradeDate.lte(end))
+        .all(conn)
+        .await?;
+    let basics = stock_daily_basic::Entity::find()
+        .filter(stock_daily_basic::Column::TradeDate.gte(start))
+        .filter(stock_daily_basic::Column::TradeDate.lte(end))
+        .all(conn)
+        .await?;
+    let universe = universe.resolve(conn).await?;
+    let universe_set: HashSet<&str> = universe.iter().map(String::as_str).collect();
+    let dailies: Vec<stock_daily::Model> = dailies.into_iter().filter(|d| universe_set.contains(d.ts_code.as_str())).collect();
+    let basics: Vec<stock_daily_basic::Model> = basics.into_iter().filter(|b| universe_set.contains(b.ts_code.as_str())).collect();
+    Ok(explain_liquidity(&universe, &dailies, &basics, window, min_avg_turnover, min_avg_amount))
+}
+
+/// 逐只评估 `ts_codes` 中的股票, 没有任何行情的股票也会出现在 `excluded` 中
+fn explain_liquidity(
+    ts_codes: &[String],
+    dailies: &[stock_daily::Model],
+    basics: &[stock_daily_basic::Model],
+    window: usize,
+    min_avg_turnover: f64,
+    min_avg_amount: f64,
+) -> ExplainedScreen<LiquidityItem> {
+    let mut amounts: HashMap<&str, Vec<f64>> = HashMap::new();
+    for daily in dailies {
+        if let Some(amount) = daily.amount.to_f64() {
+            amounts.entry(daily.ts_code.as_str()).or_default().push(amount);
+        }
+    }
+    let mut turnovers: HashMap<&str, Vec<f64>> = HashMap::new();
+    for basic in basics {
+        if let Some(turnover) = basic.turnover_rate.and_then(|v| v.to_f64()) {
+            turnovers.entry(basic.ts_code.as_str()).or_default().push(turnover);
+        }
+    }
+
+    let mut ts_codes: Vec<&str> = ts_codes.iter().map(String::as_str).collect();
+    ts_codes.sort();
+    ts_codes.dedup();
+    let avg = |values: Option<&Vec<f64>>| values.filter(|v| !v.is_empty()).map(|v| v.iter().sum::<f64>() / v.len() as f64);
+    let (mut items, mut excluded) = (vec![], vec![]);
+    for ts_code in ts_codes {
+        let amounts = amounts.get(ts_code);
+        let (avg_turnover_rate, avg_amount) = (avg(turnovers.get(ts_code)), avg(amounts));
+        let checks = vec![
+            check_at_least("trade_days", Some(amounts.map_or(0, |v| v.len()) as f64), window as f64),
+            check_at_least("avg_turnover_rate", avg_turnover_rate, min_avg_turnover),
+            check_at_least("avg_amount", avg_amount, min_avg_amount),
+        ];
+        match (explain(ts_code, checks), avg_turnover_rate, avg_amount) {
+            (None, Some(avg_turnover_rate), Some(avg_amount)) => items.push(LiquidityItem { ts_code: ts_code.to_string(), avg_turnover_rate, avg_amount }),
+            (explanation, _, _) => excluded.extend(explanation),
+        }
+    }
+    items.sort_by(|a, b| b.avg_amount.total_cmp(&a.avg_amount));
+    ExplainedScreen { items, excluded }
+}
+
+/// 放量新高筛选结果
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct VolumeBreakoutItem {
+    pub ts_code: String,
+    pub trade_date: String,
+    pub close: f64,
+    /// 此前 `price_window - 1` 个交易日的最高收盘价
+    pub prev_high_close: f64,
+    /// 成交量(手)
+    pub vol: f64,
+    /// 此前 `volume_window - 1` 个交易日的最大成交量(手)
+    pub prev_max_vol: f64,
+}
+
+/// 放量新高筛选: 最新收盘价创 `price_window` 日收盘新高, 且最新成交量创 `volume_window` 日新高, 按放量倍数降序
+///
+/// 窗口包含最新交易日; 最新交易日停牌或期间交易日不足窗口天数的股票不参与筛选, 只筛选 `universe` 范围内的股票
+pub async fn volume_breakout_screen(
+    price_window: usize,
+    volume_window: usize,
+    universe: &Universe,
+    conn: &DatabaseConnection,
+) -> Result<Vec<VolumeBreakoutItem>> {
+    Ok(volume_breakout_screen_explain(price_window, volume_window, universe, conn).await?.items)
+}
+
+/// 与 `volume_breakout_screen` 相同, 另外返回候选范围内每只被排除的股票未满足的条件(交易日数、收盘价新高、成交量新高)
+pub async fn volume_breakout_screen_explain(
+    price_window: usize,
+    volume_window: usize,
+    universe: &Universe,
+    conn: &DatabaseConnection,
+) -> Result<ExplainedScreen<VolumeBreakoutItem>> {
+    if price_window < 2 || volume_window < 2 {
+        bail!("price_window and volume_window must be at least 2");
+    }
+    let window = price_window.max(volume_window);
+    let mut dates = crate::trade_calendar_service::get_trade_calendar(window as u64 + 1, conn).await?;
+    dates.truncate(window);
+    if dates.len() < window {
+        bail!("not enough trade dates, expected: {}, actual: {}", window, dates.len());
+    }
+    let start = &dates[window - 1].cal_date;
+    let end = &dates[0].cal_date;
+
+    let dailies = stock_daily::Entity::find()
+        .filter(stock_daily::Column::TradeDate.gte(start))
+        .filter(stock_daily::Column::TradeDate.lte(end))
+        .order_by_asc(stock_daily::Column::TradeDate)
+        .all(conn)
+        .await?;
+    let universe = universe.resolve(conn).await?;
+    let universe_set: HashSet<&str> = universe.iter().map(String::as_str).collect();
+    let dailies: Vec<stock_daily::Model> = dailies.into_iter().filter(|d| universe_set.contains(d.ts_code.as_str())).collect();
+    Ok(explain_volume_breakout(&universe, &dailies, end, price_window, volume_window))
+}
+
+/// 逐只评估 `ts_codes` 中的股票, `dailies` 日期按正序排序, `latest_date` 为最新交易日
+///
+/// 历史不足或最新交易日停牌时只报告交易日数条件, 收盘价或成交量无法转换为数值时报告原因
+fn explain_volume_breakout(
+    ts_codes: &[String],
+    dailies: &[stock_daily::Model],
+    latest_date: &str,
+    price_window: usize,
+    volume_window: usize,
+) -> ExplainedScreen<VolumeBreakoutItem> {
+    let mut by_code: HashMap<&str, Vec<&stock_daily::Model>> = HashMap::new();
+    for daily in dailies {
+        by_code.entry(daily.ts_code.as_str()).or_default().push(daily);
+    }
+
+    let mut ts_codes: Vec<&str> = ts_codes.iter().map(String::as_str).collect();
+    ts_codes.sort();
+    ts_codes.dedup();
+    let window = price_window.max(volume_window);
+    let max_of = |values: &[f64]| values.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let (mut items, mut excluded) = (vec![], vec![]);
+    for ts_code in ts_codes {
+        let prices = by_code.get(ts_code).map(Vec::as_slice).unwrap_or_default();
+        // 最新交易日停牌时视为交易日不足
+        let trade_days = if prices.last().is_some_and(|p| p.trade_date == latest_date) { prices.len() } else { 0 };
+        let days_check = check_at_least("trade_days", Some(trade_days as f64), window as f64);
+        if !days_check.passed {
+            excluded.extend(explain(ts_code, vec![days_check]));
+            continue;
+        }
+        let closes = prices.iter().map(|p| p.close.to_f64()).collect::<Option<Vec<_>>>();
+        let vols = prices.iter().map(|p| p.vol.to_f64()).collect::<Option<Vec<_>>>();
+        let (Some(closes), Some(vols)) = (closes, vols) else {
+            excluded.extend(explain(ts_code, vec![check_failed("numeric_prices", "close or vol is not a valid number".to_string())]));
+            continue;
+        };
+        let (close, vol) = (closes[closes.len() - 1], vols[vols.len() - 1]);
+        let prev_high_close = max_of(&closes[closes.len() - price_window..closes.len() - 1]);
+        let prev_max_vol = max_of(&vols[vols.len() - volume_window..vols.len() - 1]);
+        let checks = vec![
+            days_check,
+            check_above("close_new_high", Some(close), prev_high_close),
+            check_above("vol_new_high", Some(vol), prev_max_vol),
+        ];
+        match explain(ts_code, checks) {
+            None => items.push(VolumeBreakoutItem {
+                ts_code: ts_code.to_string(),
+                trade_date: latest_date.to_string(),
+                close,
+                prev_high_close,
+                vol,
+                prev_max_vol,
+            }),
+            Some(explanation) => excluded.push(explanation),
+        }
+    }
+    let ratio = |item: &VolumeBreakoutItem| if item.prev_max_vol > 0f64 { item.vol / item.prev_max_vol } else { f64::INFINITY };
+    items.sort_by(|a, b| ratio(b).total_cmp(&ratio(a)));
+    ExplainedScreen { items, excluded }
+}
+
+/// 底背离扫描使用的 RSI 周期
+const DIVERGENCE_RSI_PERIOD: usize = 14;
+/// 确认价格低点需要的左右两侧K线数
+const DIVERGENCE_PIVOT_WINDOW: usize = 3;
+/// 背离在最近多少根K线内确认才算新出现
+const DIVERGENCE_RECENT_BARS: usize = 3;
+/// 底背离扫描的最大并发查询数
+const DIVERGENCE_SCAN_CONCURRENCY: usize = 8;
+
+/// RSI 底背离扫描结果
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct DivergenceItem {
+    pub ts_code: String,
+    /// 前一个价格低点
+    pub prev_trade_date: String,
+    pub prev_close: f64,
+    pub prev_rsi: f64,
+    /// 创新低的价格低点
+    pub trade_date: String,
+    pub close: f64,
+    pub rsi: f64,
+    /// 两个低点间 RSI 抬升的点数, 越大背离越强
+    pub strength: f64,
+    /// 背离确认后经过的K线数, 0 表示最新交易日刚确认
+    pub bars_since_confirmed: usize,
+}
+
+/// 全市场 RSI 底背离扫描: 价格创新低而 RSI 低点抬高, 且背离在最近几根K线内确认, 按背离强度降序
+///
+/// # 参数
+/// - `lookback`: 每只股票使用的交易日数, 需要容纳 RSI 预热期和两个价格低点
+/// - `universe`: 候选股票范围
+pub async fn divergence_scan(lookback: usize, universe: &Universe, conn: &DatabaseConnection) -> Result<Vec<DivergenceItem>> {
+    let min_lookback = DIVERGENCE_RSI_PERIOD + 4 * DIVERGENCE_PIVOT_WINDOW + 2;
+    if lookback < min_lookback {
+        bail!("lookback must be at least {}", min_lookback);
+    }
+    let mut dates = crate::trade_calendar_service::get_trade_calendar(lookback as u64 + 1, conn).await?;
+    dates.truncate(lookback);
+    if dates.len() < lookback {
+        bail!("not enough trade dates, expected: {}, actual: {}", lookback, dates.len());
+    }
+    let start = dates[lookback - 1].cal_date.as_str();
+    let end = dates[0].cal_date.as_str();
+
+    let ts_codes = universe.resolve(conn).await?;
+    Ok(scan_divergences(ts_codes, |ts_code| async move {
+        let dailies = stock_daily::Entity::find()
+            .filter(ColumnTrait::eq(&stock_daily::Column::TsCode, &ts_code))
+            .filter(stock_daily::Column::TradeDate.gte(start))
+            .filter(stock_daily::Column::TradeDate.lte(end))
+            .order_by_asc(stock_daily::Column::TradeDate)
+            .all(conn)
+            .await?;
+        Ok(dailies)
+    }).await)
+}
+
+/// `load` 返回单只股票按日期正序排序的日线
+async fn scan_divergences<F, Fut>(ts_codes: Vec<String>, load: F) -> Vec<DivergenceItem>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<Vec<stock_daily::Model>>>,
+{
+    let results = market_scan(ts_codes, DIVERGENCE_SCAN_CONCURRENCY, |ts_code| {
+        let dailies = load(ts_code.clone());
+        async move { Ok(recent_divergence(&ts_code, &dailies.await?)) }
+    }).await;
+
+    let mut items = vec![];
+    for (ts_code, result) in results {
+        match result {
+            Ok(Some(item)) => items.push(item),
+            Ok(None) => {}
+            Err(e) => warn!("divergence scan failed, ts_code: {}, error: {:?}", ts_code, e),
+        }
+    }
+    items.sort_by(|a, b| b.strength.total_cmp(&a.strength));
+    items
+}
+
+/// 最近一次底背离, 确认时间不在最近 `DIVERGENCE_RECENT_BARS` 根K线内时返回 None
+fn recent_divergence(ts_code: &str, dailies: &[stock_daily::Model]) -> Option<DivergenceItem> {
+    let closes = dailies.iter().map(|d| d.close.to_f64()).collect::<Option<Vec<f64>>>()?;
+    let divergences = bullish_rsi_divergences(&closes, DIVERGENCE_RSI_PERIOD, DIVERGENCE_PIVOT_WINDOW).ok()?;
+    let divergence = divergences.last()?;
+    let bars_since_confirmed = closes.len() - 1 - divergence.confirmed_index(DIVERGENCE_PIVOT_WINDOW);
+    (bars_since_confirmed < DIVERGENCE_RECENT_BARS).then(|| DivergenceItem {
+        ts_code: ts_code.to_string(),
+        prev_trade_date: dailies[divergence.prev_index].trade_date.clone(),
+        prev_close: divergence.prev_price,
+        prev_rsi: divergence.prev_rsi,
+        trade_date: dailies[divergence.index].trade_date.clone(),
+        close: divergence.price,
+        rsi: divergence.rsi,
+        strength: divergence.strength(),
+        bars_since_confirmed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+    use rust_decimal::Decimal;
+
+    fn daily(ts_code: &str, trade_date: &str, amount: i64) -> stock_daily::Model {
+        stock_daily::Model { amount: Decimal::from(amount), ..test_util::daily(ts_code, trade_date, 1) }
+    }
+
+    fn basic(ts_code: &str, trade_date: &str, turnover_rate: i64) -> stock_daily_basic::Model {
+        stock_daily_basic::Model {
+            ts_code: ts_code.to_string(),
+            trade_date: trade_date.to_string(),
+            close: None,
+            turnover_rate: Some(Decimal::from(turnover_rate)),
+            turnover_rate_f: None,
+            volume_ratio: None,
+            pe: None,
+            pe_ttm: None,
+            pb: None,
+            ps: None,
+            ps_ttm: None,
+            dv_ratio: None,
+            dv_ttm: None,
+            total_share: None,
+            float_share: None,
+            free_share: None,
+            total_mv: None,
+            circ_mv: None,
+        }
+    }
+
+    #[test]
+    fn test_screen_liquidity() {
+        let dates = ["20240102", "20240103", "20240104"];
+        let mut dailies = vec![];
+        let mut basics = vec![];
+        for date in dates {
+            dailies.push(daily("600000.SH", date, 500_000));
+            basics.push(basic("600000.SH", date, 3));
+            dailies.push(daily("600001.SH", date, 2_000));
+            basics.push(basic("600001.SH", date, 1));
+        }
+        // 只有两个交易日的新股
+        for date in &dates[1..] {
+            dailies.push(daily("301999.SZ", date, 900_000));
+            basics.push(basic("301999.SZ", date, 30));
+        }
+
+        let ts_codes = vec!["600000.SH".to_string(), "600001.SH".to_string(), "301999.SZ".to_string()];
+        let items = explain_liquidity(&ts_codes, &dailies, &basics, 3, 2f64, 100_000f64).items;
+        assert_eq!(
+            items,
+            vec![LiquidityItem { ts_code: "600000.SH".into(), avg_turnover_rate: 3f64, avg_amount: 500_000f64 }]
+        );
+    }
+
+    #[test]
+    fn test_explain_liquidity_failed_criterion() {
+        let dates = ["20240102", "20240103", "20240104"];
+        let mut dailies = vec![];
+        let mut basics = vec![];
+        for date in dates {
+            dailies.push(daily("600000.SH", date, 500_000));
+            basics.push(basic("600000.SH", date, 3));
+            // 交易日数和成交额满足, 只有换手率不足
+            dailies.push(daily("601166.SH", date, 300_000));
+            basics.push(basic("601166.SH", date, 1));
+        }
+        let ts_codes = vec!["600000.SH".to_string(), "601166.SH".to_string(), "688981.SH".to_string()];
+
+        let result = explain_liquidity(&ts_codes, &dailies, &basics, 3, 2f64, 100_000f64);
+        assert_eq!(result.items.len(), 1);
+        assert_eq!(result.items[0].ts_code, "600000.SH");
+        assert_eq!(
+            result.excluded[0],
+            ScreenExplanation {
+                ts_code: "601166.SH".into(),
+                failed: vec![CriterionCheck { criterion: "avg_turnover_rate", value: Some(1f64), threshold: 2f64, passed: false, reason: None }],
+            }
+        );
+        // 没有任何行情的股票三个条件都不满足
+        assert_eq!(result.excluded[1].ts_code, "688981.SH");
+        let failed: Vec<(&str, Option<f64>)> = result.excluded[1].failed.iter().map(|c| (c.criterion, c.value)).collect();
+        assert_eq!(failed, vec![("trade_days", Some(0f64)), ("avg_turnover_rate", None), ("avg_amount", None)]);
+    }
+
+    fn bar(ts_code: &str, trade_date: &str, close: f64, vol: i64) -> stock_daily::Model {
+        let close = Decimal::from_f64_retain(close).unwrap();
+        stock_daily::Model {
+            close,
+            vol: Decimal::from(vol),
+            ..daily(ts_code, trade_date, 0)
+        }
+    }
+
+    #[test]
+    fn test_screen_volume_breakout() {
+        let dates: Vec<String> = (1..=5).map(|d| format!("2024010{}", d)).collect();
+        let mut dailies = vec![];
+        for (i, date) in dates.iter().enumerate() {
+            let last = i == dates.len() - 1;
+            // 价格和成交量同时创新高
+            dailies.push(bar("600000.SH", date, if last { 11.0 } else { 10.0 }, if last { 3000 } else { 1000 }));
+            // 只有价格创新高, 成交量低于前期高点
+            dailies.push(bar("600001.SH", date, if last { 11.0 } else { 10.0 }, if i == 1 { 5000 } else { 1000 }));
+        }
+        // 历史不足的新股
+        for date in &dates[3..] {
+            dailies.push(bar("301999.SZ", date, 20.0, 9000));
+        }
+        dailies.sort_by(|a, b| a.trade_date.cmp(&b.trade_date));
+
+        let ts_codes = vec!["600000.SH".to_string(), "600001.SH".to_string(), "301999.SZ".to_string()];
+        let items = explain_volume_breakout(&ts_codes, &dailies, "20240105", 5, 5).items;
+        assert_eq!(
+            items,
+            vec![VolumeBreakoutItem {
+                ts_code: "600000.SH".into(),
+                trade_date: "20240105".into(),
+                close: 11f64,
+                prev_high_close: 10f64,
+                vol: 3000f64,
+                prev_max_vol: 1000f64,
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scan_divergences() {
+        // 急跌至低点, 反弹后缓慢跌出更低的低点, 再反弹确认
+        let mut diverging: Vec<f64> = (0..20).map(|i| 100.0 + i as f64 * 0.5).collect();
+        diverging.extend([105.0, 100.0, 95.0, 90.0, 85.0, 80.0]);
+        diverging.extend([83.0, 86.0, 89.0, 90.0]);
+        diverging.extend([89.5, 89.0, 88.0, 87.0, 86.0, 85.0, 84.0, 83.0, 82.0, 81.0, 80.5, 79.5]);
+        diverging.extend([81.0, 82.5, 84.0]);
+        // 单边下跌, 没有背离
+        let falling: Vec<f64> = (0..diverging.len()).map(|i| 100.0 - i as f64 * 0.3).collect();
+
+        let dates: Vec<String> = (0..diverging.len()).map(|i| format!("2024{:04}", i + 101)).collect();
+        let dailies = |ts_code: &str, closes: &[f64]| {
+            closes
+                .iter()
+                .zip(&dates)
+                .map(|(close, date)| bar(ts_code, date, *close, 1000))
+                .collect::<Vec<_>>()
+        };
+        let data = HashMap::from([
+            ("600000.SH".to_string(), dailies("600000.SH", &diverging)),
+            ("600001.SH".to_string(), dailies("600001.SH", &falling)),
+        ]);
+
+        let items = scan_divergences(vec!["600000.SH".into(), "600001.SH".into()], |ts_code| {
+            let dailies = data.get(&ts_code).cloned().unwrap_or_default();
+            async move { Ok(dailies) }
+        }).await;
+
+        assert_eq!(items.len(), 1);
+        let item = &items[0];
+        assert_eq!(item.ts_code, "600000.SH");
+        assert_eq!((item.prev_trade_date.as_str(), item.trade_date.as_str()), (dates[25].as_str(), dates[41].as_str()));
+        assert_eq!((item.prev_close, item.close), (80.0, 79.5));
+        assert!(item.rsi > item.prev_rsi);
+        assert_eq!(item.bars_since_confirmed, 0);
+    }
+}
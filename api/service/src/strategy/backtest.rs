@@ -0,0 +1,649 @@
+//! 策略回测
+//!
+//! 单只证券、满仓进出的简单回测: 出现买入信号时以当日收盘价按手满仓买入,
+//! 出现卖出信号或持有达到 `max_holding_days` 个交易日时以当日收盘价全部卖出; 成交价计入滑点, 并按 `CostModel` 扣除交易费用
+//!
+//! 另有多证券组合模式 `run_portfolio_backtest`, 最多同时持有 `max_positions` 只证券, 资金在空余仓位间平均分配
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use anyhow::{anyhow, bail, Result};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use common::finance::max_drawdown;
+use common::stastics::sharpe_ratio;
+use common::util::csv_util;
+
+use super::traits::{
+    BacktestResult, EquityPoint, SecurityData, StrategyConfig, StrategyPerformance, StrategySignal, TradeRecord,
+    TradeType, TradingStrategy,
+};
+
+/// A 股每手股数
+const LOT_SIZE: u32 = 100;
+
+const TRADE_CSV_HEADERS: [&str; 9] = [
+    "stock_code",
+    "entry_date",
+    "entry_price",
+    "exit_date",
+    "exit_price",
+    "quantity",
+    "cost",
+    "pnl",
+    "return_pct",
+];
+const EQUITY_CSV_HEADERS: [&str; 2] = ["trade_date", "equity"];
+
+/// 交易成本, 费率单位为基点(1bp = 0.01%)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CostModel {
+    /// 佣金费率, 买卖双向收取
+    pub commission_bps: f64,
+    /// 印花税费率, 仅卖出收取
+    pub stamp_duty_bps: f64,
+    /// 单笔最低佣金(元)
+    pub min_commission: f64,
+    /// 滑点, 买入按收盘价上浮、卖出按收盘价下浮成交
+    pub slippage_bps: f64,
+}
+
+impl Default for CostModel {
+    /// A 股常见费率: 佣金万 2.5 最低 5 元, 印花税卖出千 0.5(2023-08-28 起), 滑点 1bp
+    fn default() -> Self {
+        Self {
+            commission_bps: 2.5,
+            stamp_duty_bps: 5.0,
+            min_commission: 5.0,
+            slippage_bps: 1.0,
+        }
+    }
+}
+
+impl CostModel {
+    /// 不计任何交易成本
+    pub fn zero() -> Self {
+        Self {
+            commission_bps: 0.0,
+            stamp_duty_bps: 0.0,
+            min_commission: 0.0,
+            slippage_bps: 0.0,
+        }
+    }
+
+    /// 计入滑点后的成交价
+    pub fn fill_price(&self, trade_type: &TradeType, price: f64) -> f64 {
+        match trade_type {
+            TradeType::Buy => price * (1.0 + self.slippage_bps / 10_000.0),
+            TradeType::Sell => price * (1.0 - self.slippage_bps / 10_000.0),
+        }
+    }
+
+    /// 成交金额为 `amount` 时的佣金与印花税
+    pub fn fee(&self, trade_type: &TradeType, amount: f64) -> f64 {
+        if amount <= 0.0 {
+            return 0.0;
+        }
+        let commission = (amount * self.commission_bps / 10_000.0).max(self.min_commission);
+        let stamp_duty = match trade_type {
+            TradeType::Buy => 0.0,
+            TradeType::Sell => amount * self.stamp_duty_bps / 10_000.0,
+        };
+        commission + stamp_duty
+    }
+}
+
+/// 回测参数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestConfig {
+    /// 初始资金(元)
+    pub initial_capital: f64,
+    /// 最长持有交易日数, 为 None 时只在卖出信号时平仓
+    pub max_holding_days: Option<usize>,
+    #[serde(default)]
+    pub cost_model: CostModel,
+}
+
+impl Default for BacktestConfig {
+    fn default() -> Self {
+        Self {
+            initial_capital: 1_000_000.0,
+            max_holding_days: None,
+            cost_model: CostModel::default(),
+        }
+    }
+}
+
+/// 一次完整的买入-卖出, 由成对的 `TradeRecord` 组成
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RoundTrip {
+    pub stock_code: String,
+    pub entry_date: NaiveDate,
+    pub entry_price: f64,
+    pub exit_date: Option<NaiveDate>, // 回测结束时仍持有为 None
+    pub exit_price: Option<f64>,
+    pub quantity: u32,
+    pub cost: f64, // 买卖两笔的交易费用合计
+    pub pnl: Option<f64>, // 已扣除交易费用
+    pub return_pct: Option<f64>, // x%100
+}
+
+/// 在 `data` 上逐日运行 `strategy`, 每个交易日只使用截至当日的数据
+///
+/// `data` 需按日期正序排序; 策略分析失败(如未满足条件)视为当日无信号
+pub fn run_backtest<S: TradingStrategy>(
+    strategy: &mut S,
+    symbol: &str,
+    data: &[SecurityData],
+    config: &BacktestConfig,
+) -> Result<BacktestResult> {
+    strategy.config().validate()?;
+    strategy.validate_data(data)?;
+    if config.initial_capital <= 0.0 {
+        bail!("初始资金必须大于0");
+    }
+    let dates = parse_dates(data)?;
+    strategy.reset();
+
+    let start = strategy.required_data_points().max(1) - 1;
+    let costs = &config.cost_model;
+    let mut cash = config.initial_capital;
+    let mut position: Option<(u32, usize)> = None; // (持仓数量, 买入位置)
+    let mut trades = vec![];
+    let mut equity_curve = vec![];
+    for i in start..data.len() {
+        let close = data[i].close;
+        let signal = strategy
+            .analyze(symbol, &data[..=i])
+            .ok()
+            .map(|r| (r.strategy_signal(), r.signal_strength()));
+        match position {
+            None => {
+                if let Some(strength) = buy_strength(&signal) {
+                    if let Some((price, quantity, fee)) = buy_fill(costs, cash, close) {
+                        cash -= quantity as f64 * price + fee;
+                        trades.push(trade(symbol, dates[i], TradeType::Buy, price, quantity, strength, fee));
+                        position = Some((quantity, i));
+                    }
+                }
+            }
+            Some((quantity, entry)) => {
+                let sell_strength = sell_strength(&signal);
+                let expired = config.max_holding_days.is_some_and(|days| i - entry >= days);
+                if sell_strength.is_some() || expired {
+                    let (price, fee) = sell_fill(costs, close, quantity);
+                    cash += quantity as f64 * price - fee;
+                    trades.push(trade(symbol, dates[i], TradeType::Sell, price, quantity, sell_strength.unwrap_or(0), fee));
+                    position = None;
+                }
+            }
+        }
+        let holding = position.map_or(0.0, |(quantity, _)| quantity as f64 * close);
+        equity_curve.push(EquityPoint { trade_date: dates[i], equity: cash + holding });
+    }
+
+    let period = (dates[start], dates[data.len() - 1]);
+    let performance = calc_performance(&trades, &equity_curve, config.initial_capital, period);
+    Ok(BacktestResult {
+        strategy_name: strategy.name().to_string(),
+        period,
+        performance,
+        trades,
+        equity_curve,
+    })
+}
+
+/// 组合中的一个持仓
+struct Holding {
+    quantity: u32,
+    entry: usize, // 买入时在该证券数据中的位置
+}
+
+/// 多证券组合回测: 每个交易日先处理卖出, 再按信号强度从高到低在空余仓位内买入
+///
+/// - 每笔买入使用 `现金 / 空余仓位数` 的资金, 当日卖出的证券不在当日重新买入
+/// - 仓位已满时的买入信号直接忽略, 不会排队到之后
+/// - 各证券的数据需按日期正序排序, 可以有不同的交易日(如停牌); 权益按各持仓最近一个收盘价计算
+/// - 所有证券共用同一个策略实例, 有状态的策略需自行区分证券
+pub fn run_portfolio_backtest<S: TradingStrategy>(
+    strategy: &mut S,
+    securities: &[(String, Vec<SecurityData>)],
+    config: &BacktestConfig,
+    max_positions: usize,
+) -> Result<BacktestResult> {
+    strategy.config().validate()?;
+    if max_positions == 0 {
+        bail!("最大持仓数必须大于0");
+    }
+    if config.initial_capital <= 0.0 {
+        bail!("初始资金必须大于0");
+    }
+    let dates = securities
+        .iter()
+        .map(|(_, data)| parse_dates(data))
+        .collect::<Result<Vec<_>>>()?;
+    let calendar: BTreeSet<NaiveDate> = dates.iter().flatten().copied().collect();
+    strategy.reset();
+
+    let start = strategy.required_data_points().max(1) - 1;
+    let costs = &config.cost_model;
+    let mut cash = config.initial_capital;
+    let mut cursors = vec![0usize; securities.len()];
+    let mut last_close: Vec<Option<f64>> = vec![None; securities.len()];
+    let mut holdings: HashMap<usize, Holding> = HashMap::new();
+    let mut trades = vec![];
+    let mut equity_curve = vec![];
+    for date in calendar {
+        // 当日有行情且数据足够分析的证券: (证券序号, 数据位置, 信号)
+        let mut bars = vec![];
+        for (s, (symbol, data)) in securities.iter().enumerate() {
+            let i = cursors[s];
+            if dates[s].get(i) != Some(&date) {
+                continue;
+            }
+            cursors[s] += 1;
+            last_close[s] = Some(data[i].close);
+            if i >= start {
+                let signal = strategy
+                    .analyze(symbol, &data[..=i])
+                    .ok()
+                    .map(|r| (r.strategy_signal(), r.signal_strength()));
+                bars.push((s, i, signal));
+            }
+        }
+        if bars.is_empty() && equity_curve.is_empty() {
+            continue;
+        }
+
+        let mut sold_today = HashSet::new();
+        for (s, i, signal) in &bars {
+            let Some(holding) = holdings.get(s) else {
+                continue;
+            };
+            let sell_strength = sell_strength(signal);
+            let expired = config.max_holding_days.is_some_and(|days| i - holding.entry >= days);
+            if sell_strength.is_some() || expired {
+                let (price, fee) = sell_fill(costs, securities[*s].1[*i].close, holding.quantity);
+                cash += holding.quantity as f64 * price - fee;
+                trades.push(trade(&securities[*s].0, date, TradeType::Sell, price, holding.quantity, sell_strength.unwrap_or(0), fee));
+                holdings.remove(s);
+                sold_today.insert(*s);
+            }
+        }
+
+        let mut candidates: Vec<(usize, usize, u8)> = bars
+            .iter()
+            .filter(|(s, _, _)| !holdings.contains_key(s) && !sold_today.contains(s))
+            .filter_map(|(s, i, signal)| buy_strength(signal).map(|strength| (*s, *i, strength)))
+            .collect();
+        candidates.sort_by(|a, b| b.2.cmp(&a.2));
+        for (s, i, strength) in candidates {
+            if holdings.len() >= max_positions {
+                tracing::debug!("{} {} 仓位已满, 忽略买入信号", securities[s].0, date);
+                continue;
+            }
+            let budget = cash / (max_positions - holdings.len()) as f64;
+            if let Some((price, quantity, fee)) = buy_fill(costs, budget, securities[s].1[i].close) {
+                cash -= quantity as f64 * price + fee;
+                trades.push(trade(&securities[s].0, date, TradeType::Buy, price, quantity, strength, fee));
+                holdings.insert(s, Holding { quantity, entry: i });
+            }
+        }
+
+        let holding_value: f64 = holdings
+            .iter()
+            .map(|(s, h)| h.quantity as f64 * last_close[*s].unwrap_or(0.0))
+            .sum();
+        equity_curve.push(EquityPoint { trade_date: date, equity: cash + holding_value });
+    }
+
+    let (Some(first), Some(last)) = (equity_curve.first(), equity_curve.last()) else {
+        bail!("数据不足：没有证券达到 {} 个数据点", strategy.required_data_points());
+    };
+    let period = (first.trade_date, last.trade_date);
+    let performance = calc_performance(&trades, &equity_curve, config.initial_capital, period);
+    Ok(BacktestResult {
+        strategy_name: strategy.name().to_string(),
+        period,
+        performance,
+        trades,
+        equity_curve,
+    })
+}
+
+/// 导出回测结果, 返回 (交易明细 CSV, 每日权益曲线 CSV)
+///
+/// 交易明细按买卖配对输出, 回测结束时仍持有的仓位卖出相关列为空; 无交易时只输出表头
+pub fn to_csv(result: &BacktestResult) -> (String, String) {
+    let fmt = |v: Option<f64>, precision: usize| v.map(|v| format!("{:.*}", precision, v)).unwrap_or_default();
+    let trade_rows: Vec<Vec<String>> = round_trips(&result.trades)
+        .into_iter()
+        .map(|t| {
+            vec![
+                t.stock_code,
+                t.entry_date.to_string(),
+                fmt(Some(t.entry_price), 3),
+                t.exit_date.map(|d| d.to_string()).unwrap_or_default(),
+                fmt(t.exit_price, 3),
+                t.quantity.to_string(),
+                fmt(Some(t.cost), 2),
+                fmt(t.pnl, 2),
+                fmt(t.return_pct, 2),
+            ]
+        })
+        .collect();
+    let equity_rows: Vec<Vec<String>> = result
+        .equity_curve
+        .iter()
+        .map(|p| vec![p.trade_date.to_string(), format!("{:.2}", p.equity)])
+        .collect();
+
+    // 写入内存不会失败
+    let trades = csv_util::to_csv(&TRADE_CSV_HEADERS.to_vec(), &trade_rows).expect("write trades csv");
+    let equity = csv_util::to_csv(&EQUITY_CSV_HEADERS.to_vec(), &equity_rows).expect("write equity csv");
+    (trades, equity)
+}
+
+/// 将同一证券的买入与其后的第一笔卖出配对
+pub fn round_trips(trades: &[TradeRecord]) -> Vec<RoundTrip> {
+    let mut round_trips: Vec<RoundTrip> = vec![];
+    for trade in trades {
+        match trade.trade_type {
+            TradeType::Buy => round_trips.push(RoundTrip {
+                stock_code: trade.stock_code.clone(),
+                entry_date: trade.trade_date,
+                entry_price: trade.price,
+                exit_date: None,
+                exit_price: None,
+                quantity: trade.quantity,
+                cost: trade.cost,
+                pnl: None,
+                return_pct: None,
+            }),
+            TradeType::Sell => {
+                let Some(open) = round_trips
+                    .iter_mut()
+                    .find(|t| t.stock_code == trade.stock_code && t.exit_date.is_none())
+                else {
+                    tracing::warn!("{} 在 {} 卖出时没有持仓", trade.stock_code, trade.trade_date);
+                    continue;
+                };
+                // 买入费用计入成本, 收益率以买入金额加买入费用为基数
+                let invested = open.entry_price * open.quantity as f64 + open.cost;
+                let pnl = (trade.price - open.entry_price) * open.quantity as f64 - open.cost - trade.cost;
+                open.exit_date = Some(trade.trade_date);
+                open.exit_price = Some(trade.price);
+                open.cost += trade.cost;
+                open.pnl = Some(pnl);
+                open.return_pct = Some(pnl / invested * 100.0);
+            }
+        }
+    }
+    round_trips
+}
+
+fn parse_dates(data: &[SecurityData]) -> Result<Vec<NaiveDate>> {
+    data.iter()
+        .map(|d| NaiveDate::parse_from_str(&d.trade_date, "%Y%m%d").map_err(|e| anyhow!("日期解析失败 {}: {}", d.trade_date, e)))
+        .collect()
+}
+
+fn buy_strength(signal: &Option<(StrategySignal, u8)>) -> Option<u8> {
+    match signal {
+        Some((StrategySignal::Buy | StrategySignal::StrongBuy, strength)) => Some(*strength),
+        _ => None,
+    }
+}
+
+fn sell_strength(signal: &Option<(StrategySignal, u8)>) -> Option<u8> {
+    match signal {
+        Some((StrategySignal::Sell | StrategySignal::StrongSell, strength)) => Some(*strength),
+        _ => None,
+    }
+}
+
+/// 用不超过 `budget` 的资金(含费用)按手买入, 返回 (成交价, 数量, 费用), 不足一手时为 None
+fn buy_fill(costs: &CostModel, budget: f64, close: f64) -> Option<(f64, u32, f64)> {
+    let price = costs.fill_price(&TradeType::Buy, close);
+    let mut quantity = lots(budget, price);
+    while quantity > 0 && quantity as f64 * price + costs.fee(&TradeType::Buy, quantity as f64 * price) > budget {
+        quantity -= LOT_SIZE;
+    }
+    (quantity > 0).then(|| (price, quantity, costs.fee(&TradeType::Buy, quantity as f64 * price)))
+}
+
+/// 全部卖出, 返回 (成交价, 费用)
+fn sell_fill(costs: &CostModel, close: f64, quantity: u32) -> (f64, f64) {
+    let price = costs.fill_price(&TradeType::Sell, close);
+    (price, costs.fee(&TradeType::Sell, quantity as f64 * price))
+}
+
+/// 按手取整可买入的最大数量
+fn lots(cash: f64, price: f64) -> u32 {
+    if price <= 0.0 {
+        return 0;
+    }
+    ((cash / price) as u32 / LOT_SIZE) * LOT_SIZE
+}
+
+fn trade(
+    symbol: &str,
+    trade_date: NaiveDate,
+    trade_type: TradeType,
+    price: f64,
+    quantity: u32,
+    signal_strength: u8,
+    cost: f64,
+) -> TradeRecord {
+    TradeRecord {
+        stock_code: symbol.to_string(),
+        trade_date,
+        trade_type,
+        price,
+        quantity,
+        signal_strength,
+        cost,
+    }
+}
+
+/// 胜率、平均收益率、最大回撤均为 x%100, 只统计已平仓的交易
+fn calc_performance(
+    trades: &[TradeRecord],
+    equity_curve: &[EquityPoint],
+    initial_capital: f64,
+    period: (NaiveDate, NaiveDate),
+) -> StrategyPerformance {
+    let returns: Vec<f64> = round_trips(trades).iter().filter_map(|t| t.return_pct).collect();
+    let wins = returns.iter().filter(|r| **r > 0.0).count();
+    let (win_rate, average_return) = if returns.is_empty() {
+        (0.0, 0.0)
+    } else {
+        (
+            wins as f64 / returns.len() as f64 * 100.0,
+            returns.iter().sum::<f64>() / returns.len() as f64,
+        )
+    };
+
+    // 以初始资金为起点, 首日就亏损也计入回撤
+    let equities: Vec<f64> = std::iter::once(initial_capital).chain(equity_curve.iter().map(|p| p.equity)).collect();
+    let max_drawdown = max_drawdown(&equities).map(|d| d.drawdown_pct).unwrap_or(0f64);
+
+    let daily_returns: Vec<f64> = equity_curve
+        .windows(2)
+        .map(|w| w[1].equity / w[0].equity - 1.0)
+        .collect();
+    let sharpe_ratio = sharpe_ratio(&daily_returns, 0f64).unwrap_or(0f64);
+
+    StrategyPerformance {
+        total_trades: returns.len() as u32,
+        win_rate,
+        average_return,
+        max_drawdown,
+        sharpe_ratio,
+        analysis_period: period,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::{MaBreakoutConfig, MaBreakoutStrategy};
+
+    fn bar(trade_date: &str, close: f64) -> SecurityData {
+        SecurityData {
+            symbol: "000001.SZ".to_string(),
+            trade_date: trade_date.to_string(),
+            open: close,
+            high: close,
+            low: close,
+            close,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_run_backtest() {
+        let closes = [10.0, 10.0, 10.0, 12.0, 13.0, 14.0, 14.0];
+        let data: Vec<SecurityData> = closes
+            .iter()
+            .enumerate()
+            .map(|(i, close)| bar(&format!("202401{:02}", i + 2), *close))
+            .collect();
+        let mut strategy = MaBreakoutStrategy::new(MaBreakoutConfig {
+            ma_period: 2,
+            direction: "up".to_string(),
+            require_cross: true,
+        });
+        let config = BacktestConfig { initial_capital: 100_000.0, max_holding_days: Some(2), cost_model: CostModel::zero() };
+        let result = run_backtest(&mut strategy, "000001.SZ", &data, &config).unwrap();
+
+        // 第 4 天上穿 MA2 以 12 元买入 8300 股, 持有 2 天后以 14 元卖出
+        assert_eq!(result.trades.len(), 2);
+        assert_eq!((result.trades[0].trade_type.clone(), result.trades[0].quantity), (TradeType::Buy, 8300));
+        assert_eq!(result.trades[1].price, 14.0);
+        assert_eq!(result.equity_curve.len(), 5);
+        assert!((result.equity_curve.last().unwrap().equity - 116_600.0).abs() < 1e-6);
+        assert_eq!(result.performance.total_trades, 1);
+        assert_eq!(result.performance.win_rate, 100.0);
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let date = |s: &str| NaiveDate::parse_from_str(s, "%Y%m%d").unwrap();
+        let mut result = BacktestResult {
+            strategy_name: "test".to_string(),
+            period: (date("20240102"), date("20240105")),
+            performance: calc_performance(&[], &[], 100_000.0, (date("20240102"), date("20240105"))),
+            trades: vec![],
+            equity_curve: vec![],
+        };
+        let (trades, equity) = to_csv(&result);
+        assert_eq!(trades, format!("{}\n", TRADE_CSV_HEADERS.join(",")));
+        assert_eq!(equity, "trade_date,equity\n");
+
+        result.trades = vec![
+            trade("000001.SZ", date("20240102"), TradeType::Buy, 10.0, 100, 80, 5.0),
+            trade("000001.SZ", date("20240103"), TradeType::Sell, 11.0, 100, 0, 5.55),
+            trade("000001.SZ", date("20240104"), TradeType::Buy, 12.0, 100, 80, 5.0),
+        ];
+        result.equity_curve = ["20240102", "20240103", "20240104", "20240105"]
+            .iter()
+            .map(|d| EquityPoint { trade_date: date(d), equity: 100_000.0 })
+            .collect();
+        let (trades, equity) = to_csv(&result);
+        let lines: Vec<&str> = trades.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], TRADE_CSV_HEADERS.join(","));
+        // (11 - 10) * 100 - 5 - 5.55 = 89.45, 收益率 89.45 / 1005
+        assert_eq!(lines[1], "000001.SZ,2024-01-02,10.000,2024-01-03,11.000,100,10.55,89.45,8.90");
+        assert_eq!(lines[2], "000001.SZ,2024-01-04,12.000,,,100,5.00,,");
+        assert_eq!(equity.lines().count(), 5);
+        assert_eq!(equity.lines().next(), Some("trade_date,equity"));
+    }
+
+    #[test]
+    fn test_round_trip_pnl_includes_costs() {
+        let data: Vec<SecurityData> = [10.0, 10.0, 10.0, 12.0, 13.0, 14.0]
+            .iter()
+            .enumerate()
+            .map(|(i, close)| bar(&format!("202401{:02}", i + 2), *close))
+            .collect();
+        let cost_model = CostModel { commission_bps: 2.5, stamp_duty_bps: 5.0, min_commission: 5.0, slippage_bps: 10.0 };
+        let config = BacktestConfig { initial_capital: 100_000.0, max_holding_days: Some(2), cost_model };
+        let mut strategy = MaBreakoutStrategy::new(MaBreakoutConfig {
+            ma_period: 2,
+            direction: "up".to_string(),
+            require_cross: true,
+        });
+        let result = run_backtest(&mut strategy, "000001.SZ", &data, &config).unwrap();
+
+        // 买入: 12 * 1.001 = 12.012 元成交 8300 股, 金额 99699.6, 佣金 24.9249
+        // 卖出: 14 * 0.999 = 13.986 元成交, 金额 116083.8, 佣金 29.02095, 印花税 58.0419
+        let (buy, sell) = (&result.trades[0], &result.trades[1]);
+        assert_eq!(buy.quantity, 8300);
+        assert!((buy.price - 12.012).abs() < 1e-9);
+        assert!((buy.cost - 24.9249).abs() < 1e-6);
+        assert!((sell.cost - (29.02095 + 58.0419)).abs() < 1e-6);
+
+        let trips = round_trips(&result.trades);
+        let gross = (14.0 - 12.0) * 8300.0;
+        let expected = gross - (12.012 - 12.0) * 8300.0 - (14.0 - 13.986) * 8300.0 - 24.9249 - 29.02095 - 58.0419;
+        assert!((trips[0].pnl.unwrap() - expected).abs() < 1e-6);
+        assert!((result.equity_curve.last().unwrap().equity - (100_000.0 + expected)).abs() < 1e-6);
+
+        // 金额很小时按最低佣金收取
+        assert_eq!(config.cost_model.fee(&TradeType::Buy, 1_000.0), 5.0);
+    }
+
+    #[test]
+    fn test_portfolio_backtest_respects_position_limit() {
+        let series = |symbol: &str, closes: &[f64]| -> (String, Vec<SecurityData>) {
+            let data = closes
+                .iter()
+                .enumerate()
+                .map(|(i, close)| SecurityData { symbol: symbol.to_string(), ..bar(&format!("202401{:02}", i + 2), *close) })
+                .collect();
+            (symbol.to_string(), data)
+        };
+        // 收盘价高于 MA2 (即高于前一日收盘) 时产生买入信号, 三只股票依次在第 3/4/5 天开始上涨
+        let securities = vec![
+            series("000001.SZ", &[10.0, 10.0, 11.0, 12.0, 13.0, 14.0, 15.0, 16.0]),
+            series("000002.SZ", &[10.0, 10.0, 10.0, 11.0, 12.0, 13.0, 14.0, 15.0]),
+            series("000003.SZ", &[10.0, 10.0, 10.0, 10.0, 11.0, 12.0, 13.0, 14.0]),
+        ];
+        let mut strategy = MaBreakoutStrategy::new(MaBreakoutConfig {
+            ma_period: 2,
+            direction: "up".to_string(),
+            require_cross: false,
+        });
+        let config = BacktestConfig { initial_capital: 100_000.0, max_holding_days: Some(3), cost_model: CostModel::zero() };
+        let result = run_portfolio_backtest(&mut strategy, &securities, &config, 2).unwrap();
+
+        let date = |day: u32| NaiveDate::from_ymd_opt(2024, 1, day).unwrap();
+        let buys = |code: &str| -> Vec<NaiveDate> {
+            result
+                .trades
+                .iter()
+                .filter(|t| t.stock_code == code && t.trade_type == TradeType::Buy)
+                .map(|t| t.trade_date)
+                .collect()
+        };
+        // 第 1 只 50000 元按 11 元买入 4500 股, 第 2 只用剩余 50500 元按 11 元买入 4500 股
+        assert_eq!(buys("000001.SZ")[0], date(4));
+        assert_eq!(buys("000002.SZ")[0], date(5));
+        assert_eq!(result.trades[0].quantity, 4500);
+        assert_eq!(result.trades[1].quantity, 4500);
+        // 第 3 只在 1 月 6 日的信号因仓位已满被忽略, 1 月 7 日第 1 只到期卖出后才买入
+        assert_eq!(buys("000003.SZ")[0], date(7));
+        assert!(result.trades.iter().all(|t| t.trade_date != date(6)));
+
+        let mut open = 0i32;
+        for t in &result.trades {
+            open += if t.trade_type == TradeType::Buy { 1 } else { -1 };
+            assert!(open <= 2);
+        }
+        assert_eq!(result.equity_curve.first().unwrap().trade_date, date(4));
+        assert_eq!(result.equity_curve.len(), 6);
+    }
+}
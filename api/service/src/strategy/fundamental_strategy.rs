@@ -741,6 +741,7 @@ mod tests {
                 accounts_payable: Some(60_000_000.0),
                 market_cap: None,
                 roe: None,
+                dv_ttm: None,
             }),
             target: None,
         }]
@@ -3,6 +3,8 @@
 //! 包含各种股票交易策略的实现，基于 trait 设计以支持多种策略
 
 pub mod traits;
+pub mod backtest;
+pub mod optimize;
 pub mod price_volume_candlestick_strategy;
 pub mod bottom_volume_surge_strategy;
 pub mod long_term_bottom_reversal_strategy;
@@ -40,6 +42,7 @@ pub use traits::{
     RiskLevel,
     StrategyPerformance,
     BacktestResult,
+    EquityPoint,
     TradeRecord,
     TradeType,
     SecurityData,
@@ -0,0 +1,246 @@
+//! 策略参数优化
+//!
+//! 对参数网格中的每组配置运行回测, 按指定指标排序; 以及用滚动窗口做样本外验证
+
+use std::thread;
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+use super::backtest::{run_backtest, BacktestConfig};
+use super::traits::{BacktestResult, SecurityData, TradingStrategy};
+
+/// 参数排序指标, 均为越大越好
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum OptimizeMetric {
+    /// 总收益率 x%100
+    TotalReturn,
+    /// 夏普比率
+    Sharpe,
+    /// 总收益率 / 最大回撤, 回撤不足 1% 按 1% 计
+    ReturnOverDrawdown,
+}
+
+impl OptimizeMetric {
+    pub fn score(&self, result: &BacktestResult, initial_capital: f64) -> f64 {
+        let total_return = result
+            .equity_curve
+            .last()
+            .map_or(0.0, |p| (p.equity / initial_capital - 1.0) * 100.0);
+        match self {
+            OptimizeMetric::TotalReturn => total_return,
+            OptimizeMetric::Sharpe => result.performance.sharpe_ratio,
+            OptimizeMetric::ReturnOverDrawdown => total_return / result.performance.max_drawdown.max(1.0),
+        }
+    }
+}
+
+/// 网格搜索: 用 `build` 为 `param_grid` 中每组配置创建策略并回测, 按 `metric` 从高到低返回 (配置, 得分)
+///
+/// 各配置在多个线程中并行回测; 回测失败(如配置无效)的配置会被跳过
+pub fn grid_search<S, F>(
+    symbol: &str,
+    data: &[SecurityData],
+    param_grid: &[S::Config],
+    metric: OptimizeMetric,
+    backtest: &BacktestConfig,
+    build: F,
+) -> Result<Vec<(S::Config, f64)>>
+where
+    S: TradingStrategy,
+    F: Fn(S::Config) -> S + Sync,
+{
+    if param_grid.is_empty() {
+        bail!("参数网格为空");
+    }
+    let threads = thread::available_parallelism().map_or(1, |n| n.get()).min(param_grid.len());
+    let chunk_size = param_grid.len().div_ceil(threads);
+    let build = &build;
+
+    let mut results: Vec<(S::Config, f64)> = thread::scope(|scope| {
+        let handles: Vec<_> = param_grid
+            .chunks(chunk_size)
+            .map(|configs| {
+                scope.spawn(move || {
+                    configs
+                        .iter()
+                        .filter_map(|config| {
+                            let mut strategy = build(config.clone());
+                            match run_backtest(&mut strategy, symbol, data, backtest) {
+                                Ok(result) => Some((config.clone(), metric.score(&result, backtest.initial_capital))),
+                                Err(e) => {
+                                    tracing::warn!("{} 回测失败: {}", symbol, e);
+                                    None
+                                }
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().expect("backtest thread panicked"))
+            .collect()
+    });
+    results.sort_by(|a, b| b.1.total_cmp(&a.1));
+    Ok(results)
+}
+
+/// 滚动窗口划分, 单位为交易日(K 线根数)
+///
+/// 第 k 个窗口的样本内区间为 `[k * out_of_sample, k * out_of_sample + in_sample)`, 紧随其后的
+/// `out_of_sample` 根为样本外区间, 因此需要至少 `in_sample + windows * out_of_sample` 根数据
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalkForwardWindows {
+    pub windows: usize,
+    pub in_sample: usize,
+    pub out_of_sample: usize,
+}
+
+/// 单个滚动窗口的验证结果
+#[derive(Debug, Clone, Serialize)]
+pub struct WalkForwardResult<C> {
+    pub in_sample_start: String,
+    pub in_sample_end: String,
+    pub out_of_sample_start: String,
+    pub out_of_sample_end: String,
+    /// 样本内得分最高的配置
+    pub config: C,
+    pub in_sample_score: f64,
+    pub out_of_sample_score: f64,
+}
+
+/// 滚动样本外验证: 每个窗口在样本内做网格搜索, 再用得分最高的配置回测样本外区间
+///
+/// 样本外回测会带上区间之前的数据作为指标预热, 但只在样本外区间内交易
+pub fn walk_forward<S, F>(
+    symbol: &str,
+    data: &[SecurityData],
+    windows: WalkForwardWindows,
+    param_grid: &[S::Config],
+    metric: OptimizeMetric,
+    backtest: &BacktestConfig,
+    build: F,
+) -> Result<Vec<WalkForwardResult<S::Config>>>
+where
+    S: TradingStrategy,
+    F: Fn(S::Config) -> S + Sync,
+{
+    let WalkForwardWindows { windows: count, in_sample, out_of_sample } = windows;
+    if count == 0 || in_sample == 0 || out_of_sample == 0 {
+        bail!("窗口数、样本内长度和样本外长度都必须大于0");
+    }
+    let required = in_sample + count * out_of_sample;
+    if data.len() < required {
+        bail!(
+            "数据不足：{} 个窗口(样本内 {} + 样本外 {})需要至少 {} 个数据点，实际只有 {} 个",
+            count,
+            in_sample,
+            out_of_sample,
+            required,
+            data.len()
+        );
+    }
+
+    let mut results = vec![];
+    for k in 0..count {
+        let is_start = k * out_of_sample;
+        let oos_start = is_start + in_sample;
+        let oos_end = oos_start + out_of_sample;
+
+        let ranked = grid_search::<S, _>(symbol, &data[is_start..oos_start], param_grid, metric, backtest, &build)?;
+        let Some((config, in_sample_score)) = ranked.into_iter().next() else {
+            bail!("第 {} 个窗口样本内没有可用的配置", k + 1);
+        };
+
+        let mut strategy = build(config.clone());
+        let warmup = strategy.required_data_points().saturating_sub(1).min(oos_start);
+        let result = run_backtest(&mut strategy, symbol, &data[oos_start - warmup..oos_end], backtest)?;
+        results.push(WalkForwardResult {
+            in_sample_start: data[is_start].trade_date.clone(),
+            in_sample_end: data[oos_start - 1].trade_date.clone(),
+            out_of_sample_start: data[oos_start].trade_date.clone(),
+            out_of_sample_end: data[oos_end - 1].trade_date.clone(),
+            config,
+            in_sample_score,
+            out_of_sample_score: metric.score(&result, backtest.initial_capital),
+        });
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategy::backtest::CostModel;
+    use crate::strategy::{MaBreakoutConfig, MaBreakoutStrategy};
+
+    fn rising_data(days: usize) -> Vec<SecurityData> {
+        (0..days)
+            .map(|i| {
+                let close = 10.0 + i as f64;
+                SecurityData {
+                    symbol: "000001.SZ".to_string(),
+                    trade_date: format!("202401{:02}", i + 2),
+                    open: close,
+                    high: close,
+                    low: close,
+                    close,
+                    ..Default::default()
+                }
+            })
+            .collect()
+    }
+
+    fn ma_grid() -> Vec<MaBreakoutConfig> {
+        [2, 3]
+            .into_iter()
+            .flat_map(|ma_period| {
+                ["up", "down"].into_iter().map(move |direction| MaBreakoutConfig {
+                    ma_period,
+                    direction: direction.to_string(),
+                    require_cross: false,
+                })
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_grid_search_finds_best_config() {
+        let grid = ma_grid();
+        let backtest = BacktestConfig { initial_capital: 100_000.0, max_holding_days: None, cost_model: CostModel::zero() };
+        let ranked = grid_search("000001.SZ", &rising_data(10), &grid, OptimizeMetric::TotalReturn, &backtest, MaBreakoutStrategy::new).unwrap();
+
+        assert_eq!(ranked.len(), 4);
+        // MA2 在第 3 天 12 元买入 8300 股, 持有到 19 元: 收益 58.1%
+        let (best, score) = &ranked[0];
+        assert_eq!((best.ma_period, best.direction.as_str()), (2, "up"));
+        assert!((score - 58.1).abs() < 1e-9);
+        assert_eq!(ranked[1].0.ma_period, 3);
+        // 跌破策略只产生卖出信号, 从不建仓
+        assert!(ranked[2..].iter().all(|(config, score)| config.direction == "down" && *score == 0.0));
+
+        let empty: Vec<MaBreakoutConfig> = vec![];
+        assert!(grid_search("000001.SZ", &rising_data(10), &empty, OptimizeMetric::TotalReturn, &backtest, MaBreakoutStrategy::new).is_err());
+    }
+
+    #[test]
+    fn test_walk_forward() {
+        let windows = WalkForwardWindows { windows: 2, in_sample: 6, out_of_sample: 4 };
+        let backtest = BacktestConfig { initial_capital: 100_000.0, max_holding_days: None, cost_model: CostModel::zero() };
+        let results = walk_forward("000001.SZ", &rising_data(14), windows, &ma_grid(), OptimizeMetric::TotalReturn, &backtest, MaBreakoutStrategy::new).unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().all(|r| r.config.ma_period == 2 && r.config.direction == "up"));
+        assert_eq!((results[0].out_of_sample_start.as_str(), results[0].out_of_sample_end.as_str()), ("20240108", "20240111"));
+        // 窗口1: 样本外首日 16 元买入 6200 股, 持有到 19 元
+        assert!((results[0].out_of_sample_score - 18.6).abs() < 1e-9);
+        // 窗口2: 样本外首日 20 元买入 5000 股, 持有到 23 元
+        assert!((results[1].out_of_sample_score - 15.0).abs() < 1e-9);
+        assert_eq!(results[1].in_sample_start, "20240106");
+
+        let err = walk_forward("000001.SZ", &rising_data(13), windows, &ma_grid(), OptimizeMetric::TotalReturn, &backtest, MaBreakoutStrategy::new).unwrap_err();
+        assert!(err.to_string().contains("需要至少 14 个数据点"));
+    }
+}
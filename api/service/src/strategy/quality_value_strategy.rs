@@ -548,6 +548,7 @@ mod tests {
             accounts_payable: Some(150_000_000.0),
             market_cap,
             roe: Some(roe),
+            dv_ttm: None,
         };
         
         vec![SecurityData {
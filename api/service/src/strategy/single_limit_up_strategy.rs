@@ -14,6 +14,7 @@
 
 use anyhow::Result;
 use chrono::NaiveDate;
+use common::finance::stock::Board;
 use serde::{Deserialize, Serialize};
 use tracing::{debug, info};
 
@@ -83,24 +84,10 @@ impl SingleLimitUpStrategy {
         Self { config }
     }
     
-    /// 根据股票代码获取涨停阈值
-    /// - 688和300开头：20%（科创板、创业板）
-    /// - 920开头：30%（北交所）
-    /// - 其他：10%（主板、中小板）
-    fn get_limit_up_threshold(&self, stock_code: &str) -> f64 {
-        if stock_code.starts_with("688") || stock_code.starts_with("300") {
-            20.0
-        } else if stock_code.starts_with("920") {
-            30.0
-        } else {
-            10.0
-        }
-    }
-    
     /// 判断某天是否为涨停
     fn is_limit_up(&self, data: &SecurityData) -> bool {
         if let Some(pct_change) = data.pct_change {
-            let threshold = self.get_limit_up_threshold(&data.symbol);
+            let threshold = Board::from_ts_code(&data.symbol).limit_pct();
             let lower_bound = threshold - self.config.limit_up_tolerance;
             let upper_bound = threshold + self.config.limit_up_tolerance;
             pct_change >= lower_bound && pct_change <= upper_bound
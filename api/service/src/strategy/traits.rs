@@ -6,6 +6,7 @@ use anyhow::Result;
 use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 use rust_decimal::Decimal;
+use common::data_type::Ohlcv;
 use crate::strategy::TimeFrame::Daily;
 
 /// 通用金融产品数据
@@ -332,6 +333,12 @@ impl SecurityData {
     }
 }
 
+impl From<&SecurityData> for Ohlcv {
+    fn from(d: &SecurityData) -> Self {
+        Ohlcv { open: d.open, high: d.high, low: d.low, close: d.close, volume: d.volume }
+    }
+}
+
 /// Decimal 转 f64 的辅助函数
 fn decimal_to_f64(decimal: &Decimal) -> f64 {
     decimal.to_string().parse().unwrap_or(0.0)
@@ -858,6 +865,18 @@ pub struct BacktestResult {
     pub performance: StrategyPerformance,
     /// 详细交易记录
     pub trades: Vec<TradeRecord>,
+    /// 每日权益曲线
+    #[serde(default)]
+    pub equity_curve: Vec<EquityPoint>,
+}
+
+/// 权益曲线上的一个点
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EquityPoint {
+    /// 交易日期
+    pub trade_date: NaiveDate,
+    /// 当日收盘后的总权益(现金 + 持仓市值)
+    pub equity: f64,
 }
 
 /// 交易记录
@@ -875,6 +894,9 @@ pub struct TradeRecord {
     pub quantity: u32,
     /// 信号强度
     pub signal_strength: u8,
+    /// 交易费用(佣金 + 印花税), 滑点已计入成交价格
+    #[serde(default)]
+    pub cost: f64,
 }
 
 /// 交易类型
@@ -0,0 +1,42 @@
+//! 单元测试用的内存数据库(sqlite), 按实体建表并写入测试数据
+
+use entity::sea_orm::{ActiveModelTrait, DatabaseConnection, EntityTrait, IntoActiveModel};
+use entity::stock_daily;
+use rust_decimal::Decimal;
+
+pub(crate) use common::db::test_util::{create_table, memory_db, stock};
+
+/// 建表并写入数据
+pub(crate) async fn seed<E, A>(conn: &DatabaseConnection, entity: E, models: Vec<E::Model>)
+where
+    E: EntityTrait<ActiveModel = A>,
+    A: ActiveModelTrait<Entity = E> + Send,
+    E::Model: IntoActiveModel<A>,
+{
+    create_table(conn, entity).await;
+    if models.is_empty() {
+        return;
+    }
+    E::insert_many(models.into_iter().map(IntoActiveModel::into_active_model))
+        .exec(conn)
+        .await
+        .expect("failed to seed table");
+}
+
+/// 开高低收都为 `close` 的日线, 成交量和成交额为 0
+pub(crate) fn daily(ts_code: &str, trade_date: &str, close: i64) -> stock_daily::Model {
+    let close = Decimal::from(close);
+    stock_daily::Model {
+        ts_code: ts_code.to_string(),
+        trade_date: trade_date.to_string(),
+        open: close,
+        high: close,
+        low: close,
+        close,
+        pre_close: None,
+        change: None,
+        pct_chg: None,
+        vol: Decimal::ZERO,
+        amount: Decimal::ZERO,
+    }
+}
@@ -1,7 +1,15 @@
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::sync::RwLock;
+
 use anyhow::anyhow;
-use chrono::{Datelike, Local, NaiveDate, Timelike};
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime, Timelike};
+use once_cell::sync::Lazy;
+use serde::Serialize;
 use tracing::info;
 
+use common::config::MarketSessions;
+
 use entity::{stock_daily, trade_calendar};
 use entity::sea_orm::ActiveModelTrait;
 use entity::sea_orm::ColumnTrait;
@@ -64,6 +72,180 @@ pub async fn get_year_begin_trade_calendar(conn: &DatabaseConnection) -> anyhow:
     Ok(date)
 }
 
+/// 按 (交易所, 年份) 缓存的交易日历，每天最多加载一次
+pub struct TradeCalendarCache {
+    years: RwLock<HashMap<(String, i32), CachedYear>>,
+}
+
+struct CachedYear {
+    loaded_on: NaiveDate,
+    open_days: HashSet<NaiveDate>,
+}
+
+static TRADE_CALENDAR_CACHE: Lazy<TradeCalendarCache> = Lazy::new(TradeCalendarCache::new);
+
+/// 判断 date 是否为 exchange(SSE/SZSE...) 的交易日, 使用进程内缓存, 同一天内同一年份的日历只查询一次数据库
+pub async fn is_trading_day(date: NaiveDate, exchange: &str, conn: &DatabaseConnection) -> anyhow::Result<bool> {
+    TRADE_CALENDAR_CACHE.is_trading_day(date, exchange, conn).await
+}
+
+impl TradeCalendarCache {
+    pub fn new() -> Self {
+        Self { years: RwLock::new(HashMap::new()) }
+    }
+
+    pub async fn is_trading_day(&self, date: NaiveDate, exchange: &str, conn: &DatabaseConnection) -> anyhow::Result<bool> {
+        self.is_trading_day_with(date, exchange, |year| load_open_days(year, exchange, conn)).await
+    }
+
+    async fn is_trading_day_with<F, Fut>(&self, date: NaiveDate, exchange: &str, load: F) -> anyhow::Result<bool>
+    where
+        F: FnOnce(i32) -> Fut,
+        Fut: Future<Output = anyhow::Result<HashSet<NaiveDate>>>,
+    {
+        let key = (exchange.to_string(), date.year());
+        let today = Local::now().date_naive();
+        if let Some(is_open) = self.lookup(&key, date, today) {
+            return Ok(is_open);
+        }
+
+        let open_days = load(date.year()).await?;
+        let is_open = open_days.contains(&date);
+        self.years
+            .write()
+            .map_err(|_| anyhow!("trade calendar cache poisoned"))?
+            .insert(key, CachedYear { loaded_on: today, open_days });
+        Ok(is_open)
+    }
+
+    fn lookup(&self, key: &(String, i32), date: NaiveDate, today: NaiveDate) -> Option<bool> {
+        let years = self.years.read().ok()?;
+        years
+            .get(key)
+            .filter(|year| year.loaded_on == today)
+            .map(|year| year.open_days.contains(&date))
+    }
+}
+
+impl Default for TradeCalendarCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 市场在某一时刻的交易状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum MarketState {
+    PreOpen, // 交易日开盘前
+    Open,
+    Lunch, // 午间休市
+    Closed, // 交易日收盘后
+    Holiday, // 非交易日
+}
+
+/// `now` 时 `market` 的交易状态, 用于决定返回实时数据还是最近收盘数据
+///
+/// `market` 为交易日历中的交易所代码, 交易时段取自 `sessions` 配置, `now` 为该市场当地时间;
+/// 集合竞价计入开盘前, 半日市提前收盘后为收盘
+pub async fn market_state(now: NaiveDateTime, market: &str, sessions: &MarketSessions, conn: &DatabaseConnection) -> anyhow::Result<MarketState> {
+    market_state_with(&TRADE_CALENDAR_CACHE, now, market, sessions, conn).await
+}
+
+async fn market_state_with(
+    cache: &TradeCalendarCache,
+    now: NaiveDateTime,
+    market: &str,
+    sessions: &MarketSessions,
+    conn: &DatabaseConnection,
+) -> anyhow::Result<MarketState> {
+    let session = sessions.get(market).ok_or_else(|| anyhow!("trading sessions of market {} not configured", market))?;
+    if !cache.is_trading_day(now.date(), market, conn).await? {
+        return Ok(MarketState::Holiday);
+    }
+    let time = now.time();
+    let sessions = session.sessions_on(now.date());
+    let (Some(first), Some(last)) = (sessions.first(), sessions.last()) else {
+        return Ok(MarketState::Closed);
+    };
+    let state = if sessions.iter().any(|(start, end)| time >= *start && time < *end) {
+        MarketState::Open
+    } else if time < first.0 {
+        MarketState::PreOpen
+    } else if time < last.1 {
+        MarketState::Lunch
+    } else {
+        MarketState::Closed
+    };
+    Ok(state)
+}
+
+/// 对齐到交易日的方向
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapDirection {
+    OnOrBefore, // 当天或之前最近的交易日
+    OnOrAfter,  // 当天或之后最近的交易日
+}
+
+/// 批量对齐时在日期范围两侧多加载的自然日数, 覆盖春节、国庆等长假
+const SNAP_MARGIN_DAYS: i64 = 30;
+
+/// 把任意日期(如财报日、除权日)批量对齐到 A 股(SSE)交易日, 结果与 `dates` 一一对应
+///
+/// 只加载一次覆盖全部日期的交易日历; 日历范围内找不到对应方向的交易日时返回错误
+pub async fn snap_to_trading_days(dates: &[NaiveDate], direction: SnapDirection, conn: &DatabaseConnection) -> anyhow::Result<Vec<NaiveDate>> {
+    let (Some(min), Some(max)) = (dates.iter().min(), dates.iter().max()) else {
+        return Ok(vec![]);
+    };
+    let start = (*min - Duration::days(SNAP_MARGIN_DAYS)).format("%Y%m%d").to_string();
+    let end = (*max + Duration::days(SNAP_MARGIN_DAYS)).format("%Y%m%d").to_string();
+    let calendars: Vec<trade_calendar::Model> = trade_calendar::Entity::find()
+        .filter(ColumnTrait::eq(&trade_calendar::Column::Exchange, "SSE"))
+        .filter(ColumnTrait::eq(&trade_calendar::Column::IsOpen, 1))
+        .filter(trade_calendar::Column::CalDate.gte(&start))
+        .filter(trade_calendar::Column::CalDate.lte(&end))
+        .order_by_asc(trade_calendar::Column::CalDate)
+        .all(conn)
+        .await?;
+    let open_days: Vec<NaiveDate> = calendars
+        .iter()
+        .filter_map(|v| NaiveDate::parse_from_str(&v.cal_date, "%Y%m%d").ok())
+        .collect();
+    dates
+        .iter()
+        .map(|date| snap(&open_days, date, direction).ok_or_else(|| anyhow!("no trading day {:?} {}", direction, date)))
+        .collect()
+}
+
+/// `open_days` 按日期正序
+fn snap(open_days: &[NaiveDate], date: &NaiveDate, direction: SnapDirection) -> Option<NaiveDate> {
+    match open_days.binary_search(date) {
+        Ok(i) => Some(open_days[i]),
+        // `i` 为第一个晚于 `date` 的交易日
+        Err(i) => match direction {
+            SnapDirection::OnOrBefore => i.checked_sub(1).map(|i| open_days[i]),
+            SnapDirection::OnOrAfter => open_days.get(i).copied(),
+        },
+    }
+}
+
+async fn load_open_days(year: i32, exchange: &str, conn: &DatabaseConnection) -> anyhow::Result<HashSet<NaiveDate>> {
+    let calendars: Vec<trade_calendar::Model> = trade_calendar::Entity::find()
+        .filter(ColumnTrait::eq(&trade_calendar::Column::Exchange, exchange))
+        .filter(trade_calendar::Column::CalDate.gte(format!("{year}0101")))
+        .filter(trade_calendar::Column::CalDate.lte(format!("{year}1231")))
+        .all(conn)
+        .await?;
+    if calendars.is_empty() {
+        anyhow::bail!("trade calendar not found, exchange: {}, year: {}", exchange, year);
+    }
+    let open_days = calendars
+        .iter()
+        .filter(|v| v.is_open == 1)
+        .filter_map(|v| NaiveDate::parse_from_str(&v.cal_date, "%Y%m%d").ok())
+        .collect();
+    Ok(open_days)
+}
+
 mod tests {
     use chrono::Local;
     use entity::sea_orm::{ConnectOptions, Database};
@@ -81,4 +263,120 @@ mod tests {
         let dates = dates.iter().map(|v| v.cal_date.clone()).collect::<Vec<String>>();
         println!("calendar dates = {:?}", dates);
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_is_trading_day_cached() {
+        use std::collections::HashSet;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use chrono::NaiveDate;
+
+        let loads = AtomicUsize::new(0);
+        let load = |_year: i32| {
+            loads.fetch_add(1, Ordering::SeqCst);
+            async { Ok(HashSet::from([NaiveDate::from_ymd_opt(2024, 1, 2).unwrap()])) }
+        };
+
+        let cache = super::TradeCalendarCache::new();
+        let open = cache.is_trading_day_with(NaiveDate::from_ymd_opt(2024, 1, 2).unwrap(), "SSE", load).await.unwrap();
+        let weekend = cache.is_trading_day_with(NaiveDate::from_ymd_opt(2024, 1, 6).unwrap(), "SSE", load).await.unwrap();
+        assert!(open);
+        assert!(!weekend);
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_snap_to_trading_days() {
+        use super::SnapDirection;
+        use chrono::NaiveDate;
+        use entity::trade_calendar;
+
+        let conn = crate::test_util::memory_db().await;
+        // 20240210 ~ 20240218 春节休市(含两个周末)
+        let calendar = (5..=20)
+            .map(|d| trade_calendar::Model {
+                exchange: "SSE".to_string(),
+                cal_date: format!("202402{:02}", d),
+                is_open: if (10..=18).contains(&d) { 0 } else { 1 },
+                pretrade_date: None,
+            })
+            .collect();
+        crate::test_util::seed(&conn, trade_calendar::Entity, calendar).await;
+
+        let date = |d| NaiveDate::from_ymd_opt(2024, 2, d).unwrap();
+        // 周六、假期中的工作日、交易日本身
+        let dates = [date(10), date(14), date(19)];
+        let before = super::snap_to_trading_days(&dates, SnapDirection::OnOrBefore, &conn).await.unwrap();
+        assert_eq!(before, vec![date(9), date(9), date(19)]);
+        let after = super::snap_to_trading_days(&dates, SnapDirection::OnOrAfter, &conn).await.unwrap();
+        assert_eq!(after, vec![date(19), date(19), date(19)]);
+
+        assert!(super::snap_to_trading_days(&[], SnapDirection::OnOrAfter, &conn).await.unwrap().is_empty());
+        // 超出已有的日历
+        assert!(super::snap_to_trading_days(&[date(25)], SnapDirection::OnOrAfter, &conn).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_market_state() {
+        use super::MarketState;
+        use chrono::NaiveDate;
+        use common::config::MarketSessions;
+        use entity::trade_calendar;
+
+        let conn = crate::test_util::memory_db().await;
+        let calendar = [("20240209", 1), ("20240210", 0), ("20240211", 0), ("20240212", 0), ("20240219", 1)]
+            .iter()
+            .map(|(d, is_open)| trade_calendar::Model {
+                exchange: "SSE".to_string(),
+                cal_date: d.to_string(),
+                is_open: *is_open,
+                pretrade_date: None,
+            })
+            .collect();
+        crate::test_util::seed(&conn, trade_calendar::Entity, calendar).await;
+
+        let cache = super::TradeCalendarCache::new();
+        let at = |d: u32, h: u32, m: u32| NaiveDate::from_ymd_opt(2024, 2, d).unwrap().and_hms_opt(h, m, 0).unwrap();
+        let sessions = MarketSessions::default();
+        let state = |d, h, m| super::market_state_with(&cache, at(d, h, m), "SSE", &sessions, &conn);
+        assert_eq!(state(9, 9, 15).await.unwrap(), MarketState::PreOpen);
+        assert_eq!(state(9, 10, 30).await.unwrap(), MarketState::Open);
+        assert_eq!(state(9, 12, 0).await.unwrap(), MarketState::Lunch);
+        assert_eq!(state(9, 13, 0).await.unwrap(), MarketState::Open);
+        assert_eq!(state(9, 15, 0).await.unwrap(), MarketState::Closed);
+        // 春节假期, 周一也休市
+        assert_eq!(state(12, 10, 30).await.unwrap(), MarketState::Holiday);
+    }
+
+    #[tokio::test]
+    async fn test_market_state_half_day() {
+        use std::collections::{BTreeMap, HashMap};
+        use super::MarketState;
+        use chrono::{NaiveDate, NaiveTime};
+        use common::config::{MarketSession, MarketSessions};
+        use entity::trade_calendar;
+
+        let conn = crate::test_util::memory_db().await;
+        let calendar = ["20241223", "20241224"]
+            .iter()
+            .map(|d| trade_calendar::Model { exchange: "HK".to_string(), cal_date: d.to_string(), is_open: 1, pretrade_date: None })
+            .collect();
+        crate::test_util::seed(&conn, trade_calendar::Entity, calendar).await;
+
+        let time = |h, m| NaiveTime::from_hms_opt(h, m, 0).unwrap();
+        let hk = MarketSession {
+            sessions: vec![(time(9, 30), time(12, 0)), (time(13, 0), time(16, 0))],
+            early_close: BTreeMap::from([("20241224".to_string(), time(12, 0))]),
+        };
+        let sessions = MarketSessions::new(HashMap::from([("HK".to_string(), hk)]));
+        let cache = super::TradeCalendarCache::new();
+        let at = |d: u32, h: u32, m: u32| NaiveDate::from_ymd_opt(2024, 12, d).unwrap().and_hms_opt(h, m, 0).unwrap();
+        let state = |d, h, m| super::market_state_with(&cache, at(d, h, m), "HK", &sessions, &conn);
+        assert_eq!(state(23, 14, 0).await.unwrap(), MarketState::Open);
+        assert_eq!(state(24, 11, 0).await.unwrap(), MarketState::Open);
+        // 半日市提前收盘后为收盘, 而不是午休
+        assert_eq!(state(24, 12, 30).await.unwrap(), MarketState::Closed);
+        assert_eq!(state(24, 14, 0).await.unwrap(), MarketState::Closed);
+        // 未配置的市场
+        assert!(super::market_state_with(&cache, at(23, 10, 0), "SSE", &sessions, &conn).await.is_err());
+    }
+}
@@ -0,0 +1,122 @@
+//! 选股/扫描的候选股票范围
+
+use std::collections::HashSet;
+
+use anyhow::bail;
+use serde::{Deserialize, Serialize};
+
+use common::data_type::TsCode;
+use entity::sea_orm::{ColumnTrait, DatabaseConnection, EntityTrait, QueryFilter, QueryOrder, QuerySelect};
+use entity::{stock, ths_member};
+
+/// 候选股票范围, 各筛选接口共用
+///
+/// JSON 形式: `{"type": "all"}`、`{"type": "industry", "value": "银行"}`、
+/// `{"type": "index", "value": "885760.TI"}`、`{"type": "list", "value": ["600000.SH"]}`
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(tag = "type", content = "value", rename_all = "snake_case")]
+pub enum Universe {
+    /// 全部股票
+    #[default]
+    All,
+    /// 行业, 与 `stock.industry` 一致
+    Industry(String),
+    /// 同花顺指数成分股, 已调出的成分股不包含在内
+    Index(String),
+    /// 指定的股票
+    List(Vec<TsCode>),
+}
+
+impl Universe {
+    /// 解析为股票代码, 按代码排序并去重
+    pub async fn resolve(&self, conn: &DatabaseConnection) -> anyhow::Result<Vec<TsCode>> {
+        let mut ts_codes: Vec<TsCode> = match self {
+            Universe::All => stock::Entity::find()
+                .select_only()
+                .column(stock::Column::TsCode)
+                .into_tuple()
+                .all(conn)
+                .await?,
+            Universe::Industry(industry) => stock::Entity::find()
+                .select_only()
+                .column(stock::Column::TsCode)
+                .filter(ColumnTrait::eq(&stock::Column::Industry, industry))
+                .into_tuple()
+                .all(conn)
+                .await?,
+            Universe::Index(index_code) => {
+                let ts_codes: Vec<TsCode> = ths_member::Entity::find()
+                    .select_only()
+                    .column(ths_member::Column::ConCode)
+                    .filter(ColumnTrait::eq(&ths_member::Column::TsCode, index_code))
+                    .filter(ths_member::Column::OutDate.is_null())
+                    .order_by_asc(ths_member::Column::ConCode)
+                    .into_tuple()
+                    .all(conn)
+                    .await?;
+                if ts_codes.is_empty() {
+                    bail!("members of index {} not found", index_code);
+                }
+                ts_codes
+            }
+            Universe::List(ts_codes) => ts_codes.clone(),
+        };
+        ts_codes.sort();
+        ts_codes.dedup();
+        Ok(ts_codes)
+    }
+
+    /// 解析为股票代码集合, 供筛选时判断股票是否在范围内
+    pub async fn resolve_set(&self, conn: &DatabaseConnection) -> anyhow::Result<HashSet<TsCode>> {
+        Ok(self.resolve(conn).await?.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    fn stock(ts_code: &str, industry: &str) -> stock::Model {
+        stock::Model {
+            industry: Some(industry.to_string()),
+            list_status: Some("L".to_string()),
+            ..test_util::stock(ts_code)
+        }
+    }
+
+    fn member(index_code: &str, con_code: &str, out_date: Option<&str>) -> ths_member::Model {
+        ths_member::Model {
+            ts_code: index_code.to_string(),
+            con_code: con_code.to_string(),
+            con_name: None,
+            weight: None,
+            in_date: None,
+            out_date: out_date.map(str::to_string),
+            is_new: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resolve_universe() {
+        let conn = test_util::memory_db().await;
+        let stocks = vec![stock("600000.SH", "银行"), stock("601166.SH", "银行"), stock("300750.SZ", "电池")];
+        test_util::seed(&conn, stock::Entity, stocks).await;
+        let members = vec![
+            member("885760.TI", "300750.SZ", None),
+            member("885760.TI", "600000.SH", Some("20240101")),
+            member("885431.TI", "601166.SH", None),
+        ];
+        test_util::seed(&conn, ths_member::Entity, members).await;
+
+        assert_eq!(Universe::All.resolve(&conn).await.unwrap(), vec!["300750.SZ", "600000.SH", "601166.SH"]);
+        assert_eq!(Universe::Industry("银行".to_string()).resolve(&conn).await.unwrap(), vec!["600000.SH", "601166.SH"]);
+        assert_eq!(Universe::Index("885760.TI".to_string()).resolve(&conn).await.unwrap(), vec!["300750.SZ"]);
+        assert!(Universe::Index("000000.TI".to_string()).resolve(&conn).await.is_err());
+        let list = Universe::List(vec!["601166.SH".to_string(), "600000.SH".to_string(), "601166.SH".to_string()]);
+        assert_eq!(list.resolve(&conn).await.unwrap(), vec!["600000.SH", "601166.SH"]);
+
+        let universe: Universe = serde_json::from_str(r#"{"type": "industry", "value": "银行"}"#).unwrap();
+        assert_eq!(universe, Universe::Industry("银行".to_string()));
+    }
+}
@@ -1,6 +1,7 @@
 use anyhow::Result;
+use common::paging::Page;
 use serde::{Deserialize, Serialize};
-use entity::sea_orm::{DatabaseConnection, EntityTrait, ColumnTrait, QueryFilter, PaginatorTrait, JoinType, QuerySelect, RelationTrait, QueryOrder};
+use entity::sea_orm::{DatabaseConnection, EntityTrait, ColumnTrait, Condition, QueryFilter, PaginatorTrait, JoinType, QuerySelect, RelationTrait};
 use entity::{us_stock, us_company_info};
 use entity::sea_orm;
 /// 美股列表响应结构
@@ -47,62 +48,47 @@ pub struct UsStockQueryParams {
     pub page_size: Option<u64>,
     /// 搜索关键词（股票代码或名称）
     pub keyword: Option<String>,
-    /// 行业（中文）过滤，对应 us_company_info.industry_name_cn
+    /// 行业过滤，匹配 us_company_info.industry_name_cn 或 industry_name，"Unknown" 匹配未分类的股票
     pub industry: Option<String>,
-    /// 板块（中文）过滤，对应 us_company_info.sector_name_cn
+    /// 板块过滤，匹配 us_company_info.sector_name_cn 或 sector_name，"Unknown" 匹配未分类的股票
     pub sector: Option<String>,
 }
 
+/// 行业/板块为空时的取值
+pub const UNKNOWN_CATEGORY: &str = "Unknown";
+
 /// 分页响应结构
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct UsStockListResponse {
-    /// 股票列表
-    pub data: Vec<UsStockResponse>,
-    /// 总数量
-    pub total: u64,
-    /// 当前页码
-    pub page: u64,
-    /// 每页大小
-    pub page_size: u64,
-    /// 总页数
-    pub total_pages: u64,
-}
+pub type UsStockListResponse = Page<UsStockResponse>;
 
 /// 获取美股列表
 pub async fn get_us_stock_list(
     params: &UsStockQueryParams,
     conn: &DatabaseConnection,
 ) -> Result<UsStockListResponse> {
-    let page = params.page.unwrap_or(1);
-    let page_size = params.page_size.unwrap_or(20);
+    let page = params.page.unwrap_or(1).max(1);
+    let page_size = params.page_size.unwrap_or(20).max(1);
     let offset = (page - 1) * page_size;
 
-    let keyword_is_empty = params
-        .keyword
-        .as_ref()
-        .map(|k| k.trim().is_empty())
-        .unwrap_or(true);
-
     // 构建基础查询
     let mut base_query = us_stock::Entity::find()
         .join(JoinType::LeftJoin, us_stock::Relation::UsCompanyInfo.def());
 
-    // 行业/板块（中文）过滤：仅当 keyword 为空时生效
-    if keyword_is_empty && (params.industry.as_ref().is_some() || params.sector.as_ref().is_some()) {
-        if let Some(industry) = &params.industry {
-            let industry = industry.trim();
-            if !industry.is_empty() {
-                base_query = base_query.filter(ColumnTrait::eq(&us_company_info::Column::IndustryNameCn, industry));
-            }
-        }
-        if let Some(sector) = &params.sector {
-            let sector = sector.trim();
-            if !sector.is_empty() {
-                base_query = base_query.filter(ColumnTrait::eq(&us_company_info::Column::SectorNameCn, sector));
-            }
-        }
+    // 行业/板块过滤，可与关键词同时使用
+    if let Some(industry) = non_empty(&params.industry) {
+        base_query = base_query.filter(category_condition(
+            industry,
+            us_company_info::Column::IndustryNameCn,
+            us_company_info::Column::IndustryName,
+        ));
+    }
+    if let Some(sector) = non_empty(&params.sector) {
+        base_query = base_query.filter(category_condition(
+            sector,
+            us_company_info::Column::SectorNameCn,
+            us_company_info::Column::SectorName,
+        ));
     }
-    
+
     // 添加关键词搜索条件
     if let Some(keyword) = &params.keyword {
         if !keyword.trim().is_empty() {
@@ -166,8 +152,8 @@ pub async fn get_us_stock_list(
             name: result.name.unwrap_or_default(),
             business_description: result.business_description.unwrap_or_default(),
             business_country: result.business_country.unwrap_or_default(),
-            sector_name: result.sector_name.unwrap_or_default(),
-            industry_name: result.industry_name.unwrap_or_default(),
+            sector_name: result.sector_name.unwrap_or_else(|| UNKNOWN_CATEGORY.to_string()),
+            industry_name: result.industry_name.unwrap_or_else(|| UNKNOWN_CATEGORY.to_string()),
             business_description_cn: result.business_description_cn.unwrap_or_default(),
             sector_name_cn: result.sector_name_cn.unwrap_or_default(),
             industry_name_cn: result.industry_name_cn.unwrap_or_default(),
@@ -175,15 +161,24 @@ pub async fn get_us_stock_list(
         })
         .collect();
 
-    let total_pages = (total + page_size - 1) / page_size;
+    Ok(Page::new(data, total, page, page_size))
+}
 
-    Ok(UsStockListResponse {
-        data,
-        total,
-        page,
-        page_size,
-        total_pages,
-    })
+fn non_empty(value: &Option<String>) -> Option<&str> {
+    value.as_deref().map(str::trim).filter(|v| !v.is_empty())
+}
+
+/// 按中文或英文名匹配行业/板块，UNKNOWN_CATEGORY 匹配两者都为空的记录
+fn category_condition(value: &str, cn_column: us_company_info::Column, en_column: us_company_info::Column) -> Condition {
+    if value.eq_ignore_ascii_case(UNKNOWN_CATEGORY) {
+        Condition::all()
+            .add(Condition::any().add(cn_column.is_null()).add(ColumnTrait::eq(&cn_column, "")))
+            .add(Condition::any().add(en_column.is_null()).add(ColumnTrait::eq(&en_column, "")))
+    } else {
+        Condition::any()
+            .add(ColumnTrait::eq(&cn_column, value))
+            .add(ColumnTrait::eq(&en_column, value))
+    }
 }
 
 /// 查询结果结构（用于接收数据库 JOIN 查询结果）
@@ -220,3 +215,86 @@ impl sea_orm::FromQueryResult for UsStockQueryResult {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    fn us_stock(symbol: &str, name: &str) -> us_stock::Model {
+        us_stock::Model { symbol: symbol.into(), exchange_id: "NAS".into(), name: Some(name.into()) }
+    }
+
+    fn company(symbol: &str, sector: Option<&str>, industry: Option<&str>) -> us_company_info::Model {
+        us_company_info::Model {
+            web_address: None,
+            local_name: None,
+            local_name_language_code: None,
+            short_name: None,
+            business_country: None,
+            domicile_country: None,
+            place_of_incorporation: None,
+            year_established: None,
+            industry_name: industry.map(str::to_string),
+            industry_group_name: None,
+            sector_name: sector.map(str::to_string),
+            report_style_name: None,
+            industry_template_name: None,
+            country: None,
+            symbol: symbol.into(),
+            exchange_id: "NAS".into(),
+            business_description: None,
+            business_description_cn: None,
+            sector_name_cn: None,
+            industry_name_cn: None,
+        }
+    }
+
+    async fn seeded_db() -> DatabaseConnection {
+        let conn = test_util::memory_db().await;
+        test_util::seed(&conn, us_stock::Entity, vec![
+            us_stock("AAPL", "Apple Inc"),
+            us_stock("MSFT", "Microsoft Corp"),
+            us_stock("XOM", "Exxon Mobil Corp"),
+            us_stock("NEWCO", "New Co"),
+        ]).await;
+        test_util::seed(&conn, us_company_info::Entity, vec![
+            company("AAPL", Some("Technology"), Some("Consumer Electronics")),
+            company("MSFT", Some("Technology"), Some("Software")),
+            company("XOM", Some("Energy"), Some("Oil & Gas")),
+        ]).await;
+        conn
+    }
+
+    fn params(keyword: Option<&str>, industry: Option<&str>, sector: Option<&str>) -> UsStockQueryParams {
+        UsStockQueryParams {
+            page: Some(1),
+            page_size: Some(1),
+            keyword: keyword.map(str::to_string),
+            industry: industry.map(str::to_string),
+            sector: sector.map(str::to_string),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_filter_by_sector() {
+        let conn = seeded_db().await;
+        let page = get_us_stock_list(&params(None, None, Some("Technology")), &conn).await.unwrap();
+        assert_eq!(page.total, 2);
+        assert_eq!(page.total_pages, 2);
+        assert_eq!(page.data.len(), 1);
+        assert_eq!(page.data[0].sector_name, "Technology");
+
+        let page = get_us_stock_list(&params(Some("Micro"), Some("Software"), Some("Technology")), &conn).await.unwrap();
+        assert_eq!(page.total, 1);
+        assert_eq!(page.data[0].ts_code, "MSFT");
+    }
+
+    #[tokio::test]
+    async fn test_filter_unknown_sector() {
+        let conn = seeded_db().await;
+        let page = get_us_stock_list(&params(None, None, Some(UNKNOWN_CATEGORY)), &conn).await.unwrap();
+        assert_eq!(page.total, 1);
+        assert_eq!(page.data[0].ts_code, "NEWCO");
+        assert_eq!(page.data[0].sector_name, UNKNOWN_CATEGORY);
+    }
+}
@@ -0,0 +1,93 @@
+//! 把一只股票的日线历史导出到文件, 用于离线研究
+//!
+//! ```text
+//! cargo run --bin export_history -- --ts-code 600000.SH --out 600000.csv
+//! cargo run --bin export_history -- --ts-code 600000.SH --out 600000.parquet --format parquet --start 20200101 --end 20231231
+//! ```
+
+use std::path::PathBuf;
+
+use anyhow::{anyhow, bail, Context};
+use chrono::NaiveDate;
+use entity::sea_orm::Database;
+use service::export_service::{self, ExportFormat};
+use tracing::info;
+
+#[derive(Debug, PartialEq)]
+struct Args {
+    ts_code: String,
+    out: PathBuf,
+    format: ExportFormat,
+    start: Option<NaiveDate>,
+    end: Option<NaiveDate>,
+}
+
+fn parse_date(flag: &str, value: Option<String>) -> anyhow::Result<NaiveDate> {
+    let value = value.ok_or(anyhow!("{} requires a date", flag))?;
+    NaiveDate::parse_from_str(&value, common::date::FORMAT).with_context(|| format!("invalid {}: {}", flag, value))
+}
+
+fn parse_args<I: IntoIterator<Item = String>>(args: I) -> anyhow::Result<Args> {
+    let mut args = args.into_iter();
+    let (mut ts_code, mut out, mut format, mut start, mut end) = (None, None, ExportFormat::Csv, None, None);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--ts-code" => ts_code = Some(args.next().ok_or(anyhow!("--ts-code requires a code"))?),
+            "--out" => out = Some(PathBuf::from(args.next().ok_or(anyhow!("--out requires a path"))?)),
+            "--format" => format = args.next().ok_or(anyhow!("--format requires csv or parquet"))?.parse()?,
+            "--start" => start = Some(parse_date("--start", args.next())?),
+            "--end" => end = Some(parse_date("--end", args.next())?),
+            _ => bail!("unknown argument: {}", arg),
+        }
+    }
+    let usage = "usage: export_history --ts-code <code> --out <path> [--format csv|parquet] [--start YYYYMMDD] [--end YYYYMMDD]";
+    let (Some(ts_code), Some(out)) = (ts_code, out) else {
+        bail!(usage);
+    };
+    Ok(Args { ts_code, out, format, start, end })
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenvy::dotenv().ok();
+    tracing_subscriber::fmt::init();
+
+    let args = parse_args(std::env::args().skip(1))?;
+    let app_config = common::config::AppConfig::new()?;
+    common::db::set_max_rows(app_config.db_max_rows());
+    let conn = Database::connect(app_config.db_connect_options()).await?;
+
+    let count = export_history(&args, &conn).await?;
+    info!("exported {} rows of {} to {}", count, args.ts_code, args.out.display());
+    Ok(())
+}
+
+async fn export_history(args: &Args, conn: &entity::sea_orm::DatabaseConnection) -> anyhow::Result<usize> {
+    export_service::export_history(&args.ts_code, args.format, &args.out, args.start.as_ref(), args.end.as_ref(), conn).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let parsed = parse_args(args(&["--ts-code", "600000.SH", "--out", "a.csv", "--start", "20240101"])).unwrap();
+        assert_eq!(parsed, Args {
+            ts_code: "600000.SH".into(),
+            out: PathBuf::from("a.csv"),
+            format: ExportFormat::Csv,
+            start: NaiveDate::from_ymd_opt(2024, 1, 1),
+            end: None,
+        });
+        assert!(parse_args(args(&["--ts-code", "600000.SH"])).is_err());
+        let parsed = parse_args(args(&["--ts-code", "600000.SH", "--out", "a.parquet", "--format", "parquet"])).unwrap();
+        assert_eq!(parsed.format, ExportFormat::Parquet);
+        assert!(parse_args(args(&["--ts-code", "600000.SH", "--out", "a.csv", "--format", "xlsx"])).is_err());
+        assert!(parse_args(args(&["--ts-code", "600000.SH", "--out", "a.csv", "--end", "2024-01-01"])).is_err());
+    }
+}
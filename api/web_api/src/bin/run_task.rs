@@ -0,0 +1,97 @@
+//! 单独运行一个数据抓取任务, 不启动 web 服务和定时调度, 用于补数据和调试
+//!
+//! ```text
+//! cargo run --bin run_task -- --task FetchStockDailyTask --days 30
+//! cargo run --bin run_task -- --task FetchIncomeTask --full
+//! cargo run --bin run_task -- --list
+//! ```
+
+use anyhow::{anyhow, bail, Context};
+use entity::sea_orm::Database;
+use tracing::info;
+
+#[derive(Debug, PartialEq)]
+enum Command {
+    List,
+    Run { task: String, days: Option<u64>, full: bool },
+}
+
+fn parse_args<I: IntoIterator<Item = String>>(args: I) -> anyhow::Result<Command> {
+    let mut args = args.into_iter();
+    let mut task = None;
+    let mut days = None;
+    let mut full = false;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--list" => return Ok(Command::List),
+            "--task" => task = Some(args.next().ok_or(anyhow!("--task requires a task name"))?),
+            "--days" => {
+                let value = args.next().ok_or(anyhow!("--days requires a number"))?;
+                days = Some(value.parse::<u64>().with_context(|| format!("invalid --days: {}", value))?);
+            }
+            // 财务报表任务全量抓取, 默认只抓取新公告的报告期
+            "--full" => full = true,
+            _ => bail!("unknown argument: {}", arg),
+        }
+    }
+    let task = task.ok_or(anyhow!("usage: run_task --task <name> [--days <n>] [--full] | --list"))?;
+    if !schedule::task_names().contains(&task.as_str()) {
+        bail!("unknown task: {}, available tasks: {}", task, schedule::task_names().join(", "));
+    }
+    Ok(Command::Run { task, days, full })
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    dotenvy::dotenv().ok();
+    tracing_subscriber::fmt::init();
+
+    let (task_name, days, full) = match parse_args(std::env::args().skip(1))? {
+        Command::List => {
+            schedule::task_names().iter().for_each(|name| println!("{}", name));
+            return Ok(());
+        }
+        Command::Run { task, days, full } => (task, days, full),
+    };
+    if let Some(days) = days {
+        schedule::set_lookback_days(days)?;
+    }
+    if full {
+        schedule::set_finance_full_refresh()?;
+    }
+
+    let app_config = common::config::AppConfig::new()?;
+    schedule::set_fetch_windows(app_config.fetch_windows())?;
+    schedule::set_us_daily_fetch(app_config.us_daily_fetch())?;
+    common::db::set_max_rows(app_config.db_max_rows());
+    let conn = Database::connect(app_config.db_connect_options()).await?;
+
+    let task = schedule::create_task(&task_name, conn)?;
+    info!("run task {} once, days: {:?}", task_name, days);
+    task.run().await?;
+    info!("task {} complete", task_name);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_args() {
+        let command = parse_args(args(&["--task", "FetchStockDailyTask", "--days", "30"])).unwrap();
+        assert_eq!(command, Command::Run { task: "FetchStockDailyTask".into(), days: Some(30), full: false });
+        let command = parse_args(args(&["--task", "FetchIncomeTask", "--full"])).unwrap();
+        assert_eq!(command, Command::Run { task: "FetchIncomeTask".into(), days: None, full: true });
+        assert_eq!(parse_args(args(&["--list"])).unwrap(), Command::List);
+
+        let err = parse_args(args(&["--task", "FetchNothingTask"])).unwrap_err();
+        assert!(err.to_string().contains("unknown task: FetchNothingTask"));
+        assert!(parse_args(args(&["--task", "FetchStockDailyTask", "--days", "abc"])).is_err());
+        assert!(parse_args(args(&[])).is_err());
+    }
+}
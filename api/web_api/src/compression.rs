@@ -0,0 +1,110 @@
+use std::io::{Cursor, Write};
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::Header;
+use rocket::{Request, Response};
+use tracing::warn;
+
+/// 响应体小于该字节数时不压缩, 压缩收益抵不过开销
+const MIN_COMPRESS_SIZE: usize = 1024;
+
+/// 客户端 `Accept-Encoding` 支持 gzip 时压缩响应体
+///
+/// 已设置 `Content-Encoding` 或长度未知(流式)的响应不压缩
+pub struct GzipCompression;
+
+#[rocket::async_trait]
+impl Fairing for GzipCompression {
+    fn info(&self) -> Info {
+        Info { name: "Gzip Compression", kind: Kind::Response }
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, resp: &mut Response<'r>) {
+        if resp.headers().contains("Content-Encoding") || !resp.body().preset_size().is_some_and(|size| compresses(req, size)) {
+            return;
+        }
+        let body = match resp.body_mut().to_bytes().await {
+            Ok(body) => body,
+            Err(e) => {
+                warn!("read response body failed, uri: {}, error: {:?}", req.uri(), e);
+                return;
+            }
+        };
+        match gzip(&body) {
+            Ok(compressed) => {
+                resp.set_header(Header::new("Content-Encoding", "gzip"));
+                resp.set_sized_body(compressed.len(), Cursor::new(compressed));
+            }
+            Err(e) => {
+                warn!("gzip response body failed, uri: {}, error: {:?}", req.uri(), e);
+                // `ETag` 已按压缩后的响应生成, 不能用于未压缩的响应体
+                resp.remove_header("ETag");
+                resp.set_sized_body(body.len(), Cursor::new(body));
+            }
+        }
+        resp.adjoin_header(Header::new("Vary", "Accept-Encoding"));
+    }
+}
+
+/// 请求支持 gzip 且响应体达到压缩阈值时压缩, 条件请求按同样的规则区分压缩后的 `ETag`
+pub fn compresses(req: &Request<'_>, body_len: usize) -> bool {
+    body_len >= MIN_COMPRESS_SIZE && accepts_gzip(req.headers().get("Accept-Encoding"))
+}
+
+/// `Accept-Encoding` 中包含 gzip 且未设置 `q=0`
+fn accepts_gzip<'a>(values: impl Iterator<Item = &'a str>) -> bool {
+    values.flat_map(|v| v.split(',')).any(|encoding| {
+        let mut parts = encoding.split(';').map(str::trim);
+        let name = parts.next().unwrap_or_default();
+        let disabled = parts.any(|p| p.replace(' ', "") == "q=0" || p.replace(' ', "") == "q=0.0");
+        (name.eq_ignore_ascii_case("gzip") || name == "*") && !disabled
+    })
+}
+
+fn gzip(body: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(body)?;
+    encoder.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use rocket::get;
+    use rocket::local::asynchronous::Client;
+    use std::io::Read;
+
+    #[get("/large")]
+    fn large() -> String {
+        "0123456789".repeat(1000)
+    }
+
+    #[get("/small")]
+    fn small() -> &'static str {
+        "ok"
+    }
+
+    #[rocket::async_test]
+    async fn test_gzip_large_response() {
+        let rocket = rocket::build().attach(GzipCompression).mount("/", rocket::routes![large, small]);
+        let client = Client::tracked(rocket).await.unwrap();
+
+        let resp = client.get("/large").header(Header::new("Accept-Encoding", "gzip, deflate")).dispatch().await;
+        assert_eq!(resp.headers().get_one("Content-Encoding"), Some("gzip"));
+        let compressed = resp.into_bytes().await.unwrap();
+        assert!(compressed.len() < 10_000);
+        let mut body = String::new();
+        GzDecoder::new(compressed.as_slice()).read_to_string(&mut body).unwrap();
+        assert_eq!(body, "0123456789".repeat(1000));
+
+        // 小响应和未声明 gzip 的请求不压缩
+        let resp = client.get("/small").header(Header::new("Accept-Encoding", "gzip")).dispatch().await;
+        assert_eq!(resp.headers().get_one("Content-Encoding"), None);
+        let resp = client.get("/large").dispatch().await;
+        assert_eq!(resp.headers().get_one("Content-Encoding"), None);
+        assert!(!accepts_gzip(["gzip;q=0, br"].into_iter()));
+    }
+}
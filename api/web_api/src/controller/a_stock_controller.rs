@@ -1,16 +1,22 @@
 use rocket::{get, State};
 use tracing::info;
 
+use common::finance::stock::Board;
 use entity::sea_orm::DatabaseConnection;
-use service::stock::a_stock_service;
+use service::stock::{a_stock_service, ListStatus, StockListFilter};
 
 use crate::response::WebResponse;
 use crate::result::{IntoResult, Result};
 
-#[get("/api/a-stocks")]
-pub async fn get_a_stocks(conn: &State<DatabaseConnection>) -> Result<WebResponse<Vec<a_stock_service::AStockOverview>>> {
-    info!("获取A股列表请求: /api/a-stocks");
+/// `board`: main / chinext / star / bse; `list_status`: L(listed) / D(delisted) / P(paused), 不传则不过滤
+#[get("/api/a-stocks?<board>&<list_status>")]
+pub async fn get_a_stocks(board: Option<&str>, list_status: Option<&str>, conn: &State<DatabaseConnection>) -> Result<WebResponse<Vec<a_stock_service::AStockOverview>>> {
+    info!("获取A股列表请求: /api/a-stocks, board: {:?}, list_status: {:?}", board, list_status);
     let conn = conn as &DatabaseConnection;
-    let items = a_stock_service::get_all_a_stocks(conn).await?;
+    let filter = StockListFilter {
+        board: board.map(str::parse::<Board>).transpose()?,
+        list_status: list_status.map(str::parse::<ListStatus>).transpose()?,
+    };
+    let items = a_stock_service::get_all_a_stocks(&filter, conn).await?;
     WebResponse::new(items).into_result()
 }
@@ -0,0 +1,18 @@
+use rocket::serde::json::Json;
+use rocket::{post, State};
+
+use entity::sea_orm::DatabaseConnection;
+use service::batch_service::{self, BatchOp, BatchOpResult};
+use service::stock::quote_cache::QuoteCache;
+
+use crate::response::WebResponse;
+use crate::result::{IntoResult, Result};
+
+/// 一次请求执行多个分析操作, 例如 `[{"op": "stock_overview", "params": {"ts_code": "000001.SZ"}}]`,
+/// 结果顺序与请求一致, 单个操作失败不影响其它操作
+#[post("/api/batch", data = "<ops>")]
+pub async fn batch(ops: Json<Vec<BatchOp>>, quotes: &State<QuoteCache>, conn: &State<DatabaseConnection>) -> Result<WebResponse<Vec<BatchOpResult>>> {
+    let conn = conn as &DatabaseConnection;
+    let data = batch_service::run_batch(ops.into_inner(), quotes, conn).await?;
+    WebResponse::new(data).into_result()
+}
@@ -0,0 +1,18 @@
+use rocket::{get, State};
+
+use entity::sea_orm::DatabaseConnection;
+use service::data_quality_service::{self, DataQualityReport};
+
+use crate::response::WebResponse;
+use crate::result::{IntoResult, Result};
+
+/// 默认检查最近 20 个交易日的缺失
+const DEFAULT_WINDOW_DAYS: usize = 20;
+
+/// 各行情表的最新交易日、总行数及最近 N 个交易日有缺失的代码数, 用于判断数据拉取是否正常
+#[get("/api/admin/data-quality?<days>")]
+pub async fn data_quality(days: Option<usize>, conn: &State<DatabaseConnection>) -> Result<WebResponse<DataQualityReport>> {
+    let conn = conn as &DatabaseConnection;
+    let data = data_quality_service::data_quality_report(days.unwrap_or(DEFAULT_WINDOW_DAYS), conn).await?;
+    WebResponse::new(data).into_result()
+}
@@ -1,18 +1,65 @@
-use std::collections::HashMap;
 use rocket::{post, State};
 use rocket::serde::json::Json;
+use serde::Deserialize;
 use entity::sea_orm::DatabaseConnection;
-use service::security::{security_history_compare_service, SecurityPrice, Year};
 use service::stock::filter::stock_volumn_filter_service;
 use service::stock::filter::stock_volumn_filter_service::*;
+use service::universe::Universe;
+use crate::request;
 use crate::response::WebResponse;
 use crate::result::IntoResult;
 
-#[post("/api/stocks/filter/volumn", format = "json", data = "<query>")]
-pub async fn filter_by_volumn(query: Json<VolumnFilter>, conn: &State<DatabaseConnection>) -> crate::result::Result<WebResponse<VolumnFilterResult>> {
+/// 回看窗口最多的交易日数, 约一年
+const MAX_WINDOW: usize = 250;
+
+/// 放量筛选参数, `universe` 为候选股票范围, 不传时为全部股票
+#[derive(Debug, Clone, Deserialize)]
+pub struct VolumnFilterRequest {
+    #[serde(flatten)]
+    pub filter: VolumnFilter,
+    #[serde(default)]
+    pub universe: Universe,
+}
+
+/// 查询参数覆盖请求体中的同名阈值: `window` 回看交易日数(2 ~ 250), `multiplier` 放量倍数(不小于 1), `min_avg_vol` 均量下限(不小于 0)
+#[post("/api/stocks/filter/volumn?<window>&<multiplier>&<min_avg_vol>", format = "json", data = "<query>")]
+pub async fn filter_by_volumn(
+    query: Json<VolumnFilterRequest>,
+    window: Option<usize>,
+    multiplier: Option<f64>,
+    min_avg_vol: Option<f64>,
+    conn: &State<DatabaseConnection>,
+) -> crate::result::Result<WebResponse<VolumnFilterResult>> {
     let conn = conn as &DatabaseConnection;
-    let filter = VolumnFilter { days: query.days, rate: query.rate };
-    let datas = stock_volumn_filter_service::filter(&filter, &conn).await?;
+    let VolumnFilterRequest { filter: query, universe } = query.into_inner();
+    let filter = VolumnFilter {
+        days: request::in_range("window", window.unwrap_or(query.days as usize), 2, MAX_WINDOW)? as u64,
+        rate: request::at_least("multiplier", multiplier.unwrap_or(query.rate), 1.0)?,
+        min_avg_vol: request::at_least("min_avg_vol", min_avg_vol.unwrap_or(query.min_avg_vol), 0.0)?,
+    };
+    let datas = stock_volumn_filter_service::filter(&filter, &universe, conn).await?;
     WebResponse::new(datas).into_result()
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocket::http::{ContentType, Status};
+    use rocket::local::asynchronous::Client;
+
+    #[rocket::async_test]
+    async fn test_invalid_thresholds() {
+        let rocket = rocket::build().manage(DatabaseConnection::Disconnected).mount("/", rocket::routes![filter_by_volumn]);
+        let client = Client::tracked(rocket).await.unwrap();
+        let body = r#"{"rate": 2.0, "days": 20}"#;
+        for uri in [
+            "/api/stocks/filter/volumn?window=1",
+            "/api/stocks/filter/volumn?window=251",
+            "/api/stocks/filter/volumn?multiplier=0.5",
+            "/api/stocks/filter/volumn?min_avg_vol=-1",
+        ] {
+            let resp = client.post(uri).header(ContentType::JSON).body(body).dispatch().await;
+            assert_eq!(resp.status(), Status::BadRequest, "{}", uri);
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use anyhow::anyhow;
+use rocket::{get, post};
+use rocket::serde::json::{self, Json};
+
+use service::analysis::{compute_indicators, ComputeRequest, ComputedIndicator, IndicatorDescriptor, INDICATORS};
+
+use crate::response::WebResponse;
+use crate::result::{Error, IntoResult, Result};
+
+/// 在上传的K线上计算指标, 不读取数据库, 例如
+/// `{"bars": {"close": [10.0, 10.2, 10.1]}, "indicators": [{"name": "sma", "period": 2}]}`,
+/// 请求体无法解析、数组长度不一致或参数不合法时返回 400
+#[post("/api/indicators/compute", data = "<req>")]
+pub async fn compute(req: std::result::Result<Json<ComputeRequest>, json::Error<'_>>) -> Result<WebResponse<Vec<ComputedIndicator>>> {
+    let req = req.map_err(|e| Error::bad_request(anyhow!("invalid request body: {}", e)))?;
+    let data = compute_indicators(&req).map_err(Error::bad_request)?;
+    WebResponse::new(data).into_result()
+}
+
+/// 支持的指标及其需要的K线数组、参数(名称、默认值、最小值), 供前端生成指标配置表单
+#[get("/api/indicators")]
+pub async fn list() -> Result<WebResponse<&'static [IndicatorDescriptor]>> {
+    WebResponse::new(INDICATORS.as_slice()).into_result()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocket::http::{ContentType, Status};
+    use rocket::local::asynchronous::Client;
+    use serde_json::{json, Value};
+
+    #[rocket::async_test]
+    async fn test_compute_sma_and_rsi() {
+        let rocket = rocket::build().mount("/", rocket::routes![compute]);
+        let client = Client::tracked(rocket).await.unwrap();
+
+        let body = json!({
+            "bars": { "close": [10.0, 11.0, 12.0, 11.0, 12.0, 13.0] },
+            "indicators": [{ "name": "sma", "period": 3 }, { "name": "rsi", "period": 2 }],
+        });
+        let resp = client.post("/api/indicators/compute").header(ContentType::JSON).body(body.to_string()).dispatch().await;
+        assert_eq!(resp.status(), Status::Ok);
+        let resp: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+        assert_eq!(resp["success"], true);
+
+        let sma = &resp["data"][0];
+        assert_eq!(sma["name"], "sma_3");
+        let values = sma["series"]["value"].as_array().unwrap();
+        assert!(values[0].is_null() && values[1].is_null());
+        let expected = [11.0, 34.0 / 3.0, 35.0 / 3.0, 12.0];
+        for (value, expected) in values[2..].iter().zip(expected) {
+            assert!((value.as_f64().unwrap() - expected).abs() < 1e-9);
+        }
+
+        let rsi = &resp["data"][1];
+        assert_eq!(rsi["name"], "rsi_2");
+        let values = rsi["series"]["value"].as_array().unwrap();
+        assert_eq!(values.len(), 6);
+        assert!(values[0].is_null());
+        assert!(values.iter().filter_map(|v| v.as_f64()).all(|v| (0.0..=100.0).contains(&v)));
+
+        let body = json!({
+            "bars": { "close": [10.0, 11.0, 12.0], "high": [10.0, 11.0] },
+            "indicators": [{ "name": "sma", "period": 2 }],
+        });
+        let resp = client.post("/api/indicators/compute").header(ContentType::JSON).body(body.to_string()).dispatch().await;
+        assert_eq!(resp.status(), Status::BadRequest);
+        let resp: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+        assert_eq!(resp["success"], false);
+        assert!(resp["data"].as_str().unwrap().contains("length of high"));
+
+        // 请求体不是合法的 JSON 或缺少字段
+        for body in ["{", r#"{"bars": {"close": [10.0]}}"#] {
+            let resp = client.post("/api/indicators/compute").header(ContentType::JSON).body(body).dispatch().await;
+            assert_eq!(resp.status(), Status::BadRequest);
+            let resp: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+            assert_eq!(resp["success"], false);
+            assert!(resp["data"].as_str().unwrap().starts_with("invalid request body"));
+        }
+    }
+
+    #[rocket::async_test]
+    async fn test_list_indicators() {
+        let client = Client::tracked(rocket::build().mount("/", rocket::routes![list])).await.unwrap();
+        let resp = client.get("/api/indicators").dispatch().await;
+        assert_eq!(resp.status(), Status::Ok);
+        let resp: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+        let indicators = resp["data"].as_array().unwrap();
+        let find = |name: &str| indicators.iter().find(|i| i["name"] == name).unwrap().clone();
+
+        let rsi = find("rsi");
+        assert_eq!(rsi["inputs"], json!(["close"]));
+        assert_eq!(rsi["params"], json!([{ "name": "period", "default": 14.0, "min": 2.0, "integer": true }]));
+
+        let macd = find("macd");
+        let defaults: Vec<f64> = macd["params"].as_array().unwrap().iter().map(|p| p["default"].as_f64().unwrap()).collect();
+        assert_eq!(defaults, vec![12.0, 26.0, 9.0]);
+
+        let boll = find("boll");
+        assert_eq!(boll["params"][1], json!({ "name": "std_dev", "default": 2.0, "min": 0.1, "integer": false }));
+        assert_eq!(find("kdj")["inputs"], json!(["high", "low", "close"]));
+    }
+}
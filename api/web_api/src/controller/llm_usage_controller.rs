@@ -0,0 +1,12 @@
+use rocket::get;
+
+use common::llm::{self, UsageReport};
+
+use crate::response::WebResponse;
+use crate::result::{IntoResult, Result};
+
+/// LLM token 用量及估算费用 (进程启动以来累计)
+#[get("/api/llm/usage")]
+pub async fn llm_usage() -> Result<WebResponse<UsageReport>> {
+    WebResponse::new(llm::usage_report()).into_result()
+}
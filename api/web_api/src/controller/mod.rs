@@ -8,10 +8,9 @@ pub mod stock_history_controller;
 pub mod stock_similarity_controller;
 pub mod filter;
 pub mod stock_price_controller;
-mod stock_compare_controller;
+pub mod stock_compare_controller;
 pub mod security;
 mod stock_market_summary_controller;
-pub mod stock_asset_controller;
 pub mod stock_pick_controller;
 pub mod stock_diagnosis_controller;
 pub mod us_stock_controller;
@@ -25,4 +24,12 @@ pub mod finance_main_business_controller;
 pub mod strategy_profile_controller;
 pub mod strategy_template_controller;
 pub mod holder_per_capita_controller;
-pub mod task_controller;
\ No newline at end of file
+pub mod stock_overview_controller;
+pub mod task_controller;
+pub mod llm_usage_controller;
+pub mod data_quality_controller;
+pub mod top_movers_controller;
+pub mod batch_controller;
+pub mod indicator_controller;
+pub mod task_admin_controller;
+pub mod screen_controller;
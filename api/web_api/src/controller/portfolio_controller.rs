@@ -5,10 +5,12 @@ use tracing::info;
 use entity::sea_orm::DatabaseConnection;
 use service::portfolio_service::{
     create_portfolio, list_portfolios, get_portfolio, delete_portfolio, update_portfolio,
-    add_holding, remove_holding, get_holdings, update_holding_desc,
+    add_holding, remove_holding, get_holdings, update_holding_desc, add_trade, cost_basis,
     CreatePortfolioRequest, PortfolioResponse, AddHoldingRequest, HoldingResponse, 
-    UpdateHoldingDescRequest, UpdatePortfolioRequest,
+    UpdateHoldingDescRequest, UpdatePortfolioRequest, AddTradeRequest, CostBasis, CostBasisMethod,
 };
+use entity::holding_trade;
+use service::stock::quote_cache::QuoteCache;
 
 use crate::response::WebResponse;
 use crate::result::{IntoResult, Result};
@@ -95,12 +97,13 @@ pub async fn add_holding_handler(
 #[get("/api/portfolios/<portfolio_id>/holdings")]
 pub async fn get_holdings_handler(
     portfolio_id: i32,
+    quotes: &State<QuoteCache>,
     conn: &State<DatabaseConnection>,
 ) -> Result<WebResponse<Vec<HoldingResponse>>> {
     info!("获取投资组合 {} 的持仓列表", portfolio_id);
     
     let conn = conn as &DatabaseConnection;
-    let result = get_holdings(conn, portfolio_id).await?;
+    let result = get_holdings(conn, quotes, portfolio_id).await?;
     
     WebResponse::new(result).into_result()
 }
@@ -133,3 +136,35 @@ pub async fn remove_holding_handler(
     
     WebResponse::new(format!("Holding {} removed successfully", holding_id)).into_result()
 }
+
+#[post("/api/portfolios/<portfolio_id>/holdings/<holding_id>/trades", data = "<request>")]
+pub async fn add_trade_handler(
+    portfolio_id: i32,
+    holding_id: i32,
+    request: Json<AddTradeRequest>,
+    conn: &State<DatabaseConnection>,
+) -> Result<WebResponse<holding_trade::Model>> {
+    info!("投资组合 {} 的持仓 {} 新增成交: {:?}", portfolio_id, holding_id, request);
+    
+    let conn = conn as &DatabaseConnection;
+    let result = add_trade(conn, portfolio_id, holding_id, request.into_inner()).await?;
+    
+    WebResponse::new(result).into_result()
+}
+
+/// `method`: fifo(默认) / average
+#[get("/api/portfolios/<portfolio_id>/holdings/<holding_id>/cost_basis?<method>")]
+pub async fn cost_basis_handler(
+    portfolio_id: i32,
+    holding_id: i32,
+    method: Option<&str>,
+    conn: &State<DatabaseConnection>,
+) -> Result<WebResponse<CostBasis>> {
+    info!("获取投资组合 {} 的持仓 {} 成本, method: {:?}", portfolio_id, holding_id, method);
+    
+    let conn = conn as &DatabaseConnection;
+    let method = method.map(str::parse::<CostBasisMethod>).transpose()?.unwrap_or(CostBasisMethod::Fifo);
+    let result = cost_basis(conn, portfolio_id, holding_id, method).await?;
+    
+    WebResponse::new(result).into_result()
+}
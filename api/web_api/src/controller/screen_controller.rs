@@ -0,0 +1,79 @@
+use rocket::serde::json::Json;
+use rocket::{post, State};
+use serde::{Deserialize, Serialize};
+
+use entity::sea_orm::DatabaseConnection;
+use service::stock_picker_service::{self, ExplainedScreen, LiquidityItem, ScreenExplanation, VolumeBreakoutItem};
+use service::universe::Universe;
+
+use crate::response::WebResponse;
+use crate::result::{IntoResult, Result};
+
+/// 流动性筛选参数, 含义见 `stock_picker_service::liquidity_screen`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LiquidityScreenRequest {
+    pub min_avg_turnover: f64,
+    pub min_avg_amount: f64,
+    pub window: usize,
+    #[serde(default)]
+    pub universe: Universe,
+}
+
+/// 放量新高筛选参数, 含义见 `stock_picker_service::volume_breakout_screen`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VolumeBreakoutScreenRequest {
+    pub price_window: usize,
+    pub volume_window: usize,
+    #[serde(default)]
+    pub universe: Universe,
+}
+
+/// 筛选结果, `explain=true` 时带上被排除的股票及未满足的条件
+#[derive(Debug, Clone, Serialize)]
+pub struct ScreenResponse<T> {
+    pub items: Vec<T>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub excluded: Option<Vec<ScreenExplanation>>,
+}
+
+impl<T> From<ExplainedScreen<T>> for ScreenResponse<T> {
+    fn from(screen: ExplainedScreen<T>) -> Self {
+        Self { items: screen.items, excluded: Some(screen.excluded) }
+    }
+}
+
+/// 流动性筛选, `explain=true` 时返回候选范围内每只被排除的股票的实际值与阈值
+#[post("/api/screen/liquidity?<explain>", data = "<request>")]
+pub async fn liquidity_screen(
+    explain: Option<bool>,
+    request: Json<LiquidityScreenRequest>,
+    conn: &State<DatabaseConnection>,
+) -> Result<WebResponse<ScreenResponse<LiquidityItem>>> {
+    let conn = conn as &DatabaseConnection;
+    let LiquidityScreenRequest { min_avg_turnover, min_avg_amount, window, universe } = request.into_inner();
+    let data = if explain.unwrap_or(false) {
+        stock_picker_service::liquidity_screen_explain(min_avg_turnover, min_avg_amount, window, &universe, conn).await?.into()
+    } else {
+        let items = stock_picker_service::liquidity_screen(min_avg_turnover, min_avg_amount, window, &universe, conn).await?;
+        ScreenResponse { items, excluded: None }
+    };
+    WebResponse::new(data).into_result()
+}
+
+/// 放量新高筛选, `explain=true` 时返回候选范围内每只被排除的股票的实际值与阈值
+#[post("/api/screen/volume-breakout?<explain>", data = "<request>")]
+pub async fn volume_breakout_screen(
+    explain: Option<bool>,
+    request: Json<VolumeBreakoutScreenRequest>,
+    conn: &State<DatabaseConnection>,
+) -> Result<WebResponse<ScreenResponse<VolumeBreakoutItem>>> {
+    let conn = conn as &DatabaseConnection;
+    let VolumeBreakoutScreenRequest { price_window, volume_window, universe } = request.into_inner();
+    let data = if explain.unwrap_or(false) {
+        stock_picker_service::volume_breakout_screen_explain(price_window, volume_window, &universe, conn).await?.into()
+    } else {
+        let items = stock_picker_service::volume_breakout_screen(price_window, volume_window, &universe, conn).await?;
+        ScreenResponse { items, excluded: None }
+    };
+    WebResponse::new(data).into_result()
+}
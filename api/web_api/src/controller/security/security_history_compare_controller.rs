@@ -4,9 +4,13 @@ use service::security::{SecurityPrice, SecurityType, Year};
 use rocket::serde::json::Json;
 use crate::response::WebResponse;
 use rocket::{post, State};
+use chrono::NaiveDate;
 use serde_derive::Deserialize;
+use chrono::{Datelike, Local, Months};
+use common::config::Lookback;
 use common::data_type::period::Period;
-use service::security::security_history_compare_service;
+use service::security::security_history_compare_service::{self, NormalizedSeries};
+use crate::request::{self, Validated};
 use crate::result::{IntoResult, Result};
 
 #[derive(Deserialize)]
@@ -14,13 +18,58 @@ struct HistoryQuery {
     #[serde(rename = "tsCode")]
     ts_code: String,
     r#type: SecurityType,
+    #[serde(default)]
     years: Vec<Year>,
     period: Period,
 }
 
+#[derive(Deserialize)]
+struct CompareSecurity {
+    #[serde(rename = "tsCode")]
+    ts_code: String,
+    r#type: SecurityType,
+}
+
+#[derive(Deserialize)]
+struct NormalizedCompareQuery {
+    securities: Vec<CompareSecurity>,
+    period: Period,
+    start: String,
+    end: String,
+}
+
 #[post("/api/securities/history/compare", format = "json", data = "<query>")]
-pub async fn security_history_compare(query: Json<HistoryQuery>, conn: &State<DatabaseConnection>) -> Result<WebResponse<HashMap<Year, Vec<SecurityPrice>>>> {
+pub async fn security_history_compare(query: Json<HistoryQuery>, conn: &State<DatabaseConnection>, lookback: &State<Lookback>) -> Result<WebResponse<HashMap<Year, Vec<SecurityPrice>>>> {
+    let conn = conn as &DatabaseConnection;
+    let years = compare_years(&query.years, lookback.security_compare.clamp(None), lookback.security_compare.max)?;
+    let datas = security_history_compare_service::get_security_by_years(query.r#type, &query.ts_code, query.period, &years, &conn).await?;
+    WebResponse::new(datas).into_result()
+}
+
+/// 多只证券在同一区间的相对表现, 每条走势在起始交易日归一化为 100
+///
+/// 区间超过 `[lookback.security_compare] max` 年时只保留靠近 `end` 的部分
+#[post("/api/securities/history/compare/normalized", format = "json", data = "<query>")]
+pub async fn security_history_compare_normalized(query: Json<NormalizedCompareQuery>, conn: &State<DatabaseConnection>, lookback: &State<Lookback>) -> Result<WebResponse<Vec<NormalizedSeries>>> {
     let conn = conn as &DatabaseConnection;
-    let datas = security_history_compare_service::get_security_by_years(query.r#type, &query.ts_code, query.period, &query.years, &conn).await?;
+    let start = request::date_dash("start", &query.start)?;
+    let end = request::date_dash("end", &query.end)?;
+    let earliest = end.checked_sub_months(Months::new(lookback.security_compare.max * 12)).unwrap_or(NaiveDate::MIN);
+    let start = start.max(earliest);
+    let securities: Vec<(SecurityType, String)> = query.securities.iter().map(|s| (s.r#type, s.ts_code.clone())).collect();
+    let datas = security_history_compare_service::compare_normalized(&securities, query.period, &start, &end, conn).await?;
     WebResponse::new(datas).into_result()
 }
+
+/// 未指定年份时取最近 `default` 年(含今年), 去重后的年份超过 `max` 个时返回 400
+fn compare_years(requested: &[Year], default: u32, max: u32) -> Validated<Vec<Year>> {
+    if requested.is_empty() {
+        let this_year = Local::now().year() as Year;
+        return Ok((0..default).map(|i| this_year - i).collect());
+    }
+    let mut years = requested.to_vec();
+    years.sort_unstable_by(|a, b| b.cmp(a));
+    years.dedup();
+    request::in_range("years", years.len(), 1, max as usize)?;
+    Ok(years)
+}
@@ -1,14 +1,38 @@
 use rocket::{post, State};
 use rocket::serde::json::Json;
+use serde::Deserialize;
 use entity::sea_orm::DatabaseConnection;
 use service::stock::filter::security_volatility_service;
-use service::stock::filter::security_volatility_service::{VolatilityFilter, VolatilityResponse};
+use service::stock::filter::security_volatility_service::{VolatilityFilter, VolatilityOptions, VolatilityResponse};
+use service::universe::Universe;
+use crate::request;
 use crate::response::WebResponse;
 use crate::result::{IntoResult, Result};
 
-#[post("/api/security/filter/volatility", format = "json", data = "<query>")]
-pub async fn filter_by_volatility(query: Json<VolatilityFilter>, conn: &State<DatabaseConnection>) -> Result<WebResponse<VolatilityResponse>> {
+/// 波动率筛选参数, `universe` 为候选股票范围, 不传时为全部股票
+#[derive(Debug, Clone, Deserialize)]
+pub struct VolatilityFilterRequest {
+    #[serde(flatten)]
+    pub filter: VolatilityFilter,
+    #[serde(default)]
+    pub universe: Universe,
+}
+
+/// `window`: 计算波动率的收益率个数, 2 ~ days - 1, 不传时使用整个区间;
+/// `annualize`: 是否年化, 默认 true
+#[post("/api/security/filter/volatility?<window>&<annualize>", format = "json", data = "<query>")]
+pub async fn filter_by_volatility(
+    query: Json<VolatilityFilterRequest>,
+    window: Option<usize>,
+    annualize: Option<bool>,
+    conn: &State<DatabaseConnection>,
+) -> Result<WebResponse<VolatilityResponse>> {
     let conn = conn as &DatabaseConnection;
-    let datas = security_volatility_service::filter(&query, conn).await?;
+    // days 个交易日最多有 days - 1 个收益率
+    let VolatilityFilterRequest { filter, universe } = query.into_inner();
+    let max_window = (filter.days as usize).saturating_sub(1);
+    let window = window.map(|w| request::in_range("window", w, 2, max_window)).transpose()?;
+    let options = VolatilityOptions { window, annualize: annualize.unwrap_or(true) };
+    let datas = security_volatility_service::filter(&filter, &options, &universe, conn).await?;
     WebResponse::new(datas).into_result()
 }
@@ -1,14 +0,0 @@
-use rocket::{post, State};
-use rocket::serde::json::Json;
-use entity::sea_orm::DatabaseConnection;
-use service::stock::filter::security_volatility_service;
-use service::stock::filter::security_volatility_service::{VolatilityFilter, VolatilityResponse};
-use crate::response::WebResponse;
-use crate::result::{IntoResult, Result};
-
-#[post("/api/stock/asset", format = "json", data = "<query>")]
-pub async fn get_asset(query: Json<VolatilityFilter>, conn: &State<DatabaseConnection>) -> Result<WebResponse<VolatilityResponse>> {
-    let conn = conn as &DatabaseConnection;
-    let datas = security_volatility_service::filter(&query, conn).await?;
-    WebResponse::new(datas).into_result()
-}
@@ -1,6 +1,18 @@
-use rocket::get;
+use rocket::{get, State};
 
-#[get("/api/stocks/compare")]
-pub async fn stocks_compare() {
+use entity::sea_orm::DatabaseConnection;
+use service::stock::stock_compare_service::{self, FundamentalComparison};
 
-}
\ No newline at end of file
+use crate::request;
+use crate::response::WebResponse;
+use crate::result::{IntoResult, Result};
+
+/// 两只股票最新基本面指标并排对比, 每行标记哪只股票更好
+#[get("/api/stocks/compare?<code_a>&<code_b>")]
+pub async fn stocks_compare(code_a: String, code_b: String, conn: &State<DatabaseConnection>) -> Result<WebResponse<FundamentalComparison>> {
+    request::ts_code(&code_a)?;
+    request::ts_code(&code_b)?;
+    let conn = conn as &DatabaseConnection;
+    let data = stock_compare_service::compare_fundamentals(&code_a, &code_b, conn).await?;
+    WebResponse::new(data).into_result()
+}
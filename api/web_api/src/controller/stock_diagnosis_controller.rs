@@ -3,36 +3,66 @@ use serde::{Deserialize, Serialize};
 use tracing::info;
 
 use entity::sea_orm::DatabaseConnection;
-use service::diagnosis::{diagnosis, DiagnosisResult};
+use service::diagnosis::{diagnosis, diagnosis_detailed, DetailedDiagnosis, DiagnosisResult};
 
+use crate::request;
 use crate::response::WebResponse;
 use crate::result::{IntoResult, Result};
 
+/// 诊断只加载最近 90 天的数据, 主力资金流向最多统计 60 个交易日
+const MAX_MONEYFLOW_PERIOD: usize = 60;
+
 /// 股票诊断请求参数
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StockDiagnosisParams {
     /// 股票代码
     pub tscode: String,
+    /// 是否返回指标序列
+    pub detailed: Option<bool>,
+    /// 主力资金流向统计天数
+    pub moneyflow_period: Option<usize>,
+}
+
+/// 股票诊断响应
+///
+/// 使用 `#[serde(untagged)]`, 默认只返回诊断结果, `detailed=true` 时额外带上指标序列
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum StockDiagnosisResponse {
+    /// 诊断结果
+    Basic(DiagnosisResult),
+    /// 带指标序列的诊断结果
+    Detailed(DetailedDiagnosis),
 }
 
 /// 股票诊断接口
 /// 
 /// # 参数
 /// * `tscode` - 股票代码，例如: 000001.SZ
+/// * `detailed` - 为 true 时返回 MA/MACD/RSI/KDJ 指标序列，默认 false
+/// * `moneyflow_period` - 主力资金流向统计天数, 1 到 60 之间, 默认 5
 /// 
 /// # 返回
 /// 返回股票的综合诊断结果，包括技术指标分析和投资建议
-#[get("/api/stock/diagnosis?<tscode>")]
+#[get("/api/stock/diagnosis?<tscode>&<detailed>&<moneyflow_period>")]
 pub async fn stock_diagnosis(
     tscode: String,
+    detailed: Option<bool>,
+    moneyflow_period: Option<usize>,
     conn: &State<DatabaseConnection>
-) -> Result<WebResponse<DiagnosisResult>> {
+) -> Result<WebResponse<StockDiagnosisResponse>> {
     info!("股票诊断请求 - 股票代码: {}", tscode);
+    request::ts_code(&tscode)?;
+    let moneyflow_period = moneyflow_period.map(|v| request::in_range("moneyflow_period", v, 1, MAX_MONEYFLOW_PERIOD)).transpose()?;
     
     let conn = conn as &DatabaseConnection;
     
     // 调用诊断服务
-    let diagnosis_result = diagnosis(&tscode, conn).await?;
+    let diagnosis_result = if detailed.unwrap_or(false) {
+        StockDiagnosisResponse::Detailed(diagnosis_detailed(&tscode, moneyflow_period, conn).await?)
+    } else {
+        StockDiagnosisResponse::Basic(diagnosis(&tscode, moneyflow_period, conn).await?)
+    };
     
     info!("股票 {} 诊断完成", tscode);
     
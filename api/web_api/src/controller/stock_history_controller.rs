@@ -1,12 +1,13 @@
-use anyhow::anyhow;
-use chrono::NaiveDate;
+use chrono::Local;
+use common::config::Lookback;
 use entity::sea_orm::DatabaseConnection;
 use rocket::{get, State};
 use rocket::FromForm;
 use serde_derive::Serialize;
 
+use crate::request;
 use crate::response::WebResponse;
-use crate::result::{IntoResult, Result};
+use crate::result::{Error, IntoResult, Result};
 use service::stock::stock_history_service;
 
 #[derive(FromForm, Debug)]
@@ -34,21 +35,22 @@ pub struct StockHistoryResp {
 pub async fn get_stock_history(
     params: StockHistoryParams,
     conn: &State<DatabaseConnection>,
+    lookback: &State<Lookback>,
 ) -> Result<WebResponse<Vec<StockHistoryResp>>> {
     let conn = conn as &DatabaseConnection;
+    request::ts_code(&params.ts_code)?;
 
-    let (start_date, end_date) = if let (Some(s), Some(e)) = (&params.start_date, &params.end_date) {
-        let start = NaiveDate::parse_from_str(s, common::date::FORMAT_DASH)
-            .map_err(|err| anyhow!("start_date format error: {}", err))?;
-        let end = NaiveDate::parse_from_str(e, common::date::FORMAT_DASH)
-            .map_err(|err| anyhow!("end_date format error: {}", err))?;
-        (start, end)
-    } else if let Some(tp) = &params.time_period {
-        let p = stock_history_service::parse_time_period(tp)?;
-        stock_history_service::resolve_date_range_from_period(&p)
+    // 未指定区间时按配置的默认窗口, 区间过长时截断为配置的最大窗口
+    let (start_date, end_date) = if let Some(tp) = params.time_period.as_ref().filter(|_| params.start_date.is_none() && params.end_date.is_none()) {
+        let p = stock_history_service::parse_time_period(tp).map_err(Error::bad_request)?;
+        let (start, end) = stock_history_service::resolve_date_range_from_period(&p);
+        (Some(start), Some(end))
     } else {
-        return Err(anyhow!("either start_date/end_date or time_period is required").into());
+        let start = params.start_date.as_deref().map(|s| request::date_dash("start_date", s)).transpose()?;
+        let end = params.end_date.as_deref().map(|e| request::date_dash("end_date", e)).transpose()?;
+        (start, end)
     };
+    let (start_date, end_date) = lookback.stock_history.date_range(start_date, end_date, Local::now().date_naive());
 
     let points = stock_history_service::get_stock_history(conn, &params.ts_code, &start_date, &end_date).await?;
 
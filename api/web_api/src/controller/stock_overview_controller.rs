@@ -0,0 +1,62 @@
+use rocket::serde::json::Json;
+use rocket::{get, post, State};
+
+use entity::sea_orm::DatabaseConnection;
+use service::stock::quote_cache::QuoteCache;
+use service::stock::stock_overview_service::{self, BatchOverviewItem, BatchOverviewRequest, StockOverview};
+
+use crate::request;
+use crate::response::WebResponse;
+use crate::result::{IntoResult, Result};
+
+#[get("/api/stock/overview?<ts_code>")]
+pub async fn stock_overview(ts_code: &str, quotes: &State<QuoteCache>, conn: &State<DatabaseConnection>) -> Result<WebResponse<StockOverview>> {
+    let ts_code = request::ts_code(ts_code)?;
+    let conn = conn as &DatabaseConnection;
+    let data = stock_overview_service::stock_overview(ts_code, quotes, conn).await?;
+    WebResponse::new(data).into_result()
+}
+
+#[post("/api/stock/overview/batch", data = "<request>")]
+pub async fn stock_overview_batch(
+    request: Json<BatchOverviewRequest>,
+    quotes: &State<QuoteCache>,
+    conn: &State<DatabaseConnection>,
+) -> Result<WebResponse<Vec<BatchOverviewItem>>> {
+    let ts_codes = request.into_inner().ts_codes;
+    for ts_code in &ts_codes {
+        request::ts_code(ts_code)?;
+    }
+    let conn = conn as &DatabaseConnection;
+    let data = stock_overview_service::stock_overviews(ts_codes, quotes, conn).await?;
+    WebResponse::new(data).into_result()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocket::http::{ContentType, Status};
+    use rocket::local::asynchronous::Client;
+    use serde_json::Value;
+
+    #[rocket::async_test]
+    async fn test_invalid_ts_code_rejected() {
+        // 校验在访问数据库之前, 不需要真实连接
+        let rocket = rocket::build()
+            .manage(DatabaseConnection::Disconnected)
+            .manage(QuoteCache::default())
+            .mount("/", rocket::routes![stock_overview_batch]);
+        let client = Client::tracked(rocket).await.unwrap();
+
+        let resp = client
+            .post("/api/stock/overview/batch")
+            .header(ContentType::JSON)
+            .body(r#"{"ts_codes": ["000001.SZ", "600000"]}"#)
+            .dispatch()
+            .await;
+        assert_eq!(resp.status(), Status::BadRequest);
+        let body: Value = serde_json::from_str(&resp.into_string().await.unwrap()).unwrap();
+        assert_eq!(body["success"], false);
+        assert!(body["data"].as_str().unwrap().starts_with("invalid ts_code: \"600000\""));
+    }
+}
@@ -3,8 +3,10 @@ use rocket::{post, State};
 use rocket::serde::json::{Json, Value as JsonValue};
 use serde::{Deserialize, Serialize};
 use entity::sea_orm::DatabaseConnection;
+use crate::controller::screen_controller::ScreenResponse;
 use crate::response::WebResponse;
 use service::stock_picker_service::*;
+use service::universe::Universe;
 use crate::result::IntoResult;
 
 /// 选股请求参数
@@ -16,6 +18,9 @@ pub struct StockPickRequest {
     /// 策略设置（动态字段，根据 type 不同而不同）
     /// 使用 JsonValue 来接收任意 JSON 对象
     pub settings: Option<JsonValue>,
+    /// 候选股票范围, 默认全部股票
+    #[serde(default)]
+    pub universe: Universe,
 }
 
 /// 选股响应
@@ -29,18 +34,28 @@ pub struct StockPickResponse {
     pub strategy_type: String,
 }
 
-/// 简单选股接口（使用默认配置）
-#[post("/api/stocks/pick", data = "<request>")]
-pub async fn pick(conn: &State<DatabaseConnection>,   request: Json<StockPickRequest>,) -> crate::result::Result<WebResponse<Vec<StockPickResult>>> {
+/// 选股结果, 不带 `explain` 时与原来一样只返回股票列表
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+pub enum StockPickData {
+    Items(Vec<StockPickResult>),
+    Explained(ScreenResponse<StockPickResult>),
+}
+
+/// 简单选股接口（使用默认配置）, `explain=true` 时另外返回候选范围内每只被排除的股票未满足的条件
+#[post("/api/stocks/pick?<explain>", data = "<request>")]
+pub async fn pick(conn: &State<DatabaseConnection>, explain: Option<bool>, request: Json<StockPickRequest>) -> crate::result::Result<WebResponse<StockPickData>> {
     let conn = conn as &DatabaseConnection;
 
     let picker_service = StockPickerService::new(conn.clone());
     let end = Local::now().date_naive();
     let start = end.checked_sub_months(Months::new(5)).unwrap();
-    let req = request.into_inner();
-    let strategy = req.strategy;
-    let settings = req.settings;
+    let StockPickRequest { strategy, settings, universe } = request.into_inner();
 
-    let datas = picker_service.pick_stocks(&start, &end, &strategy, settings).await?;
+    let datas = if explain.unwrap_or(false) {
+        StockPickData::Explained(picker_service.pick_stocks_explain(&start, &end, &strategy, settings, &universe).await?.into())
+    } else {
+        StockPickData::Items(picker_service.pick_stocks(&start, &end, &strategy, settings, &universe).await?)
+    };
     WebResponse::new(datas).into_result()
 }
@@ -1,18 +1,32 @@
-use anyhow::anyhow;
-use chrono::NaiveDate;
+use chrono::Local;
+use common::config::Lookback;
 use rocket::{get, State};
 use rocket::serde::json::Json;
 use tracing::error;
 use entity::sea_orm::DatabaseConnection;
 use entity::stock_daily;
+use service::stock::quote_cache::{LatestClose, QuoteCache};
 use service::stock::stock_price_service;
+use crate::request;
 use crate::response::WebResponse;
 use crate::result::{IntoResult, Result};
+/// `start`/`end` 可选, 未指定时按配置的默认窗口, 区间过长时截断为配置的最大窗口
 #[get("/api/stocks/price?<ts_code>&<start>&<end>")]
-pub async fn stock_price(ts_code: &str, start: &str, end: &str, conn: &State<DatabaseConnection>) -> Result<WebResponse<Vec<stock_daily::Model>>> {
+pub async fn stock_price(ts_code: &str, start: Option<&str>, end: Option<&str>, conn: &State<DatabaseConnection>, lookback: &State<Lookback>) -> Result<WebResponse<Vec<stock_daily::Model>>> {
     let conn = conn as &DatabaseConnection;
-    let start = NaiveDate::parse_from_str(start, common::date::FORMAT_DASH).map_err(|e| anyhow!("start date format error: {}", e))?;
-    let end = NaiveDate::parse_from_str(end, common::date::FORMAT_DASH).map_err(|e| anyhow!("end date format error: {}", e))?;
+    let ts_code = request::ts_code(ts_code)?;
+    let start = start.map(|v| request::date_dash("start", v)).transpose()?;
+    let end = end.map(|v| request::date_dash("end", v)).transpose()?;
+    let (start, end) = lookback.stock_price.date_range(start, end, Local::now().date_naive());
     let data = stock_price_service::get_stock_prices(ts_code, &start, &end, &conn).await?;
     WebResponse::new(data).into_result()
 }
+
+/// 最新收盘价, 使用进程内短时缓存
+#[get("/api/stocks/latest-close?<ts_code>")]
+pub async fn latest_close(ts_code: &str, quotes: &State<QuoteCache>, conn: &State<DatabaseConnection>) -> Result<WebResponse<LatestClose>> {
+    let conn = conn as &DatabaseConnection;
+    let ts_code = request::ts_code(ts_code)?;
+    let data = quotes.latest_close(ts_code, conn).await?;
+    WebResponse::new(data).into_result()
+}
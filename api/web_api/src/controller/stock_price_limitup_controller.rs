@@ -1,4 +1,4 @@
-use rocket::{FromForm, get, State};
+use rocket::{get, post, State};
 use rocket::serde::json::Json;
 use serde_derive::Deserialize;
 use tracing::info;
@@ -7,6 +7,7 @@ use service::stock::filter::*;
 use crate::response::WebResponse;
 use service::stock::filter::stock_price_limit_service::*;
 use crate::result::{IntoResult, Result};
+use service::universe::Universe;
 // #[derive(Debug, Deserialize, FromForm)]
 // pub struct FilterParams {
 //
@@ -15,6 +16,23 @@ use crate::result::{IntoResult, Result};
 #[get("/api/stocks/filter?<past_ndays>")]
 pub async fn stock_price_limitup(past_ndays: u64, conn: &State<DatabaseConnection>) -> Result<WebResponse<LimitupStocks>> {
     let conn = conn as &DatabaseConnection;
-    let data = stock_price_limit_service::filter_continue_price_limit(past_ndays, &conn).await?;
+    let data = stock_price_limit_service::filter_continue_price_limit(past_ndays, &Universe::All, &conn).await?;
+    WebResponse::new(data).into_result()
+}
+
+/// 连续涨停筛选参数, `universe` 为候选股票范围, 不传时为全部股票
+#[derive(Debug, Deserialize)]
+pub struct PriceLimitFilterRequest {
+    pub past_ndays: u64,
+    #[serde(default)]
+    pub universe: Universe,
+}
+
+/// 与 `GET /api/stocks/filter` 相同, 可在请求体中指定候选股票范围
+#[post("/api/stocks/filter", format = "json", data = "<request>")]
+pub async fn stock_price_limitup_in_universe(request: Json<PriceLimitFilterRequest>, conn: &State<DatabaseConnection>) -> Result<WebResponse<LimitupStocks>> {
+    let conn = conn as &DatabaseConnection;
+    let PriceLimitFilterRequest { past_ndays, universe } = request.into_inner();
+    let data = stock_price_limit_service::filter_continue_price_limit(past_ndays, &universe, conn).await?;
     WebResponse::new(data).into_result()
 }
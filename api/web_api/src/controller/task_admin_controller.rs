@@ -0,0 +1,24 @@
+use rocket::{get, post, State};
+
+use schedule::{TaskAdminItem, TaskManager, TaskRunResult};
+
+use crate::request::{self, Admin};
+use crate::response::WebResponse;
+use crate::result::{IntoResult, Result};
+
+/// 所有已注册任务的调度、启用状态和最近一次运行的时间、结果、耗时
+#[get("/api/admin/tasks")]
+pub async fn admin_tasks(task_manager: &State<TaskManager>) -> Result<WebResponse<Vec<TaskAdminItem>>> {
+    let data = task_manager.admin_list().await?;
+    WebResponse::new(data).into_result()
+}
+
+/// 手动运行一次任务, `days` 覆盖本次运行的回溯天数; 需要请求头 `X-Admin-Token`
+///
+/// 同名任务正在运行时返回错误; 任务本身失败时仍返回运行记录, `status` 为 error
+#[post("/api/admin/tasks/<name>/run?<days>")]
+pub async fn run_admin_task(name: &str, days: Option<usize>, _admin: Admin, task_manager: &State<TaskManager>) -> Result<WebResponse<TaskRunResult>> {
+    let days = days.map(|days| request::positive("days", days)).transpose()?;
+    let data = task_manager.run_registered(name, days.map(|days| days as u64)).await?;
+    WebResponse::new(data).into_result()
+}
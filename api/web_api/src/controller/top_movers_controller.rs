@@ -0,0 +1,44 @@
+use rocket::serde::json::Json;
+use rocket::{get, post, State};
+use serde_derive::Deserialize;
+
+use entity::sea_orm::DatabaseConnection;
+use service::analysis::{self, TopMovers};
+use service::universe::Universe;
+
+use crate::request;
+use crate::response::WebResponse;
+use crate::result::{IntoResult, Result};
+
+/// 默认涨幅榜、跌幅榜各返回 20 只
+const DEFAULT_TOP_N: usize = 20;
+
+/// 指定交易日的涨幅榜和跌幅榜, 涨跌停的股票会被标记
+#[get("/api/market/top-movers?<trade_date>&<top_n>")]
+pub async fn top_movers(trade_date: String, top_n: Option<usize>, conn: &State<DatabaseConnection>) -> Result<WebResponse<TopMovers>> {
+    request::date("trade_date", &trade_date)?;
+    let top_n = request::positive("top_n", top_n.unwrap_or(DEFAULT_TOP_N))?;
+    let conn = conn as &DatabaseConnection;
+    let data = analysis::top_movers(&trade_date, top_n, &Universe::All, conn).await?;
+    WebResponse::new(data).into_result()
+}
+
+/// 涨跌幅榜参数, `universe` 为候选股票范围, 不传时为全部股票
+#[derive(Debug, Deserialize)]
+pub struct TopMoversRequest {
+    pub trade_date: String,
+    pub top_n: Option<usize>,
+    #[serde(default)]
+    pub universe: Universe,
+}
+
+/// 与 `GET /api/market/top-movers` 相同, 可在请求体中指定候选股票范围
+#[post("/api/market/top-movers", format = "json", data = "<request>")]
+pub async fn top_movers_in_universe(request: Json<TopMoversRequest>, conn: &State<DatabaseConnection>) -> Result<WebResponse<TopMovers>> {
+    let TopMoversRequest { trade_date, top_n, universe } = request.into_inner();
+    request::date("trade_date", &trade_date)?;
+    let top_n = request::positive("top_n", top_n.unwrap_or(DEFAULT_TOP_N))?;
+    let conn = conn as &DatabaseConnection;
+    let data = analysis::top_movers(&trade_date, top_n, &universe, conn).await?;
+    WebResponse::new(data).into_result()
+}
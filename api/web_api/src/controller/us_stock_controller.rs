@@ -18,9 +18,9 @@ pub struct UsStockParams {
     pub page_size: Option<u64>,
     /// 搜索关键词（股票代码或名称）
     pub keyword: Option<String>,
-    /// 行业（中文）过滤，对应 us_company_info.industry_name_cn
+    /// 行业过滤，中文或英文行业名，"Unknown" 表示未分类
     pub industry: Option<String>,
-    /// 板块（中文）过滤，对应 us_company_info.sector_name_cn
+    /// 板块过滤，中文或英文板块名，"Unknown" 表示未分类
     pub sector: Option<String>,
 }
 
@@ -30,13 +30,16 @@ pub struct UsStockParams {
 /// * `page` - 页码，从1开始，默认1
 /// * `page_size` - 每页大小，默认20
 /// * `keyword` - 搜索关键词，支持股票代码或名称模糊搜索，可选
+/// * `industry` - 行业过滤，可与关键词同时使用，可选
+/// * `sector` - 板块过滤，可与关键词同时使用，可选
 /// 
 /// # 返回
-/// 返回分页的美股列表数据
+/// 返回分页的美股列表数据，包含总数和总页数
 /// 
 /// # 示例
 /// ```
 /// GET /api/us-stocks?page=1&page_size=20&keyword=AAPL
+/// GET /api/us-stocks?sector=Technology&industry=Software
 /// ```
 #[get("/api/us-stocks?<params..>")]
 pub async fn get_us_stocks(
@@ -10,6 +10,14 @@ pub fn internal_error(req: &Request) -> Json<WebResponse<String>> {
     Json(WebResponse::failed(msg))
 }
 
+/// 请求体无法解析为接口需要的类型
+#[catch(422)]
+pub fn unprocessable_entity(req: &Request) -> Json<WebResponse<String>> {
+    let msg = format!("Unprocessable request body: {:?}", req);
+    error!(msg);
+    Json(WebResponse::failed(msg))
+}
+
 #[catch(404)]
 pub fn not_found(req: &Request) -> Json<WebResponse<String>> {
     let msg = format!("Resource not found: {:?}", req);
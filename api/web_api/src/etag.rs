@@ -0,0 +1,135 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+use std::io::Cursor;
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::{Header, Method, Status};
+use rocket::{Request, Response};
+use tracing::warn;
+
+use crate::compression;
+
+/// 返回历史行情的 GET 接口, 历史数据基本不变, 适合客户端缓存
+const ETAG_PATHS: [&str; 2] = ["/api/stocks/history", "/api/securities/price"];
+
+/// 历史行情接口的条件请求: 按响应体生成 `ETag`, 请求的 `If-None-Match` 与之相同时返回 304 和空响应体
+///
+/// 需要在压缩之前挂载, 保证 `ETag` 按未压缩的响应体计算; 会被压缩的响应使用带 `-gzip` 后缀的 `ETag`,
+/// 与未压缩的响应区分开
+pub struct ConditionalGet;
+
+#[rocket::async_trait]
+impl Fairing for ConditionalGet {
+    fn info(&self) -> Info {
+        Info { name: "Conditional GET", kind: Kind::Response }
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, resp: &mut Response<'r>) {
+        if req.method() != Method::Get || resp.status() != Status::Ok || !ETAG_PATHS.contains(&req.uri().path().as_str()) {
+            return;
+        }
+        let body = match resp.body_mut().to_bytes().await {
+            Ok(body) => body,
+            Err(e) => {
+                warn!("read response body failed, uri: {}, error: {:?}", req.uri(), e);
+                return;
+            }
+        };
+        let mut etag = etag(&body);
+        if compression::compresses(req, body.len()) {
+            etag = gzip_etag(&etag);
+        }
+        resp.set_header(Header::new("ETag", etag.clone()));
+        if req.headers().get("If-None-Match").any(|v| matches_etag(v, &etag)) {
+            resp.set_status(Status::NotModified);
+            resp.remove_header("Content-Type");
+            // 304 不经过压缩, 由这里声明 `ETag` 随 `Accept-Encoding` 变化
+            resp.set_header(Header::new("Vary", "Accept-Encoding"));
+            resp.set_sized_body(0, Cursor::new(Vec::new()));
+        } else {
+            resp.set_sized_body(body.len(), Cursor::new(body));
+        }
+    }
+}
+
+/// 强校验 `ETag`: 响应体长度和哈希值
+fn etag(body: &[u8]) -> String {
+    let mut hasher = DefaultHasher::new();
+    hasher.write(body);
+    format!("\"{:x}-{:016x}\"", body.len(), hasher.finish())
+}
+
+/// gzip 压缩后的响应体字节不同, 使用单独的强校验 `ETag`
+fn gzip_etag(etag: &str) -> String {
+    format!("{}-gzip\"", etag.trim_end_matches('"'))
+}
+
+/// `If-None-Match` 可以是 `*` 或逗号分隔的多个 `ETag`, 弱校验前缀 `W/` 忽略
+fn matches_etag(if_none_match: &str, etag: &str) -> bool {
+    if_none_match
+        .split(',')
+        .map(|v| v.trim().trim_start_matches("W/"))
+        .any(|v| v == "*" || v == etag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compression::GzipCompression;
+    use rocket::get;
+    use rocket::local::asynchronous::Client;
+
+    #[get("/api/stocks/history")]
+    fn history() -> &'static str {
+        "[{\"date\":\"20240102\",\"close\":10.0}]"
+    }
+
+    #[rocket::async_test]
+    async fn test_not_modified_with_etag() {
+        let rocket = rocket::build().attach(ConditionalGet).mount("/", rocket::routes![history]);
+        let client = Client::tracked(rocket).await.unwrap();
+
+        let resp = client.get("/api/stocks/history").dispatch().await;
+        assert_eq!(resp.status(), Status::Ok);
+        let etag = resp.headers().get_one("ETag").unwrap().to_string();
+        assert_eq!(resp.into_string().await.unwrap(), history());
+
+        let resp = client.get("/api/stocks/history").header(Header::new("If-None-Match", etag.clone())).dispatch().await;
+        assert_eq!(resp.status(), Status::NotModified);
+        assert_eq!(resp.headers().get_one("ETag"), Some(etag.as_str()));
+        assert!(resp.into_bytes().await.unwrap_or_default().is_empty());
+
+        let resp = client.get("/api/stocks/history").header(Header::new("If-None-Match", "\"0-0\"")).dispatch().await;
+        assert_eq!(resp.status(), Status::Ok);
+    }
+
+    #[get("/api/securities/price")]
+    fn price() -> String {
+        history().repeat(100)
+    }
+
+    #[rocket::async_test]
+    async fn test_gzip_response_has_own_etag() {
+        let rocket = rocket::build().attach(ConditionalGet).attach(GzipCompression).mount("/", rocket::routes![price]);
+        let client = Client::tracked(rocket).await.unwrap();
+        let gzip = || Header::new("Accept-Encoding", "gzip");
+
+        let resp = client.get("/api/securities/price").dispatch().await;
+        let identity_etag = resp.headers().get_one("ETag").unwrap().to_string();
+        let resp = client.get("/api/securities/price").header(gzip()).dispatch().await;
+        assert_eq!(resp.headers().get_one("Content-Encoding"), Some("gzip"));
+        let gzip_etag = resp.headers().get_one("ETag").unwrap().to_string();
+        assert_ne!(gzip_etag, identity_etag);
+
+        // 未压缩响应的 `ETag` 不能让客户端复用压缩后的缓存, 反之亦然
+        let resp = client.get("/api/securities/price").header(gzip()).header(Header::new("If-None-Match", identity_etag.clone())).dispatch().await;
+        assert_eq!(resp.status(), Status::Ok);
+        let resp = client.get("/api/securities/price").header(Header::new("If-None-Match", gzip_etag.clone())).dispatch().await;
+        assert_eq!(resp.status(), Status::Ok);
+
+        let resp = client.get("/api/securities/price").header(gzip()).header(Header::new("If-None-Match", gzip_etag.clone())).dispatch().await;
+        assert_eq!(resp.status(), Status::NotModified);
+        assert_eq!(resp.headers().get_one("ETag"), Some(gzip_etag.as_str()));
+        assert_eq!(resp.headers().get_one("Vary"), Some("Accept-Encoding"));
+    }
+}
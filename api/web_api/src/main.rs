@@ -8,7 +8,7 @@ use std::time::Duration;
 use anyhow::{anyhow, Context};
 
 use crate::resource::AppState;
-use entity::sea_orm::{ConnectOptions, Database, DatabaseConnection};
+use entity::sea_orm::{Database, DatabaseConnection};
 use http::header::HeaderName;
 use rocket::{launch, routes, get};
 use rocket::catchers;
@@ -31,6 +31,7 @@ use tracing_subscriber::fmt::time::ChronoLocal;
 use controller::*;
 use controller::security::stock;
 use schedule::TaskManager;
+use service::stock::quote_cache::QuoteCache;
 use service::task_scheduler_service::TaskSchedulerService;
 
 mod resource;
@@ -41,6 +42,8 @@ mod response;
 mod request;
 mod error_handlers;
 mod result;
+mod compression;
+mod etag;
 
 pub struct RequestLogger;
 
@@ -127,24 +130,35 @@ async fn rocket() -> _ {
     init_log_context().expect("Failed to init log context");
    // tracing_subscriber::fmt::init();
 
+    let app_config = common::config::AppConfig::new().expect("Failed to load config");
+    schedule::set_fetch_windows(app_config.fetch_windows()).expect("Failed to set fetch windows");
+    schedule::set_us_daily_fetch(app_config.us_daily_fetch()).expect("Failed to set us daily fetch config");
+    common::db::set_max_rows(app_config.db_max_rows());
     let conn = get_db_conn().await;
     let task_manager: TaskManager = schedule::create_task_manager(conn.clone())
         .await
         .unwrap_or_else(|e| panic!("Failed to init task manager: {:?}", e));
     let conn_schedule = conn.clone();
+    let schedule_manager = task_manager.clone();
     info!("start schedule");
     tokio::spawn(async move {
-        schedule::start_schedule(conn_schedule)
+        schedule::start_schedule(schedule_manager, conn_schedule)
             .await
             .expect("Failed to start schedule");
     });
     rocket::build()
         .attach(RequestLogger)
+        .attach(etag::ConditionalGet)
+        .attach(compression::GzipCompression)
         .manage(conn.clone())
+        .manage(app_config.lookback())
+        .manage(QuoteCache::default())
+        .manage(request::AdminToken(app_config.admin_token()))
         .manage(task_manager)
         .manage(TaskSchedulerService::new(conn))
         .mount("/", routes![
             stock_price_limitup_controller::stock_price_limitup,
+            stock_price_limitup_controller::stock_price_limitup_in_universe,
             macd_stastic_controller::macd_stastic,
             stock_bias_ratio_controller::get_bias_ratio,
             security_search_controller::search_securities,
@@ -152,8 +166,10 @@ async fn rocket() -> _ {
             stock_history_controller::get_stock_history,
             stock_similarity_controller::get_stock_similarity,
             stock_price_controller::stock_price,
+            stock_price_controller::latest_close,
             security::security_price_controller::get_security_price,
             security::security_history_compare_controller::security_history_compare,
+            security::security_history_compare_controller::security_history_compare_normalized,
 
             stock::get_stock_areas,
             stock::get_stock_industries,
@@ -176,6 +192,8 @@ async fn rocket() -> _ {
             portfolio_controller::get_holdings_handler,
             portfolio_controller::update_holding_desc_handler,
             portfolio_controller::remove_holding_handler,
+            portfolio_controller::add_trade_handler,
+            portfolio_controller::cost_basis_handler,
 
             etf_controller::get_etf_list,
             etf_controller::get_etf_holdings,
@@ -203,9 +221,25 @@ async fn rocket() -> _ {
             strategy_template_controller::list_strategy_templates_handler,
 
             holder_per_capita_controller::get_holder_per_capita,
+
+            stock_overview_controller::stock_overview,
+            stock_overview_controller::stock_overview_batch,
+
+            llm_usage_controller::llm_usage,
+            data_quality_controller::data_quality,
+            top_movers_controller::top_movers,
+            top_movers_controller::top_movers_in_universe,
+            batch_controller::batch,
+            indicator_controller::compute,
+            indicator_controller::list,
+            stock_compare_controller::stocks_compare,
+            task_admin_controller::admin_tasks,
+            task_admin_controller::run_admin_task,
+            screen_controller::liquidity_screen,
+            screen_controller::volume_breakout_screen,
         ])
         .mount("/", task_controller::routes())
-        .register("/", catchers![error_handlers::internal_error, error_handlers::not_found])
+        .register("/", catchers![error_handlers::internal_error, error_handlers::not_found, error_handlers::unprocessable_entity])
 }
 
 
@@ -255,9 +289,7 @@ async fn start(conn: DatabaseConnection) -> anyhow::Result<()> {
 }
 
 async fn get_db_conn() -> DatabaseConnection {
-    let db_url = common::config::AppConfig::new().unwrap().database_url();
-    let mut opt = ConnectOptions::new(db_url);
-    opt.sqlx_logging(false); // Disable SQLx log
+    let opt = common::config::AppConfig::new().unwrap().db_connect_options();
     // opt.sqlx_logging_level(log::LevelFilter::Warn); // Or set SQLx log level
 
     return Database::connect(opt).await.unwrap();
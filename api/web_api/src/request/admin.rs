@@ -0,0 +1,56 @@
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome};
+use rocket::Request;
+
+/// 管理接口 token 所在的请求头
+pub const ADMIN_TOKEN_HEADER: &str = "X-Admin-Token";
+
+/// 配置中的管理接口 token, 通过 `manage` 注册; 为 None 时所有管理接口都拒绝
+pub struct AdminToken(pub Option<String>);
+
+/// 管理接口的请求守卫, 请求头 `X-Admin-Token` 与配置一致时才放行, 否则返回 401
+pub struct Admin;
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Admin {
+    type Error = &'static str;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let expected = req.rocket().state::<AdminToken>().and_then(|token| token.0.as_deref());
+        match (expected, req.headers().get_one(ADMIN_TOKEN_HEADER)) {
+            (Some(expected), Some(token)) if token == expected => Outcome::Success(Admin),
+            (None, _) => Outcome::Error((Status::Unauthorized, "admin token is not configured")),
+            _ => Outcome::Error((Status::Unauthorized, "invalid admin token")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocket::http::Header;
+    use rocket::local::asynchronous::Client;
+    use rocket::post;
+
+    #[post("/api/admin/ping")]
+    fn ping(_admin: Admin) -> &'static str {
+        "pong"
+    }
+
+    #[rocket::async_test]
+    async fn test_admin_token() {
+        let rocket = rocket::build().manage(AdminToken(Some("secret".to_string()))).mount("/", rocket::routes![ping]);
+        let client = Client::tracked(rocket).await.unwrap();
+        assert_eq!(client.post("/api/admin/ping").dispatch().await.status(), Status::Unauthorized);
+        let resp = client.post("/api/admin/ping").header(Header::new(ADMIN_TOKEN_HEADER, "wrong")).dispatch().await;
+        assert_eq!(resp.status(), Status::Unauthorized);
+        let resp = client.post("/api/admin/ping").header(Header::new(ADMIN_TOKEN_HEADER, "secret")).dispatch().await;
+        assert_eq!(resp.status(), Status::Ok);
+
+        // 未配置 token 时一律拒绝
+        let rocket = rocket::build().manage(AdminToken(None)).mount("/", rocket::routes![ping]);
+        let client = Client::tracked(rocket).await.unwrap();
+        let resp = client.post("/api/admin/ping").header(Header::new(ADMIN_TOKEN_HEADER, "")).dispatch().await;
+        assert_eq!(resp.status(), Status::Unauthorized);
+    }
+}
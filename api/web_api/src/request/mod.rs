@@ -0,0 +1,103 @@
+//! 控制器入参校验, 在调用 service 之前拦截格式错误的输入并返回 400,
+//! 避免格式错误的代码、日期直接落到数据库查询里变成难以理解的错误
+
+use anyhow::anyhow;
+use chrono::NaiveDate;
+
+use crate::result::Error;
+
+mod admin;
+
+pub use admin::{Admin, AdminToken};
+
+pub type Validated<T> = std::result::Result<T, Error>;
+
+/// 股票/基金/指数代码: 6 位数字 + `.SH`/`.SZ`/`.BJ`, 如 `000001.SZ`
+pub fn ts_code(value: &str) -> Validated<&str> {
+    let valid = match value.split_once('.') {
+        Some((code, exchange)) => code.len() == 6 && code.bytes().all(|b| b.is_ascii_digit()) && matches!(exchange, "SH" | "SZ" | "BJ"),
+        None => false,
+    };
+    if !valid {
+        return Err(bad_request(format!("invalid ts_code: {:?}, expected 6 digits with .SH/.SZ/.BJ suffix, e.g. 000001.SZ", value)));
+    }
+    Ok(value)
+}
+
+/// `%Y%m%d` 格式的日期, 如 `20240102`
+pub fn date<'a>(name: &str, value: &'a str) -> Validated<&'a str> {
+    if value.len() != 8 || NaiveDate::parse_from_str(value, common::date::FORMAT).is_err() {
+        return Err(bad_request(format!("invalid {}: {:?}, expected date in YYYYMMDD format, e.g. 20240102", name, value)));
+    }
+    Ok(value)
+}
+
+/// `%Y-%m-%d` 格式的日期, 如 `2024-01-02`
+pub fn date_dash(name: &str, value: &str) -> Validated<NaiveDate> {
+    NaiveDate::parse_from_str(value, common::date::FORMAT_DASH)
+        .map_err(|_| bad_request(format!("invalid {}: {:?}, expected date in YYYY-MM-DD format, e.g. 2024-01-02", name, value)))
+}
+
+/// 大于 0 的整数
+pub fn positive(name: &str, value: usize) -> Validated<usize> {
+    if value == 0 {
+        return Err(bad_request(format!("invalid {}: must be a positive integer", name)));
+    }
+    Ok(value)
+}
+
+/// 位于 `[min, max]` 之间的整数
+pub fn in_range(name: &str, value: usize, min: usize, max: usize) -> Validated<usize> {
+    if value < min || value > max {
+        return Err(bad_request(format!("invalid {}: {}, must be between {} and {}", name, value, min, max)));
+    }
+    Ok(value)
+}
+
+/// 不小于 `min` 的数值, NaN 视为不合法
+pub fn at_least(name: &str, value: f64, min: f64) -> Validated<f64> {
+    if value.is_nan() || value < min {
+        return Err(bad_request(format!("invalid {}: {}, must be at least {}", name, value, min)));
+    }
+    Ok(value)
+}
+
+fn bad_request(msg: String) -> Error {
+    Error::bad_request(anyhow!(msg))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validators() {
+        assert!(ts_code("000001.SZ").is_ok());
+        assert!(ts_code("688981.SH").is_ok());
+        assert!(ts_code("430047.BJ").is_ok());
+        for invalid in ["000001", "00001.SZ", "000001.sz", "000001.HK", "A00001.SZ", ""] {
+            assert!(ts_code(invalid).is_err(), "{}", invalid);
+        }
+
+        assert!(date("trade_date", "20240229").is_ok());
+        for invalid in ["2024-01-02", "20230229", "2024012", "abcdefgh"] {
+            assert!(date("trade_date", invalid).is_err(), "{}", invalid);
+        }
+
+        assert_eq!(date_dash("start", "2024-02-29").ok(), NaiveDate::from_ymd_opt(2024, 2, 29));
+        for invalid in ["20240102", "2023-02-29", "2024-1-2x"] {
+            assert!(date_dash("start", invalid).is_err(), "{}", invalid);
+        }
+
+        assert_eq!(positive("top_n", 5).ok(), Some(5));
+        assert!(positive("top_n", 0).is_err());
+
+        assert_eq!(in_range("window", 2, 2, 19).ok(), Some(2));
+        assert!(in_range("window", 1, 2, 19).is_err());
+        assert!(in_range("window", 20, 2, 19).is_err());
+
+        assert_eq!(at_least("multiplier", 1.5, 1.0).ok(), Some(1.5));
+        assert!(at_least("multiplier", 0.5, 1.0).is_err());
+        assert!(at_least("multiplier", f64::NAN, 1.0).is_err());
+    }
+}
@@ -4,12 +4,19 @@ use rocket::response::Responder;
 use rocket::serde::json::Json;
 use crate::response::WebResponse;
 
-pub struct Error(anyhow::Error);
+/// 默认以 200 返回 `success: false`, 参数错误等需要区分状态码的场景用 `bad_request`
+pub struct Error(anyhow::Error, Status);
 pub type Result<T> = std::result::Result<Json<T>, Error>;
 
+impl Error {
+    pub fn bad_request(e: anyhow::Error) -> Self {
+        Error(e, Status::BadRequest)
+    }
+}
+
 impl From<anyhow::Error> for Error {
     fn from(e: anyhow::Error) -> Self {
-        Error(e)
+        Error(e, Status::Ok)
     }
 }
 
@@ -18,6 +25,7 @@ impl<'r> Responder<'r, 'static> for Error {
         let msg = Json(WebResponse::failed(self.0.to_string()));
         Response::build_from(msg. respond_to(req)?)
             .header(ContentType::new("application", "json"))
+            .status(self.1)
             .ok()
     }
 }
@@ -0,0 +1,10 @@
+-- ============================================================================
+-- 复权因子, 来自 tushare adj_factor, 每个交易日每只股票一条
+-- ============================================================================
+CREATE TABLE adj_factor (
+    ts_code VARCHAR(20) NOT NULL COMMENT 'TS代码',
+    trade_date VARCHAR(20) NOT NULL COMMENT '交易日期',
+    adj_factor DECIMAL(20, 6) NULL COMMENT '复权因子',
+    PRIMARY KEY (ts_code, trade_date),
+    INDEX idx_trade_date (trade_date)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='复权因子';
@@ -0,0 +1,21 @@
+-- ============================================================================
+-- 分红送股, 来自 tushare dividend, 同一分红方案的预案、股东大会通过、实施各一条
+-- ============================================================================
+CREATE TABLE dividend (
+    ts_code VARCHAR(20) NOT NULL COMMENT 'TS代码',
+    end_date VARCHAR(20) NOT NULL COMMENT '分红年度',
+    ann_date VARCHAR(20) NOT NULL COMMENT '预案公告日',
+    div_proc VARCHAR(20) NOT NULL COMMENT '实施进度',
+    stk_div DECIMAL(10, 4) NULL COMMENT '每股送转',
+    stk_bo_rate DECIMAL(10, 4) NULL COMMENT '每股送股比例',
+    stk_co_rate DECIMAL(10, 4) NULL COMMENT '每股转增比例',
+    cash_div DECIMAL(10, 4) NULL COMMENT '每股分红(税后)',
+    cash_div_tax DECIMAL(10, 4) NULL COMMENT '每股分红(税前)',
+    record_date VARCHAR(20) NULL COMMENT '股权登记日',
+    ex_date VARCHAR(20) NULL COMMENT '除权除息日',
+    pay_date VARCHAR(20) NULL COMMENT '派息日',
+    div_listdate VARCHAR(20) NULL COMMENT '红股上市日',
+    imp_ann_date VARCHAR(20) NULL COMMENT '实施公告日',
+    PRIMARY KEY (ts_code, end_date, ann_date, div_proc),
+    INDEX idx_ex_date (ex_date)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='分红送股';
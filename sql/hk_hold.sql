@@ -0,0 +1,14 @@
+-- ============================================================================
+-- 沪深港通持股明细(北向资金持股), 每个交易日一份快照, 来自 tushare hk_hold
+-- ============================================================================
+CREATE TABLE hk_hold (
+    ts_code VARCHAR(20) NOT NULL COMMENT 'TS代码',
+    trade_date VARCHAR(20) NOT NULL COMMENT '交易日期',
+    code VARCHAR(20) NULL COMMENT '原始代码',
+    name VARCHAR(100) NULL COMMENT '股票名称',
+    vol DECIMAL(20, 2) NULL COMMENT '持股数量(股)',
+    ratio DECIMAL(10, 4) NULL COMMENT '持股占比(%), 占已发行股份百分比',
+    exchange VARCHAR(10) NULL COMMENT '类型: SH沪股通 SZ深股通 HK港股通',
+    PRIMARY KEY (ts_code, trade_date),
+    INDEX idx_trade_date (trade_date)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='沪深港通持股明细';
@@ -0,0 +1,13 @@
+-- ============================================================================
+-- 持仓买卖记录表, 每笔买入为一个批次(lot), 卖出按成本计算方法冲减批次
+-- ============================================================================
+CREATE TABLE holding_trade (
+    id INT NOT NULL AUTO_INCREMENT COMMENT '主键',
+    holding_id INT NOT NULL COMMENT '持仓ID',
+    side CHAR(1) NOT NULL COMMENT '买卖方向: B 买入, S 卖出',
+    trade_date VARCHAR(8) NOT NULL COMMENT '成交日期 yyyyMMdd',
+    quantity DECIMAL(20, 4) NOT NULL COMMENT '成交数量(股)',
+    price DECIMAL(20, 4) NOT NULL COMMENT '成交价格',
+    PRIMARY KEY (id),
+    INDEX idx_holding_date (holding_id, trade_date)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='持仓买卖记录表';
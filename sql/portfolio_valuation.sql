@@ -0,0 +1,10 @@
+-- ============================================================================
+-- 组合估值表, 记录组合在持仓变动(入金/出金)日及期初期末的市值, 用于计算时间加权收益率
+-- ============================================================================
+CREATE TABLE portfolio_valuation (
+    portfolio_id INT NOT NULL COMMENT '组合ID',
+    trade_date VARCHAR(8) NOT NULL COMMENT '日期 yyyyMMdd',
+    market_value DECIMAL(20, 4) NOT NULL COMMENT '当日收盘后组合市值, 已包含当日现金流',
+    cash_flow DECIMAL(20, 4) NOT NULL DEFAULT 0 COMMENT '当日外部现金流, 入金为正, 出金为负',
+    PRIMARY KEY (portfolio_id, trade_date)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='组合估值表';
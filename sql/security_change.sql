@@ -0,0 +1,12 @@
+-- ============================================================================
+-- 证券变动表, 股票列表任务每次运行时与已有数据对比, 记录新上市 / 退市 / 更名
+-- ============================================================================
+CREATE TABLE security_change (
+    id BIGINT PRIMARY KEY AUTO_INCREMENT,
+    ts_code VARCHAR(20) NOT NULL COMMENT '证券代码',
+    kind VARCHAR(20) NOT NULL COMMENT '变动类型: Listed, Delisted, Renamed',
+    detail VARCHAR(500) NULL COMMENT '变动说明, 如更名前后的名称',
+    detected_at VARCHAR(20) NOT NULL COMMENT '发现时间 yyyy-MM-dd HH:mm:ss',
+    INDEX idx_detected_at (detected_at),
+    INDEX idx_ts_code (ts_code)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='证券变动表';
@@ -0,0 +1,14 @@
+-- ============================================================================
+-- A股 / 美股 关联映射表, 保存 LLM 计算的相似度评分
+-- ============================================================================
+CREATE TABLE stock_peer_map (
+    cn_code VARCHAR(20) NOT NULL COMMENT 'A股代码, 如 300063.SZ',
+    us_code VARCHAR(20) NOT NULL COMMENT '美股代码, 如 IRDM',
+    composite_score INT NOT NULL COMMENT '综合关联度(0~100)',
+    main_business_score INT NULL COMMENT '主营业务相似度(0~100)',
+    industry_score INT NULL COMMENT '行业板块相似度(0~100)',
+    concept_score INT NULL COMMENT '概念板块相似度(0~100)',
+    computed_at VARCHAR(20) NOT NULL COMMENT '计算时间 yyyy-MM-dd HH:mm:ss',
+    PRIMARY KEY (cn_code, us_code),
+    INDEX idx_cn_score (cn_code, composite_score)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='A股美股关联映射表';
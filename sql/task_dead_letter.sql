@@ -0,0 +1,15 @@
+-- ============================================================================
+-- 任务死信表, 抓取任务写入失败的单行数据及错误, 供排查后重试, 不影响同批次其它数据
+-- ============================================================================
+CREATE TABLE task_dead_letter (
+    id BIGINT PRIMARY KEY AUTO_INCREMENT,
+    task_name VARCHAR(100) NOT NULL COMMENT '任务名',
+    target_table VARCHAR(100) NOT NULL COMMENT '写入的表名',
+    payload TEXT NOT NULL COMMENT '写入失败的数据, JSON',
+    error VARCHAR(1000) NOT NULL COMMENT '最近一次失败的错误',
+    status VARCHAR(20) NOT NULL COMMENT '状态: pending 待处理, retried 重试成功',
+    retry_count INT NOT NULL DEFAULT 0 COMMENT '重试次数',
+    created_at VARCHAR(20) NOT NULL COMMENT '创建时间 yyyy-MM-dd HH:mm:ss',
+    retried_at VARCHAR(20) NULL COMMENT '最近一次重试时间 yyyy-MM-dd HH:mm:ss',
+    INDEX idx_task_status (task_name, status)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COMMENT='任务死信表';